@@ -0,0 +1,1787 @@
+//! The command-line program: parsing arguments, running commands, and
+//! printing their results.
+
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitCode};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::workspace::{self, Target};
+use crate::watch;
+use crate::baseline::{self, Baseline};
+use crate::graph::Graph;
+use crate::owners::Owners;
+use crate::cache::{self, Cache};
+use crate::rules::{self, FileKind};
+use crate::stats::{self, Inventory};
+use crate::terminal::{self, Color, Paint};
+use crate::timings::Timings;
+use crate::{
+    all_rules, cli, config, count, daemon, explain, fix, git, lint_target_cached,
+    lint_target_changed, lint_text_with, linted_files, log, lsp, skipped_files, output, precommit,
+    scaffold, schema, urls, Counts, Diagnostic, Severity,
+};
+
+// Printing goes through these rather than the std macros, which panic
+// once the reader goes away, as with `claude-lint | head`. Output that
+// cannot be written is dropped, and the exit code still counts.
+macro_rules! print {
+    ($($arg:tt)*) => {{
+        let _ = write!(io::stdout(), $($arg)*);
+    }};
+}
+macro_rules! println {
+    ($($arg:tt)*) => {{
+        let _ = writeln!(io::stdout(), $($arg)*);
+    }};
+}
+macro_rules! eprint {
+    ($($arg:tt)*) => {{
+        let _ = write!(io::stderr(), $($arg)*);
+    }};
+}
+macro_rules! eprintln {
+    ($($arg:tt)*) => {{
+        let _ = writeln!(io::stderr(), $($arg)*);
+    }};
+}
+
+const USAGE: &str = "\
+Lints Claude Code context: CLAUDE.md, agents, skills, and commands.
+
+usage: claude-lint [OPTIONS] [PATH...]
+       claude-lint COMMAND [OPTIONS] [ARGS]
+
+Each PATH is a .claude directory to lint, .claude by default, or with
+--recursive a workspace to search, . by default. A PATH may also be a
+single file, whose kind comes from its place in a .claude directory.
+Without a command, claude-lint lints as check does. Each command lists
+its own options with COMMAND --help.
+
+exit codes:
+  0  no errors
+  1  errors were found, or more warnings than --max-warnings
+  2  bad arguments, or a config or baseline that does not load
+  3  a missing or unreadable path, a file that cannot be written, git
+     failing, or an internal error
+";
+
+/// The help: the usage, the options, commands, and environment variables
+/// from the command-line tables, and the exit codes.
+fn help() -> String {
+    let (usage, exit_codes) = USAGE.split_at(
+        USAGE
+            .find("exit codes:")
+            .expect("USAGE lists the exit codes"),
+    );
+    format!(
+        "{}options:\n{}\ncommands:\n{}\nenvironment:\n{}\n{}",
+        usage,
+        cli::options_help(cli::OPTIONS),
+        cli::commands_help(cli::COMMANDS),
+        cli::environment_help(),
+        exit_codes
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    Sarif,
+    Github,
+    Codeclimate,
+    Junit,
+    Checkstyle,
+    Tap,
+    Html,
+    Ndjson,
+}
+
+impl Format {
+    fn name(self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Json => "json",
+            Format::Sarif => "sarif",
+            Format::Github => "github",
+            Format::Codeclimate => "codeclimate",
+            Format::Junit => "junit",
+            Format::Checkstyle => "checkstyle",
+            Format::Tap => "tap",
+            Format::Html => "html",
+            Format::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Which directories to lint: the project's, the user's ~/.claude, or
+/// both.
+#[derive(Clone, Copy, PartialEq)]
+enum Scope {
+    Project,
+    User,
+    All,
+}
+
+/// How text output arranges findings: one flat list, or under a header
+/// per file, rule, or owner with a table of counts at the end.
+#[derive(Clone, Copy, PartialEq)]
+enum Group {
+    None,
+    File,
+    Rule,
+    Owner,
+}
+
+/// What to lint: the paths named on the command line, or the default,
+/// and the user-level directory, depending on scope.
+struct Selection {
+    paths: Vec<PathBuf>,
+    /// The kind of every file among the paths, overriding inference.
+    kind: Option<FileKind>,
+    recursive: bool,
+    scope: Scope,
+    /// Whether the CLAUDE.md files around a .claude directory are linted
+    /// with it, and a project with only those is linted at all.
+    discover: bool,
+    /// Whether the paths are files from pre-commit, of which only the
+    /// context files are linted.
+    pre_commit: bool,
+}
+
+/// How to print text output.
+#[derive(Clone, Copy)]
+struct Text<'a> {
+    group: Group,
+    paint: Paint,
+    /// The text linted from stdin, shown in snippets in place of the
+    /// file on disk.
+    buffer: Option<&'a str>,
+    /// Who owns each file, from CODEOWNERS.
+    owners: Option<&'a Owners>,
+}
+
+/// Options that override what each target's config says.
+#[derive(Clone, Copy)]
+struct Overrides {
+    profile: Option<config::Profile>,
+    /// Whether `extends` may fetch a config over http(s).
+    remote: bool,
+    strict: bool,
+    follow_symlinks: bool,
+    discover: bool,
+    /// Stop after this many errors, for --max-errors and --fail-fast.
+    max_errors: Option<usize>,
+    /// Whether a baseline hides some findings.
+    baseline: bool,
+}
+
+/// Exit codes: findings fail a run with LINT_FAILURE, bad arguments or
+/// config with USAGE_FAILURE, and files that cannot be read or written,
+/// or a bug, with IO_FAILURE.
+const LINT_FAILURE: u8 = 1;
+const USAGE_FAILURE: u8 = 2;
+const IO_FAILURE: u8 = 3;
+
+/// Why a run could not finish.
+enum Failure {
+    /// Bad arguments, or a config or baseline that does not load.
+    Usage(String),
+    /// A path that is missing or cannot be read or written, or git failing.
+    Io(String),
+}
+
+impl Failure {
+    fn exit(self) -> ExitCode {
+        let (message, code) = match self {
+            Failure::Usage(m) => (m, USAGE_FAILURE),
+            Failure::Io(m) => (m, IO_FAILURE),
+        };
+        eprintln!("error: {}", message);
+        ExitCode::from(code)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum FixMode {
+    Write,
+    DryRun,
+}
+
+pub(crate) fn main() -> ExitCode {
+    // A panic is a bug; it fails like I/O rather than with Rust's 101.
+    let report = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        report(info);
+        process::exit(IO_FAILURE.into());
+    }));
+    log::init(env::var("RUST_LOG").ok().as_deref().and_then(log::from_filter));
+
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|a| a == "help") {
+        // `help` alone, or `help COMMAND`.
+        match args.get(1).map(|name| cli::command(name).ok_or(name)) {
+            None => print!("{}", help()),
+            Some(Ok(command)) => print!("{}", command.help()),
+            Some(Err(name)) => return Failure::Usage(format!("unknown command '{}'", name)).exit(),
+        }
+        return ExitCode::SUCCESS;
+    }
+    // Without a command, the arguments lint like `check`.
+    let command = args.first().and_then(|a| cli::command(a));
+    let parsed = match command {
+        Some(c) => c.parse(args.drain(1..)),
+        None => cli::parse(cli::OPTIONS, args),
+    };
+    let mut parsed = match parsed {
+        Ok(p) => p,
+        Err(e) => return Failure::Usage(e).exit(),
+    };
+    // The environment goes first, so that the command line wins.
+    match cli::environment(command.map_or(cli::OPTIONS, |c| c.options)) {
+        Ok(set) => drop(parsed.options.splice(0..0, set)),
+        Err(e) => return Failure::Usage(e).exit(),
+    }
+    if parsed.has("help") {
+        print!("{}", command.map_or_else(help, |c| c.help()));
+        return ExitCode::SUCCESS;
+    }
+    let name = command.map(|c| c.name);
+    match name {
+        Some("lsp") => {
+            return match lsp::serve(&mut io::stdin().lock(), &mut io::stdout().lock()) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("error: lsp: {}", e);
+                    ExitCode::from(IO_FAILURE)
+                }
+            };
+        }
+        Some("daemon") => {
+            let socket = parsed
+                .options
+                .into_iter()
+                .find_map(|(name, value)| (name == "socket").then_some(value).flatten());
+            let socket = socket.map_or_else(daemon::default_socket, PathBuf::from);
+            return match daemon::serve(&socket, || eprintln!("listening on {}", socket.display())) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("error: daemon: {}", e);
+                    ExitCode::from(IO_FAILURE)
+                }
+            };
+        }
+        Some("rules") => {
+            print_rules();
+            return ExitCode::SUCCESS;
+        }
+        Some("explain") => {
+            let key = &parsed.operands[0];
+            let Some(rule) = all_rules()
+                .into_iter()
+                .find(|r| r.id.eq_ignore_ascii_case(key) || r.name == *key)
+            else {
+                eprintln!("error: unknown rule '{}'", key);
+                return ExitCode::from(USAGE_FAILURE);
+            };
+            print!("{}", explain::render(rule));
+            return ExitCode::SUCCESS;
+        }
+        Some("init") => {
+            let root = PathBuf::from(parsed.operands.first().map_or(".claude", String::as_str));
+            return match scaffold::init(&root) {
+                Ok(created) => {
+                    if created.is_empty() {
+                        println!("{} already has every starter file", root.display());
+                    }
+                    for p in created {
+                        let slash = if p.is_dir() { "/" } else { "" };
+                        println!("created {}{}", p.display(), slash);
+                    }
+                    ExitCode::SUCCESS
+                }
+                Err(e) => Failure::Io(e).exit(),
+            };
+        }
+        Some("new") => {
+            return match new_file(&parsed.operands) {
+                Ok(true) => ExitCode::SUCCESS,
+                Ok(false) => ExitCode::from(LINT_FAILURE),
+                Err(f) => f.exit(),
+            };
+        }
+        Some("hook-config") => {
+            print!("{}", precommit::HOOKS);
+            return ExitCode::SUCCESS;
+        }
+        Some("install-hook") => {
+            let hook = if parsed.has("pre-push") {
+                precommit::Hook::PrePush
+            } else {
+                precommit::Hook::PreCommit
+            };
+            let done = if parsed.has("uninstall") {
+                precommit::uninstall(Path::new("."), hook)
+                    .map(|path| format!("removed {}", path.display()))
+            } else {
+                precommit::install(Path::new("."), hook, parsed.has("force"))
+                    .map(|path| format!("installed {}", path.display()))
+            };
+            return match done {
+                Ok(message) => {
+                    println!("{}", message);
+                    ExitCode::SUCCESS
+                }
+                Err(e) => Failure::Io(e).exit(),
+            };
+        }
+        Some("completions") => {
+            return match cli::completions(&parsed.operands[0]) {
+                Ok(script) => {
+                    print!("{}", script);
+                    ExitCode::SUCCESS
+                }
+                Err(e) => Failure::Usage(e).exit(),
+            };
+        }
+        Some("config") => {
+            return match check_config(parsed) {
+                Ok(true) => ExitCode::SUCCESS,
+                Ok(false) => ExitCode::from(LINT_FAILURE),
+                Err(f) => f.exit(),
+            };
+        }
+        Some("stats") => {
+            return match print_stats(parsed) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(f) => f.exit(),
+            };
+        }
+        Some("graph") => {
+            return match print_graph(parsed) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(f) => f.exit(),
+            };
+        }
+        _ => {}
+    }
+
+    let mut paths = Vec::new();
+    let mut kind = None;
+    let mut format = Format::Text;
+    let mut output_path = None;
+    let mut group = Group::None;
+    let mut color = Color::Auto;
+    let mut config_path = None;
+    let mut profile = None;
+    let mut remote = false;
+    let mut strict = false;
+    let mut follow_symlinks = true;
+    let mut discover = true;
+    let mut max_warnings = None;
+    let mut max_errors = None;
+    let mut fail_fast = false;
+    let mut fix_mode = (name == Some("fix")).then_some(FixMode::Write);
+    let mut recursive = false;
+    let mut scope = Scope::Project;
+    let mut use_cache = true;
+    let mut watch = name == Some("watch");
+    let mut baseline_path = None;
+    let mut write_baseline = None;
+    let mut changed = None;
+    let mut diff_filter = None;
+    let mut stdin = false;
+    let mut stdin_path = None;
+    let mut verbose = false;
+    let mut quiet = false;
+    let mut check_urls = false;
+    let mut timings = None;
+
+    paths.extend(parsed.operands.into_iter().map(PathBuf::from));
+    // The parser has checked that values are given and fit.
+    for (name, value) in parsed.options {
+        let arg = value.clone().unwrap_or_default();
+        match name {
+            "format" => {
+                format = match arg.as_str() {
+                    "json" => Format::Json,
+                    "ndjson" => Format::Ndjson,
+                    "sarif" => Format::Sarif,
+                    "github" => Format::Github,
+                    "codeclimate" => Format::Codeclimate,
+                    "junit" => Format::Junit,
+                    "checkstyle" => Format::Checkstyle,
+                    "tap" => Format::Tap,
+                    "html" => Format::Html,
+                    _ => Format::Text,
+                };
+            }
+            "log-level" => match log::Level::parse(&arg) {
+                Ok(level) => log::init(level),
+                Err(e) => return Failure::Usage(e).exit(),
+            },
+            "output" => output_path = Some(PathBuf::from(arg)),
+            "group-by" => {
+                group = match arg.as_str() {
+                    "file" => Group::File,
+                    "rule" => Group::Rule,
+                    "owner" => Group::Owner,
+                    _ => Group::None,
+                };
+            }
+            "color" => {
+                color = match arg.as_str() {
+                    "always" => Color::Always,
+                    "never" => Color::Never,
+                    _ => Color::Auto,
+                };
+            }
+            "config" => config_path = Some(PathBuf::from(arg)),
+            "baseline" => baseline_path = Some(PathBuf::from(arg)),
+            "write-baseline" => write_baseline = Some(PathBuf::from(arg)),
+            "changed" => changed = Some(value),
+            "diff-filter" => diff_filter = Some(value),
+            "kind" => kind = FileKind::parse(&arg),
+            "stdin" => stdin = true,
+            "stdin-path" => stdin_path = Some(PathBuf::from(arg)),
+            "remote-config" => remote = true,
+            "profile" => profile = config::Profile::parse(&arg).ok(),
+            "strict" => strict = true,
+            "verbose" => verbose = true,
+            "quiet" => quiet = true,
+            "recursive" => recursive = true,
+            "no-cache" => use_cache = false,
+            "no-follow-symlinks" => follow_symlinks = false,
+            "no-discover" => discover = false,
+            "check-urls" => check_urls = true,
+            "timings" => timings = Some(value.map_or(10, |n| n.parse().unwrap_or(10))),
+            "watch" => watch = true,
+            "user" => scope = Scope::User,
+            "scope" => {
+                scope = match arg.as_str() {
+                    "user" => Scope::User,
+                    "all" => Scope::All,
+                    _ => Scope::Project,
+                };
+            }
+            "fix" => fix_mode = Some(FixMode::Write),
+            "fix-dry-run" | "dry-run" => fix_mode = Some(FixMode::DryRun),
+            "max-warnings" => max_warnings = arg.parse().ok(),
+            "max-errors" => match arg.parse::<usize>() {
+                Ok(n) if n > 0 => max_errors = Some(n),
+                _ => {
+                    return Failure::Usage("--max-errors requires a positive number".to_string())
+                        .exit()
+                }
+            },
+            "fail-fast" => fail_fast = true,
+            _ => unreachable!("every lint option is handled"),
+        }
+    }
+    if stdin_path.is_some() != stdin {
+        eprintln!("error: --stdin and --stdin-path go together");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    if stdin && (!paths.is_empty() || recursive || scope != Scope::Project || changed.is_some()) {
+        eprintln!("error: --stdin lints one file and cannot be combined with paths, --recursive, \
+                   --scope, or --changed");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    if stdin && (fix_mode.is_some() || watch) {
+        eprintln!("error: --stdin cannot be combined with --fix or --watch");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    if timings.is_some() && watch {
+        eprintln!("error: --timings cannot be combined with --watch");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    if timings.is_some() {
+        crate::timings::enable();
+    }
+    if quiet && (verbose || watch) {
+        eprintln!("error: --quiet cannot be combined with --verbose or --watch");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    if check_urls && (stdin || watch) {
+        eprintln!("error: --check-urls cannot be combined with --stdin or --watch");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    let buffer = match stdin.then(read_stdin).transpose() {
+        Ok(b) => b,
+        Err(f) => return f.exit(),
+    };
+    let pre_commit = name == Some("pre-commit");
+    if paths.is_empty() && !pre_commit {
+        paths.push(PathBuf::from(if recursive { "." } else { ".claude" }));
+    }
+    let selection = Selection { paths, kind, recursive, scope, discover, pre_commit };
+    if pre_commit && selection.paths.iter().all(|path| precommit::kind(path).is_none()) {
+        return ExitCode::SUCCESS;
+    }
+    if fail_fast {
+        max_errors = Some(1);
+    }
+    if max_errors.is_some() && write_baseline.is_some() {
+        eprintln!("error: --write-baseline records every finding and cannot be combined with \
+                   --fail-fast or --max-errors");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    let overrides = Overrides {
+        profile,
+        remote,
+        strict,
+        follow_symlinks,
+        discover,
+        max_errors,
+        baseline: baseline_path.is_some(),
+    };
+    if group != Group::None && format != Format::Text {
+        eprintln!("error: --group-by only applies to text output");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    if output_path.is_some() && format == Format::Text {
+        eprintln!("error: --output needs a --format other than text");
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    let shown = buffer.as_ref().map(|b| String::from_utf8_lossy(b));
+    let owners = match (group == Group::Owner || matches!(format, Format::Json | Format::Ndjson))
+        .then(|| Owners::discover(selection.paths.first().map_or(Path::new("."), PathBuf::as_path)))
+        .transpose()
+    {
+        Ok(o) => o.flatten(),
+        Err(e) => return Failure::Io(e).exit(),
+    };
+    if group == Group::Owner && owners.is_none() {
+        eprintln!(
+            "error: --group-by owner needs a CODEOWNERS file in .github/, at the top, or in docs/"
+        );
+        return ExitCode::from(USAGE_FAILURE);
+    }
+    let text = Text {
+        group,
+        paint: Paint(color.enabled()),
+        buffer: shown.as_deref(),
+        owners: owners.as_ref(),
+    };
+
+    if watch {
+        if fix_mode.is_some() {
+            eprintln!("error: --watch prints text output and cannot be combined with --fix");
+            return ExitCode::from(USAGE_FAILURE);
+        }
+        if format != Format::Text {
+            eprintln!(
+                "error: --watch prints text output and cannot be combined with --format {}",
+                format.name()
+            );
+            return ExitCode::from(USAGE_FAILURE);
+        }
+        watch_loop(&selection, config_path.as_deref(), overrides, use_cache, verbose, text);
+    }
+
+    let mut baseline = match baseline_path.as_deref().map(Baseline::load).transpose() {
+        Ok(b) => b,
+        Err(e) => return Failure::Usage(e).exit(),
+    };
+    // For --user alone the paths are unused; git then looks at ~/.claude.
+    let first = &selection.paths[0];
+    let git_dir = match crate::user_dir() {
+        Some(dir) if scope == Scope::User => dir,
+        // A missing .claude is a project with only its CLAUDE.md.
+        _ if first.is_file() || !first.exists() => first
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf(),
+        _ => first.clone(),
+    };
+    let changed = match changed
+        .map(|base| git::changed_files(&git_dir, base.as_deref()))
+        .transpose()
+    {
+        Ok(c) => c,
+        Err(e) => return Failure::Io(e).exit(),
+    };
+    let touched = match diff_filter
+        .map(|base| git::touched_lines(&git_dir, base.as_deref()))
+        .transpose()
+    {
+        Ok(t) => t,
+        Err(e) => return Failure::Io(e).exit(),
+    };
+    // Each target's findings go through the baseline and the error limit
+    // as soon as it is done, so that NDJSON can print them then and the
+    // limit can stop the targets after it.
+    let stream = format == Format::Ndjson && write_baseline.is_none();
+    let mut sink: Box<dyn Write> = match output_path.as_deref().filter(|_| stream) {
+        Some(path) => match fs::File::create(path) {
+            Ok(file) => Box::new(io::BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("error: {}: cannot write report: {}", path.display(), e);
+                return ExitCode::from(IO_FAILURE);
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+    let mut sink_error = None;
+    let mut known = 0;
+    let mut untouched = 0;
+    let mut errors_left = max_errors.unwrap_or(usize::MAX);
+    let mut emit = |diags: &mut Vec<Diagnostic>| {
+        if write_baseline.is_some() {
+            return;
+        }
+        if let Some(t) = &touched {
+            untouched += t.filter(diags);
+        }
+        if let Some(b) = &mut baseline {
+            known += b.filter(diags);
+        }
+        cap(diags, &mut errors_left);
+        if stream && sink_error.is_none() {
+            // Findings are sorted by path, so each file's come out together
+            // and are flushed as soon as they are written.
+            for (i, d) in diags.iter().enumerate() {
+                let mut written = writeln!(sink, "{}", output::ndjson(d, owners.as_ref()));
+                if diags.get(i + 1).is_none_or(|next| next.path != d.path) {
+                    written = written.and_then(|()| sink.flush());
+                }
+                if let Err(e) = written {
+                    sink_error = Some(e);
+                    return;
+                }
+            }
+        }
+    };
+    let linted = match (&buffer, &stdin_path) {
+        (Some(b), Some(path)) => {
+            lint_stdin(b, path, kind, config_path.as_deref(), overrides).map(|mut results| {
+                for (_, diags) in &mut results {
+                    emit(diags);
+                }
+                results
+            })
+        }
+        _ => selection.targets().and_then(|targets| {
+            lint_all(
+                targets,
+                config_path.as_deref(),
+                overrides,
+                fix_mode,
+                use_cache,
+                changed.as_deref(),
+                verbose,
+                &mut emit,
+            )
+        }),
+    };
+    let mut results = match linted {
+        Ok(r) => r,
+        Err(f) => return f.exit(),
+    };
+    if check_urls {
+        if let Err(f) = add_url_findings(
+            &mut results,
+            config_path.as_deref(),
+            overrides,
+            use_cache,
+            changed.as_deref(),
+            &mut emit,
+        ) {
+            return f.exit();
+        }
+    }
+    if let Some(e) = sink_error {
+        let to = output_path
+            .as_deref()
+            .map_or_else(|| "stdout".to_string(), |p| p.display().to_string());
+        eprintln!("error: {}: cannot write report: {}", to, e);
+        return ExitCode::from(IO_FAILURE);
+    }
+    drop(sink);
+
+    if let Some(out) = write_baseline {
+        let mut diags: Vec<Diagnostic> = results.into_iter().flat_map(|(_, d)| d).collect();
+        crate::sort(&mut diags);
+        if let Err(e) = fs::write(&out, baseline::render(&diags)) {
+            eprintln!("error: {}: cannot write baseline: {}", out.display(), e);
+            return ExitCode::from(IO_FAILURE);
+        }
+        eprintln!("wrote {} finding(s) to {}", diags.len(), out.display());
+        return ExitCode::SUCCESS;
+    }
+
+    // Each target's findings are sorted, but targets are not in path
+    // order relative to each other, so the flat list is sorted again.
+    let mut diags: Vec<Diagnostic> = results.iter().flat_map(|(_, d)| d.iter().cloned()).collect();
+    crate::sort(&mut diags);
+    // Results do not keep their configs; each loads again for its excludes.
+    let files = || -> Vec<PathBuf> {
+        results
+            .iter()
+            .flat_map(|(t, _)| {
+                linted_files(
+                    t,
+                    &overrides
+                        .load(config_path.as_deref(), t)
+                        .unwrap_or_default(),
+                )
+            })
+            .collect()
+    };
+    let timings = timings.map(|n| {
+        let mut t = crate::timings::take();
+        t.truncate(n);
+        t
+    });
+    let errors = count(&diags, Severity::Error);
+    let warnings = count(&diags, Severity::Warning);
+    let failed = errors > 0 || max_warnings.is_some_and(|max| warnings > max);
+    let report = match format {
+        Format::Text if quiet && !failed => None,
+        Format::Text => {
+            print_results(
+                &results,
+                &component_counts(&results, config_path.as_deref(), overrides),
+                &diags,
+                recursive,
+                text,
+            );
+            if untouched > 0 {
+                eprintln!("{} finding(s) on lines the change did not touch not shown", untouched);
+            }
+            if known > 0 {
+                eprintln!("{} known finding(s) in the baseline not shown", known);
+            }
+            if let Some(max) = max_errors.filter(|&max| errors >= max) {
+                eprintln!("stopped after {}; there may be more", plural(max, "error"));
+            }
+            None
+        }
+        Format::Json => Some(output::json(&diags, timings.as_ref(), owners.as_ref()) + "\n"),
+        Format::Sarif => Some(output::sarif(&diags) + "\n"),
+        Format::Github => Some(output::github(&diags)),
+        Format::Codeclimate => Some(output::codeclimate(&diags) + "\n"),
+        Format::Junit => Some(output::junit(&files(), &diags) + "\n"),
+        Format::Checkstyle => Some(output::checkstyle(&diags) + "\n"),
+        Format::Tap => Some(output::tap(&files(), &diags)),
+        Format::Html => Some(output::html(&files(), &diags, text.buffer)),
+        Format::Ndjson => None,
+    };
+    match (report, &output_path) {
+        (Some(report), Some(out)) => {
+            if let Err(e) = fs::write(out, report) {
+                eprintln!("error: {}: cannot write report: {}", out.display(), e);
+                return ExitCode::from(IO_FAILURE);
+            }
+        }
+        (Some(report), None) => print!("{}", report),
+        (None, _) => {}
+    }
+    if let Some(t) = timings.as_ref().filter(|_| format != Format::Json) {
+        print_timings(t);
+    }
+
+    if failed {
+        ExitCode::from(LINT_FAILURE)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+impl Overrides {
+    /// Loads the config for a target, unless one is given, and applies
+    /// the overrides to it.
+    fn load(self, config_path: Option<&Path>, target: &Target) -> Result<config::Config, Failure> {
+        let mut config = config::load(config_path, &target.config_root(), self.remote)
+            .map_err(Failure::Usage)?;
+        if let Some(profile) = self.profile {
+            config.set_profile(profile);
+        }
+        config.strict |= self.strict;
+        // Errors a baseline hides must not stop a target early.
+        config.max_errors = self.max_errors.filter(|_| !self.baseline);
+        config.exclude.skip_symlinks(!self.follow_symlinks);
+        config.exclude.skip_outside(!self.discover);
+        Ok(config)
+    }
+}
+
+impl Selection {
+    /// Returns the targets to lint, in argument order.
+    fn targets(&self) -> Result<Vec<Target>, Failure> {
+        if self.pre_commit {
+            let files = self.paths.iter().filter_map(|path| Some((path, precommit::kind(path)?)));
+            return files.map(|(path, kind)| file_target(path, Some(kind))).collect();
+        }
+        let mut targets = Vec::new();
+        if self.scope != Scope::User {
+            for path in &self.paths {
+                if path.is_file() {
+                    targets.push(file_target(path, self.kind)?);
+                    continue;
+                }
+                // A project may keep its CLAUDE.md beside a .claude it lacks.
+                let bare = path.file_name().is_some_and(|n| n == ".claude")
+                    && path.parent().is_some_and(|p| p.join("CLAUDE.md").is_file());
+                if self.discover && bare && !path.exists() {
+                    targets.push(Target::Dir(path.to_path_buf()));
+                    continue;
+                }
+                if !path.is_dir() {
+                    return Err(Failure::Io(format!(
+                        "{} is not a file or directory",
+                        path.display()
+                    )));
+                }
+                if self.kind.is_some() {
+                    return Err(Failure::Usage(format!(
+                        "--kind applies to files, and {} is a directory",
+                        path.display()
+                    )));
+                }
+                if self.recursive {
+                    let mut found = workspace::discover(path);
+                    if self.discover {
+                        workspace::drop_nested(&mut found);
+                    }
+                    if found.is_empty() {
+                        return Err(Failure::Io(format!(
+                            "no .claude directories or CLAUDE.md files under {}",
+                            path.display()
+                        )));
+                    }
+                    targets.extend(found);
+                } else {
+                    targets.push(Target::Dir(path.to_path_buf()));
+                }
+            }
+        }
+        // The combined scope skips a missing ~/.claude; --user alone needs it.
+        match crate::user_dir() {
+            Some(dir) if self.scope != Scope::Project && dir.is_dir() => {
+                targets.push(Target::User(dir))
+            }
+            _ if self.scope == Scope::User => {
+                return Err(Failure::Io("no user-level ~/.claude directory".to_string()))
+            }
+            _ => {}
+        }
+        Ok(targets)
+    }
+}
+
+/// Makes a target of a single file, of the given kind or the one its
+/// place in a .claude directory implies. A CLAUDE.md is linted as usual,
+/// imports and all.
+fn file_target(path: &Path, kind: Option<FileKind>) -> Result<Target, Failure> {
+    let abs = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let Some(kind) = kind.or_else(|| FileKind::infer(&abs)) else {
+        return Err(Failure::Usage(format!(
+            "{}: cannot tell what kind of file this is; pass --kind",
+            path.display()
+        )));
+    };
+    if kind == FileKind::ClaudeMd && rules::is_claude_md(path) {
+        return Ok(Target::ClaudeMd(path.to_path_buf()));
+    }
+    Ok(Target::File(path.to_path_buf(), kind))
+}
+
+/// Reads the text to lint with --stdin.
+fn read_stdin() -> Result<Vec<u8>, Failure> {
+    let mut buffer = Vec::new();
+    io::stdin()
+        .read_to_end(&mut buffer)
+        .map_err(|e| Failure::Io(format!("cannot read stdin: {}", e)))?;
+    Ok(buffer)
+}
+
+/// Lints text from stdin as the file at path, of the given kind or the
+/// one the path implies, with the config that file would have.
+fn lint_stdin(
+    text: &[u8],
+    path: &Path,
+    kind: Option<FileKind>,
+    config_path: Option<&Path>,
+    overrides: Overrides,
+) -> Result<Vec<(Target, Vec<Diagnostic>)>, Failure> {
+    let Some(kind) = kind.or_else(|| FileKind::infer(path)) else {
+        return Err(Failure::Usage(format!(
+            "{}: cannot tell what kind of file this is; pass --kind",
+            path.display()
+        )));
+    };
+    let target = Target::File(path.to_path_buf(), kind);
+    let config = overrides.load(config_path, &target)?;
+    let diags = lint_text_with(path, kind, text.to_vec(), &config);
+    Ok(vec![(target, diags)])
+}
+
+/// Lints each target with the config next to it, unless one is given,
+/// applying fixes first if asked. With a changed-file list, targets
+/// without changes are left out, as are files the config excludes.
+/// Verbose lists the excluded files. Each target's findings go through
+/// emit as soon as it is done.
+#[allow(clippy::too_many_arguments)]
+fn lint_all(
+    targets: Vec<Target>,
+    config_path: Option<&Path>,
+    overrides: Overrides,
+    fix_mode: Option<FixMode>,
+    use_cache: bool,
+    changed: Option<&[PathBuf]>,
+    verbose: bool,
+    emit: &mut dyn FnMut(&mut Vec<Diagnostic>),
+) -> Result<Vec<(Target, Vec<Diagnostic>)>, Failure> {
+    let lint = |target: &Target, config: &config::Config, cache: &Cache| match changed {
+        Some(files) => lint_target_changed(target, config, cache, files),
+        None => Some(lint_target_cached(target, config, cache)),
+    };
+    let user = targets.iter().find_map(|t| match t {
+        Target::User(dir) => Some(dir.clone()),
+        _ => None,
+    });
+    let mut results = Vec::new();
+    let mut errors = 0;
+    for target in targets {
+        let start = Instant::now();
+        log::write(
+            log::Level::Info,
+            "claude_lint",
+            format_args!("linting {}", target.path().display()),
+        );
+        let config = overrides.load(config_path, &target)?;
+        let skipped = skipped_files(&target, &config);
+        if verbose {
+            for path in &skipped {
+                eprintln!("skipped {}", path.display());
+            }
+        }
+        if matches!(target, Target::File(..)) && !skipped.is_empty() {
+            continue;
+        }
+        // A lone CLAUDE.md has nothing cacheable.
+        let cache_path = match &target {
+            Target::Dir(dir) | Target::User(dir) if use_cache => Some(cache::default_path(dir)),
+            _ => None,
+        };
+        let cache = cache_path.as_deref().map_or_else(Cache::empty, Cache::load);
+        let Some(mut diags) = lint(&target, &config, &cache) else {
+            continue;
+        };
+
+        if let Some(mode) = fix_mode {
+            if run_fixes(mode, &diags).map_err(Failure::Io)? {
+                diags = lint(&target, &config, &cache).unwrap_or_default();
+            }
+        }
+        if let (Target::Dir(dir), Some(user)) = (&target, &user) {
+            diags.extend(crate::lint_shadowed(dir, user, &config));
+            crate::sort(&mut diags);
+        }
+        if let Some(path) = cache_path {
+            if let Err(e) = cache.save(&path) {
+                eprintln!("warning: {}: cannot write cache: {}", path.display(), e);
+            }
+        }
+        if verbose {
+            print_evaluated(&target, &config, &diags);
+        }
+        let ms = start.elapsed().as_secs_f64() * 1000.0;
+        log::write(
+            log::Level::Info,
+            "claude_lint",
+            format_args!(
+                "linted {} in {:.1}ms: {} finding(s)",
+                target.path().display(),
+                ms,
+                diags.len()
+            ),
+        );
+        emit(&mut diags);
+        errors += count(&diags, Severity::Error);
+        results.push((target, diags));
+        if overrides.max_errors.is_some_and(|max| errors >= max) {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+/// Keeps findings up to the one that uses up the errors left, and drops
+/// the rest.
+fn cap(diags: &mut Vec<Diagnostic>, left: &mut usize) {
+    let mut end = diags.len();
+    for (i, d) in diags.iter().enumerate() {
+        if *left == 0 {
+            end = i;
+            break;
+        }
+        if d.severity == Severity::Error {
+            *left -= 1;
+        }
+    }
+    diags.truncate(end);
+}
+
+/// Lists, for --verbose, the files a target checked and whether each
+/// rule the config leaves on found anything.
+fn print_evaluated(target: &Target, config: &config::Config, diags: &[Diagnostic]) {
+    for path in linted_files(target, config) {
+        eprintln!("checked {}", path.display());
+    }
+    let root = target.config_root();
+    for rule in all_rules().into_iter().filter(|r| !config.disables(r.id, &root)) {
+        match diags.iter().filter(|d| d.rule == rule.id).count() {
+            0 => eprintln!("pass {} {}", rule.id, rule.name),
+            n => eprintln!("fail {} {} ({})", rule.id, rule.name, plural(n, "finding")),
+        }
+    }
+}
+
+/// Adds each target's dead links to its findings, passing them through
+/// emit first. With a changed-file list, only links in changed files are
+/// checked.
+fn add_url_findings(
+    results: &mut [(Target, Vec<Diagnostic>)],
+    config_path: Option<&Path>,
+    overrides: Overrides,
+    use_cache: bool,
+    changed: Option<&[PathBuf]>,
+    emit: &mut dyn FnMut(&mut Vec<Diagnostic>),
+) -> Result<(), Failure> {
+    for (target, diags) in results {
+        let config = overrides.load(config_path, target)?;
+        let mut files = linted_files(target, &config);
+        if let Some(changed) = changed {
+            files.retain(|f| fs::canonicalize(f).is_ok_and(|f| changed.contains(&f)));
+        }
+        let cache_path = match target {
+            Target::Dir(dir) | Target::User(dir) if use_cache => {
+                Some(urls::default_cache_path(dir))
+            }
+            _ => None,
+        };
+        let mut dead =
+            crate::check_urls(&files, &config, cache_path.as_deref()).map_err(Failure::Io)?;
+        emit(&mut dead);
+        diags.extend(dead);
+        crate::sort(diags);
+    }
+    Ok(())
+}
+
+/// Re-lints whenever a watched file changes, clearing the screen first.
+/// Runs until interrupted.
+fn watch_loop(
+    selection: &Selection,
+    config_path: Option<&Path>,
+    overrides: Overrides,
+    use_cache: bool,
+    verbose: bool,
+    text: Text,
+) -> ! {
+    let mut last = None;
+    loop {
+        // Rediscover targets so that new .claude directories are picked up.
+        let targets = selection.targets();
+        let stamp = targets.as_ref().map_or(0, |t| watch::stamp(t, config_path));
+        if last != Some(stamp) {
+            // Clear a terminal; separate runs piped elsewhere instead.
+            if io::stdout().is_terminal() {
+                print!("\x1b[2J\x1b[H");
+            } else if last.is_some() {
+                println!("{}", "-".repeat(72));
+            }
+            last = Some(stamp);
+            let _ = io::stdout().flush();
+            match targets.and_then(|t| {
+                lint_all(
+                    t,
+                    config_path,
+                    overrides,
+                    None,
+                    use_cache,
+                    None,
+                    verbose,
+                    &mut |_| {},
+                )
+            }) {
+                Ok(results) => {
+                    let diags: Vec<Diagnostic> = results
+                        .iter()
+                        .flat_map(|(_, d)| d.iter().cloned())
+                        .collect();
+                    let counts = component_counts(&results, config_path, overrides);
+                    print_results(&results, &counts, &diags, selection.recursive, text);
+                }
+                Err(Failure::Usage(e) | Failure::Io(e)) => eprintln!("error: {}", e),
+            }
+            eprintln!("\nwatching for changes (Ctrl-C to stop)");
+        }
+        thread::sleep(watch::INTERVAL);
+    }
+}
+
+/// Writes or previews fixes. Returns whether any file was rewritten.
+fn run_fixes(mode: FixMode, diags: &[Diagnostic]) -> Result<bool, String> {
+    let (changes, skipped) = fix::changes(diags);
+    for warning in skipped {
+        eprintln!("warning: {}", warning);
+    }
+    match mode {
+        FixMode::Write => {
+            for c in &changes {
+                fs::write(&c.path, &c.after)
+                    .map_err(|e| format!("{}: cannot write fix: {}", c.path.display(), e))?;
+            }
+            if !changes.is_empty() {
+                eprintln!("fixed {} file(s)", changes.len());
+            }
+            Ok(!changes.is_empty())
+        }
+        FixMode::DryRun => {
+            for c in changes {
+                let name = c.path.to_string_lossy();
+                print!("{}", fix::unified_diff(&name, &c.before, &c.after));
+            }
+            Ok(false)
+        }
+    }
+}
+
+// Output.
+
+/// Prints the slowest rules and files, for --timings.
+fn print_timings(timings: &Timings) {
+    let ms = |d: &Duration| format!("{:>10.3}ms", d.as_secs_f64() * 1000.0);
+    eprintln!("\nslowest rules:");
+    for (id, took) in &timings.rules {
+        let name = all_rules().into_iter().find(|r| r.id == *id).map_or("", |r| r.name);
+        eprintln!("{}  {} {}", ms(took), id, name);
+    }
+    eprintln!("slowest files:");
+    for (path, took) in &timings.files {
+        eprintln!("{}  {}", ms(took), path.display());
+    }
+}
+
+/// Prints every rule as an aligned table.
+fn print_rules() {
+    let rules = all_rules();
+    let width = rules.iter().map(|r| r.name.len()).max().unwrap_or(0);
+    for r in rules {
+        println!(
+            "{}  {:<7}  {:<width$}  {}",
+            r.id,
+            r.severity.as_str(),
+            r.name,
+            r.description,
+            width = width
+        );
+    }
+}
+
+/// Creates an agent or skill from `new KIND NAME [DIR]` and lints it.
+/// Returns whether it passes.
+fn new_file(args: &[String]) -> Result<bool, Failure> {
+    let (kind, name, root) = match args {
+        [kind, name] => (kind, name, PathBuf::from(".claude")),
+        [kind, name, dir] => (kind, name, PathBuf::from(dir)),
+        _ => {
+            return Err(Failure::Usage(
+                "usage: claude-lint new agent|skill NAME [DIR]".to_string(),
+            ))
+        }
+    };
+    scaffold::kebab_case(name).map_err(Failure::Usage)?;
+    let path = match kind.as_str() {
+        "agent" => scaffold::new_agent(&root, name).map_err(Failure::Io)?,
+        "skill" => scaffold::new_skill(&root, name).map_err(Failure::Io)?,
+        k => return Err(Failure::Usage(format!("cannot create '{}' (expected agent or skill)", k))),
+    };
+    println!("created {}{}", path.display(), if path.is_dir() { "/" } else { "" });
+    let config = config::load(None, &root, false).map_err(Failure::Usage)?;
+    let diags: Vec<Diagnostic> = crate::lint_dir_with(&root, &config)
+        .into_iter()
+        .filter(|d| d.path.starts_with(&path))
+        .collect();
+    print_diags(
+        &diags,
+        Text {
+            group: Group::None,
+            paint: Paint(Color::Auto.enabled()),
+            buffer: None,
+            owners: None,
+        },
+    );
+    Ok(count(&diags, Severity::Error) == 0)
+}
+
+/// Runs `config check`, which reports every problem with the config for
+/// a directory and returns whether there were none, or `config schema`.
+fn check_config(parsed: cli::Parsed) -> Result<bool, Failure> {
+    let remote = parsed.has("remote-config");
+    let config_path = parsed
+        .options
+        .into_iter()
+        .find_map(|(name, value)| (name == "config").then_some(value).flatten());
+    let root = PathBuf::from(parsed.operands.get(1).map_or(".claude", String::as_str));
+    match parsed.operands[0].as_str() {
+        // The schema is the same for every config, so --config, which
+        // may come from the environment, is ignored.
+        "schema" if parsed.operands.len() > 1 => Err(Failure::Usage(
+            "config schema takes no directory".to_string(),
+        )),
+        "schema" => {
+            print!("{}", schema::json());
+            Ok(true)
+        }
+        "check" => match config::check(config_path.as_deref().map(Path::new), &root, remote)
+            .map_err(Failure::Io)?
+        {
+            None => {
+                println!("ok: no {} for {}; the defaults apply", config::FILE_NAME, root.display());
+                Ok(true)
+            }
+            Some((path, problems)) if problems.is_empty() => {
+                println!("ok: {} is valid", path.display());
+                Ok(true)
+            }
+            Some((path, problems)) => {
+                for problem in &problems {
+                    println!("error: {}: {}", path.display(), problem);
+                }
+                println!("\n{} problem(s) in {}", problems.len(), path.display());
+                Ok(false)
+            }
+        },
+        c => Err(Failure::Usage(format!(
+            "unknown config command '{}' (expected check or schema)",
+            c
+        ))),
+    }
+}
+
+/// Prints an inventory of a .claude directory: counts, lines, and
+/// estimated tokens per kind of file, the largest files, and each file's
+/// name and description.
+fn print_graph(parsed: cli::Parsed) -> Result<(), Failure> {
+    let mut config_path = None;
+    let mut remote = false;
+    let mut json = false;
+    for (name, value) in parsed.options {
+        match (name, value.as_deref()) {
+            ("config", Some(p)) => config_path = Some(PathBuf::from(p)),
+            ("remote-config", _) => remote = true,
+            ("format", Some(f)) => json = f == "json",
+            _ => {}
+        }
+    }
+    let root = parsed
+        .operands
+        .into_iter()
+        .next()
+        .map_or_else(|| PathBuf::from(".claude"), PathBuf::from);
+    if !root.is_dir() {
+        return Err(Failure::Io(format!("{}: not a directory", root.display())));
+    }
+    let config = config::load(config_path.as_deref(), &root, remote).map_err(Failure::Usage)?;
+    let graph = Graph::collect(&root, &config);
+    if json {
+        println!("{}", graph.json());
+    } else {
+        print!("{}", graph.dot());
+    }
+    Ok(())
+}
+
+fn print_stats(parsed: cli::Parsed) -> Result<(), Failure> {
+    let mut config_path = None;
+    let mut remote = false;
+    let mut format = Format::Text;
+    for (name, value) in parsed.options {
+        match (name, value.as_deref()) {
+            ("config", Some(p)) => config_path = Some(PathBuf::from(p)),
+            ("remote-config", _) => remote = true,
+            ("format", Some("json")) => format = Format::Json,
+            _ => format = Format::Text,
+        }
+    }
+    let root = parsed
+        .operands
+        .into_iter()
+        .next()
+        .map_or_else(|| PathBuf::from(".claude"), PathBuf::from);
+    if !root.is_dir() {
+        return Err(Failure::Io(format!("{}: not a directory", root.display())));
+    }
+    let config = config::load(config_path.as_deref(), &root, remote).map_err(Failure::Usage)?;
+    let inventory = Inventory::collect(&root, &config);
+    if format == Format::Json {
+        println!("{}", inventory.json(LARGEST));
+        return Ok(());
+    }
+
+    let counts: Vec<String> = [
+        FileKind::Agent,
+        FileKind::Skill,
+        FileKind::Reference,
+        FileKind::Command,
+    ]
+    .iter()
+    .map(|&k| {
+        let n = inventory.totals(Some(k)).files;
+        let label = kind_label(k);
+        format!(
+            "{} {}",
+            n,
+            if n == 1 {
+                label.trim_end_matches('s')
+            } else {
+                label
+            }
+        )
+    })
+    .collect();
+    println!("{}: {}\n", root.display(), counts.join(", "));
+
+    let total = inventory.totals(None);
+    let digits = |n: usize| n.to_string().len();
+    let (lw, tw) = (digits(total.lines).max(5), digits(total.tokens).max(6));
+    println!("{:<10}  {:>5}  {:>lw$}  {:>tw$}", "", "files", "lines", "tokens", lw = lw, tw = tw);
+    let kinds = FileKind::ALL.iter().map(|&k| (kind_label(k), inventory.totals(Some(k))));
+    for (label, t) in kinds.chain([("total", total)]) {
+        println!(
+            "{:<10}  {:>5}  {:>lw$}  {:>tw$}",
+            label,
+            t.files,
+            t.lines,
+            t.tokens,
+            lw = lw,
+            tw = tw
+        );
+    }
+
+    let relative = |p: &Path| p.strip_prefix(&root).unwrap_or(p).display().to_string();
+    let largest = inventory.largest(LARGEST);
+    if !largest.is_empty() {
+        println!("\nlargest:");
+        for f in largest {
+            println!("  {:>tw$}  {}", f.tokens, relative(&f.path), tw = tw);
+        }
+    }
+
+    for kind in [FileKind::Agent, FileKind::Skill, FileKind::Command] {
+        let files: Vec<&stats::File> = inventory.files.iter().filter(|f| f.kind == kind).collect();
+        if files.is_empty() {
+            continue;
+        }
+        let name = |f: &stats::File| {
+            let fallback = if kind == FileKind::Skill {
+                f.path.parent()
+            } else {
+                Some(f.path.as_path())
+            };
+            f.name.clone().unwrap_or_else(|| {
+                fallback
+                    .and_then(Path::file_stem)
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        };
+        let row = |name: String, f: &stats::File| {
+            let description = f.description.as_deref().map_or_else(String::new, |d| clip(d, 60));
+            [name, plural(f.lines, "line"), plural(f.tokens, "token"), description]
+        };
+        let mut rows = Vec::new();
+        for f in files {
+            rows.push(row(name(f), f));
+            if kind == FileKind::Skill {
+                let dir = f.path.parent().unwrap_or(Path::new(""));
+                for r in inventory
+                    .files
+                    .iter()
+                    .filter(|r| r.kind == FileKind::Reference && r.path.starts_with(dir))
+                {
+                    rows.push(row(
+                        format!(
+                            "  {}",
+                            r.path.strip_prefix(dir).unwrap_or(&r.path).display()
+                        ),
+                        r,
+                    ));
+                }
+            }
+        }
+        let width = |i: usize| {
+            rows.iter()
+                .map(|r: &[String; 4]| r[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        let (nw, lw, tw) = (width(0), width(1), width(2));
+        println!("\n{}:", kind_label(kind));
+        for [name, lines, tokens, description] in &rows {
+            let line = format!(
+                "  {:<nw$}  {:>lw$}  {:>tw$}  {}",
+                name,
+                lines,
+                tokens,
+                description,
+                nw = nw,
+                lw = lw,
+                tw = tw
+            );
+            println!("{}", line.trim_end());
+        }
+    }
+
+    let readable: Vec<&stats::File> = inventory
+        .files
+        .iter()
+        .filter(|f| f.prose.words > 0)
+        .collect();
+    if !readable.is_empty() {
+        println!("\nreadability:");
+        let rows: Vec<[String; 3]> = readable
+            .iter()
+            .map(|f| {
+                [
+                    format!("{:.1} words/sentence", f.prose.sentence_length()),
+                    format!("grade {:>4.1}", f.prose.grade()),
+                    relative(&f.path),
+                ]
+            })
+            .collect();
+        let width = |i: usize| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0);
+        let (sw, gw) = (width(0), width(1));
+        for [length, grade, path] in &rows {
+            println!("  {:>sw$}  {:>gw$}  {}", length, grade, path, sw = sw, gw = gw);
+        }
+    }
+
+    println!(
+        "\ntokens are estimates; budget {} per file, {} in all",
+        config.tokens.max_per_file, config.tokens.max_total
+    );
+    Ok(())
+}
+
+/// How many files the stats list as the largest.
+const LARGEST: usize = 5;
+
+fn kind_label(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::ClaudeMd => "CLAUDE.md",
+        FileKind::Agent => "agents",
+        FileKind::Skill => "skills",
+        FileKind::Reference => "references",
+        FileKind::Command => "commands",
+    }
+}
+
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+/// Shortens text to at most max chars on one line.
+fn clip(text: &str, max: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= max {
+        return text;
+    }
+    let cut: String = text.chars().take(max - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+fn print_text(root: &Path, counts: Option<Counts>, diags: &[Diagnostic], text: Text) {
+    if diags.is_empty() {
+        println!("ok: {} passes all checks{}", root.display(), inventory(counts));
+        return;
+    }
+    print_diags(diags, text);
+    if text.group != Group::None {
+        eprintln!();
+        print_table(diags);
+    }
+    eprintln!("\n{}{}", summary(diags), inventory(counts));
+    print_fixable(diags);
+}
+
+/// Prints text output. Counts holds each directory target's agents,
+/// skills, and commands, in the order of results.
+fn print_results(
+    results: &[(Target, Vec<Diagnostic>)],
+    counts: &[Option<Counts>],
+    diags: &[Diagnostic],
+    recursive: bool,
+    text: Text,
+) {
+    if results.is_empty() {
+        println!("ok: no files to lint");
+    } else if results.len() == 1 && !recursive {
+        print_text(results[0].0.path(), counts[0], diags, text);
+    } else {
+        print_workspace(results, counts, diags, text);
+    }
+}
+
+/// Prints results grouped per target, then a workspace total.
+fn print_workspace(
+    results: &[(Target, Vec<Diagnostic>)],
+    counts: &[Option<Counts>],
+    diags: &[Diagnostic],
+    text: Text,
+) {
+    for ((target, diags), &counts) in results.iter().zip(counts) {
+        let label = match target {
+            Target::User(dir) => format!("{} (user)", dir.display()),
+            _ => target.path().display().to_string(),
+        };
+        if diags.is_empty() {
+            println!("ok: {} passes all checks{}", label, inventory(counts));
+            continue;
+        }
+        print_diags(diags, text);
+        eprintln!("{}: {}{}\n", label, summary(diags), inventory(counts));
+    }
+
+    let failing = results.iter().filter(|(_, d)| !d.is_empty()).count();
+    if failing == 0 {
+        println!("ok: {} target(s) pass all checks", results.len());
+        return;
+    }
+    if text.group != Group::None {
+        print_table(diags);
+        eprintln!();
+    }
+    eprintln!("{} of {} target(s) with findings: {}", failing, results.len(), summary(diags));
+    print_fixable(diags);
+}
+
+fn print_diags(diags: &[Diagnostic], text: Text) {
+    let paint = text.paint;
+    let label = |d: &Diagnostic| {
+        format!(
+            "{}{}",
+            paint.severity(d.severity),
+            paint.bold(&format!("[{}]", d.rule))
+        )
+    };
+    match text.group {
+        Group::None => {
+            // Diagnostics are sorted by path, so one file is read at a time.
+            let mut source: Option<(&Path, Option<String>)> = None;
+            for d in diags {
+                match d.span {
+                    Some(s) => eprintln!(
+                        "{}: {}:{}: {}",
+                        label(d),
+                        d.path.display(),
+                        s.line,
+                        d.message
+                    ),
+                    None => eprintln!("{}: {}: {}", label(d), d.path.display(), d.message),
+                }
+                if d.span.is_none() {
+                    continue;
+                }
+                if source.as_ref().is_none_or(|(p, _)| *p != d.path) {
+                    let content = text
+                        .buffer
+                        .map(str::to_string)
+                        .or_else(|| fs::read_to_string(&d.path).ok());
+                    source = Some((&d.path, content));
+                }
+                if let Some((_, Some(content))) = &source {
+                    eprint!("{}", terminal::snippet(d, content, paint));
+                }
+            }
+        }
+        Group::File => {
+            let width = diags
+                .iter()
+                .filter_map(|d| d.span)
+                .map(|s| s.line.to_string().len())
+                .max()
+                .unwrap_or(0);
+            for (i, d) in diags.iter().enumerate() {
+                if i == 0 || d.path != diags[i - 1].path {
+                    if i > 0 {
+                        eprintln!();
+                    }
+                    eprintln!("{}", paint.bold(&d.path.display().to_string()));
+                }
+                let line = d.span.map_or_else(String::new, |s| s.line.to_string());
+                eprintln!(
+                    "  {}  {}: {}",
+                    paint.gutter(&format!("{:>width$}", line, width = width)),
+                    label(d),
+                    d.message
+                );
+            }
+        }
+        Group::Rule => {
+            // The sort is stable, so each rule's findings stay in path order.
+            let mut by_rule: Vec<&Diagnostic> = diags.iter().collect();
+            by_rule.sort_by_key(|d| d.rule);
+            for (i, d) in by_rule.iter().enumerate() {
+                if i == 0 || d.rule != by_rule[i - 1].rule {
+                    if i > 0 {
+                        eprintln!();
+                    }
+                    eprintln!("{} {}", paint.bold(d.rule), d.rule().name);
+                }
+                match d.span {
+                    Some(s) => eprintln!(
+                        "  {}: {}:{}: {}",
+                        paint.severity(d.severity),
+                        d.path.display(),
+                        s.line,
+                        d.message
+                    ),
+                    None => eprintln!(
+                        "  {}: {}: {}",
+                        paint.severity(d.severity),
+                        d.path.display(),
+                        d.message
+                    ),
+                }
+            }
+        }
+        Group::Owner => {
+            let owners = text.owners.expect("grouping by owner needs CODEOWNERS");
+            let owner = |d: &Diagnostic| owners.of(&d.path).join(" ");
+            // Unowned findings go last; the sort keeps each owner's in path order.
+            let mut by_owner: Vec<(String, &Diagnostic)> =
+                diags.iter().map(|d| (owner(d), d)).collect();
+            by_owner.sort_by(|a, b| (a.0.is_empty(), &a.0).cmp(&(b.0.is_empty(), &b.0)));
+            for (i, (owner, d)) in by_owner.iter().enumerate() {
+                if i == 0 || *owner != by_owner[i - 1].0 {
+                    if i > 0 {
+                        eprintln!();
+                    }
+                    eprintln!(
+                        "{}",
+                        paint.bold(if owner.is_empty() {
+                            "(no owner)"
+                        } else {
+                            owner
+                        })
+                    );
+                }
+                match d.span {
+                    Some(s) => eprintln!(
+                        "  {}: {}:{}: {}",
+                        label(d),
+                        d.path.display(),
+                        s.line,
+                        d.message
+                    ),
+                    None => eprintln!("  {}: {}: {}", label(d), d.path.display(), d.message),
+                }
+            }
+        }
+    }
+}
+
+/// Prints the findings per rule and severity, with a total row.
+fn print_table(diags: &[Diagnostic]) {
+    const SEVERITIES: [Severity; 3] = [Severity::Error, Severity::Warning, Severity::Info];
+    let mut by_rule: Vec<&Diagnostic> = diags.iter().collect();
+    by_rule.sort_by_key(|d| d.rule);
+    let mut rows: Vec<(&str, &str, [usize; 3])> = Vec::new();
+    let mut total = [0; 3];
+    for d in by_rule {
+        let i = SEVERITIES.iter().position(|&s| s == d.severity).expect("every severity is listed");
+        if rows.last().is_none_or(|r| r.0 != d.rule) {
+            rows.push((d.rule, d.rule().name, [0; 3]));
+        }
+        rows.last_mut().expect("a row was just pushed").2[i] += 1;
+        total[i] += 1;
+    }
+    let id = rows.iter().map(|r| r.0.len()).max().unwrap_or(0).max("total".len());
+    let name = rows.iter().map(|r| r.1.len()).max().unwrap_or(0);
+    let row = |rule: &str, rule_name: &str, n: [String; 3]| {
+        eprintln!(
+            "{:id$}  {:name$}  {:>6}  {:>8}  {:>4}",
+            rule,
+            rule_name,
+            n[0],
+            n[1],
+            n[2],
+            id = id,
+            name = name
+        );
+    };
+    row("rule", "", ["errors", "warnings", "info"].map(str::to_string));
+    for (rule, rule_name, n) in rows {
+        row(rule, rule_name, n.map(|n| n.to_string()));
+    }
+    row("total", "", total.map(|n| n.to_string()));
+}
+
+/// Renders counts like "2 error(s), 1 warning(s)".
+fn summary(diags: &[Diagnostic]) -> String {
+    let mut parts = Vec::new();
+    for (severity, label) in [
+        (Severity::Error, "error(s)"),
+        (Severity::Warning, "warning(s)"),
+        (Severity::Info, "info"),
+    ] {
+        let n = count(diags, severity);
+        if n > 0 {
+            parts.push(format!("{} {}", n, label));
+        }
+    }
+    parts.join(", ")
+}
+
+/// Renders a directory's counts like " (3 agents, 1 skill, 0 commands)",
+/// or nothing for a target that is not a directory.
+fn inventory(counts: Option<Counts>) -> String {
+    counts.map_or_else(String::new, |c| {
+        format!(
+            " ({}, {}, {})",
+            plural(c.agents, "agent"),
+            plural(c.skills, "skill"),
+            plural(c.commands, "command")
+        )
+    })
+}
+
+/// Counts the agents, skills, and commands of each directory target.
+fn component_counts(
+    results: &[(Target, Vec<Diagnostic>)],
+    config_path: Option<&Path>,
+    overrides: Overrides,
+) -> Vec<Option<Counts>> {
+    results
+        .iter()
+        .map(|(target, _)| {
+            overrides
+                .load(config_path, target)
+                .ok()
+                .and_then(|c| crate::component_counts(target, &c))
+        })
+        .collect()
+}
+
+fn print_fixable(diags: &[Diagnostic]) {
+    let fixable = diags.iter().filter(|d| d.fix.is_some()).count();
+    if fixable > 0 {
+        eprintln!("{} fixable with --fix", fixable);
+    }
+}
@@ -0,0 +1,145 @@
+//! Files a skill carries beside SKILL.md: scripts in `scripts/` and
+//! templates in `templates/`.
+//!
+//! Scripts are run, so each needs a shebang and the executable bit, and
+//! SKILL.md has to mention it or the model never learns it exists.
+//! Templates are copied or filled in, so each one SKILL.md names has to
+//! exist.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::glob;
+use crate::hooks::is_executable;
+use crate::jobs;
+use crate::rules::{Check, Document, FileKind};
+use crate::{Diagnostic, Span};
+
+/// Flags scripts without a shebang line or the executable bit.
+pub(crate) struct Scripts;
+
+impl Check for Scripts {
+    fn id(&self) -> &'static str {
+        "CL038"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut diags = Vec::new();
+        for path in scripts(skill_dir(doc)) {
+            let bytes = fs::read(&path).unwrap_or_default();
+            if !bytes.starts_with(b"#!") {
+                diags.push(Diagnostic::of(
+                    &path,
+                    self.id(),
+                    "script has no shebang line".to_string(),
+                ));
+            }
+            if !is_executable(&path) {
+                diags.push(Diagnostic::of(
+                    &path,
+                    self.id(),
+                    "script is not executable".to_string(),
+                ));
+            }
+        }
+        diags
+    }
+}
+
+/// Flags scripts SKILL.md does not mention, and templates it names that
+/// do not exist.
+pub(crate) struct AssetReferences;
+
+impl Check for AssetReferences {
+    fn id(&self) -> &'static str {
+        "CL039"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let dir = skill_dir(doc);
+        let mut diags = Vec::new();
+        for path in scripts(dir) {
+            let rel = path.strip_prefix(dir).map(glob::slashed).unwrap_or_default();
+            if mentions(doc.content, "scripts/").iter().all(|(_, _, p)| *p != rel) {
+                let msg = format!("script '{}' is not mentioned in SKILL.md", rel);
+                diags.push(Diagnostic::of(doc.path, self.id(), msg));
+            }
+        }
+        for (line, column, rel) in mentions(doc.content, "templates/") {
+            if !dir.join(&rel).exists() {
+                let span = Span { line, column, length: rel.chars().count() };
+                let msg = format!("template '{}' does not exist", rel);
+                diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(span)));
+            }
+        }
+        diags
+    }
+}
+
+/// Returns the bytes a skill's asset findings depend on beyond SKILL.md:
+/// each script's path, mode, and content. Cache keys hash them in.
+pub(crate) fn state(dir: &Path) -> Vec<u8> {
+    let mut out = Vec::new();
+    for path in scripts(dir) {
+        out.extend_from_slice(path.to_string_lossy().as_bytes());
+        out.push(u8::from(is_executable(&path)));
+        out.extend(fs::read(&path).unwrap_or_default());
+    }
+    out
+}
+
+fn skill_dir<'a>(doc: &Document<'a>) -> &'a Path {
+    doc.path.parent().unwrap_or(Path::new(""))
+}
+
+/// Returns the files in a skill's scripts/ and its subdirectories,
+/// sorted.
+fn scripts(dir: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    for dir in jobs::dirs_within(&dir.join("scripts"), usize::MAX) {
+        out.extend(
+            fs::read_dir(&dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| !p.is_dir()),
+        );
+    }
+    out.sort();
+    out
+}
+
+/// Finds paths under a skill directory that text names, as in
+/// `scripts/build.sh` or `./templates/pr.md`, with the line and column
+/// each starts at. A path ends at whitespace, quotes, brackets, or
+/// punctuation that closes a sentence.
+fn mentions(text: &str, prefix: &str) -> Vec<(usize, usize, String)> {
+    let mut out = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        for (start, _) in line.match_indices(prefix) {
+            let before = line[..start].strip_suffix("./").unwrap_or(&line[..start]);
+            if before.ends_with(|c: char| c.is_alphanumeric() || matches!(c, '/' | '.' | '-' | '_'))
+            {
+                continue;
+            }
+            let rest = &line[start..];
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "`'\"()[]<>,;".contains(c))
+                .unwrap_or(rest.len());
+            let path = rest[..end].trim_end_matches(['.', ':', '!', '?']);
+            if path.len() > prefix.len() {
+                out.push((i + 1, line[..start].chars().count() + 1, path.to_string()));
+            }
+        }
+    }
+    out
+}
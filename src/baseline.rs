@@ -0,0 +1,102 @@
+//! Baselines of accepted findings.
+//!
+//! A baseline records a repository's existing findings so that only new
+//! ones fail the run. Findings are matched by rule, path, message, and
+//! the text of the flagged line, not by line number, so edits elsewhere
+//! in a file do not resurface them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::json::{self, Value};
+use crate::{cache, Diagnostic};
+
+pub struct Baseline {
+    /// How many findings with each fingerprint are accepted.
+    counts: HashMap<u64, usize>,
+}
+
+/// Returns a finding's fingerprint.
+pub fn fingerprint(d: &Diagnostic) -> u64 {
+    let line = d
+        .span
+        .and_then(|s| {
+            fs::read_to_string(&d.path)
+                .ok()?
+                .lines()
+                .nth(s.line - 1)
+                .map(|l| l.trim().to_string())
+        })
+        .unwrap_or_default();
+    cache::hash(&[
+        d.rule.as_bytes(),
+        d.path.to_string_lossy().as_bytes(),
+        d.message.as_bytes(),
+        line.as_bytes(),
+    ])
+}
+
+/// Renders a baseline holding diags. Rule, path, and message are kept
+/// alongside each fingerprint for review; only the fingerprint is read
+/// back.
+pub fn render(diags: &[Diagnostic]) -> String {
+    let mut out = String::from("{\"version\":1,\"findings\":[");
+    for (i, d) in diags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&format!(
+            "\n{{\"rule\":{},\"path\":{},\"message\":{},\"fingerprint\":\"{:016x}\"}}",
+            json::string(d.rule),
+            json::string(&d.path.to_string_lossy()),
+            json::string(&d.message),
+            fingerprint(d),
+        ));
+    }
+    out.push_str("\n]}\n");
+    out
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let src = fs::read_to_string(path)
+            .map_err(|e| format!("{}: cannot read baseline: {}", path.display(), e))?;
+        let doc = json::parse(&src).map_err(|e| {
+            format!(
+                "{}:{}:{}: {}",
+                path.display(),
+                e.pos.line,
+                e.pos.column,
+                e.message
+            )
+        })?;
+        let findings = doc
+            .get("findings")
+            .and_then(Value::as_array)
+            .ok_or_else(|| format!("{}: not a claude-lint baseline", path.display()))?;
+        let mut counts = HashMap::new();
+        for f in findings {
+            let hex = f.get("fingerprint").and_then(Value::as_str).unwrap_or_default();
+            let Ok(n) = u64::from_str_radix(hex, 16) else {
+                return Err(format!("{}: invalid fingerprint '{}'", path.display(), hex));
+            };
+            *counts.entry(n).or_insert(0) += 1;
+        }
+        Ok(Baseline { counts })
+    }
+
+    /// Drops findings the baseline accepts and returns how many it
+    /// dropped. A fingerprint recorded n times accepts n findings.
+    pub fn filter(&mut self, diags: &mut Vec<Diagnostic>) -> usize {
+        let before = diags.len();
+        diags.retain(|d| match self.counts.get_mut(&fingerprint(d)) {
+            Some(n) if *n > 0 => {
+                *n -= 1;
+                false
+            }
+            _ => true,
+        });
+        before - diags.len()
+    }
+}
@@ -0,0 +1,241 @@
+//! On-disk cache of per-file results.
+//!
+//! Each cacheable job is keyed by a hash of the tool version, the file
+//! layout of the linted directory, the job's path, and its file's
+//! content. Findings are stored before config is applied, so changing
+//! `.claude-lint.toml` never needs a full rerun. Cross-file checks that
+//! read more than one file (CLAUDE.md imports, settings, `.mcp.json`)
+//! are never cached.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::fix::Edit;
+use crate::json::{self, Value};
+use crate::log;
+use crate::{Diagnostic, Severity, Span, RULES};
+
+pub const FILE_NAME: &str = ".claude-lint-cache";
+
+pub struct Cache {
+    old: HashMap<u64, Vec<Diagnostic>>,
+    /// Entries used or produced by this run; only these are saved.
+    new: Mutex<HashMap<u64, Vec<Diagnostic>>>,
+}
+
+/// Returns where the cache for a linted directory lives: next to it, like
+/// the config.
+pub fn default_path(root: &Path) -> PathBuf {
+    root.parent().unwrap_or(Path::new("")).join(FILE_NAME)
+}
+
+impl Cache {
+    pub fn empty() -> Self {
+        Cache { old: HashMap::new(), new: Mutex::new(HashMap::new()) }
+    }
+
+    /// Loads a cache file. A missing, corrupt, or other-version cache is
+    /// treated as empty.
+    pub fn load(path: &Path) -> Self {
+        let mut cache = Cache::empty();
+        let Ok(src) = fs::read_to_string(path) else {
+            log::debug!("no cache at {}", path.display());
+            return cache;
+        };
+        let Ok(doc) = json::parse(&src) else {
+            log::debug!("{}: corrupt cache, ignored", path.display());
+            return cache;
+        };
+        if doc.get("version").and_then(Value::as_str) != Some(env!("CARGO_PKG_VERSION")) {
+            log::debug!("{}: cache from another version, ignored", path.display());
+            return cache;
+        }
+        for entry in doc
+            .get("entries")
+            .and_then(Value::as_array)
+            .unwrap_or_default()
+        {
+            let key = entry
+                .get("key")
+                .and_then(Value::as_str)
+                .and_then(|k| u64::from_str_radix(k, 16).ok());
+            let diags = entry
+                .get("diagnostics")
+                .and_then(Value::as_array)
+                .map(|ds| ds.iter().map(read_diagnostic).collect::<Option<Vec<_>>>());
+            if let (Some(key), Some(Some(diags))) = (key, diags) {
+                cache.old.insert(key, diags);
+            }
+        }
+        log::debug!("loaded {} cache entries from {}", cache.old.len(), path.display());
+        cache
+    }
+
+    /// Writes the entries this run used, dropping stale ones. Skips the
+    /// write when nothing changed.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if !self.changed() && path.is_file() {
+            return Ok(());
+        }
+        write(path, &self.new.lock().expect("no lint job panicked"))
+    }
+
+    /// Writes the loaded entries, for a process that rolled over entries
+    /// it has not saved yet.
+    pub(crate) fn save_loaded(&self, path: &Path) -> io::Result<()> {
+        write(path, &self.old)
+    }
+
+    /// Whether this run used or produced other entries than were loaded.
+    pub(crate) fn changed(&self) -> bool {
+        let new = self.new.lock().expect("no lint job panicked");
+        new.len() != self.old.len() || !new.keys().all(|k| self.old.contains_key(k))
+    }
+
+    /// Keeps every loaded entry through the next save, for runs that
+    /// only visit some files.
+    pub(crate) fn keep_all(&self) {
+        let mut new = self.new.lock().expect("no lint job panicked");
+        for (key, diags) in &self.old {
+            new.entry(*key).or_insert_with(|| diags.clone());
+        }
+    }
+
+    /// Makes the entries this run used the loaded ones, for a process
+    /// that lints again without reading the cache back from disk.
+    pub fn roll_over(&mut self) {
+        self.old = std::mem::take(self.new.get_mut().expect("no lint job panicked"));
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<Vec<Diagnostic>> {
+        let diags = self.old.get(&key)?.clone();
+        self.put(key, diags.clone());
+        Some(diags)
+    }
+
+    pub(crate) fn put(&self, key: u64, diags: Vec<Diagnostic>) {
+        self.new.lock().expect("no lint job panicked").insert(key, diags);
+    }
+}
+
+/// Writes cache entries to path.
+fn write(path: &Path, entries: &HashMap<u64, Vec<Diagnostic>>) -> io::Result<()> {
+    let mut keys: Vec<&u64> = entries.keys().collect();
+    keys.sort();
+
+    let mut out = format!(
+        "{{\"version\":{},\"entries\":[",
+        json::string(env!("CARGO_PKG_VERSION"))
+    );
+    for (i, key) in keys.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&format!("\n{{\"key\":\"{:016x}\",\"diagnostics\":[", key));
+        for (j, d) in entries[key].iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            out.push_str(&write_diagnostic(d));
+        }
+        out.push_str("]}");
+    }
+    out.push_str("\n]}\n");
+    fs::write(path, out)
+}
+
+/// FNV-1a over a sequence of byte strings, each followed by a separator
+/// so that ("ab", "c") and ("a", "bc") hash differently.
+pub(crate) fn hash(parts: &[&[u8]]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for &b in part.iter().chain(&[0xff]) {
+            h ^= u64::from(b);
+            h = h.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    h
+}
+
+/// Hashes the version and the sorted list of paths under root. Adding,
+/// removing, or renaming any file changes it, which covers checks that
+/// look at more than their own file, like link targets and orphaned
+/// references.
+pub(crate) fn fingerprint(root: &Path) -> u64 {
+    let mut paths = Vec::new();
+    list(root, &mut paths);
+    paths.sort();
+    let mut parts: Vec<&[u8]> = vec![env!("CARGO_PKG_VERSION").as_bytes()];
+    parts.extend(paths.iter().map(|p| p.as_bytes()));
+    hash(&parts)
+}
+
+fn list(dir: &Path, out: &mut Vec<String>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        out.push(path.to_string_lossy().into_owned());
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            list(&path, out);
+        }
+    }
+}
+
+fn write_diagnostic(d: &Diagnostic) -> String {
+    let span = d.span.map_or("null".to_string(), |s| {
+        format!("[{},{},{}]", s.line, s.column, s.length)
+    });
+    let fix = d.fix.as_ref().map_or("null".to_string(), |e| {
+        format!("[{},{},{}]", e.start, e.end, json::string(&e.text))
+    });
+    format!(
+        "{{\"path\":{},\"rule\":{},\"severity\":{},\"message\":{},\"span\":{},\"fix\":{}}}",
+        json::string(&d.path.to_string_lossy()),
+        json::string(d.rule),
+        json::string(d.severity.as_str()),
+        json::string(&d.message),
+        span,
+        fix,
+    )
+}
+
+fn read_diagnostic(v: &Value) -> Option<Diagnostic> {
+    let path = v.get("path")?.as_str()?;
+    // Only rules this build knows; Diagnostic::new insists on it.
+    let rule = RULES.iter().find(|r| Some(r.id) == v.get("rule").and_then(Value::as_str))?.id;
+    let message = v.get("message")?.as_str()?.to_string();
+    let number = |v: &Value| match v.kind {
+        json::Kind::Number(n) if n >= 0.0 => Some(n as usize),
+        _ => None,
+    };
+    let span = match v.get("span")?.as_array() {
+        Some([line, column, length]) => Some(Span {
+            line: number(line)?,
+            column: number(column)?,
+            length: number(length)?,
+        }),
+        Some(_) => return None,
+        None => None,
+    };
+    let fix = match v.get("fix")?.as_array() {
+        Some([start, end, text]) => Some(Edit {
+            start: number(start)?,
+            end: number(end)?,
+            text: text.as_str()?.to_string(),
+        }),
+        Some(_) => return None,
+        None => None,
+    };
+    let mut d = Diagnostic::of(Path::new(path), rule, message).at(span);
+    // Checks may lower a finding below its rule's default.
+    d.severity = match v.get("severity")?.as_str()? {
+        "error" => Severity::Error,
+        "warning" => Severity::Warning,
+        "info" => Severity::Info,
+        _ => return None,
+    };
+    d.fix = fix;
+    Some(d)
+}
@@ -0,0 +1,2619 @@
+//! The built-in checks: one [`Check`] per rule that looks at a single
+//! file, plus the directory checks that run before them.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::assets;
+use crate::frontmatter::{self, Field, Value};
+use crate::glob::{self, Glob};
+use crate::ignore::Ignore;
+use crate::jobs;
+use crate::links::{self, local_target, normalize};
+use crate::markdown::{self, Heading, Kind, Line, Markdown};
+use crate::prose::{self, Phrase, Position};
+use crate::readability;
+use crate::rules::{Check, Document, FileKind};
+use crate::{config, fix, Diagnostic, Span};
+
+/// Every built-in per-file check, in the order they run.
+pub(crate) const BUILTIN: &[&dyn Check] = &[
+    &MissingFrontmatter,
+    &InvalidFrontmatter,
+    &RequiredFields,
+    &UnknownFields,
+    &FieldTypes,
+    &AgentName,
+    &SkillName,
+    &SkillDescription,
+    &Phrases {
+        id: "CL010",
+        kinds: &[FileKind::ClaudeMd],
+        label: "workflow verb",
+        phrases: WORKFLOW_VERBS,
+    },
+    &Phrases {
+        id: "CL032",
+        kinds: &[FileKind::Skill],
+        label: "success criteria term",
+        phrases: SUCCESS_TERMS,
+    },
+    &SuccessCriteria,
+    &CodeBlocks,
+    &Procedural,
+    &Arguments,
+    &ReferencesSection,
+    &DanglingReferences,
+    &OrphanedReferences,
+    &links::BrokenLinks,
+    &MachinePaths,
+    &assets::Scripts,
+    &assets::AssetReferences,
+    &SingleTitle,
+    &HeadingLevels,
+    &DuplicateHeadings,
+    &EmptySections,
+];
+
+fn count_lines(s: &str) -> usize {
+    s.lines().count()
+}
+
+// Directory checks.
+
+/// Reports a CLAUDE.md missing. Reading it reports the rest.
+pub fn check_claude_md(path: &Path, diags: &mut Vec<Diagnostic>) {
+    if !path.is_file() {
+        diags.push(Diagnostic::of(path, "CL001", "missing file".to_string()));
+    }
+}
+
+/// Reports a skill directory without a SKILL.md.
+pub fn check_skill_dir(skill_dir: &Path, diags: &mut Vec<Diagnostic>) {
+    if !skill_dir.join("SKILL.md").is_file() {
+        diags.push(Diagnostic::of(
+            skill_dir,
+            "CL003",
+            "missing SKILL.md".to_string(),
+        ));
+    }
+}
+
+/// Reports files in agents/ and the given subdirectories of it that are
+/// not Markdown, which Claude Code never loads. Dotfiles, like
+/// `.gitkeep`, are left alone.
+pub fn check_agents_dir(dirs: &[PathBuf], exclude: &Ignore, diags: &mut Vec<Diagnostic>) {
+    for dir in dirs {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .collect();
+        paths.sort();
+        for path in paths {
+            let hidden = path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            if hidden || !path.is_file() || markdown::is_markdown(&path) || exclude.excludes(&path)
+            {
+                continue;
+            }
+            diags.push(Diagnostic::of(
+                &path,
+                "CL027",
+                "not a Markdown file, so it is never loaded as an agent".to_string(),
+            ));
+        }
+    }
+}
+
+/// Reports symlinks in a directory and its subdirectories whose targets
+/// are missing or loop. Links are not followed here, so a linked
+/// directory is not walked twice.
+pub fn check_symlinks(dir: &Path, exclude: &Ignore, diags: &mut Vec<Diagnostic>) {
+    let mut entries: Vec<fs::DirEntry> =
+        fs::read_dir(dir).into_iter().flatten().flatten().collect();
+    entries.sort_by_key(|e| e.path());
+    for entry in entries {
+        let path = entry.path();
+        let Ok(kind) = entry.file_type() else {
+            continue;
+        };
+        if exclude.excludes(&path) {
+            continue;
+        }
+        if kind.is_dir() {
+            check_symlinks(&path, exclude, diags);
+        } else if kind.is_symlink() && fs::metadata(&path).is_err() {
+            let target =
+                fs::read_link(&path).map_or_else(|_| "?".to_string(), |t| t.display().to_string());
+            diags.push(Diagnostic::of(
+                &path,
+                "CL113",
+                format!("symlink to '{}' does not resolve", target),
+            ));
+        }
+    }
+}
+
+// Prose checks.
+
+const WORKFLOW_VERBS: &[Phrase] = &[
+    Phrase { text: "must then", position: Position::Anywhere },
+    Phrase { text: "next,", position: Position::SentenceStart },
+    Phrase { text: "step 1", position: Position::Anywhere },
+    Phrase { text: "step 2", position: Position::Anywhere },
+    Phrase { text: "first,", position: Position::SentenceStart },
+    Phrase { text: "second,", position: Position::SentenceStart },
+    Phrase { text: "finally,", position: Position::SentenceStart },
+    Phrase { text: "afterward", position: Position::Anywhere },
+    Phrase { text: "afterwards", position: Position::Anywhere },
+    Phrase { text: "subsequently", position: Position::Anywhere },
+];
+
+const SUCCESS_TERMS: &[Phrase] = &[
+    Phrase { text: "success criteria", position: Position::Anywhere },
+    Phrase { text: "must ensure", position: Position::Anywhere },
+    Phrase { text: "must verify", position: Position::Anywhere },
+    Phrase { text: "requirement:", position: Position::Anywhere },
+    Phrase { text: "requirements:", position: Position::Anywhere },
+    Phrase { text: "you must", position: Position::Anywhere },
+];
+
+/// Instructions to check a result, at the start of a sentence or list
+/// item.
+const VERIFY_PHRASES: &[Phrase] = &[
+    Phrase { text: "ensure", position: Position::SentenceStart },
+    Phrase { text: "verify", position: Position::SentenceStart },
+    Phrase { text: "confirm", position: Position::SentenceStart },
+    Phrase { text: "make sure", position: Position::SentenceStart },
+    Phrase { text: "check that", position: Position::SentenceStart },
+];
+
+/// Words in the line before an ordered list that make it a list of
+/// acceptance criteria.
+const ACCEPTANCE_WORDS: &[&str] = &[
+    "acceptance",
+    "accepted",
+    "criteria",
+    "done when",
+    "definition of done",
+    "checklist",
+];
+
+/// Flags success criteria that no fixed phrase catches in skills:
+/// sentences that tell the reader to verify a result, ordered lists
+/// introduced as acceptance criteria, and checkbox task lists.
+struct SuccessCriteria;
+
+impl Check for SuccessCriteria {
+    fn id(&self) -> &'static str {
+        "CL032"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut diags = Vec::new();
+        for phrase in VERIFY_PHRASES {
+            for span in prose::find(&doc.markdown, phrase) {
+                let msg = format!("contains verification instruction '{}'", phrase.text);
+                diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(span)));
+            }
+        }
+
+        let lines = &doc.markdown.lines;
+        for (i, line) in lines.iter().enumerate() {
+            let Kind::ListItem { ordered } = line.kind else {
+                continue;
+            };
+            let before = lines[..i].iter().rev().find(|l| l.kind != Kind::Blank);
+            let first_item = before.is_none_or(|l| !matches!(l.kind, Kind::ListItem { .. }));
+            let span = Span {
+                line: line.number,
+                column: line.text.len() - line.text.trim_start().len() + 1,
+                length: 1,
+            };
+            // Checkboxes are reported once per run of them.
+            if checkbox(line) && !before.is_some_and(checkbox) {
+                diags.push(
+                    Diagnostic::of(
+                        doc.path,
+                        self.id(),
+                        "contains checkbox task list".to_string(),
+                    )
+                    .at(Some(span)),
+                );
+            } else if ordered && first_item {
+                let intro = before.map(|l| l.text.to_lowercase()).unwrap_or_default();
+                if let Some(word) = ACCEPTANCE_WORDS.iter().find(|w| intro.contains(*w)) {
+                    let msg = format!(
+                        "contains numbered acceptance list, introduced with '{}'",
+                        word
+                    );
+                    diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(span)));
+                }
+            }
+        }
+        diags
+    }
+}
+
+/// Whether a line is a task list item, as in `- [ ] done`.
+fn checkbox(line: &Line) -> bool {
+    let Some((_, at)) = markdown::list_marker(line.text) else {
+        return false;
+    };
+    let content = line.text[at..].trim_start();
+    ["[ ]", "[x]", "[X]"].iter().any(|mark| content.starts_with(mark))
+}
+
+/// Flags banned phrases in prose.
+struct Phrases {
+    id: &'static str,
+    kinds: &'static [FileKind],
+    /// What a match is called in the message.
+    label: &'static str,
+    phrases: &'static [Phrase],
+}
+
+impl Check for Phrases {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut diags = Vec::new();
+        for phrase in self.phrases {
+            for span in prose::find(&doc.markdown, phrase) {
+                diags.push(Diagnostic::of(
+                    doc.path,
+                    self.id,
+                    format!("contains {} '{}'", self.label, phrase.text),
+                ).at(Some(span)));
+            }
+        }
+        diags
+    }
+}
+
+/// Reports the first fenced code block, backtick or tilde.
+struct CodeBlocks;
+
+impl Check for CodeBlocks {
+    fn id(&self) -> &'static str {
+        "CL005"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind != FileKind::Reference
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let md = &doc.markdown;
+        let Some(block) = md.code_blocks.first() else {
+            return Vec::new();
+        };
+        let column = md.line(block.start_line).map_or(1, |l| {
+            l.text.len() - l.text.trim_start().len() + 1
+        });
+        vec![Diagnostic::of(
+            doc.path,
+            "CL005",
+            "contains fenced code block".to_string(),
+        ).at(Some(Span { line: block.start_line, column, length: 3 }))]
+    }
+}
+
+/// Caps a file kind's length in lines.
+/// Reports files over their kind's line limit, which the config may
+/// change, for some paths or all.
+pub(crate) struct LineLimit<'a> {
+    id: &'static str,
+    kind: FileKind,
+    max: usize,
+    config: &'a config::Config,
+}
+
+/// The line limits, with their defaults.
+pub(crate) fn line_limits(config: &config::Config) -> [LineLimit<'_>; 3] {
+    [
+        LineLimit { id: "CL020", kind: FileKind::Agent, max: 120, config },
+        LineLimit { id: "CL031", kind: FileKind::Skill, max: 500, config },
+        LineLimit { id: "CL050", kind: FileKind::Command, max: 120, config },
+    ]
+}
+
+impl Check for LineLimit<'_> {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == self.kind
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let lines = count_lines(doc.content);
+        let max = self.config.option(self.id, "max_lines", doc.path).unwrap_or(self.max);
+        if lines <= max {
+            return Vec::new();
+        }
+        vec![Diagnostic::of(
+            doc.path,
+            self.id,
+            format!("too long ({} lines, max {})", lines, max),
+        )]
+    }
+}
+
+/// Flags step-by-step sections in agents and commands.
+struct Procedural;
+
+impl Check for Procedural {
+    fn id(&self) -> &'static str {
+        "CL021"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::Agent | FileKind::Command)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let procedural = ["procedure", "workflow", "steps"];
+        let mut diags = Vec::new();
+        for h in doc.markdown.headings.iter().filter(|h| h.level >= 2) {
+            let lower = h.text.to_lowercase();
+            if procedural.iter().any(|p| lower.starts_with(p)) {
+                diags.push(Diagnostic::of(
+                    doc.path,
+                    "CL021",
+                    format!("contains procedural section '{}'", heading_label(h)),
+                ).at(Some(heading_span(h))));
+            }
+        }
+        diags
+    }
+}
+
+/// Flags procedures in CLAUDE.md and agents that no section title gives
+/// away: numbered lists longer than the rule's max_items, and runs of
+/// sentences that open with temporal connectives, as in "Then run the
+/// tests. Finally, push."
+pub(crate) struct Sequences<'a>(pub &'a config::Config);
+
+const MAX_LIST_ITEMS: usize = 5;
+
+/// Connectives that order steps when they open a sentence. Ordinals
+/// count only with their comma, so "Second opinions help" passes.
+const TEMPORAL: &[&str] = &[
+    "then",
+    "next",
+    "after that",
+    "afterwards",
+    "afterward",
+    "finally",
+    "lastly",
+    "now",
+    "once done",
+    "when done",
+    "before that",
+    "first,",
+    "second,",
+    "third,",
+];
+
+/// Words after a connective that make the sentence a narrative rather
+/// than an instruction, as in "Then the build runs."
+const NARRATIVE: &[&str] = &[
+    "the", "a", "an", "it", "its", "this", "that", "these", "those", "we", "they", "he", "she",
+    "i", "there",
+];
+
+impl Check for Sequences<'_> {
+    fn id(&self) -> &'static str {
+        "CL011"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::ClaudeMd | FileKind::Agent)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let max = self.0.option(self.id(), "max_items", doc.path).unwrap_or(MAX_LIST_ITEMS);
+        let mut diags = Vec::new();
+        for (first, items) in ordered_lists(&doc.markdown) {
+            if items > max {
+                let msg = format!("numbered list of {} steps; expected at most {}", items, max);
+                diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(first)));
+            }
+        }
+
+        // Connective-led sentences, grouped by the block of prose they
+        // are in.
+        let mut found: Vec<(usize, Span, &str)> = Vec::new();
+        for &connective in TEMPORAL {
+            for span in prose::find_text(&doc.markdown, connective, Position::SentenceStart) {
+                let Some(line) = doc.markdown.line(span.line) else {
+                    continue;
+                };
+                let start = line
+                    .text
+                    .char_indices()
+                    .nth(span.column - 1)
+                    .map_or(line.text.len(), |(i, _)| i);
+                let rest = prose::mask(&line.text[start + connective.len()..]).to_lowercase();
+                let next = rest
+                    .trim_start_matches([',', ' '])
+                    .split(|c: char| !c.is_alphanumeric())
+                    .next()
+                    .unwrap_or("");
+                if !NARRATIVE.contains(&next) {
+                    found.push((
+                        block(&doc.markdown, span.line),
+                        span,
+                        connective.trim_end_matches(','),
+                    ));
+                }
+            }
+        }
+        found.sort_by_key(|&(block, span, _)| (block, span.line, span.column));
+        for run in found.chunk_by(|a, b| a.0 == b.0).filter(|run| run.len() > 1) {
+            let mut words: Vec<String> = Vec::new();
+            for &(_, _, word) in run {
+                let word = format!("'{}'", word);
+                if !words.contains(&word) {
+                    words.push(word);
+                }
+            }
+            let msg = format!(
+                "{} sentences in one paragraph or list open with {}, like steps",
+                run.len(),
+                words.join(", ")
+            );
+            diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(run[0].1)));
+        }
+        diags
+    }
+}
+
+/// Ordered lists, as the span of each one's first marker and its number
+/// of items. Items of a list nested inside count toward that list
+/// alone.
+fn ordered_lists(md: &Markdown) -> Vec<(Span, usize)> {
+    let mut out = Vec::new();
+    // Open lists, innermost last: indentation, first marker, items.
+    let mut open: Vec<(usize, Span, usize)> = Vec::new();
+    let mut prev = Kind::Blank;
+    for line in &md.lines {
+        let indent = line.text.len() - line.text.trim_start().len();
+        // Lists deeper than the line end; an unordered item, or anything
+        // else that is not a continuation, also ends one level with it.
+        let keep = match line.kind {
+            Kind::Blank | Kind::Code | Kind::Comment => None,
+            // A paragraph right after an item continues it, indented or not.
+            Kind::Paragraph if prev != Kind::Blank => None,
+            Kind::ListItem { ordered: true } => Some(indent + 1),
+            _ => Some(indent),
+        };
+        if let Some(keep) = keep {
+            while open.last().is_some_and(|&(at, _, _)| at >= keep) {
+                let (_, span, items) = open.pop().expect("checked above");
+                out.push((span, items));
+            }
+        }
+        if let Kind::ListItem { ordered: true } = line.kind {
+            match open.last_mut() {
+                Some((at, _, items)) if *at == indent => *items += 1,
+                _ => open.push((
+                    indent,
+                    Span {
+                        line: line.number,
+                        column: indent + 1,
+                        length: 1,
+                    },
+                    1,
+                )),
+            }
+        }
+        prev = line.kind;
+    }
+    out.extend(open.into_iter().rev().map(|(_, span, items)| (span, items)));
+    out.sort_by_key(|&(span, _)| span.line);
+    out
+}
+
+/// A number for the run of paragraph and list lines a line is in, so
+/// lines with the same number are in one block.
+fn block(md: &Markdown, number: usize) -> usize {
+    md.lines
+        .iter()
+        .take_while(|l| l.number <= number)
+        .filter(|l| !matches!(l.kind, Kind::Paragraph | Kind::ListItem { .. }))
+        .count()
+}
+
+/// Flags paths into one person's home directory, which exist on no one
+/// else's machine.
+struct MachinePaths;
+
+/// Home directory roots, each followed by a user name.
+const HOME_ROOTS: &[&str] = &["/Users/", "/home/", "C:\\Users\\", "C:/Users/"];
+
+impl Check for MachinePaths {
+    fn id(&self) -> &'static str {
+        "CL094"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::ClaudeMd | FileKind::Agent | FileKind::Skill)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let project = project_name(doc.path);
+        let mut diags = Vec::new();
+        for line in doc.markdown.lines.iter().filter(|l| l.kind != Kind::Frontmatter) {
+            for root in HOME_ROOTS {
+                for (start, _) in line.text.match_indices(root) {
+                    let before = line.text[..start].trim_end_matches("file://");
+                    if before.ends_with(|c: char| {
+                        c.is_alphanumeric() || matches!(c, '/' | '\\' | '.' | '-' | '_' | ':')
+                    }) {
+                        continue;
+                    }
+                    let rest = &line.text[start..];
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || "`'\"()[]<>,;".contains(c))
+                        .unwrap_or(rest.len());
+                    let path = rest[..end].trim_end_matches(['.', ':', '!', '?']);
+                    let parts: Vec<&str> = path[root.len()..].split(['/', '\\']).collect();
+                    // The user name, and then something under it.
+                    if parts.len() < 2 || parts[0].is_empty() {
+                        continue;
+                    }
+                    let under = parts[1..].join("/");
+                    let suggestion = match project
+                        .as_deref()
+                        .and_then(|p| parts[1..].iter().position(|part| *part == p))
+                    {
+                        Some(i) if i + 2 < parts.len() => {
+                            format!("'{}', relative to the repository", parts[i + 2..].join("/"))
+                        }
+                        _ => format!("a path relative to the repository, or '~/{}'", under),
+                    };
+                    let msg = format!("'{}' is a path on one machine; use {}", path, suggestion);
+                    let span = Span {
+                        line: line.number,
+                        column: line.text[..start].chars().count() + 1,
+                        length: path.chars().count(),
+                    };
+                    diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(span)));
+                }
+            }
+        }
+        diags
+    }
+}
+
+/// The name of the directory the .claude directory a file is in sits
+/// in, which is usually the repository's.
+fn project_name(path: &Path) -> Option<String> {
+    let claude = path
+        .ancestors()
+        .find(|a| a.file_name().is_some_and(|n| n == ".claude"))?;
+    let project = fs::canonicalize(claude)
+        .ok()?
+        .parent()?
+        .file_name()?
+        .to_string_lossy()
+        .into_owned();
+    Some(project)
+}
+
+fn heading_span(h: &Heading) -> Span {
+    Span { line: h.line, column: h.column, length: h.text.chars().count() }
+}
+
+fn heading_label(h: &Heading) -> String {
+    format!("{} {}", "#".repeat(h.level as usize), h.text)
+}
+
+// Heading checks.
+
+/// Requires agents and skills to have exactly one H1, their title.
+struct SingleTitle;
+
+impl Check for SingleTitle {
+    fn id(&self) -> &'static str {
+        "CL120"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::Agent | FileKind::Skill)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut titles = doc.markdown.headings.iter().filter(|h| h.level == 1);
+        let Some(first) = titles.next() else {
+            return vec![Diagnostic::of(doc.path, self.id(), "no H1 heading".to_string())];
+        };
+        titles
+            .map(|h| {
+                let msg = format!(
+                    "another H1 heading '{}' (the first is on line {})",
+                    heading_label(h),
+                    first.line
+                );
+                Diagnostic::of(doc.path, self.id(), msg).at(Some(heading_span(h)))
+            })
+            .collect()
+    }
+}
+
+/// Flags headings more than one level below the heading before them.
+struct HeadingLevels;
+
+impl Check for HeadingLevels {
+    fn id(&self) -> &'static str {
+        "CL121"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::Agent | FileKind::Skill)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        doc.markdown
+            .headings
+            .windows(2)
+            .filter(|pair| pair[1].level > pair[0].level + 1)
+            .map(|pair| {
+                let msg = format!(
+                    "heading '{}' skips from H{} to H{}",
+                    heading_label(&pair[1]),
+                    pair[0].level,
+                    pair[1].level
+                );
+                Diagnostic::of(doc.path, self.id(), msg).at(Some(heading_span(&pair[1])))
+            })
+            .collect()
+    }
+}
+
+/// Flags headings whose text repeats an earlier one, whatever the level
+/// or case.
+struct DuplicateHeadings;
+
+impl Check for DuplicateHeadings {
+    fn id(&self) -> &'static str {
+        "CL122"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::Agent | FileKind::Skill)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let headings = &doc.markdown.headings;
+        let mut diags = Vec::new();
+        for (i, h) in headings.iter().enumerate() {
+            let same = |e: &&Heading| markdown::same_title(e.text, h.text);
+            if let Some(first) = headings[..i].iter().find(same) {
+                let msg = format!(
+                    "duplicate heading '{}' (first on line {})",
+                    heading_label(h),
+                    first.line
+                );
+                diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(heading_span(h))));
+            }
+        }
+        diags
+    }
+}
+
+/// Flags headings with nothing under them before the next heading at
+/// the same or a higher level. Subheadings count as content, so a title
+/// straight above its first section is fine.
+struct EmptySections;
+
+impl Check for EmptySections {
+    fn id(&self) -> &'static str {
+        "CL123"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::Agent | FileKind::Skill)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        doc.markdown
+            .headings
+            .iter()
+            .filter(|h| {
+                doc.markdown
+                    .section(h)
+                    .iter()
+                    .all(|l| matches!(l.kind, Kind::Blank | Kind::Setext | Kind::Comment))
+            })
+            .map(|h| {
+                let msg = format!("section '{}' is empty", heading_label(h));
+                Diagnostic::of(doc.path, self.id(), msg).at(Some(heading_span(h)))
+            })
+            .collect()
+    }
+}
+
+/// Words a body needs by default to not be trivial.
+const MIN_BODY_WORDS: usize = 10;
+
+/// Flags files that are empty, or whose body after the frontmatter has
+/// fewer words than the min_words option. Headings and comments do not
+/// count; code does.
+pub(crate) struct TrivialFiles<'a>(pub &'a config::Config);
+
+impl Check for TrivialFiles<'_> {
+    fn id(&self) -> &'static str {
+        "CL127"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind != FileKind::ClaudeMd
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        if doc.content.trim_start_matches('\u{feff}').trim().is_empty() {
+            return vec![Diagnostic::of(doc.path, self.id(), "file is empty".to_string())];
+        }
+        let min = self
+            .0
+            .option(self.id(), "min_words", doc.path)
+            .unwrap_or(MIN_BODY_WORDS);
+        let body = doc.markdown.lines.iter().filter(|l| {
+            !matches!(
+                l.kind,
+                Kind::Frontmatter | Kind::Heading(_) | Kind::Setext | Kind::Comment
+            )
+        });
+        let words = body
+            .flat_map(|l| l.text.split_whitespace())
+            .filter(|w| w.contains(char::is_alphanumeric))
+            .count();
+        if words >= min {
+            return Vec::new();
+        }
+        let msg = match words {
+            0 if doc.frontmatter.is_some() => "file has nothing after its frontmatter".to_string(),
+            0 => "file has no content beyond headings".to_string(),
+            n => format!(
+                "body has {} word{}; expected at least {}",
+                n,
+                if n == 1 { "" } else { "s" },
+                min
+            ),
+        };
+        vec![Diagnostic::of(doc.path, self.id(), msg)]
+    }
+}
+
+// Section checks, from the config's policy for each kind.
+
+fn has_section(doc: &Document, title: &str) -> bool {
+    doc.markdown.heading(2, title).is_some()
+}
+
+/// Reports required sections that are missing.
+pub(crate) struct RequiredSections<'a>(pub &'a config::Config);
+
+impl Check for RequiredSections<'_> {
+    fn id(&self) -> &'static str {
+        "CL124"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        self.0.sections(kind).is_some_and(|s| s.required.is_some())
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let required = self
+            .0
+            .sections(doc.kind)
+            .and_then(|s| s.required.as_deref())
+            .unwrap_or_default();
+        required
+            .iter()
+            .filter(|title| !has_section(doc, title))
+            .map(|title| {
+                Diagnostic::of(
+                    doc.path,
+                    self.id(),
+                    format!("missing '## {}' section", title),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Reports sections the policy forbids.
+pub(crate) struct ForbiddenSections<'a>(pub &'a config::Config);
+
+impl Check for ForbiddenSections<'_> {
+    fn id(&self) -> &'static str {
+        "CL125"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        self.0.sections(kind).is_some_and(|s| !s.forbidden.is_empty())
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let forbidden = self.0.sections(doc.kind).map_or(&[][..], |s| &s.forbidden);
+        doc.markdown
+            .headings
+            .iter()
+            .filter(|h| h.level == 2 && forbidden.iter().any(|f| markdown::same_title(h.text, f)))
+            .map(|h| {
+                let msg = format!(
+                    "section '{}' is not allowed in {} files",
+                    heading_label(h),
+                    doc.kind.as_str()
+                );
+                Diagnostic::of(doc.path, self.id(), msg).at(Some(heading_span(h)))
+            })
+            .collect()
+    }
+}
+
+/// Reports sections that come before one the policy puts ahead of them.
+pub(crate) struct SectionOrder<'a>(pub &'a config::Config);
+
+impl Check for SectionOrder<'_> {
+    fn id(&self) -> &'static str {
+        "CL126"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        self.0.sections(kind).is_some_and(|s| !s.order.is_empty())
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let order = self.0.sections(doc.kind).map_or(&[][..], |s| &s.order);
+        let mut latest: Option<(usize, &Heading)> = None;
+        let mut diags = Vec::new();
+        for h in doc.markdown.headings.iter().filter(|h| h.level == 2) {
+            let Some(rank) = order.iter().position(|o| markdown::same_title(h.text, o)) else {
+                continue;
+            };
+            match latest {
+                Some((later, prev)) if rank < later => {
+                    let msg = format!(
+                        "section '{}' should come before '{}' (expected order: {})",
+                        heading_label(h),
+                        heading_label(prev),
+                        order.join(", ")
+                    );
+                    diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(heading_span(h))));
+                }
+                _ => latest = Some((rank, h)),
+            }
+        }
+        diags
+    }
+}
+
+/// Reports `##` headings that name a known section in another case or
+/// with other spacing, as `## capability` does `## Capability`. They
+/// match anyway; the fix writes them the expected way.
+pub(crate) struct HeadingCasing<'a>(pub &'a config::Config);
+
+impl Check for HeadingCasing<'_> {
+    fn id(&self) -> &'static str {
+        "CL128"
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut known: Vec<&str> = Vec::new();
+        if doc.kind == FileKind::Skill {
+            known.extend(["Capability", "References"]);
+        }
+        if let Some(s) = self.0.sections(doc.kind) {
+            known.extend(s.required.iter().flatten().chain(&s.order).map(String::as_str));
+        }
+        let mut diags = Vec::new();
+        for h in doc.markdown.headings.iter().filter(|h| h.level == 2) {
+            let Some(title) = known.iter().find(|k| markdown::same_title(h.text, k)) else {
+                continue;
+            };
+            let Some(line) = doc.markdown.line(h.line) else {
+                continue;
+            };
+            let atx = format!("## {}", title);
+            let expected = if line.text.trim_start().starts_with('#') {
+                atx.as_str()
+            } else {
+                title
+            };
+            if line.text == expected {
+                continue;
+            }
+            let msg = format!(
+                "heading '{}' should be written '{}'",
+                line.text.trim(),
+                expected
+            );
+            diags.push(
+                Diagnostic::of(doc.path, self.id(), msg)
+                    .at(Some(heading_span(h)))
+                    .with_fix(fix::Edit {
+                        start: line.offset,
+                        end: line.offset + line.text.len(),
+                        text: expected.to_string(),
+                    }),
+            );
+        }
+        diags
+    }
+}
+
+// Frontmatter checks.
+
+/// A frontmatter field a file kind understands.
+struct FieldSpec {
+    key: &'static str,
+    required: bool,
+    kind: FieldKind,
+}
+
+#[derive(Clone, Copy)]
+enum FieldKind {
+    String,
+    /// A comma-separated string or a list, like `tools`.
+    StringOrList,
+    Mapping,
+}
+
+const SKILL_FIELDS: &[FieldSpec] = &[
+    FieldSpec { key: "name", required: true, kind: FieldKind::String },
+    FieldSpec { key: "description", required: true, kind: FieldKind::String },
+    FieldSpec { key: "allowed-tools", required: false, kind: FieldKind::StringOrList },
+    FieldSpec { key: "argument-hint", required: false, kind: FieldKind::StringOrList },
+    FieldSpec { key: "model", required: false, kind: FieldKind::String },
+    FieldSpec { key: "disable-model-invocation", required: false, kind: FieldKind::String },
+    FieldSpec { key: "user-invocable", required: false, kind: FieldKind::String },
+    FieldSpec { key: "license", required: false, kind: FieldKind::String },
+    FieldSpec { key: "metadata", required: false, kind: FieldKind::Mapping },
+];
+
+const AGENT_FIELDS: &[FieldSpec] = &[
+    FieldSpec { key: "name", required: true, kind: FieldKind::String },
+    FieldSpec { key: "description", required: true, kind: FieldKind::String },
+    FieldSpec { key: "tools", required: false, kind: FieldKind::StringOrList },
+    FieldSpec { key: "model", required: false, kind: FieldKind::String },
+    FieldSpec { key: "color", required: false, kind: FieldKind::String },
+];
+
+const COMMAND_FIELDS: &[FieldSpec] = &[
+    FieldSpec { key: "description", required: true, kind: FieldKind::String },
+    FieldSpec { key: "allowed-tools", required: false, kind: FieldKind::StringOrList },
+    FieldSpec { key: "argument-hint", required: false, kind: FieldKind::StringOrList },
+    FieldSpec { key: "model", required: false, kind: FieldKind::String },
+    FieldSpec { key: "disable-model-invocation", required: false, kind: FieldKind::String },
+];
+
+/// Returns the fields a file kind declares, for kinds with frontmatter.
+fn field_specs(kind: FileKind) -> Option<&'static [FieldSpec]> {
+    match kind {
+        FileKind::Agent => Some(AGENT_FIELDS),
+        FileKind::Skill => Some(SKILL_FIELDS),
+        FileKind::Command => Some(COMMAND_FIELDS),
+        FileKind::ClaudeMd | FileKind::Reference => None,
+    }
+}
+
+struct MissingFrontmatter;
+
+impl Check for MissingFrontmatter {
+    fn id(&self) -> &'static str {
+        "CL004"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        field_specs(kind).is_some()
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        if doc.frontmatter.is_some() {
+            return Vec::new();
+        }
+        // Insert after any byte order mark, with the file's line ending.
+        let at = if doc.content.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+        let nl = if starts_crlf(doc.content) { "\r\n" } else { "\n" };
+        vec![Diagnostic::of(
+            doc.path,
+            "CL004",
+            "missing YAML frontmatter".to_string(),
+        ).with_fix(fix::Edit::insert(at, format!("---{}---{}{}", nl, nl, nl)))]
+    }
+}
+
+struct InvalidFrontmatter;
+
+impl Check for InvalidFrontmatter {
+    fn id(&self) -> &'static str {
+        "CL006"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        field_specs(kind).is_some()
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let e = match &doc.frontmatter {
+            Some(Err(e)) => e,
+            Some(Ok(fm)) => return duplicate_keys(doc, fm),
+            None => return Vec::new(),
+        };
+        let diag = Diagnostic::of(doc.path, "CL006", format!("invalid frontmatter: {}", e.message))
+            .at(Some(Span { line: e.line, column: 1, length: 0 }));
+        match close_fix(doc.content).filter(|_| e.message == frontmatter::NOT_CLOSED) {
+            Some(edit) => vec![diag.with_fix(edit)],
+            None => vec![diag],
+        }
+    }
+}
+
+/// Reports keys given twice, which YAML does not allow; parsers that
+/// accept them keep one value and drop the other without a word.
+fn duplicate_keys(doc: &Document, fm: &frontmatter::Frontmatter) -> Vec<Diagnostic> {
+    let mut diags = Vec::new();
+    for (i, field) in fm.fields.iter().enumerate() {
+        if let Some(first) = fm.fields[..i].iter().find(|f| f.key == field.key) {
+            let msg = format!(
+                "invalid frontmatter: duplicate key '{}' (first on line {})",
+                field.key, first.line
+            );
+            diags.push(Diagnostic::of(doc.path, "CL006", msg).at(field_span(field)));
+        }
+    }
+    diags
+}
+
+/// Closes an unclosed block at the first blank line, when every line
+/// before it reads as YAML.
+fn close_fix(content: &str) -> Option<fix::Edit> {
+    let mut offset = 0;
+    for (i, raw) in content.split_inclusive('\n').enumerate() {
+        let line = raw.trim_end_matches(['\n', '\r']);
+        if i > 0 && line.trim().is_empty() {
+            let nl = if raw.ends_with("\r\n") { "\r\n" } else { "\n" };
+            return Some(fix::Edit::insert(offset, format!("---{}", nl)));
+        }
+        if i > 0 && !(line.starts_with([' ', '\t', '#']) || line.contains(':')) {
+            return None;
+        }
+        offset += raw.len();
+    }
+    None
+}
+
+/// Reports required fields that are missing or empty.
+struct RequiredFields;
+
+impl Check for RequiredFields {
+    fn id(&self) -> &'static str {
+        "CL007"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        field_specs(kind).is_some()
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let (Some(fm), Some(specs)) = (doc.fields(), field_specs(doc.kind)) else {
+            return Vec::new();
+        };
+        let mut diags = Vec::new();
+        for spec in specs.iter().filter(|s| s.required) {
+            let Some(field) = fm.get(spec.key) else {
+                diags.push(Diagnostic::of(
+                    doc.path,
+                    "CL007",
+                    format!("missing required frontmatter field '{}'", spec.key),
+                ).at(Some(Span { line: 1, column: 1, length: 3 })));
+                continue;
+            };
+            if is_empty(&field.value) {
+                diags.push(Diagnostic::of(
+                    doc.path,
+                    "CL007",
+                    format!("frontmatter field '{}' is empty", field.key),
+                ).at(field_span(field)));
+            }
+        }
+        diags
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Reports keys the file kind does not understand. A likely typo comes
+/// with the field it was meant to be, and a fix when that field is not
+/// also set.
+struct UnknownFields;
+
+impl Check for UnknownFields {
+    fn id(&self) -> &'static str {
+        "CL008"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        field_specs(kind).is_some()
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let (Some(fm), Some(specs)) = (doc.fields(), field_specs(doc.kind)) else {
+            return Vec::new();
+        };
+        let mut diags = Vec::new();
+        for field in fm.fields.iter().filter(|f| !specs.iter().any(|s| s.key == f.key)) {
+            let Some(known) = nearest(&field.key, specs.iter().map(|s| s.key)) else {
+                diags.push(
+                    Diagnostic::of(
+                        doc.path,
+                        "CL008",
+                        format!("unknown frontmatter field '{}'", field.key),
+                    )
+                    .at(field_span(field)),
+                );
+                continue;
+            };
+            let msg = format!(
+                "unknown frontmatter field '{}' (did you mean '{}'?)",
+                field.key, known
+            );
+            let diag = Diagnostic::of(doc.path, "CL008", msg).at(field_span(field));
+            let start: usize = doc
+                .content
+                .split_inclusive('\n')
+                .take(field.line - 1)
+                .map(str::len)
+                .sum();
+            if fm.get(known).is_some() || !doc.content[start..].starts_with(&field.key) {
+                diags.push(diag);
+                continue;
+            }
+            diags.push(diag.with_fix(fix::Edit {
+                start,
+                end: start + field.key.len(),
+                text: known.to_string(),
+            }));
+        }
+        diags
+    }
+}
+
+/// Reports known fields whose value has the wrong type.
+struct FieldTypes;
+
+impl Check for FieldTypes {
+    fn id(&self) -> &'static str {
+        "CL009"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        field_specs(kind).is_some()
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let (Some(fm), Some(specs)) = (doc.fields(), field_specs(doc.kind)) else {
+            return Vec::new();
+        };
+        let mut diags = Vec::new();
+        for field in &fm.fields {
+            let Some(spec) = specs.iter().find(|s| s.key == field.key) else {
+                continue;
+            };
+            // Empty required fields are CL007's.
+            if spec.required && is_empty(&field.value) {
+                continue;
+            }
+            let ok = matches!(
+                (spec.kind, &field.value),
+                (_, Value::Null)
+                    | (FieldKind::String, Value::String(_))
+                    | (FieldKind::StringOrList, Value::String(_) | Value::List(_))
+                    | (FieldKind::Mapping, Value::Map(_))
+            );
+            if !ok {
+                let expected = match spec.kind {
+                    FieldKind::String => "a string",
+                    FieldKind::StringOrList => "a string or list",
+                    FieldKind::Mapping => "a mapping",
+                };
+                diags.push(Diagnostic::of(
+                    doc.path,
+                    "CL009",
+                    format!(
+                        "frontmatter field '{}' should be {}, found {}",
+                        field.key,
+                        expected,
+                        field.value.type_name()
+                    ),
+                ).at(field_span(field)));
+            }
+        }
+        diags
+    }
+}
+
+/// Holds frontmatter to one layout: keys in the configured order, values
+/// quoted the same way, and a blank line before the body. The style is
+/// held here, and None turns the check off. The block is rewritten by the
+/// fix unless a key repeats (CL006), since which value is meant is a
+/// guess.
+pub(crate) struct FrontmatterFormat<'a>(pub &'a Option<config::FrontmatterStyle>);
+
+impl Check for FrontmatterFormat<'_> {
+    fn id(&self) -> &'static str {
+        "CL112"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        field_specs(kind).is_some()
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let (Some(style), Some(fm), Some(specs)) = (self.0, doc.fields(), field_specs(doc.kind))
+        else {
+            return Vec::new();
+        };
+        // Each line's start and text, line endings included.
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for raw in doc.content.split_inclusive('\n') {
+            lines.push((offset, raw));
+            offset += raw.len();
+        }
+        let rank = |key: &str| {
+            let listed = style.order.len();
+            style.order.iter().position(|k| k == key)
+                .or_else(|| specs.iter().position(|s| s.key == key).map(|i| listed + i))
+                .unwrap_or(listed + specs.len())
+        };
+
+        let repeated = fm
+            .fields
+            .iter()
+            .enumerate()
+            .any(|(i, f)| fm.fields[..i].iter().any(|p| p.key == f.key));
+
+        let mut sorted: Vec<&Field> = fm.fields.iter().collect();
+        sorted.sort_by_key(|f| rank(&f.key));
+        // Where each field's lines end, by the line of its key.
+        let nexts = fm
+            .fields
+            .iter()
+            .skip(1)
+            .map(|f| f.line)
+            .chain([fm.end_line]);
+        let ends: HashMap<usize, usize> = fm
+            .fields
+            .iter()
+            .zip(nexts)
+            .map(|(f, next)| (f.line, next))
+            .collect();
+        let mut expected: Vec<&str> = sorted.iter().map(|f| f.key.as_str()).collect();
+        expected.dedup();
+        let mut layout = Vec::new();
+        let mut latest: Option<&Field> = None;
+        for (i, field) in fm.fields.iter().enumerate() {
+            // Repeats are CL006's.
+            if fm.fields[..i].iter().any(|f| f.key == field.key) {
+                continue;
+            }
+            match latest {
+                Some(prev) if rank(&field.key) < rank(&prev.key) => {
+                    let msg = format!(
+                        "frontmatter key '{}' should come before '{}' (expected order: {})",
+                        field.key,
+                        prev.key,
+                        expected.join(", ")
+                    );
+                    layout.push(Diagnostic::of(doc.path, self.id(), msg).at(field_span(field)));
+                }
+                _ => latest = Some(field),
+            }
+        }
+
+        // The first line of each field, requoted where its quoting is off.
+        let mut firsts: HashMap<usize, String> = HashMap::new();
+        for field in &fm.fields {
+            let next = ends[&field.line];
+            let Value::String(value) = &field.value else {
+                continue;
+            };
+            // Values that go on past their key's line are left alone.
+            if lines[field.line..next - 1]
+                .iter()
+                .any(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+            {
+                continue;
+            }
+            let (_, raw) = lines[field.line - 1];
+            let line = raw.trim_end_matches(['\n', '\r']);
+            let Some(written) = line
+                .strip_prefix(field.key.as_str())
+                .and_then(|r| r.trim_start().strip_prefix(':'))
+                .map(str::trim)
+            else {
+                continue;
+            };
+            let quote = written.chars().next().filter(|&c| c == '"' || c == '\'');
+            // A trailing comment would be lost in the rewrite.
+            let whole = match quote {
+                Some(q) => written.len() > 1 && written.ends_with(q),
+                None => written == value,
+            };
+            if !whole {
+                continue;
+            }
+            let double = style.quotes == config::Quotes::Double || !plain_safe(value);
+            let msg = match (quote, double) {
+                (Some(_), false) => "needs no quotes",
+                (Some('\''), true) => "should use double quotes",
+                (None, true) => "should be double-quoted",
+                _ => continue,
+            };
+            let text = if double { double_quoted(value) } else { value.clone() };
+            let at = written.as_ptr() as usize - line.as_ptr() as usize;
+            let span = Span {
+                line: field.line,
+                column: line[..at].chars().count() + 1,
+                length: written.chars().count(),
+            };
+            let msg = format!("frontmatter value of '{}' {}", field.key, msg);
+            layout.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(span)));
+            firsts.insert(field.line, format!("{}: {}{}", field.key, text, &raw[line.len()..]));
+        }
+
+        if let (false, Some(first), Some(diag)) = (repeated, fm.fields.first(), layout.first_mut())
+        {
+            let mut text = String::new();
+            for field in &sorted {
+                for n in field.line..ends[&field.line] {
+                    match firsts.get(&n) {
+                        Some(line) => text.push_str(line),
+                        None => text.push_str(lines[n - 1].1),
+                    }
+                }
+            }
+            let (start, end) = (lines[first.line - 1].0, lines[fm.end_line - 1].0);
+            diag.fix = Some(fix::Edit { start, end, text });
+        }
+        let mut diags = layout;
+
+        if let Some((_, next)) = lines.get(fm.end_line).filter(|(_, l)| !l.trim().is_empty()) {
+            let nl = if lines[fm.end_line - 1].1.ends_with("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            };
+            let span = Span {
+                line: fm.end_line + 1,
+                column: 1,
+                length: next.trim_end().chars().count(),
+            };
+            diags.push(
+                Diagnostic::of(
+                    doc.path,
+                    self.id(),
+                    "no blank line after the frontmatter".to_string(),
+                )
+                .at(Some(span))
+                .with_fix(fix::Edit::insert(fm.body_start, nl.to_string())));
+        }
+        diags
+    }
+}
+
+/// Whether a string reads the same written without quotes: not empty, not
+/// padded, free of what YAML would take for syntax, and not a boolean,
+/// null, or number.
+fn plain_safe(s: &str) -> bool {
+    !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !s.contains(['\n', '\t', '\r'])
+        && !matches!(
+            s.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+        )
+        && s.parse::<f64>().is_err()
+}
+
+fn double_quoted(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+fn field_span(field: &Field) -> Option<Span> {
+    Some(Span { line: field.line, column: 1, length: field.key.chars().count() })
+}
+
+/// Returns the span of a field's value text, searching the field's lines
+/// up to the end of the frontmatter, or the key's span if it is not there.
+fn value_span(doc: &Document, field: &Field, text: &str) -> Option<Span> {
+    let end = doc.fields().map_or(field.line, |fm| fm.end_line);
+    for (i, line) in doc.content.lines().enumerate().take(end).skip(field.line - 1) {
+        if let Some(at) = line.find(text) {
+            return Some(Span {
+                line: i + 1,
+                column: line[..at].chars().count() + 1,
+                length: text.chars().count(),
+            });
+        }
+    }
+    field_span(field)
+}
+
+/// Returns the candidate closest to a misspelled name, ignoring case, if
+/// any is close enough to be a likely typo.
+fn nearest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let max = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|c| (edit_distance(&name, &c.to_lowercase()), c))
+        .filter(|&(d, _)| d <= max)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, c)| c)
+}
+
+/// Edit distance over chars, counting a swap of neighbors as one edit,
+/// since `modle` is one slip away from `model`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut before: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j - 1] + cost).min(row[j - 1] + 1).min(prev[j] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut prev, row);
+    }
+    prev[b.len()]
+}
+
+// Agent checks.
+
+/// An agent's `name` must match its file name.
+struct AgentName;
+
+impl Check for AgentName {
+    fn id(&self) -> &'static str {
+        "CL023"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Agent
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let (Some(field), Some(stem)) = (
+            doc.fields().and_then(|fm| fm.get("name")),
+            doc.path.file_stem(),
+        ) else {
+            return Vec::new();
+        };
+        let stem = stem.to_string_lossy();
+        let Value::String(name) = &field.value else {
+            return Vec::new();
+        };
+        let name = name.trim();
+        if name.is_empty() || name == stem {
+            return Vec::new();
+        }
+        let diag = Diagnostic::of(
+            doc.path,
+            "CL023",
+            format!("name '{}' does not match file name '{}'", name, stem),
+        ).at(field_span(field));
+        match rename_fix(doc.content, field, &stem) {
+            Some(edit) => vec![diag.with_fix(edit)],
+            None => vec![diag],
+        }
+    }
+}
+
+/// The tools Claude Code gives agents.
+const KNOWN_TOOLS: &[&str] = &[
+    "Agent",
+    "AskUserQuestion",
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "LS",
+    "MultiEdit",
+    "NotebookEdit",
+    "NotebookRead",
+    "Read",
+    "Skill",
+    "SlashCommand",
+    "Task",
+    "TodoRead",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+/// Every tool an agent lists must be a known one or an MCP tool
+/// (`mcp__server__tool`). The config can name more; they are held here.
+pub(crate) struct Tools<'a>(pub &'a [String]);
+
+impl Check for Tools<'_> {
+    fn id(&self) -> &'static str {
+        "CL024"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Agent
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some(field) = doc.fields().and_then(|fm| fm.get("tools")) else {
+            return Vec::new();
+        };
+        let items: Vec<&str> = match &field.value {
+            Value::String(s) => s.split(',').collect(),
+            Value::List(items) => items.iter().map(String::as_str).collect(),
+            _ => return Vec::new(),
+        };
+        let known = || KNOWN_TOOLS.iter().copied().chain(self.0.iter().map(String::as_str));
+        let mut diags = Vec::new();
+        for item in items {
+            // A specifier, as in Bash(git:*), narrows a tool but does not
+            // rename it.
+            let tool = item.split('(').next().unwrap_or("").trim();
+            if tool.is_empty() || tool.starts_with("mcp__") || known().any(|k| k == tool) {
+                continue;
+            }
+            let hint = nearest(tool, known())
+                .map_or_else(String::new, |n| format!(" (did you mean '{}'?)", n));
+            diags.push(
+                Diagnostic::of(
+                    doc.path,
+                    "CL024",
+                    format!("unknown tool '{}'{}", tool, hint),
+                )
+                .at(value_span(doc, field, tool)),
+            );
+        }
+        diags
+    }
+}
+
+/// The model aliases agents may name.
+const MODEL_ALIASES: &[&str] = &["sonnet", "opus", "haiku", "inherit"];
+
+/// Whether a model looks like a full Claude model ID, such as
+/// `claude-sonnet-4-5-20250929` or `claude-3-5-haiku-latest`.
+fn is_model_id(model: &str) -> bool {
+    model.strip_prefix("claude-").is_some_and(|rest| {
+        rest.split(['-', '.']).all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        }) && ["opus", "sonnet", "haiku"]
+            .iter()
+            .any(|family| rest.split('-').any(|part| part == *family))
+    })
+}
+
+/// An agent's model must be an alias, a full model ID, or allowed in the
+/// config; the allowed names are held here.
+pub(crate) struct Model<'a>(pub &'a [String]);
+
+impl Check for Model<'_> {
+    fn id(&self) -> &'static str {
+        "CL025"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Agent
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some(field) = doc.fields().and_then(|fm| fm.get("model")) else {
+            return Vec::new();
+        };
+        let Value::String(model) = &field.value else {
+            return Vec::new();
+        };
+        let model = model.trim();
+        if model.is_empty()
+            || MODEL_ALIASES.contains(&model)
+            || is_model_id(model)
+            || self.0.iter().any(|m| m == model)
+        {
+            return Vec::new();
+        }
+        let known = MODEL_ALIASES
+            .iter()
+            .copied()
+            .chain(self.0.iter().map(String::as_str));
+        let hint =
+            nearest(model, known).map_or_else(String::new, |n| format!(" (did you mean '{}'?)", n));
+        vec![Diagnostic::of(
+            doc.path,
+            "CL025",
+            format!("unknown model '{}'{}", model, hint),
+        ).at(value_span(doc, field, model))]
+    }
+}
+
+// Skill checks.
+
+/// Returns the directory of a skill's SKILL.md.
+fn skill_dir<'a>(doc: &Document<'a>) -> &'a Path {
+    doc.path.parent().unwrap_or(Path::new(""))
+}
+
+struct SkillName;
+
+impl Check for SkillName {
+    fn id(&self) -> &'static str {
+        "CL034"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let (Some(field), Some(dir)) = (
+            doc.fields().and_then(|fm| fm.get("name")),
+            skill_dir(doc).file_name(),
+        ) else {
+            return Vec::new();
+        };
+        let dir = dir.to_string_lossy();
+        match &field.value {
+            Value::String(name) if !name.trim().is_empty() && name.trim() != dir.as_ref() => {
+                let diag = Diagnostic::of(
+                    doc.path,
+                    "CL034",
+                    format!("name '{}' does not match directory '{}'", name.trim(), dir),
+                ).at(field_span(field));
+                match rename_fix(doc.content, field, &dir) {
+                    Some(edit) => vec![diag.with_fix(edit)],
+                    None => vec![diag],
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Claude Code ignores skill descriptions longer than this.
+const MAX_SKILL_DESCRIPTION: usize = 1024;
+
+struct SkillDescription;
+
+impl Check for SkillDescription {
+    fn id(&self) -> &'static str {
+        "CL035"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some(field) = doc.fields().and_then(|fm| fm.get("description")) else {
+            return Vec::new();
+        };
+        let Value::String(desc) = &field.value else {
+            return Vec::new();
+        };
+        let len = desc.trim().chars().count();
+        if len <= MAX_SKILL_DESCRIPTION {
+            return Vec::new();
+        }
+        vec![Diagnostic::of(
+            doc.path,
+            "CL035",
+            format!("description too long ({} characters, max {})", len, MAX_SKILL_DESCRIPTION),
+        ).at(field_span(field))]
+    }
+}
+
+/// Requires `## Capability` in skills, unless the config lists the
+/// sections skills require itself.
+pub(crate) struct Capability<'a>(pub &'a config::Config);
+
+impl Check for Capability<'_> {
+    fn id(&self) -> &'static str {
+        "CL030"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill && self.0.sections(kind).is_none_or(|s| s.required.is_none())
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        if doc.markdown.heading(2, "Capability").is_some() {
+            return Vec::new();
+        }
+        vec![Diagnostic::of(
+            doc.path,
+            "CL030",
+            "missing '## Capability' section".to_string(),
+        )]
+    }
+}
+
+/// Sets a floor on what `## Capability` says, in words and sentences,
+/// and flags one that opens with an instruction rather than describing
+/// the skill. The floors are the rule's min_words and min_sentences.
+pub(crate) struct CapabilityContent<'a>(pub &'a config::Config);
+
+const MIN_CAPABILITY_WORDS: usize = 15;
+const MIN_CAPABILITY_SENTENCES: usize = 1;
+
+/// Words that open an instruction or address the reader, where a
+/// description would start with a third-person verb, as in "Deploys".
+const IMPERATIVES: &[&str] = &[
+    "add",
+    "always",
+    "analyze",
+    "apply",
+    "ask",
+    "build",
+    "call",
+    "check",
+    "configure",
+    "create",
+    "describe",
+    "do",
+    "don't",
+    "ensure",
+    "find",
+    "fix",
+    "follow",
+    "generate",
+    "get",
+    "go",
+    "install",
+    "look",
+    "make",
+    "never",
+    "open",
+    "please",
+    "read",
+    "remember",
+    "review",
+    "run",
+    "set",
+    "start",
+    "take",
+    "try",
+    "update",
+    "use",
+    "verify",
+    "write",
+    "you",
+];
+
+impl Check for CapabilityContent<'_> {
+    fn id(&self) -> &'static str {
+        "CL037"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some(heading) = doc.markdown.heading(2, "Capability") else {
+            return Vec::new();
+        };
+        // Prose only, without list markers or code spans.
+        let lines: Vec<(&Line, String)> = doc
+            .markdown
+            .section(heading)
+            .iter()
+            .filter(|l| matches!(l.kind, Kind::Paragraph | Kind::ListItem { .. }))
+            .map(|l| {
+                let masked = prose::mask(l.text);
+                let start =
+                    markdown::list_marker(&masked).map_or(0, |(_, at)| at.min(masked.len()));
+                (l, " ".repeat(start) + &masked[start..])
+            })
+            .collect();
+        let words: Vec<&str> = lines
+            .iter()
+            .flat_map(|(_, text)| text.split_whitespace())
+            .filter(|w| w.chars().any(char::is_alphanumeric))
+            .collect();
+        let ends = |w: &str| {
+            w.trim_end_matches(['"', '\'', ')', '*', '_'])
+                .ends_with(['.', '!', '?'])
+        };
+        let sentences = words.iter().filter(|w| ends(w)).count()
+            + usize::from(words.last().is_some_and(|w| !ends(w)));
+
+        let mut diags = Vec::new();
+        let min_words = self
+            .0
+            .option(self.id(), "min_words", doc.path)
+            .unwrap_or(MIN_CAPABILITY_WORDS);
+        let min_sentences = self
+            .0
+            .option(self.id(), "min_sentences", doc.path)
+            .unwrap_or(MIN_CAPABILITY_SENTENCES);
+        let count =
+            |n: usize, what: &str| format!("{} {}{}", n, what, if n == 1 { "" } else { "s" });
+        let msg = match (words.len() < min_words, sentences < min_sentences) {
+            (true, true) => Some(format!(
+                "Capability section has {} and {}; expected at least {} and {}",
+                count(words.len(), "word"),
+                count(sentences, "sentence"),
+                count(min_words, "word"),
+                count(min_sentences, "sentence"),
+            )),
+            (true, false) => Some(format!(
+                "Capability section has {}; expected at least {}",
+                count(words.len(), "word"),
+                min_words,
+            )),
+            (false, true) => Some(format!(
+                "Capability section has {}; expected at least {}",
+                count(sentences, "sentence"),
+                min_sentences,
+            )),
+            (false, false) => None,
+        };
+        if let Some(msg) = msg {
+            diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(heading_span(heading))));
+        }
+        if let Some((line, text)) = lines.first() {
+            let start = text.find(char::is_alphabetic).unwrap_or(0);
+            let first: String = text[start..]
+                .chars()
+                .take_while(|&c| c.is_alphabetic() || c == '\'')
+                .collect();
+            if IMPERATIVES.contains(&first.to_lowercase().as_str()) {
+                let msg = format!("'## Capability' opens with '{}', an instruction; describe what \
+                                   the skill makes possible", first);
+                let span = Span {
+                    line: line.number,
+                    column: text[..start].chars().count() + 1,
+                    length: first.chars().count(),
+                };
+                diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(span)));
+            }
+        }
+        diags
+    }
+}
+
+/// Flags SKILL.md bodies where too many sentences address the model as
+/// "you" or open with an instruction, past the rule's max_percent.
+pub(crate) struct Imperatives<'a>(pub &'a config::Config);
+
+const MAX_IMPERATIVE_PERCENT: usize = 25;
+
+/// Bodies with fewer sentences say too little to judge by proportion.
+const MIN_IMPERATIVE_SENTENCES: usize = 4;
+
+const SECOND_PERSON: &[&str] = &[
+    "you", "your", "yours", "yourself", "you're", "you'll", "you've", "you'd",
+];
+
+impl Check for Imperatives<'_> {
+    fn id(&self) -> &'static str {
+        "CL143"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let sentences = readability::sentences(&doc.markdown);
+        if sentences.len() < MIN_IMPERATIVE_SENTENCES {
+            return Vec::new();
+        }
+        let plain = |w: &str| {
+            w.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+                .replace('’', "'")
+                .to_lowercase()
+        };
+        let flagged: Vec<(&readability::Sentence, String)> = sentences
+            .iter()
+            .filter_map(|s| {
+                let words: Vec<String> = s.words.iter().map(|w| plain(w)).collect();
+                let cue = words.iter().find(|w| SECOND_PERSON.contains(&w.as_str())).or_else(|| {
+                    words.first().filter(|w| IMPERATIVES.contains(&w.as_str()))
+                })?;
+                Some((s, cue.clone()))
+            })
+            .collect();
+        let max = self
+            .0
+            .option(self.id(), "max_percent", doc.path)
+            .unwrap_or(MAX_IMPERATIVE_PERCENT);
+        let percent = flagged.len() * 100 / sentences.len();
+        let Some((first, cue)) = flagged.first().filter(|_| percent > max) else {
+            return Vec::new();
+        };
+        let msg = format!(
+            "{} of {} sentences ({}%) address the model or give it orders, as with '{}'; expected \
+             at most {}%",
+            flagged.len(),
+            sentences.len(),
+            percent,
+            cue,
+            max
+        );
+        vec![Diagnostic::of(doc.path, self.id(), msg).at(Some(first.span))]
+    }
+}
+
+/// A skill with references/ must list them under '## References'.
+struct ReferencesSection;
+
+impl Check for ReferencesSection {
+    fn id(&self) -> &'static str {
+        "CL033"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let refs_dir = skill_dir(doc).join("references");
+        if !refs_dir.is_dir() || doc.markdown.heading(2, "References").is_some() {
+            return Vec::new();
+        }
+        vec![Diagnostic::of(
+            doc.path,
+            "CL033",
+            "has references/ but no '## References' section".to_string(),
+        ).with_fix(references_section_fix(doc.content, &refs_dir))]
+    }
+}
+
+/// Returns a skill's References section, if it has references/ and one.
+fn references_section<'d, 'a>(doc: &'d Document<'a>) -> Option<(&'d Heading<'a>, &'d [Line<'a>])> {
+    if !skill_dir(doc).join("references").is_dir() {
+        return None;
+    }
+    let heading = doc.markdown.heading(2, "References")?;
+    Some((heading, doc.markdown.section(heading)))
+}
+
+/// Returns the links in a section, with their resolved paths.
+fn section_links<'a>(section: &[Line<'a>]) -> Vec<(usize, markdown::Link<'a>, String)> {
+    let mut out = Vec::new();
+    for line in section
+        .iter()
+        .filter(|l| matches!(l.kind, Kind::Paragraph | Kind::ListItem { .. }))
+    {
+        for link in markdown::links(line.text) {
+            if let Some(target) = local_target(link.target) {
+                out.push((line.number, link, target));
+            }
+        }
+    }
+    out
+}
+
+/// Every relative link in the References section must resolve.
+struct DanglingReferences;
+
+impl Check for DanglingReferences {
+    fn id(&self) -> &'static str {
+        "CL041"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some((_, section)) = references_section(doc) else {
+            return Vec::new();
+        };
+        let mut diags = Vec::new();
+        for (line, link, target) in section_links(section) {
+            if !normalize(&skill_dir(doc).join(&target)).exists() {
+                diags.push(
+                    Diagnostic::of(
+                        doc.path,
+                        "CL041",
+                        format!("'## References' links to missing file '{}'", target),
+                    )
+                    .at(Some(Span {
+                        line,
+                        column: link.column,
+                        length: link.target.chars().count(),
+                    })),
+                );
+            }
+        }
+        diags
+    }
+}
+
+/// Every reference file must be linked from the References section.
+struct OrphanedReferences;
+
+impl Check for OrphanedReferences {
+    fn id(&self) -> &'static str {
+        "CL042"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some((heading, section)) = references_section(doc) else {
+            return Vec::new();
+        };
+        let dir = skill_dir(doc);
+        let refs_dir = dir.join("references");
+        let linked: Vec<_> = section_links(section)
+            .into_iter()
+            .map(|(_, _, target)| normalize(&dir.join(target)))
+            .collect();
+        let mut files = reference_files(&refs_dir);
+        files.retain(|name| !linked.contains(&normalize(&refs_dir.join(name))));
+
+        // Fixes append to the last non-blank line of the section, or go
+        // before a fence left open there, where a link would be code.
+        let mut open = None;
+        for line in section.iter().filter(|l| l.kind == Kind::Fence) {
+            open = if open.is_some() { None } else { Some(line) };
+        }
+        let last = section
+            .iter()
+            .rev()
+            .find(|l| l.kind != Kind::Blank)
+            .or(doc.markdown.line(heading.line));
+        let nl = if starts_crlf(doc.content) {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let mut diags = Vec::new();
+        for name in files {
+            let mut diag = Diagnostic::of(
+                doc.path,
+                "CL042",
+                format!("'## References' does not link references/{}", name),
+            ).at(Some(heading_span(heading)));
+            if let Some(fence) = open {
+                diag = diag.with_fix(fix::Edit::insert(
+                    fence.offset,
+                    format!("- [{}](references/{}){}", name, name, nl),
+                ));
+            } else if let Some(line) = last {
+                let end = line.offset + line.text.len();
+                diag = diag.with_fix(fix::Edit::insert(
+                    end,
+                    format!("{}- [{}](references/{})", nl, name, name),
+                ));
+            }
+            diags.push(diag);
+        }
+        diags
+    }
+}
+
+/// Returns the sorted paths of the Markdown files in references/ and its
+/// subdirectories, relative to it, with `/` between components.
+fn reference_files(refs_dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = files_within(refs_dir)
+        .iter()
+        .filter(|p| markdown::is_markdown(p))
+        .filter_map(|p| Some(glob::slashed(p.strip_prefix(refs_dir).ok()?)))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Returns the files in a directory and its subdirectories.
+fn files_within(dir: &Path) -> Vec<PathBuf> {
+    let dirs = jobs::dirs_within(dir, usize::MAX);
+    let paths = dirs.iter().flat_map(|d| {
+        fs::read_dir(d)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+    });
+    paths.filter(|p| !p.is_dir()).collect()
+}
+
+// Reference checks.
+
+/// Reference files must say near the top that they are optional, in one
+/// of the phrasings below or those the config adds.
+pub(crate) struct OptionalNote<'a>(pub &'a [String]);
+
+/// Phrasings that mark a reference as elective, matched anywhere in a
+/// word so "optionally" counts.
+const ELECTIVE: &[&str] = &[
+    "optional",
+    "only if needed",
+    "only when needed",
+    "only if you need",
+    "as needed",
+    "not required",
+    "skip unless",
+    "can be skipped",
+    "may be skipped",
+    "for reference only",
+    "background reading",
+];
+
+impl Check for OptionalNote<'_> {
+    fn id(&self) -> &'static str {
+        "CL040"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Reference
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        // Lines are joined with spaces so a phrasing may wrap.
+        let head = doc.content.lines().take(15).collect::<Vec<_>>().join(" ").to_lowercase();
+        let head = head.split_whitespace().collect::<Vec<_>>().join(" ");
+        let extra = self.0.iter().map(|p| p.to_lowercase());
+        if ELECTIVE.iter().map(|p| p.to_string()).chain(extra).any(|p| head.contains(&p)) {
+            return Vec::new();
+        }
+        vec![Diagnostic::of(
+            doc.path,
+            "CL040",
+            "should say near the top that it is optional".to_string(),
+        ).with_fix(optional_note_fix(doc))]
+    }
+}
+
+/// Keeps reference files skimmable: at most max_lines lines and about
+/// max_tokens tokens each.
+pub(crate) struct ReferenceSize<'a>(pub &'a config::Config);
+
+const MAX_REFERENCE_LINES: usize = 300;
+const MAX_REFERENCE_TOKENS: usize = 3000;
+
+impl Check for ReferenceSize<'_> {
+    fn id(&self) -> &'static str {
+        "CL043"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Reference
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let max_lines = self
+            .0
+            .option(self.id(), "max_lines", doc.path)
+            .unwrap_or(MAX_REFERENCE_LINES);
+        let max_tokens = self
+            .0
+            .option(self.id(), "max_tokens", doc.path)
+            .unwrap_or(MAX_REFERENCE_TOKENS);
+        let lines = count_lines(doc.content);
+        let tokens = self.0.tokens.estimate(doc.content);
+        let mut over = Vec::new();
+        if lines > max_lines {
+            over.push(format!("{} lines, max {}", lines, max_lines));
+        }
+        if tokens > max_tokens {
+            over.push(format!("about {} tokens, max {}", tokens, max_tokens));
+        }
+        if over.is_empty() {
+            return Vec::new();
+        }
+        vec![Diagnostic::of(doc.path, self.id(), format!("too long ({})", over.join("; ")))]
+    }
+}
+
+/// Caps the reference files a skill has at max_files.
+pub(crate) struct ReferenceCount<'a>(pub &'a config::Config);
+
+const MAX_REFERENCE_FILES: usize = 10;
+
+impl Check for ReferenceCount<'_> {
+    fn id(&self) -> &'static str {
+        "CL044"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let max = self.0.option(self.id(), "max_files", doc.path).unwrap_or(MAX_REFERENCE_FILES);
+        let count = reference_files(&skill_dir(doc).join("references")).len();
+        if count <= max {
+            return Vec::new();
+        }
+        let msg = format!("skill has {} reference files, max {}", count, max);
+        vec![Diagnostic::of(doc.path, self.id(), msg)]
+    }
+}
+
+/// Flags files in a skill's references/ that are not Markdown, such as
+/// binaries or data dumps, unless a `[references] allow` glob matches
+/// their path within references/.
+pub(crate) struct ReferenceTypes<'a>(pub &'a [String]);
+
+impl Check for ReferenceTypes<'_> {
+    fn id(&self) -> &'static str {
+        "CL045"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Skill
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let refs_dir = skill_dir(doc).join("references");
+        let allow: Vec<Glob> = self.0.iter().map(|p| Glob::new(p)).collect();
+        let mut files = files_within(&refs_dir);
+        files.sort();
+        let mut diags = Vec::new();
+        for path in files {
+            if markdown::is_markdown(&path) {
+                continue;
+            }
+            let rel = path.strip_prefix(&refs_dir).map(glob::slashed).unwrap_or_default();
+            if allow.iter().any(|g| g.matches(&rel)) {
+                continue;
+            }
+            let bytes = fs::read(&path).unwrap_or_default();
+            let what = if bytes.contains(&0) || std::str::from_utf8(&bytes).is_err() {
+                "binary file"
+            } else {
+                "file"
+            };
+            let msg = format!(
+                "unexpected {} in references/, not Markdown ({} bytes)",
+                what,
+                bytes.len()
+            );
+            diags.push(Diagnostic::of(&path, self.id(), msg));
+        }
+        diags
+    }
+}
+
+// Command checks.
+
+/// File names become slash commands and agent names, and skill
+/// directories skill names, so they must follow the configured
+/// convention, held here.
+pub(crate) struct FileNames(pub config::FileNames);
+
+impl Check for FileNames {
+    fn id(&self) -> &'static str {
+        "CL051"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        matches!(kind, FileKind::Agent | FileKind::Skill | FileKind::Command)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let (what, name) = match doc.kind {
+            FileKind::Skill => ("directory", doc.path.parent().and_then(Path::file_name)),
+            _ => ("file", doc.path.file_stem()),
+        };
+        let name = name.map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        if follows(&name, self.0) {
+            return Vec::new();
+        }
+        vec![Diagnostic::of(
+            doc.path,
+            "CL051",
+            format!("{} name '{}' is not {}", what, name, self.0.label()),
+        )]
+    }
+}
+
+/// Whether a name is lowercase words of letters and digits, joined by
+/// single separators of the convention's kind.
+fn follows(s: &str, convention: config::FileNames) -> bool {
+    let separators: &[char] = match convention {
+        config::FileNames::Kebab => &['-'],
+        config::FileNames::Snake => &['_'],
+        config::FileNames::Lowercase => &['-', '_'],
+    };
+    !s.is_empty()
+        && s.split(separators).all(|w| {
+            !w.is_empty() && w.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// Flags misspelled argument placeholders, and an argument-hint that the
+/// body never uses.
+struct Arguments;
+
+impl Check for Arguments {
+    fn id(&self) -> &'static str {
+        "CL052"
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        kind == FileKind::Command
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut diags = Vec::new();
+        let used = placeholders(doc.path, &doc.markdown, &mut diags);
+        if let Some(field) = doc.fields().and_then(|fm| fm.get("argument-hint")) {
+            if !used {
+                diags.push(Diagnostic::of(
+                    doc.path,
+                    "CL052",
+                    "declares argument-hint but never uses '$ARGUMENTS' or '$1'..'$9'".to_string(),
+                ).at(field_span(field)));
+            }
+        }
+        diags
+    }
+}
+
+/// Reports misspelled placeholders and returns whether a real one is
+/// used.
+fn placeholders(path: &Path, md: &Markdown, diags: &mut Vec<Diagnostic>) -> bool {
+    let mut used = false;
+    for line in md.lines.iter().filter(|l| l.kind != markdown::Kind::Frontmatter) {
+        let mut rest = line.text;
+        let mut offset = 0;
+        while let Some(i) = rest.find('$') {
+            let word: String = rest[i + 1..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '{')
+                .collect();
+            let bare = word.trim_start_matches('{');
+            if word == "ARGUMENTS" || (bare.len() == 1 && bare.chars().all(|c| c.is_ascii_digit()))
+            {
+                used = true;
+            } else if bare.eq_ignore_ascii_case("arguments")
+                || bare.eq_ignore_ascii_case("argument")
+                || bare.eq_ignore_ascii_case("args")
+            {
+                let column = line.text[..offset + i].chars().count() + 1;
+                diags.push(Diagnostic::of(
+                    path,
+                    "CL052",
+                    format!("'${}' is not a placeholder; use '$ARGUMENTS' or '$1'..'$9'", word),
+                ).at(Some(Span { line: line.number, column, length: word.chars().count() + 1 })));
+            }
+            offset += i + 1;
+            rest = &rest[i + 1..];
+        }
+    }
+    used
+}
+
+// Encoding checks.
+
+/// Whether a file's first line ends in CRLF.
+fn starts_crlf(content: &str) -> bool {
+    content.split_once('\n').is_some_and(|(line, _)| line.ends_with('\r'))
+}
+
+/// Line endings must match the configured style; the style is held here,
+/// and None turns the check off.
+pub(crate) struct LineEndings(pub Option<config::LineEndings>);
+
+impl Check for LineEndings {
+    fn id(&self) -> &'static str {
+        "CL111"
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some(style) = self.0 else {
+            return Vec::new();
+        };
+        let mut crlf = match style {
+            config::LineEndings::Lf => Some(false),
+            config::LineEndings::Crlf => Some(true),
+            config::LineEndings::Consistent => None,
+        };
+        // The first line that differs, as its number, start, and text.
+        let mut first = None;
+        let mut count = 0;
+        let mut offset = 0;
+        for (i, raw) in doc.content.split_inclusive('\n').enumerate() {
+            let start = offset;
+            offset += raw.len();
+            let Some(line) = raw.strip_suffix('\n') else {
+                continue;
+            };
+            let this = line.ends_with('\r');
+            if *crlf.get_or_insert(this) != this {
+                count += 1;
+                first.get_or_insert((i + 1, start, line.trim_end_matches('\r')));
+            }
+        }
+        let (Some((line, start, text)), Some(crlf)) = (first, crlf) else {
+            return Vec::new();
+        };
+        let (found, expected) = if crlf { ("LF", "CRLF") } else { ("CRLF", "LF") };
+        let mut msg = format!("{} line ending, expected {}", found, expected);
+        if style == config::LineEndings::Consistent {
+            msg.push_str(" as on line 1");
+        }
+        if count > 1 {
+            msg.push_str(&format!(" ({} lines)", count));
+        }
+        let nl = if crlf { "\r\n" } else { "\n" };
+        let fixed: String = doc.content[start..]
+            .split_inclusive('\n')
+            .map(|raw| match raw.strip_suffix('\n') {
+                Some(line) => format!("{}{}", line.trim_end_matches('\r'), nl),
+                None => raw.to_string(),
+            })
+            .collect();
+        let span = Span { line, column: text.chars().count() + 1, length: 1 };
+        vec![Diagnostic::of(doc.path, "CL111", msg)
+            .at(Some(span))
+            .with_fix(fix::Edit { start, end: doc.content.len(), text: fixed })]
+    }
+}
+
+// Fixes.
+
+/// Rewrites a `name: value` line to hold the given name. Values that
+/// span lines are left for a person to fix.
+fn rename_fix(content: &str, field: &Field, name: &str) -> Option<fix::Edit> {
+    let start: usize = content.split_inclusive('\n').take(field.line - 1).map(str::len).sum();
+    let line = content[start..].split('\n').next()?.trim_end_matches('\r');
+    let value = line.strip_prefix(&field.key)?.trim_start().strip_prefix(':')?.trim();
+    if value.is_empty() || value.starts_with(['|', '>']) {
+        return None;
+    }
+    Some(fix::Edit { start, end: start + line.len(), text: format!("{}: {}", field.key, name) })
+}
+
+/// Appends a '## References' section linking every reference file, with
+/// the file's own line endings.
+fn references_section_fix(content: &str, refs_dir: &Path) -> fix::Edit {
+    let nl = if starts_crlf(content) { "\r\n" } else { "\n" };
+    let mut text = String::new();
+    if !content.is_empty() && !content.ends_with('\n') {
+        text.push_str(nl);
+    }
+    text.push_str(&format!("{}## References{}{}", nl, nl, nl));
+    for name in reference_files(refs_dir) {
+        text.push_str(&format!("- [{}](references/{}){}", name, name, nl));
+    }
+    fix::Edit::insert(content.len(), text)
+}
+
+/// Inserts an optional note after the title, or at the top of the body
+/// if untitled, with the file's own line endings. Frontmatter and setext
+/// titles are kept whole.
+fn optional_note_fix(doc: &Document) -> fix::Edit {
+    const NOTE: &str = "This reference is optional.";
+    let nl = if starts_crlf(doc.content) { "\r\n" } else { "\n" };
+    let lines = &doc.markdown.lines;
+    let top = lines.iter().position(|l| !matches!(l.kind, Kind::Blank | Kind::Frontmatter));
+    // The number of lines to keep above the note.
+    let keep = match top {
+        Some(i) if lines[i].kind == Kind::Heading(1) => {
+            i + 1 + usize::from(lines.get(i + 1).is_some_and(|l| l.kind == Kind::Setext))
+        }
+        _ => lines
+            .iter()
+            .take_while(|l| l.kind == Kind::Frontmatter)
+            .count(),
+    };
+    let at: usize = doc.content.split_inclusive('\n').take(keep).map(str::len).sum();
+    let rest = &doc.content[at..];
+    let mut text = String::new();
+    if at > 0 {
+        if !doc.content[..at].ends_with('\n') {
+            text.push_str(nl);
+        }
+        text.push_str(nl);
+    }
+    text.push_str(NOTE);
+    text.push_str(nl);
+    if !rest.is_empty() && (at == 0 || !rest.starts_with(['\n', '\r'])) {
+        text.push_str(nl);
+    }
+    fix::Edit::insert(at, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    /// Lints a skill whose References section ends in an unclosed fence,
+    /// applying CL042's fixes, and returns the skill after each pass.
+    fn fix_twice(name: &str) -> Vec<String> {
+        let root =
+            std::env::temp_dir().join(format!("claude-lint-{}-{}", name, std::process::id()));
+        let skill = root.join("skills/demo");
+        fs::create_dir_all(skill.join("references")).unwrap();
+        fs::write(skill.join("references/extra.md"), "Optional: more detail.\n").unwrap();
+        let body = "---\nname: demo\ndescription: Demo skill. Use when testing.\n---\n\n\
+                    ## References\n\nSee below.\n\n```\nnot closed\n";
+        fs::write(skill.join("SKILL.md"), body).unwrap();
+        let mut passes = Vec::new();
+        for _ in 0..2 {
+            let diags: Vec<_> = crate::lint_dir(&root)
+                .into_iter()
+                .filter(|d| d.rule == "CL042")
+                .collect();
+            for change in crate::fix::changes(&diags).0 {
+                fs::write(&change.path, change.after).unwrap();
+            }
+            passes.push(fs::read_to_string(skill.join("SKILL.md")).unwrap());
+        }
+        fs::remove_dir_all(&root).unwrap();
+        passes
+    }
+
+    #[test]
+    fn orphaned_reference_fix_is_idempotent() {
+        let passes = fix_twice("cl042");
+        assert_eq!(passes[0].matches("- [extra.md](references/extra.md)").count(), 1);
+        assert!(passes[0].contains("See below.\n\n- [extra.md](references/extra.md)\n```\n"));
+        assert_eq!(passes[0], passes[1]);
+    }
+}
@@ -0,0 +1,1113 @@
+//! Command-line syntax, declared once: the options and commands the
+//! parser accepts, which the help lists and shell completions are
+//! generated from.
+//!
+//! Each command takes its own options, and `--help` lists them. A bare
+//! `claude-lint` lints like `check`, and also takes `--fix` and `--watch`.
+//!
+//! Options are `--name`, `--name VALUE`, or `--name=VALUE`, and a few
+//! have a one-letter form. An optional value is only taken when it
+//! fits, does not start with `-`, and is not an existing path, so that
+//! `--changed .claude` still lints `.claude`. `--` ends the options.
+//!
+//! A few options can also be set with a `CLAUDE_LINT_*` environment
+//! variable, so that CI can adjust a run without editing the checked-in
+//! config. The command line wins over the environment.
+
+use std::path::Path;
+
+use crate::all_rules;
+use crate::config::Profile;
+
+/// The binary's name, as completions register it.
+pub const BIN: &str = "claude-lint";
+
+/// What an option's value or a command's operand is, for checking it
+/// and completing it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// One of a fixed list.
+    Choice(&'static [&'static str]),
+    File,
+    Dir,
+    Number,
+    /// A rule ID or name.
+    Rule,
+    /// Anything else, like a git ref.
+    Text,
+}
+
+impl Kind {
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            Kind::Choice(choices) => choices.contains(&value),
+            Kind::Number => value.parse::<usize>().is_ok(),
+            _ => true,
+        }
+    }
+}
+
+/// An option's value: its placeholder in the help, its kind, and whether
+/// it can be left out.
+#[derive(Clone, Copy, Debug)]
+pub struct Value {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub optional: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Opt {
+    pub long: &'static str,
+    pub short: Option<char>,
+    pub value: Option<Value>,
+    pub help: &'static str,
+}
+
+/// A subcommand, with what its operands complete to.
+#[derive(Clone, Copy, Debug)]
+pub struct Command {
+    pub name: &'static str,
+    /// The operands, as the help shows them.
+    pub args: &'static str,
+    pub help: &'static str,
+    pub options: &'static [Opt],
+    pub operand: Option<Kind>,
+    /// How many operands it takes, at least and at most.
+    pub arity: (usize, usize),
+}
+
+const fn flag(long: &'static str, help: &'static str) -> Opt {
+    Opt { long, short: None, value: None, help }
+}
+
+const fn short(long: &'static str, short: char, help: &'static str) -> Opt {
+    Opt { long, short: Some(short), value: None, help }
+}
+
+const fn with(long: &'static str, name: &'static str, kind: Kind, help: &'static str) -> Opt {
+    Opt { long, short: None, value: Some(Value { name, kind, optional: false }), help }
+}
+
+const fn maybe(long: &'static str, name: &'static str, kind: Kind, help: &'static str) -> Opt {
+    Opt { long, short: None, value: Some(Value { name, kind, optional: true }), help }
+}
+
+pub const FORMATS: &[&str] = &[
+    "text",
+    "json",
+    "ndjson",
+    "sarif",
+    "github",
+    "codeclimate",
+    "junit",
+    "checkstyle",
+    "tap",
+    "html",
+];
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell"];
+
+const FORMAT: Opt = with(
+    "format",
+    "FORMAT",
+    Kind::Choice(FORMATS),
+    "text, json, ndjson, sarif, github, codeclimate, junit, checkstyle, tap, or html",
+);
+const OUTPUT: Opt = with(
+    "output",
+    "FILE",
+    Kind::File,
+    "write the report to FILE instead of stdout; not for text",
+);
+const GROUP_BY: Opt = with(
+    "group-by",
+    "GROUP",
+    Kind::Choice(&["file", "rule", "owner", "none"]),
+    "group text output by file, rule, or CODEOWNERS owner, or none",
+);
+const COLOR: Opt = with(
+    "color",
+    "WHEN",
+    Kind::Choice(&["auto", "always", "never"]),
+    "color text output: auto, always, or never",
+);
+const KIND: Opt = with(
+    "kind",
+    "KIND",
+    Kind::Choice(&["claude_md", "agent", "skill", "reference", "command"]),
+    "lint files as claude_md, agent, skill, reference, or command",
+);
+const STDIN: Opt = flag("stdin", "lint text from stdin as one file");
+const STDIN_PATH: Opt = with(
+    "stdin-path",
+    "PATH",
+    Kind::File,
+    "the file the text on stdin is for, which sets its kind and config; it is not read",
+);
+const CONFIG: Opt = with(
+    "config",
+    "FILE",
+    Kind::File,
+    "use FILE instead of .claude-lint.toml",
+);
+const PROFILE: Opt = with(
+    "profile",
+    "NAME",
+    Kind::Choice(Profile::NAMES),
+    "use the strict, default, or relaxed rules instead of the config's extends",
+);
+const REMOTE_CONFIG: Opt = flag(
+    "remote-config",
+    "let the config extend one fetched over http(s); needs curl",
+);
+const STRICT: Opt = flag("strict", "treat warnings as errors");
+const MAX_WARNINGS: Opt = with(
+    "max-warnings",
+    "N",
+    Kind::Number,
+    "fail when there are more than N warnings",
+);
+const MAX_ERRORS: Opt = with("max-errors", "N", Kind::Number, "stop after N errors");
+const FAIL_FAST: Opt = flag("fail-fast", "stop after the first error");
+const FIX: Opt = flag("fix", "apply fixes, then lint again; same as the fix command");
+const FIX_DRY_RUN: Opt = flag("fix-dry-run", "show fixes as a diff without applying them");
+const DRY_RUN: Opt = flag("dry-run", "show fixes as a diff without applying them");
+const BASELINE: Opt = with(
+    "baseline",
+    "FILE",
+    Kind::File,
+    "hide the findings recorded in FILE",
+);
+const WRITE_BASELINE: Opt = with(
+    "write-baseline",
+    "FILE",
+    Kind::File,
+    "record the current findings in FILE",
+);
+const CHANGED: Opt = maybe(
+    "changed",
+    "REF",
+    Kind::Text,
+    "lint only files changed since REF",
+);
+const DIFF_FILTER: Opt = maybe(
+    "diff-filter",
+    "REF",
+    Kind::Text,
+    "report only findings on lines changed since REF, or in files added since",
+);
+const RECURSIVE: Opt = flag("recursive", "lint every .claude directory under DIR");
+const SCOPE: Opt = with(
+    "scope",
+    "SCOPE",
+    Kind::Choice(&["project", "user", "all"]),
+    "project, user, or all",
+);
+const USER: Opt = flag("user", "same as --scope user");
+const NO_CACHE: Opt = flag("no-cache", "ignore and do not update the cache");
+const NO_FOLLOW_SYMLINKS: Opt = flag(
+    "no-follow-symlinks",
+    "leave symlinks out rather than follow them",
+);
+const NO_DISCOVER: Opt = flag(
+    "no-discover",
+    "lint only inside .claude, not the project's CLAUDE.md files around it",
+);
+const CHECK_URLS: Opt = flag(
+    "check-urls",
+    "report http(s) links that do not answer; needs curl and the network",
+);
+const TIMINGS: Opt = maybe(
+    "timings",
+    "N",
+    Kind::Number,
+    "print the N slowest rules and files, 10 by default; JSON output includes them",
+);
+const WATCH: Opt = flag(
+    "watch",
+    "lint again whenever a file changes; same as the watch command",
+);
+const QUIET: Opt = short("quiet", 'q', "print text output only when the run fails");
+const VERBOSE: Opt = short(
+    "verbose",
+    'v',
+    "list the files checked and left out, and each rule's result",
+);
+const LOG_LEVEL: Opt = with(
+    "log-level",
+    "LEVEL",
+    Kind::Choice(&["off", "error", "warn", "info", "debug", "trace"]),
+    "log what the run does to stderr: off, error, warn, info, debug, or trace; \
+     RUST_LOG also sets it",
+);
+const HELP: Opt = short("help", 'h', "print this help");
+
+/// The options of a bare `claude-lint`, which lints like `check`. It
+/// also takes `--fix` and `--watch`, from before there were commands.
+pub const OPTIONS: &[Opt] = &[
+    FORMAT,
+    OUTPUT,
+    GROUP_BY,
+    COLOR,
+    KIND,
+    STDIN,
+    STDIN_PATH,
+    CONFIG,
+    REMOTE_CONFIG,
+    PROFILE,
+    STRICT,
+    MAX_WARNINGS,
+    MAX_ERRORS,
+    FAIL_FAST,
+    FIX,
+    FIX_DRY_RUN,
+    BASELINE,
+    WRITE_BASELINE,
+    CHANGED,
+    DIFF_FILTER,
+    RECURSIVE,
+    SCOPE,
+    USER,
+    NO_CACHE,
+    NO_FOLLOW_SYMLINKS,
+    NO_DISCOVER,
+    CHECK_URLS,
+    TIMINGS,
+    WATCH,
+    QUIET,
+    VERBOSE,
+    LOG_LEVEL,
+    HELP,
+];
+
+const CHECK: &[Opt] = &[
+    FORMAT,
+    OUTPUT,
+    GROUP_BY,
+    COLOR,
+    KIND,
+    STDIN,
+    STDIN_PATH,
+    CONFIG,
+    REMOTE_CONFIG,
+    PROFILE,
+    STRICT,
+    MAX_WARNINGS,
+    MAX_ERRORS,
+    FAIL_FAST,
+    BASELINE,
+    WRITE_BASELINE,
+    CHANGED,
+    DIFF_FILTER,
+    RECURSIVE,
+    SCOPE,
+    USER,
+    NO_CACHE,
+    NO_FOLLOW_SYMLINKS,
+    NO_DISCOVER,
+    CHECK_URLS,
+    TIMINGS,
+    QUIET,
+    VERBOSE,
+    LOG_LEVEL,
+    HELP,
+];
+
+/// Fixing rewrites files, so there is no stdin to fix.
+const FIX_OPTIONS: &[Opt] = &[
+    DRY_RUN,
+    FORMAT,
+    OUTPUT,
+    GROUP_BY,
+    COLOR,
+    KIND,
+    CONFIG,
+    REMOTE_CONFIG,
+    PROFILE,
+    STRICT,
+    MAX_WARNINGS,
+    MAX_ERRORS,
+    FAIL_FAST,
+    BASELINE,
+    WRITE_BASELINE,
+    CHANGED,
+    DIFF_FILTER,
+    RECURSIVE,
+    SCOPE,
+    USER,
+    NO_CACHE,
+    NO_FOLLOW_SYMLINKS,
+    NO_DISCOVER,
+    CHECK_URLS,
+    TIMINGS,
+    QUIET,
+    VERBOSE,
+    LOG_LEVEL,
+    HELP,
+];
+
+/// Watching prints text and runs until stopped, so reports, baselines,
+/// and limits do not apply.
+const WATCH_OPTIONS: &[Opt] = &[
+    GROUP_BY,
+    COLOR,
+    KIND,
+    CONFIG,
+    REMOTE_CONFIG,
+    PROFILE,
+    STRICT,
+    RECURSIVE,
+    SCOPE,
+    USER,
+    NO_CACHE,
+    NO_FOLLOW_SYMLINKS,
+    NO_DISCOVER,
+    VERBOSE,
+    LOG_LEVEL,
+    HELP,
+];
+
+/// pre-commit passes the files to lint, one kind each, and reads the
+/// report as text.
+const PRE_COMMIT_OPTIONS: &[Opt] = &[
+    FIX,
+    COLOR,
+    GROUP_BY,
+    CONFIG,
+    REMOTE_CONFIG,
+    PROFILE,
+    STRICT,
+    MAX_WARNINGS,
+    MAX_ERRORS,
+    FAIL_FAST,
+    QUIET,
+    VERBOSE,
+    LOG_LEVEL,
+    HELP,
+];
+
+const ANY: usize = usize::MAX;
+
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "check",
+        args: "[PATH...]",
+        help: "lint paths, .claude by default; the same as no command",
+        options: CHECK,
+        operand: Some(Kind::File),
+        arity: (0, ANY),
+    },
+    Command {
+        name: "fix",
+        args: "[PATH...]",
+        help: "apply fixes, then lint again",
+        options: FIX_OPTIONS,
+        operand: Some(Kind::File),
+        arity: (0, ANY),
+    },
+    Command {
+        name: "watch",
+        args: "[PATH...]",
+        help: "lint again whenever a file changes",
+        options: WATCH_OPTIONS,
+        operand: Some(Kind::File),
+        arity: (0, ANY),
+    },
+    Command {
+        name: "rules",
+        args: "",
+        help: "list the rules",
+        options: &[HELP],
+        operand: None,
+        arity: (0, 0),
+    },
+    Command {
+        name: "explain",
+        args: "RULE",
+        help: "explain a rule, by ID or name",
+        options: &[HELP],
+        operand: Some(Kind::Rule),
+        arity: (1, 1),
+    },
+    Command {
+        name: "init",
+        args: "[DIR]",
+        help: "create a starter .claude directory",
+        options: &[HELP],
+        operand: Some(Kind::Dir),
+        arity: (0, 1),
+    },
+    Command {
+        name: "new",
+        args: "agent|skill NAME [DIR]",
+        help: "create an agent or skill and lint it",
+        options: &[HELP],
+        operand: Some(Kind::Choice(&["agent", "skill"])),
+        arity: (2, 3),
+    },
+    Command {
+        name: "stats",
+        args: "[DIR]",
+        help: "count files, lines, and tokens",
+        options: &[
+            CONFIG,
+            REMOTE_CONFIG,
+            with("format", "FORMAT", Kind::Choice(&["text", "json"]), "text or json"),
+            HELP,
+        ],
+        operand: Some(Kind::Dir),
+        arity: (0, 1),
+    },
+    Command {
+        name: "graph",
+        args: "[DIR]",
+        help: "print which files import and link to which, with orphans highlighted",
+        options: &[
+            CONFIG,
+            REMOTE_CONFIG,
+            with("format", "FORMAT", Kind::Choice(&["dot", "json"]), "dot or json"),
+            HELP,
+        ],
+        operand: Some(Kind::Dir),
+        arity: (0, 1),
+    },
+    Command {
+        name: "config",
+        args: "check|schema [DIR]",
+        help: "check the config for every problem, or print its JSON Schema",
+        options: &[CONFIG, REMOTE_CONFIG, HELP],
+        operand: Some(Kind::Choice(&["check", "schema"])),
+        arity: (1, 2),
+    },
+    Command {
+        name: "pre-commit",
+        args: "[FILE...]",
+        help: "lint the files the pre-commit framework passes, skipping ones that are not context \
+               files",
+        options: PRE_COMMIT_OPTIONS,
+        operand: Some(Kind::File),
+        arity: (0, ANY),
+    },
+    Command {
+        name: "hook-config",
+        args: "",
+        help: "print the hook definitions for a .pre-commit-hooks.yaml",
+        options: &[HELP],
+        operand: None,
+        arity: (0, 0),
+    },
+    Command {
+        name: "install-hook",
+        args: "",
+        help: "install a git hook that lints the staged context files before each commit",
+        options: &[
+            flag(
+                "pre-push",
+                "install a pre-push hook instead, which lints what the branch changed",
+            ),
+            flag("uninstall", "remove the hook instead, if claude-lint installed it"),
+            flag("force", "replace a hook that claude-lint did not install"),
+            HELP,
+        ],
+        operand: None,
+        arity: (0, 0),
+    },
+    Command {
+        name: "lsp",
+        args: "",
+        help: "run the language server on stdin and stdout",
+        options: &[HELP],
+        operand: None,
+        arity: (0, 0),
+    },
+    Command {
+        name: "daemon",
+        args: "",
+        help: "answer lint requests for single files on a local socket, keeping config and cache \
+               warm",
+        options: &[
+            with(
+                "socket",
+                "PATH",
+                Kind::File,
+                "listen on PATH instead of claude-lint.sock in the runtime directory",
+            ),
+            HELP,
+        ],
+        operand: None,
+        arity: (0, 0),
+    },
+    Command {
+        name: "completions",
+        args: "SHELL",
+        help: "print a completion script for bash, zsh, fish, or powershell",
+        options: &[HELP],
+        operand: Some(Kind::Choice(SHELLS)),
+        arity: (1, 1),
+    },
+];
+
+/// Returns a command by name.
+pub fn command(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|c| c.name == name)
+}
+
+impl Command {
+    /// Parses the arguments after the command's name, and checks that
+    /// there are as many operands as it takes, unless help is asked for.
+    pub fn parse(&self, args: impl IntoIterator<Item = String>) -> Result<Parsed, String> {
+        let parsed = parse(self.options, args).map_err(|e| format!("{}: {}", self.name, e))?;
+        let (min, max) = self.arity;
+        if parsed.has("help") {
+            return Ok(parsed);
+        }
+        if parsed.operands.len() < min {
+            return Err(format!("{} requires {}", self.name, self.args));
+        }
+        if let Some(extra) = parsed.operands.get(max) {
+            let usage = format!("{} {} {}", BIN, self.name, self.args);
+            return Err(format!(
+                "{}: unexpected argument '{}' (usage: {})",
+                self.name,
+                extra,
+                usage.trim_end()
+            ));
+        }
+        Ok(parsed)
+    }
+
+    /// The command's help: its usage, what it does, and its options.
+    pub fn help(&self) -> String {
+        let mut about = self.help.to_string();
+        about[..1].make_ascii_uppercase();
+        let usage = format!("{} {} [OPTIONS] {}", BIN, self.name, self.args);
+        format!(
+            "usage: {}\n\n{}.\n\noptions:\n{}",
+            usage.trim_end(),
+            about,
+            options_help(self.options)
+        )
+    }
+}
+
+/// Environment variables that set options, with the option each sets.
+pub const ENVIRONMENT: &[(&str, &str)] = &[
+    ("CLAUDE_LINT_CONFIG", "config"),
+    ("CLAUDE_LINT_PROFILE", "profile"),
+    ("CLAUDE_LINT_FORMAT", "format"),
+    ("CLAUDE_LINT_COLOR", "color"),
+    ("CLAUDE_LINT_MAX_WARNINGS", "max-warnings"),
+];
+
+/// Returns the options that the environment sets among the ones given,
+/// checked like values on the command line. An empty variable is unset.
+pub fn environment(options: &'static [Opt]) -> Result<Vec<(&'static str, Option<String>)>, String> {
+    let mut out = Vec::new();
+    for &(name, long) in ENVIRONMENT {
+        let Some(opt) = options.iter().find(|o| o.long == long) else {
+            continue;
+        };
+        let Some(v) = opt.value else {
+            continue;
+        };
+        if let Some(value) = std::env::var(name).ok().filter(|value| !value.is_empty()) {
+            out.push((
+                opt.long,
+                Some(check(opt, v, value).map_err(|e| format!("{}: {}", name, e))?),
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Options and operands from a command line, in the order given. Each
+/// option is known by its long name.
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub options: Vec<(&'static str, Option<String>)>,
+    pub operands: Vec<String>,
+}
+
+impl Parsed {
+    /// Whether an option was given, by long name.
+    pub fn has(&self, long: &str) -> bool {
+        self.options.iter().any(|(name, _)| *name == long)
+    }
+}
+
+/// Parses a command line against the options it may use. Unknown
+/// options, missing values, and values that do not fit are errors.
+pub fn parse(
+    options: &'static [Opt],
+    args: impl IntoIterator<Item = String>,
+) -> Result<Parsed, String> {
+    let mut out = Parsed::default();
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            out.operands.extend(args);
+            break;
+        }
+        let (opt, inline) = if let Some(long) = arg.strip_prefix("--") {
+            let (name, inline) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            (options.iter().find(|o| o.long == name), inline)
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            let mut chars = arg.chars().skip(1);
+            let letter = chars.next().filter(|_| chars.next().is_none());
+            (options.iter().find(|o| o.short.is_some() && o.short == letter), None)
+        } else {
+            out.operands.push(arg);
+            continue;
+        };
+        let Some(opt) = opt else {
+            return Err(format!("unknown option '{}'", arg));
+        };
+        let value = match (opt.value, inline) {
+            (None, Some(_)) => return Err(format!("--{} takes no value", opt.long)),
+            (None, None) => None,
+            (Some(v), Some(value)) => Some(check(opt, v, value)?),
+            (Some(v), None) if v.optional => {
+                args.next_if(|a| !a.starts_with('-') && v.kind.accepts(a) && !Path::new(a).exists())
+            }
+            (Some(v), None) => match args.next() {
+                Some(value) => Some(check(opt, v, value)?),
+                None => return Err(format!("--{} requires a value", opt.long)),
+            },
+        };
+        out.options.push((opt.long, value));
+    }
+    Ok(out)
+}
+
+fn check(opt: &Opt, v: Value, value: String) -> Result<String, String> {
+    if v.kind.accepts(&value) {
+        return Ok(value);
+    }
+    match v.kind {
+        Kind::Choice(choices) => Err(format!(
+            "unknown value '{}' for --{} (expected {})",
+            value,
+            opt.long,
+            or_list(choices)
+        )),
+        _ => Err(format!("--{} requires a number", opt.long)),
+    }
+}
+
+/// Joins words as "a, b, or c".
+pub(crate) fn or_list(words: &[&str]) -> String {
+    match words {
+        [] => String::new(),
+        [one] => one.to_string(),
+        [a, b] => format!("{} or {}", a, b),
+        [rest @ .., last] => format!("{}, or {}", rest.join(", "), last),
+    }
+}
+
+/// Renders options as the help lists them, one per line with its help
+/// wrapped beside it.
+pub fn options_help(options: &[Opt]) -> String {
+    let rows: Vec<(String, &str)> = options
+        .iter()
+        .map(|o| {
+            let mut left = match o.short {
+                Some(c) => format!("-{}, --{}", c, o.long),
+                None => format!("--{}", o.long),
+            };
+            match o.value {
+                Some(v) if v.optional => left.push_str(&format!(" [{}]", v.name)),
+                Some(v) => left.push_str(&format!(" {}", v.name)),
+                None => {}
+            }
+            (left, o.help)
+        })
+        .collect();
+    table(&rows)
+}
+
+/// Renders commands as the help lists them.
+pub fn commands_help(commands: &[Command]) -> String {
+    let rows: Vec<(String, &str)> = commands
+        .iter()
+        .map(|c| {
+            (
+                format!("{} {}", c.name, c.args).trim_end().to_string(),
+                c.help,
+            )
+        })
+        .collect();
+    table(&rows)
+}
+
+/// Renders the environment variables as the help lists them.
+pub fn environment_help() -> String {
+    let helps: Vec<String> = ENVIRONMENT
+        .iter()
+        .map(|&(_, long)| format!("sets --{}", long))
+        .collect();
+    let rows: Vec<(String, &str)> = ENVIRONMENT
+        .iter()
+        .zip(&helps)
+        .map(|(&(name, _), help)| (name.to_string(), help.as_str()))
+        .collect();
+    table(&rows)
+}
+
+/// Lays out rows with the right column at 25 and lines at most 72 wide,
+/// starting the right column on the next line when the left is too long.
+fn table(rows: &[(String, &str)]) -> String {
+    const COLUMN: usize = 25;
+    const WIDTH: usize = 72;
+    let mut out = String::new();
+    for (left, help) in rows {
+        let mut line = format!("  {}", left);
+        if line.len() + 2 > COLUMN {
+            out.push_str(&line);
+            out.push('\n');
+            line.clear();
+        }
+        for word in help.split_whitespace() {
+            if line.len() > COLUMN && line.len() + 1 + word.len() > WIDTH {
+                out.push_str(&line);
+                out.push('\n');
+                line.clear();
+            }
+            if line.len() < COLUMN {
+                line.push_str(&" ".repeat(COLUMN - line.len()));
+            } else {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Returns the completion script for a shell.
+pub fn completions(shell: &str) -> Result<String, String> {
+    match shell {
+        "bash" => Ok(bash()),
+        "zsh" => Ok(zsh()),
+        "fish" => Ok(fish()),
+        "powershell" => Ok(powershell()),
+        _ => Err(format!("unknown shell '{}' (expected {})", shell, or_list(SHELLS))),
+    }
+}
+
+/// The words an operand or value of a kind completes to, when it has a
+/// fixed set.
+fn words(kind: Kind) -> Option<Vec<String>> {
+    match kind {
+        Kind::Choice(choices) => Some(choices.iter().map(|c| c.to_string()).collect()),
+        Kind::Rule => Some(
+            all_rules()
+                .iter()
+                .flat_map(|r| [r.id.to_string(), r.name.to_string()])
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn option_words(options: &[Opt]) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for o in options {
+        if let Some(c) = o.short {
+            out.push(format!("-{}", c));
+        }
+        out.push(format!("--{}", o.long));
+    }
+    out.join(" ")
+}
+
+fn bash() -> String {
+    // Completes the value after an option, and returns.
+    let values = |options: &[Opt]| -> String {
+        let mut cases = String::new();
+        for o in options {
+            let Some(v) = o.value else {
+                continue;
+            };
+            let reply = match (v.kind, words(v.kind)) {
+                (_, Some(words)) => format!(
+                    "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                    words.join(" ")
+                ),
+                (Kind::File, None) => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+                (Kind::Dir, None) => "COMPREPLY=($(compgen -d -- \"$cur\"))".to_string(),
+                _ if v.optional => continue,
+                _ => "COMPREPLY=()".to_string(),
+            };
+            cases.push_str(&format!("                --{})\n", o.long));
+            cases.push_str(&format!("                    {}\n", reply));
+            cases.push_str("                    return\n                    ;;\n");
+        }
+        if cases.is_empty() {
+            return cases;
+        }
+        format!("            case \"$prev\" in\n{}            esac\n", cases)
+    };
+    let mut commands = String::new();
+    for c in COMMANDS {
+        let operand = match c.operand.and_then(words) {
+            Some(words) => format!("compgen -W \"{}\" -- \"$cur\"", words.join(" ")),
+            None if c.operand == Some(Kind::Dir) => "compgen -d -- \"$cur\"".to_string(),
+            None if c.operand == Some(Kind::File) => "compgen -f -- \"$cur\"".to_string(),
+            None => "true".to_string(),
+        };
+        commands.push_str(&format!(
+            "        {})\n{}            opts=\"{}\"\n            operands=$({})\n            ;;\n",
+            c.name,
+            values(c.options),
+            option_words(c.options),
+            operand
+        ));
+    }
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    format!(
+        "# bash completion for {bin}\n\
+         _claude_lint() {{\n    \
+             local cur prev opts operands\n    \
+             cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    \
+             prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    \
+             case \"${{COMP_WORDS[1]}}\" in\n{commands}        \
+                 *)\n{lint_values}            \
+                     opts=\"{lint}\"\n            \
+                     operands=$(compgen -f -- \"$cur\")\n            \
+                     if [[ $COMP_CWORD -eq 1 ]]; then\n                \
+                         operands=\"$(compgen -W \"{names}\" -- \"$cur\") $operands\"\n            \
+                     fi\n            \
+                     ;;\n    \
+             esac\n    \
+             if [[ $cur == -* ]]; then\n        \
+                 COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n    \
+             else\n        \
+                 COMPREPLY=($operands)\n    \
+             fi\n\
+         }}\n\
+         complete -o filenames -F _claude_lint {bin}\n",
+        bin = BIN,
+        commands = commands,
+        lint_values = values(OPTIONS),
+        lint = option_words(OPTIONS),
+        names = names.join(" "),
+    )
+}
+
+fn zsh() -> String {
+    let quote = |s: &str| s.replace('\'', "'\\''");
+    // Brackets end an option's description.
+    let escape = |s: &str| quote(&s.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]"));
+    let specs = |options: &[Opt]| -> Vec<String> {
+        let mut out = Vec::new();
+        for o in options {
+            let names = match o.short {
+                Some(c) => format!("'(-{c} --{l})'{{-{c},--{l}}}'", c = c, l = o.long),
+                None => format!("'--{}", o.long),
+            };
+            let value = match o.value {
+                None => String::new(),
+                Some(v) => {
+                    let action = match (v.kind, words(v.kind)) {
+                        (_, Some(words)) => format!("({})", words.join(" ")),
+                        (Kind::File, None) => "_files".to_string(),
+                        (Kind::Dir, None) => "_files -/".to_string(),
+                        _ => " ".to_string(),
+                    };
+                    let colon = if v.optional { "::" } else { ":" };
+                    format!("{}{}:{}", colon, v.name.to_lowercase(), action)
+                }
+            };
+            let eq = if o.value.is_some_and(|v| !v.optional) && o.short.is_none() {
+                "="
+            } else {
+                ""
+            };
+            out.push(format!("{}{}[{}]{}'", names, eq, escape(o.help), value));
+        }
+        out
+    };
+    let arguments = |specs: Vec<String>| -> String {
+        if specs.is_empty() {
+            return String::new();
+        }
+        format!("        _arguments \\\n            {}\n", specs.join(" \\\n            "))
+    };
+    let mut commands = String::new();
+    let mut described = String::new();
+    for c in COMMANDS {
+        described.push_str(&format!("        '{}:{}'\n", c.name, quote(c.help)));
+        let label = c
+            .args
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_matches(['[', ']'])
+            .to_lowercase();
+        let mut specs = specs(c.options);
+        match (c.operand, c.operand.and_then(words)) {
+            (_, Some(words)) => specs.push(format!("'1:{}:({})'", label, words.join(" "))),
+            (Some(Kind::Dir), None) => specs.push(format!("'1:{}:_files -/'", label)),
+            (Some(Kind::File), None) => specs.push(format!("'*:{}:_files'", label)),
+            _ => {}
+        }
+        commands.push_str(&format!(
+            "    {})\n        shift words; (( CURRENT-- ))\n{}        ;;\n",
+            c.name,
+            arguments(specs)
+        ));
+    }
+    let mut lint = specs(OPTIONS);
+    lint.push("'*:path:_files'".to_string());
+    format!(
+        "#compdef {bin}\n\n\
+         _claude_lint() {{\n    \
+             local -a commands\n    \
+             commands=(\n{described}    )\n    \
+             if (( CURRENT == 2 )) && [[ $words[2] != -* ]]; then\n        \
+                 _describe command commands\n    \
+             fi\n    \
+             case $words[2] in\n{commands}    \
+             *)\n{lint}        \
+                 ;;\n    \
+             esac\n\
+         }}\n\n\
+         _claude_lint \"$@\"\n",
+        bin = BIN,
+        described = described,
+        commands = commands,
+        lint = arguments(lint),
+    )
+}
+
+fn fish() -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"));
+    let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+    let spec = |o: &Opt, condition: &str| -> String {
+        let mut line = format!("complete -c {} -n {} -l {}", BIN, quote(condition), o.long);
+        if let Some(c) = o.short {
+            line.push_str(&format!(" -s {}", c));
+        }
+        if let Some(v) = o.value {
+            match (v.kind, words(v.kind)) {
+                (_, Some(words)) => line.push_str(&format!(" -x -a {}", quote(&words.join(" ")))),
+                (Kind::File | Kind::Dir, None) => line.push_str(" -r -F"),
+                _ if v.optional => {}
+                _ => line.push_str(" -x"),
+            }
+        }
+        line.push_str(&format!(" -d {}\n", quote(o.help)));
+        line
+    };
+    let mut out = format!("# fish completion for {}\n\ncomplete -c {} -f\n", BIN, BIN);
+    let outside = format!("not __fish_seen_subcommand_from {}", names.join(" "));
+    for c in COMMANDS {
+        out.push_str(&format!(
+            "complete -c {} -n __fish_use_subcommand -a {} -d {}\n",
+            BIN,
+            c.name,
+            quote(c.help)
+        ));
+    }
+    out.push_str(&format!("complete -c {} -n {} -F\n", BIN, quote(&outside)));
+    for o in OPTIONS {
+        out.push_str(&spec(o, &outside));
+    }
+    for c in COMMANDS {
+        let inside = format!("__fish_seen_subcommand_from {}", c.name);
+        match (c.operand, c.operand.and_then(words)) {
+            (_, Some(words)) => out.push_str(&format!(
+                "complete -c {} -n {} -a {}\n",
+                BIN,
+                quote(&inside),
+                quote(&words.join(" "))
+            )),
+            (Some(Kind::File | Kind::Dir), None) => {
+                out.push_str(&format!("complete -c {} -n {} -F\n", BIN, quote(&inside)))
+            }
+            _ => {}
+        }
+        for o in c.options {
+            out.push_str(&spec(o, &inside));
+        }
+    }
+    out
+}
+
+fn powershell() -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+    let list = |words: &[String]| words.iter().map(|w| quote(w)).collect::<Vec<_>>().join(", ");
+    let names = |options: &[Opt]| -> Vec<String> {
+        let mut out = Vec::new();
+        for o in options {
+            out.push(format!("--{}", o.long));
+            if let Some(c) = o.short {
+                out.push(format!("-{}", c));
+            }
+        }
+        out
+    };
+    // The values of a command's options, by option.
+    let values = |options: &[Opt]| -> String {
+        let mut out = Vec::new();
+        for o in options {
+            if let Some(words) = o.value.and_then(|v| words(v.kind)) {
+                out.push(format!("{} = @({})", quote(&format!("--{}", o.long)), list(&words)));
+            }
+        }
+        if out.is_empty() {
+            return "@{}".to_string();
+        }
+        format!("@{{ {} }}", out.join("; "))
+    };
+    let mut commands = format!(
+        "        '' = @{{ options = @({}); values = {}; operands = @() }}\n",
+        list(&names(OPTIONS)),
+        values(OPTIONS)
+    );
+    for c in COMMANDS {
+        commands.push_str(&format!(
+            "        {} = @{{ options = @({}); values = {}; operands = @({}) }}\n",
+            quote(c.name),
+            list(&names(c.options)),
+            values(c.options),
+            list(&c.operand.and_then(words).unwrap_or_default())
+        ));
+    }
+    let all: Vec<String> = COMMANDS.iter().map(|c| c.name.to_string()).collect();
+    format!(
+        "# PowerShell completion for {bin}\n\
+         Register-ArgumentCompleter -Native -CommandName {bin} -ScriptBlock {{\n    \
+             param($wordToComplete, $commandAst, $cursorPosition)\n    \
+             $commands = @{{\n{commands}    }}\n    \
+             $words = @($commandAst.CommandElements | ForEach-Object {{ $_.ToString() }})\n    \
+             if ($wordToComplete -ne '') {{ $words = @($words | Select-Object -SkipLast 1) }}\n    \
+             $name = if ($words.Count -gt 1 -and $commands.ContainsKey($words[1])) \
+                 {{ $words[1] }} else {{ '' }}\n    \
+             $command = $commands[$name]\n    \
+             $prev = $words[-1]\n    \
+             $candidates = if ($command.values.ContainsKey($prev)) {{\n        \
+                 $command.values[$prev]\n    \
+             }} elseif ($wordToComplete -like '-*') {{\n        \
+                 $command.options\n    \
+             }} elseif ($name -eq '' -and $words.Count -eq 1) {{\n        \
+                 @({all})\n    \
+             }} else {{\n        \
+                 $command.operands\n    \
+             }}\n    \
+             $candidates | Where-Object {{ $_ -like \"$wordToComplete*\" }} | \
+                 ForEach-Object {{\n        \
+                 [System.Management.Automation.CompletionResult]::new($_, $_, \
+                     'ParameterValue', $_)\n    \
+             }}\n\
+         }}\n",
+        bin = BIN,
+        commands = commands,
+        all = list(&all),
+    )
+}
@@ -0,0 +1,105 @@
+//! Rules defined in the config file.
+//!
+//! A custom rule flags prose that matches a regex or any of a list of
+//! phrases, or with `require`, files where nothing matches. Prose is
+//! matched as [`prose`](crate::prose) sees it, so code and comments never
+//! count, and a line at a time, so `^` and `$` anchor to line ends.
+
+use crate::markdown::Markdown;
+use crate::prose::{self, Position};
+use crate::regex::Regex;
+use crate::rules::{Check, Document, FileKind};
+use crate::{Diagnostic, Span};
+
+pub enum Matcher {
+    Pattern(Regex),
+    /// Phrases matched case-insensitively on word boundaries.
+    Phrases(Vec<String>),
+}
+
+pub struct Custom {
+    pub id: &'static str,
+    pub kinds: Vec<FileKind>,
+    pub matcher: Matcher,
+    /// Reports files without a match instead of each match.
+    pub require: bool,
+    /// The finding's message; `{match}` stands for the matched text.
+    pub message: String,
+}
+
+impl Custom {
+    /// Returns the span and text of every match in prose lines, or the
+    /// line a pattern gave up on and why.
+    fn find(&self, md: &Markdown) -> Result<Vec<(Span, String)>, (Span, String)> {
+        let mut out = Vec::new();
+        match &self.matcher {
+            Matcher::Pattern(regex) => {
+                for line in md.lines.iter().filter(|l| prose::is_prose(l.kind)) {
+                    let text = prose::mask(line.text);
+                    let line_span = Span {
+                        line: line.number,
+                        column: 1,
+                        length: line.text.chars().count(),
+                    };
+                    for (start, end) in regex.find_all(&text).map_err(|e| (line_span, e))? {
+                        let span = Span {
+                            line: line.number,
+                            column: text[..start].chars().count() + 1,
+                            length: text[start..end].chars().count(),
+                        };
+                        out.push((span, text[start..end].to_string()));
+                    }
+                }
+            }
+            Matcher::Phrases(phrases) => {
+                for phrase in phrases {
+                    for span in prose::find_text(md, phrase, Position::Anywhere) {
+                        let line = md
+                            .lines
+                            .iter()
+                            .find(|l| l.number == span.line)
+                            .map_or("", |l| l.text);
+                        let text = line
+                            .chars()
+                            .skip(span.column - 1)
+                            .take(span.length)
+                            .collect();
+                        out.push((span, text));
+                    }
+                }
+                out.sort_by_key(|(s, _)| (s.line, s.column));
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl Check for Custom {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn applies_to(&self, kind: FileKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let found = match self.find(&doc.markdown) {
+            Ok(found) => found,
+            Err((span, e)) => return vec![Diagnostic::of(doc.path, self.id, e).at(Some(span))],
+        };
+        if self.require {
+            if found.is_empty() {
+                return vec![Diagnostic::of(doc.path, self.id, self.message.clone())];
+            }
+            return Vec::new();
+        }
+        found
+            .into_iter()
+            .map(|(span, text)| {
+                Diagnostic::of(doc.path, self.id, self.message.replace("{match}", &text))
+                    .at(Some(span))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,281 @@
+//! A long-running server for editor plugins, on a Unix domain socket.
+//!
+//! Running the binary on every keystroke pays for process startup, for
+//! loading the config, and for reading the whole cache, each time. The
+//! daemon pays once: it keeps each project's config in memory, and the
+//! findings of every file it has seen, keyed by the file's content, so
+//! per request only the file asked about is analyzed afresh. The checks
+//! over the whole tree still read the files they look at. The cache is
+//! written back at most every few seconds, and when the daemon stops.
+//!
+//! Requests and responses are one JSON object per line. A request names
+//! a file by its `path`, and may carry the editor's unsaved `text`,
+//! which is linted with only the checks that look at one file at a time.
+//! The response is `{"diagnostics":[...]}`, each diagnostic as in JSON
+//! output, or `{"error":"..."}`. `{"stop":true}` stops the daemon.
+//! Each connection is served on a thread of its own, so a client that
+//! keeps one open does not hold up the others.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::cache::{self, Cache};
+use crate::config::{self, Config};
+use crate::json::{self, Kind, Value};
+use crate::lsp::target_for;
+use crate::rules::FileKind;
+use crate::workspace::Target;
+use crate::{ignore, lint_target, lint_target_changed, lint_text_with, output, Diagnostic};
+
+/// The socket's file name in the runtime directory.
+const SOCKET_NAME: &str = "claude-lint.sock";
+
+/// How long new cache entries may wait before they are written out.
+const SAVE_DELAY: Duration = Duration::from_secs(5);
+
+/// Where the daemon listens unless told otherwise: in `$XDG_RUNTIME_DIR`,
+/// which only its user can reach, or else in the temporary directory,
+/// under a name with the user's in it.
+pub fn default_socket() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir).join(SOCKET_NAME);
+    }
+    match std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+        Ok(user) if !user.is_empty() => {
+            std::env::temp_dir().join(format!("claude-lint-{}.sock", user))
+        }
+        _ => std::env::temp_dir().join(SOCKET_NAME),
+    }
+}
+
+/// What the daemon keeps for one config root between requests.
+struct Warm {
+    config: Config,
+    /// When the files the config was loaded from last changed.
+    stamp: Vec<Option<SystemTime>>,
+    cache: Cache,
+    /// Where the cache is saved, for directory targets.
+    cache_path: Option<PathBuf>,
+    /// Whether the cache has entries that are not on disk yet.
+    unsaved: bool,
+    saved: Instant,
+}
+
+#[derive(Default)]
+struct Daemon {
+    warm: HashMap<PathBuf, Warm>,
+    stopping: bool,
+}
+
+/// Listens on socket, calling ready once it does, and answers requests
+/// until one asks the daemon to stop. A socket left behind by a daemon
+/// that did not stop cleanly is replaced; one that a daemon still
+/// listens on is an error.
+#[cfg(unix)]
+pub fn serve(socket: &Path, ready: impl FnOnce()) -> io::Result<()> {
+    use std::io::BufReader;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            let message = format!("{}: a daemon is already listening there", socket.display());
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, message));
+        }
+        fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    ready();
+    let daemon = Arc::new(Mutex::new(Daemon::default()));
+    let result = loop {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) => break Err(e),
+        };
+        if lock(&daemon).stopping {
+            break Ok(());
+        }
+        let (daemon, wake) = (Arc::clone(&daemon), socket.to_path_buf());
+        std::thread::spawn(move || match answer(&daemon, BufReader::new(&stream), &stream) {
+            // Wakes the accept loop so that it sees the daemon is stopping.
+            Ok(false) => drop(UnixStream::connect(&wake)),
+            Ok(true) => {}
+            // A client that goes away mid-answer only loses its answer.
+            Err(e) => crate::log::debug!("connection dropped: {}", e),
+        });
+    };
+    for warm in lock(&daemon).warm.values_mut() {
+        warm.flush();
+    }
+    let _ = fs::remove_file(socket);
+    result
+}
+
+/// Std has no local sockets off Unix.
+#[cfg(not(unix))]
+pub fn serve(_: &Path, _: impl FnOnce()) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "the daemon needs Unix domain sockets"))
+}
+
+/// Takes the daemon's state, which a request that panicked leaves as
+/// usable as any other.
+fn lock(daemon: &Mutex<Daemon>) -> std::sync::MutexGuard<'_, Daemon> {
+    daemon.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Answers the requests on one connection until the client closes it.
+/// Returns false when a request asks the daemon to stop.
+fn answer(daemon: &Mutex<Daemon>, input: impl BufRead, mut out: impl Write) -> io::Result<bool> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match json::parse(&line) {
+            Ok(request)
+                if request
+                    .get("stop")
+                    .is_some_and(|v| matches!(v.kind, Kind::Bool(true))) =>
+            {
+                lock(daemon).stopping = true;
+                writeln!(out, "{{\"stopped\":true}}")?;
+                return Ok(false);
+            }
+            Ok(request) => match lock(daemon).lint(&request) {
+                Ok(diags) => {
+                    let list: Vec<String> = diags.iter().map(|d| output::ndjson(d, None)).collect();
+                    format!("{{\"diagnostics\":[{}]}}", list.join(","))
+                }
+                Err(e) => error(&e),
+            },
+            Err(e) => error(&format!("invalid request: {}", e.message)),
+        };
+        writeln!(out, "{}", response)?;
+        out.flush()?;
+    }
+    Ok(true)
+}
+
+impl Daemon {
+    /// Lints the file a request names, returning only its findings.
+    fn lint(&mut self, request: &Value) -> Result<Vec<Diagnostic>, String> {
+        let Some(path) = request.get("path").and_then(Value::as_str) else {
+            return Err("request has no path".to_string());
+        };
+        let path = absolute(Path::new(path));
+        let Some(target) = target_for(&path) else {
+            return Err(format!(
+                "{}: not in a .claude directory, and not a CLAUDE.md",
+                path.display()
+            ));
+        };
+        let warm = self.warm(&target)?;
+        if let Some(text) = request.get("text").and_then(Value::as_str) {
+            let Some(kind) = FileKind::infer(&path) else {
+                return Err(format!("{}: cannot tell what kind of file this is", path.display()));
+            };
+            return Ok(lint_text_with(&path, kind, text.as_bytes().to_vec(), &warm.config));
+        }
+        let diags = match &target {
+            Target::Dir(_) | Target::User(_) => {
+                let diags = lint_target_changed(
+                    &target,
+                    &warm.config,
+                    &warm.cache,
+                    std::slice::from_ref(&path),
+                );
+                warm.save();
+                diags.unwrap_or_default()
+            }
+            Target::ClaudeMd(_) | Target::File(..) => lint_target(&target, &warm.config),
+        };
+        Ok(diags.into_iter().filter(|d| d.path == path).collect())
+    }
+
+    /// Returns what is kept for a target, loading its config afresh the
+    /// first time and whenever it changed on disk.
+    fn warm(&mut self, target: &Target) -> Result<&mut Warm, String> {
+        let root = target.config_root();
+        let stamp = stamp(&root);
+        if self.warm.get(&root).is_none_or(|w| w.stamp != stamp) {
+            let config = config::load(None, &root, false)?;
+            let cache_path = match target {
+                Target::Dir(dir) | Target::User(dir) => Some(cache::default_path(dir)),
+                Target::ClaudeMd(_) | Target::File(..) => None,
+            };
+            // Cached findings are stored before config applies, so they
+            // outlive a config change.
+            let cache = match self.warm.remove(&root) {
+                Some(old) => old.cache,
+                None => cache_path.as_deref().map_or_else(Cache::empty, Cache::load),
+            };
+            let (unsaved, saved) = (false, Instant::now());
+            self.warm.insert(
+                root.clone(),
+                Warm {
+                    config,
+                    stamp,
+                    cache,
+                    cache_path,
+                    unsaved,
+                    saved,
+                },
+            );
+        }
+        Ok(self.warm.get_mut(&root).expect("inserted above"))
+    }
+}
+
+impl Warm {
+    /// Keeps the cache entries a request used for the next request, and
+    /// writes them out once they have waited long enough.
+    fn save(&mut self) {
+        self.unsaved |= self.cache.changed();
+        self.cache.roll_over();
+        if self.saved.elapsed() >= SAVE_DELAY {
+            self.flush();
+        }
+    }
+
+    /// Writes the cache if it has entries that are not on disk yet.
+    fn flush(&mut self) {
+        if let (true, Some(path)) = (self.unsaved, &self.cache_path) {
+            // A read-only tree only loses the speedup across restarts.
+            let _ = self.cache.save_loaded(path);
+        }
+        self.unsaved = false;
+        self.saved = Instant::now();
+    }
+}
+
+/// When the files a config root's config is read from last changed.
+fn stamp(root: &Path) -> Vec<Option<SystemTime>> {
+    let parent = root.parent().unwrap_or(Path::new(""));
+    [
+        parent.join(config::FILE_NAME),
+        parent.join(config::WORDS_FILE_NAME),
+        root.join(ignore::FILE_NAME),
+    ]
+    .iter()
+    .map(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+    .collect()
+}
+
+/// Makes a path absolute without needing it to exist, as a new file's
+/// unsaved buffer does not.
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(abs) = fs::canonicalize(path) {
+        return abs;
+    }
+    match (path.parent().and_then(|d| fs::canonicalize(d).ok()), path.file_name()) {
+        (Some(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn error(message: &str) -> String {
+    format!("{{\"error\":{}}}", json::string(message))
+}
@@ -0,0 +1,146 @@
+//! Prose repeated across a directory's Markdown files.
+//!
+//! Every file in a session's context costs tokens, so a paragraph pasted
+//! into CLAUDE.md, an agent, and a skill is paid for three times. Lines
+//! of prose are normalized, ignoring case, punctuation, list markers,
+//! and wrapping whitespace, and each run of min_lines of them is hashed
+//! as a shingle. A run that matches one in an earlier file is reported
+//! at the later copy, which names the first. A CLAUDE.md is also held
+//! to sharing no line with the .claude/CLAUDE.md beside it.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache;
+use crate::config::Config;
+use crate::jobs;
+use crate::markdown::{self, Kind};
+use crate::{Diagnostic, Span};
+
+const MIN_LINES: usize = 3;
+
+/// Lines with fewer words say too little to count as copied.
+const MIN_WORDS: usize = 3;
+
+/// A normalized prose line and where it is.
+struct Line {
+    number: usize,
+    text: String,
+}
+
+/// Reports blocks of at least min_lines prose lines that repeat a block
+/// in an earlier file.
+pub(crate) fn check_duplicates(root: &Path, config: &Config, diags: &mut Vec<Diagnostic>) {
+    let min = config.option("CL102", "min_lines", root).unwrap_or(MIN_LINES).max(1);
+    let files: Vec<(PathBuf, Vec<Line>)> = jobs::documents(root, &config.exclude)
+        .into_iter()
+        .filter_map(|(path, _)| {
+            let content = fs::read_to_string(&path).ok()?;
+            Some((path, prose_lines(&content)))
+        })
+        .collect();
+
+    // The first place each shingle appears, as a file and line index.
+    let mut first: HashMap<u64, (usize, usize)> = HashMap::new();
+    for (f, (path, lines)) in files.iter().enumerate() {
+        let shingles: Vec<u64> = lines.windows(min).map(shingle).collect();
+        let mut i = 0;
+        while i < shingles.len() {
+            let Some(&(g, j)) = first.get(&shingles[i]) else {
+                i += 1;
+                continue;
+            };
+            // Extend the block while both copies keep matching.
+            let (earlier, theirs) = (&files[g].0, &files[g].1);
+            let mut n = 1;
+            while i + n < shingles.len()
+                && theirs
+                    .get(j + n + min - 1)
+                    .is_some_and(|l| l.text == lines[i + n + min - 1].text)
+            {
+                n += 1;
+            }
+            let last = n + min - 2;
+            let (start, end) = (lines[i].number, lines[i + last].number);
+            let msg = format!(
+                "lines {}-{} repeat {}:{}-{}",
+                start,
+                end,
+                earlier.display(),
+                theirs[j].number,
+                theirs[j + last].number
+            );
+            diags.push(Diagnostic::of(path, "CL102", msg).at(Some(Span {
+                line: start,
+                column: 1,
+                length: 1,
+            })));
+            i += last + 1;
+        }
+        for (i, &s) in shingles.iter().enumerate() {
+            first.entry(s).or_insert((f, i));
+        }
+    }
+}
+
+/// Reports a CLAUDE.md that shares lines with the one in the .claude
+/// directory beside it. Claude Code loads both as project memory, so
+/// neither overrides the other and a shared line is read twice.
+pub(crate) fn check_overlap(claude_md: &Path, diags: &mut Vec<Diagnostic>) {
+    if claude_md.file_name().is_none_or(|n| n != "CLAUDE.md") {
+        return;
+    }
+    let inner = claude_md.parent().unwrap_or(Path::new("")).join(".claude").join("CLAUDE.md");
+    let (Ok(ours), Ok(theirs)) = (fs::read_to_string(claude_md), fs::read_to_string(&inner)) else {
+        return;
+    };
+    let theirs: HashSet<String> = prose_lines(&theirs).into_iter().map(|l| l.text).collect();
+    let shared: Vec<Line> = prose_lines(&ours)
+        .into_iter()
+        .filter(|l| theirs.contains(&l.text))
+        .collect();
+    let Some(first) = shared.first() else {
+        return;
+    };
+    let msg = format!(
+        "shares {} line(s) with {}; Claude Code loads both, so neither overrides the other",
+        shared.len(),
+        inner.display()
+    );
+    diags.push(Diagnostic::of(claude_md, "CL012", msg).at(Some(Span {
+        line: first.number,
+        column: 1,
+        length: 1,
+    })));
+}
+
+/// Returns a file's paragraph and list lines, normalized, leaving out
+/// short ones.
+fn prose_lines(content: &str) -> Vec<Line> {
+    markdown::parse(content)
+        .lines
+        .iter()
+        .filter(|l| matches!(l.kind, Kind::Paragraph | Kind::ListItem { .. }))
+        .filter_map(|l| {
+            let text = markdown::list_marker(l.text)
+                .map_or(l.text, |(_, at)| l.text.get(at..).unwrap_or(""));
+            let words: Vec<String> = text
+                .split_whitespace()
+                .map(|w| {
+                    w.chars()
+                        .filter(|c| c.is_alphanumeric())
+                        .flat_map(char::to_lowercase)
+                        .collect::<String>()
+                })
+                .filter(|w| !w.is_empty())
+                .collect();
+            (words.len() >= MIN_WORDS).then(|| Line { number: l.number, text: words.join(" ") })
+        })
+        .collect()
+}
+
+fn shingle(lines: &[Line]) -> u64 {
+    let parts: Vec<&[u8]> = lines.iter().map(|l| l.text.as_bytes()).collect();
+    cache::hash(&parts)
+}
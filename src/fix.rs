@@ -0,0 +1,197 @@
+//! Autofixes: applying edits attached to diagnostics, and showing them as
+//! unified diffs.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::Diagnostic;
+
+/// Replaces the byte range start..end of a file with text.
+#[derive(Clone, Debug)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+impl Edit {
+    pub fn insert(at: usize, text: String) -> Self {
+        Edit { start: at, end: at, text }
+    }
+}
+
+/// A file whose content changes under the collected fixes.
+pub struct Change {
+    pub path: PathBuf,
+    pub before: String,
+    pub after: String,
+}
+
+/// Groups fixes by file and computes the fixed content of each. A file
+/// that cannot be read as UTF-8 is left alone, with a warning in the
+/// second list; writing a fix computed over anything else would lose its
+/// content.
+pub fn changes(diags: &[Diagnostic]) -> (Vec<Change>, Vec<String>) {
+    let mut by_path: BTreeMap<&PathBuf, Vec<&Edit>> = BTreeMap::new();
+    for d in diags {
+        if let Some(edit) = &d.fix {
+            by_path.entry(&d.path).or_default().push(edit);
+        }
+    }
+
+    let (mut out, mut skipped) = (Vec::new(), Vec::new());
+    for (path, edits) in by_path {
+        let before = match fs::read(path).map(String::from_utf8) {
+            Ok(Ok(content)) => content,
+            Ok(Err(_)) => {
+                skipped.push(format!(
+                    "{}: not valid UTF-8, so its fixes were not applied",
+                    path.display()
+                ));
+                continue;
+            }
+            // Files are created by their own edits, so a missing file is empty.
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                skipped.push(format!(
+                    "{}: cannot read, so its fixes were not applied: {}",
+                    path.display(),
+                    e
+                ));
+                continue;
+            }
+        };
+        let after = apply(&before, edits);
+        if after != before {
+            out.push(Change { path: path.clone(), before, after });
+        }
+    }
+    (out, skipped)
+}
+
+/// Applies edits back to front so earlier offsets stay valid. An edit
+/// overlapping one already applied is dropped; the next run picks it up.
+fn apply(content: &str, mut edits: Vec<&Edit>) -> String {
+    // Inserts at the same offset land in diagnostic order.
+    edits.sort_by_key(|e| (e.start, e.end));
+    let mut out = content.to_string();
+    let mut limit = usize::MAX;
+    for e in edits.into_iter().rev() {
+        if e.end > limit || e.end > content.len() {
+            continue;
+        }
+        out.replace_range(e.start..e.end, &e.text);
+        limit = e.start;
+    }
+    out
+}
+
+/// Renders a unified diff between two versions of a file.
+pub fn unified_diff(path: &str, before: &str, after: &str) -> String {
+    const CONTEXT: usize = 3;
+
+    let a: Vec<&str> = before.lines().collect();
+    let b: Vec<&str> = after.lines().collect();
+    let ops = diff_lines(&a, &b);
+
+    let mut out = if path.starts_with('/') {
+        format!("--- {}\n+++ {}\n", path, path)
+    } else {
+        format!("--- a/{}\n+++ b/{}\n", path, path)
+    };
+    let mut i = 0;
+    while i < ops.len() {
+        if matches!(ops[i], Op::Same(..)) {
+            i += 1;
+            continue;
+        }
+
+        // Grow the hunk while changes are within 2*CONTEXT of each other.
+        let start = i.saturating_sub(CONTEXT);
+        let mut last_change = i;
+        for (j, op) in ops.iter().enumerate().skip(i) {
+            if !matches!(op, Op::Same(..)) {
+                last_change = j;
+            } else if j - last_change > 2 * CONTEXT {
+                break;
+            }
+        }
+        let end = (last_change + 1 + CONTEXT).min(ops.len());
+
+        let (mut a_start, mut b_start, mut a_len, mut b_len) = (None, None, 0, 0);
+        let mut body = String::new();
+        for op in &ops[start..end] {
+            match *op {
+                Op::Same(ai, bi) => {
+                    a_start.get_or_insert(ai);
+                    b_start.get_or_insert(bi);
+                    a_len += 1;
+                    b_len += 1;
+                    body.push_str(&format!(" {}\n", a[ai]));
+                }
+                Op::Delete(ai, bi) => {
+                    a_start.get_or_insert(ai);
+                    b_start.get_or_insert(bi);
+                    a_len += 1;
+                    body.push_str(&format!("-{}\n", a[ai]));
+                }
+                Op::Insert(ai, bi) => {
+                    a_start.get_or_insert(ai);
+                    b_start.get_or_insert(bi);
+                    b_len += 1;
+                    body.push_str(&format!("+{}\n", b[bi]));
+                }
+            }
+        }
+        // Unified diffs number an empty range by the line before it.
+        let a_first = if a_len == 0 { a_start.unwrap_or(0) } else { a_start.unwrap_or(0) + 1 };
+        let b_first = if b_len == 0 { b_start.unwrap_or(0) } else { b_start.unwrap_or(0) + 1 };
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", a_first, a_len, b_first, b_len));
+        out.push_str(&body);
+        i = end;
+    }
+    out
+}
+
+/// One line of an edit script, carrying the positions in both inputs.
+#[derive(Clone, Copy)]
+enum Op {
+    Same(usize, usize),
+    Delete(usize, usize),
+    Insert(usize, usize),
+}
+
+/// Longest-common-subsequence line diff. Context files are small, so
+/// the quadratic table is fine.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            ops.push(Op::Same(i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(Op::Delete(i, j));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(i, j));
+            j += 1;
+        }
+    }
+    ops
+}
@@ -0,0 +1,433 @@
+//! YAML frontmatter reader.
+//!
+//! Frontmatter in agents and skills is a flat mapping, so this reads the
+//! subset that shows up there: `key: value` pairs with plain, quoted, or
+//! block scalars, block and flow lists, and one level of nested mapping.
+//! Anything fancier (anchors, tags, multi-document streams) is reported
+//! as an error rather than guessed at.
+
+use std::ops::Range;
+
+/// A parsed frontmatter block.
+#[derive(Clone, Debug)]
+pub struct Frontmatter {
+    pub fields: Vec<Field>,
+    /// 1-based line of the closing delimiter.
+    pub end_line: usize,
+    /// Byte offset of the body, just past the closing delimiter line.
+    pub body_start: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub key: String,
+    pub value: Value,
+    /// 1-based line of the key in the file.
+    pub line: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    String(String),
+    List(Vec<String>),
+    Map(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "mapping",
+        }
+    }
+}
+
+/// The message for a block with no closing delimiter.
+pub const NOT_CLOSED: &str = "frontmatter is not closed with '---'";
+
+/// A parse failure at a 1-based file line.
+#[derive(Clone, Debug)]
+pub struct Error {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Frontmatter {
+    /// Returns the first field with the given key.
+    pub fn get(&self, key: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.key == key)
+    }
+
+    /// Returns a field's string value, if it is a string.
+    pub fn string(&self, key: &str) -> Option<&str> {
+        match self.get(key).map(|f| &f.value) {
+            Some(Value::String(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the 1-based lines a field spans, end-exclusive. A block
+    /// scalar goes on until the next key or the end.
+    pub fn lines_of(&self, field: &Field) -> Range<usize> {
+        let end = self
+            .fields
+            .iter()
+            .map(|f| f.line)
+            .filter(|&l| l > field.line)
+            .min()
+            .unwrap_or(self.end_line);
+        field.line..end
+    }
+}
+
+/// Parses the frontmatter at the top of content. Returns None when the
+/// file does not start with a `---` line. A byte order mark before it and
+/// CRLF line endings are allowed.
+pub fn parse(content: &str) -> Option<Result<Frontmatter, Error>> {
+    let bom = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let rest = bom.strip_prefix("---\n").or_else(|| bom.strip_prefix("---\r\n"))?;
+    Some(parse_block(rest, content.len() - rest.len()))
+}
+
+fn parse_block(rest: &str, mut offset: usize) -> Result<Frontmatter, Error> {
+    // Collect lines up to the closing delimiter; line 1 is the opener.
+    let mut lines = Vec::new();
+    let mut end = None;
+    for (i, raw) in rest.split_inclusive('\n').enumerate() {
+        let line = raw.trim_end_matches('\n').trim_end_matches('\r');
+        offset += raw.len();
+        if line.trim_end() == "---" {
+            end = Some((i + 2, offset));
+            break;
+        }
+        lines.push((i + 2, line));
+    }
+    let (end_line, body_start) = end.ok_or_else(|| Error {
+        line: 1,
+        message: NOT_CLOSED.to_string(),
+    })?;
+
+    let fields = parse_mapping(&lines)?
+        .into_iter()
+        .map(|(key, value, line)| Field { key, value, line })
+        .collect();
+    Ok(Frontmatter { fields, end_line, body_start })
+}
+
+type Line<'a> = (usize, &'a str);
+
+/// Parses lines at a single indentation level into key/value pairs.
+fn parse_mapping(lines: &[Line]) -> Result<Vec<(String, Value, usize)>, Error> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let (n, line) = lines[i];
+        i += 1;
+        if is_blank(line) {
+            continue;
+        }
+        if line.starts_with('\t') {
+            return Err(err(n, "tabs are not allowed for indentation"));
+        }
+        if line.starts_with(' ') {
+            return Err(err(n, "unexpected indentation"));
+        }
+
+        let (key, rest) = split_key(line).ok_or_else(|| err(n, "expected 'key: value'"))?;
+        if key.is_empty() {
+            return Err(err(n, "empty key"));
+        }
+        let rest = strip_comment(rest.trim_start()).trim();
+
+        // Gather indented continuation lines.
+        let start = i;
+        while i < lines.len() && (is_blank(lines[i].1) || lines[i].1.starts_with([' ', '\t'])) {
+            i += 1;
+        }
+        let mut block = &lines[start..i];
+        // Trailing blank lines belong to nobody.
+        while let Some(((_, l), head)) = block.split_last() {
+            if l.trim().is_empty() {
+                block = head;
+            } else {
+                break;
+            }
+        }
+
+        let value = if rest.starts_with('|') || rest.starts_with('>') {
+            block_scalar(rest, block, n)?
+        } else if rest.is_empty() {
+            nested(block)?
+        } else if !block.is_empty() && !rest.starts_with(['"', '\'', '[', '{']) {
+            // A plain scalar folded across lines.
+            let mut s = rest.to_string();
+            for (_, l) in block {
+                let l = strip_comment(l).trim();
+                if !l.is_empty() {
+                    s.push(' ');
+                    s.push_str(l);
+                }
+            }
+            Value::String(s)
+        } else if let Some(&(bn, _)) = block.first() {
+            return Err(err(bn, "unexpected indentation"));
+        } else {
+            inline_value(rest, n)?
+        };
+        out.push((key.to_string(), value, n));
+    }
+    Ok(out)
+}
+
+fn err(line: usize, message: &str) -> Error {
+    Error { line, message: message.to_string() }
+}
+
+fn is_blank(line: &str) -> bool {
+    let t = line.trim();
+    t.is_empty() || t.starts_with('#')
+}
+
+/// Splits "key: rest" at the first colon followed by a space or the end
+/// of the line, so "http://x" style colons stay in the key or value.
+fn split_key(line: &str) -> Option<(&str, &str)> {
+    if let Some(q) = line.chars().next().filter(|&c| c == '"' || c == '\'') {
+        let close = line[1..].find(q)? + 1;
+        let after = line[close + 1..].trim_start().strip_prefix(':')?;
+        if !after.is_empty() && !after.starts_with([' ', '\t']) {
+            return None;
+        }
+        return Some((&line[1..close], after));
+    }
+    let mut start = 0;
+    loop {
+        let colon = start + line[start..].find(':')?;
+        let after = &line[colon + 1..];
+        if after.is_empty() || after.starts_with([' ', '\t']) {
+            return Some((line[..colon].trim_end(), after));
+        }
+        start = colon + 1;
+    }
+}
+
+/// Removes a trailing " # comment". Quotes only matter when the value
+/// itself is quoted; apostrophes in plain text are just text.
+fn strip_comment(s: &str) -> &str {
+    // Start scanning after a leading quoted string, if any.
+    let mut from = 0;
+    let mut prev = ' ';
+    if let Some(q) = s.chars().next().filter(|&c| c == '"' || c == '\'') {
+        match s[1..].find(q) {
+            Some(close) => {
+                from = close + 2;
+                prev = q;
+            }
+            None => return s,
+        }
+    }
+    for (i, c) in s[from..].char_indices() {
+        if c == '#' && (prev == ' ' || prev == '\t') {
+            return &s[..from + i];
+        }
+        prev = c;
+    }
+    s
+}
+
+fn block_scalar(indicator: &str, block: &[Line], line: usize) -> Result<Value, Error> {
+    let folded = indicator.starts_with('>');
+    let chomp = &indicator[1..];
+    if !matches!(chomp, "" | "-" | "+") {
+        return Err(err(line, "unsupported block scalar indicator"));
+    }
+
+    let indent = block
+        .iter()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(_, l)| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let body: Vec<&str> = block
+        .iter()
+        .map(|(_, l)| if l.len() >= indent { &l[indent..] } else { "" })
+        .collect();
+
+    let mut s = String::new();
+    for (i, l) in body.iter().enumerate() {
+        if i > 0 {
+            let joined = folded && !l.is_empty() && !body[i - 1].is_empty();
+            s.push(if joined { ' ' } else { '\n' });
+        }
+        s.push_str(l);
+    }
+    if chomp != "-" && !s.is_empty() {
+        s.push('\n');
+    }
+    Ok(Value::String(s))
+}
+
+/// Parses an indented block under a key with no inline value.
+fn nested(block: &[Line]) -> Result<Value, Error> {
+    let content: Vec<&Line> = block.iter().filter(|(_, l)| !is_blank(l)).collect();
+    let Some(&&(first_line, first)) = content.first() else {
+        return Ok(Value::Null);
+    };
+
+    if first.trim_start().starts_with("- ") || first.trim() == "-" {
+        let mut items = Vec::new();
+        for &&(n, l) in &content {
+            let item = l.trim_start();
+            let item = item
+                .strip_prefix("- ")
+                .or_else(|| (item == "-").then_some(""))
+                .ok_or_else(|| err(n, "expected '- item' in list"))?;
+            match inline_value(strip_comment(item).trim(), n)? {
+                Value::String(s) => items.push(s),
+                Value::Null => items.push(String::new()),
+                _ => return Err(err(n, "nested collections in lists are not supported")),
+            }
+        }
+        return Ok(Value::List(items));
+    }
+
+    let indent = first.len() - first.trim_start().len();
+    let mut dedented = Vec::new();
+    for &(n, l) in block {
+        if is_blank(l) {
+            continue;
+        }
+        let this = l.len() - l.trim_start().len();
+        if this < indent {
+            return Err(err(n, "inconsistent indentation"));
+        }
+        dedented.push((n, &l[indent..]));
+    }
+    let pairs = parse_mapping(&dedented).map_err(|e| {
+        if e.line == first_line && e.message == "expected 'key: value'" {
+            err(e.line, "expected a list or mapping")
+        } else {
+            e
+        }
+    })?;
+    Ok(Value::Map(pairs.into_iter().map(|(k, v, _)| (k, v)).collect()))
+}
+
+/// Parses a value written on the same line as its key.
+fn inline_value(s: &str, line: usize) -> Result<Value, Error> {
+    if s.is_empty() || s == "~" || s == "null" {
+        return Ok(Value::Null);
+    }
+    if s.starts_with(['&', '*', '!']) {
+        return Err(err(line, "anchors, aliases, and tags are not supported"));
+    }
+    if let Some(inner) = s.strip_prefix('[') {
+        let inner = inner
+            .strip_suffix(']')
+            .ok_or_else(|| err(line, "unterminated flow list"))?;
+        let mut items = Vec::new();
+        for part in split_flow(inner) {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            items.push(scalar(part, line)?);
+        }
+        return Ok(Value::List(items));
+    }
+    if let Some(inner) = s.strip_prefix('{') {
+        let inner = inner
+            .strip_suffix('}')
+            .ok_or_else(|| err(line, "unterminated flow mapping"))?;
+        let mut pairs = Vec::new();
+        for part in split_flow(inner) {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let (k, v) = split_key(part)
+                .or_else(|| part.strip_suffix(':').map(|k| (k, "")))
+                .ok_or_else(|| err(line, "expected 'key: value' in flow mapping"))?;
+            pairs.push((k.to_string(), inline_value(v.trim(), line)?));
+        }
+        return Ok(Value::Map(pairs));
+    }
+    scalar(s, line).map(Value::String)
+}
+
+/// Splits flow collection items on top-level commas.
+fn split_flow(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == ',' => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            None => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn scalar(s: &str, line: usize) -> Result<String, Error> {
+    if let Some(inner) = s.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    return if chars.as_str().trim().is_empty() {
+                        Ok(out)
+                    } else {
+                        Err(err(line, "unexpected text after quoted string"))
+                    };
+                }
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('/') => out.push('/'),
+                    Some(c) => {
+                        out.push('\\');
+                        out.push(c);
+                    }
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        return Err(err(line, "unterminated double-quoted string"));
+    }
+    if let Some(inner) = s.strip_prefix('\'') {
+        let mut out = String::new();
+        let mut chars = inner.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    out.push('\'');
+                    continue;
+                }
+                let rest: String = chars.collect();
+                return if rest.trim().is_empty() {
+                    Ok(out)
+                } else {
+                    Err(err(line, "unexpected text after quoted string"))
+                };
+            }
+            out.push(c);
+        }
+        return Err(err(line, "unterminated single-quoted string"));
+    }
+    Ok(s.to_string())
+}
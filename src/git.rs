@@ -0,0 +1,188 @@
+//! Changed-file discovery through git, for `--changed`, the lines a
+//! change touched, for `--diff-filter`, and where a repository keeps its
+//! hooks, for `install-hook`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::Diagnostic;
+
+/// Returns the absolute paths of files that differ from base in the
+/// working tree of the repository holding dir, including untracked ones
+/// and deletions. Without a base, compares against the merge base with
+/// the default branch.
+pub fn changed_files(dir: &Path, base: Option<&str>) -> Result<Vec<PathBuf>, String> {
+    let (top, base) = resolve(dir, base)?;
+    let diff = git(&top, &["diff", "--name-only", "-z", &base, "--"])?;
+    let untracked = git(
+        &top,
+        &[
+            "ls-files",
+            "--others",
+            "--exclude-standard",
+            "--full-name",
+            "-z",
+        ],
+    )?;
+    let mut files: Vec<PathBuf> = diff
+        .split('\0')
+        .chain(untracked.split('\0'))
+        .filter(|p| !p.is_empty())
+        .map(|p| top.join(p))
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// The lines a change touched, by absolute path.
+#[derive(Debug, Default)]
+pub struct Touched {
+    files: HashMap<PathBuf, Lines>,
+}
+
+#[derive(Debug)]
+enum Lines {
+    /// A file the change added, all of whose lines are new.
+    All,
+    /// Ranges of lines, from the first to the last, 1-based.
+    Ranges(Vec<(usize, usize)>),
+}
+
+impl Touched {
+    /// Drops findings on lines the change did not touch and returns how
+    /// many it dropped. A finding on a whole file stays only when the
+    /// change added the file.
+    pub fn filter(&self, diags: &mut Vec<Diagnostic>) -> usize {
+        let mut canonical: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+        let before = diags.len();
+        diags.retain(|d| {
+            let path = canonical
+                .entry(d.path.clone())
+                .or_insert_with(|| fs::canonicalize(&d.path).ok());
+            match (path.as_ref().and_then(|p| self.files.get(p)), d.span) {
+                (Some(Lines::All), _) => true,
+                (Some(Lines::Ranges(ranges)), Some(span)) => ranges
+                    .iter()
+                    .any(|&(first, last)| (first..=last).contains(&span.line)),
+                _ => false,
+            }
+        });
+        before - diags.len()
+    }
+}
+
+/// Returns the lines that differ from base in the working tree of the
+/// repository holding dir, with untracked files as added. Without a
+/// base, compares against the merge base with the default branch.
+pub fn touched_lines(dir: &Path, base: Option<&str>) -> Result<Touched, String> {
+    let (top, base) = resolve(dir, base)?;
+    let diff = git(
+        &top,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "--unified=0",
+            "--no-color",
+            "--no-ext-diff",
+            &base,
+            "--",
+        ],
+    )?;
+    let mut touched = Touched::default();
+    let mut current: Option<PathBuf> = None;
+    let mut added = false;
+    for line in diff.lines() {
+        if let Some(old) = line.strip_prefix("--- ") {
+            added = old == "/dev/null";
+        } else if let Some(new) = line.strip_prefix("+++ ") {
+            current = new.strip_prefix("b/").map(|p| top.join(p));
+            if let Some(path) = &current {
+                touched.files.insert(
+                    path.clone(),
+                    if added {
+                        Lines::All
+                    } else {
+                        Lines::Ranges(Vec::new())
+                    },
+                );
+            }
+        } else if let (Some(hunk), Some(path)) = (line.strip_prefix("@@ "), &current) {
+            // "@@ -a,b +c,d @@": d lines from c are new; d is 1 if left
+            // out, and 0 for a hunk that only deletes.
+            let new = hunk.split(' ').find_map(|part| part.strip_prefix('+')).unwrap_or_default();
+            let (start, count) = new.split_once(',').unwrap_or((new, "1"));
+            let (Ok(start), Ok(count)) = (start.parse::<usize>(), count.parse::<usize>()) else {
+                continue;
+            };
+            if let (true, Some(Lines::Ranges(ranges))) = (count > 0, touched.files.get_mut(path)) {
+                ranges.push((start, start + count - 1));
+            }
+        }
+    }
+    let untracked = git(
+        &top,
+        &[
+            "ls-files",
+            "--others",
+            "--exclude-standard",
+            "--full-name",
+            "-z",
+        ],
+    )?;
+    for path in untracked.split('\0').filter(|p| !p.is_empty()) {
+        touched.files.insert(top.join(path), Lines::All);
+    }
+    Ok(touched)
+}
+
+/// Finds the top of the repository holding dir, and checks base, or
+/// finds the merge base with the default branch.
+fn resolve(dir: &Path, base: Option<&str>) -> Result<(PathBuf, String), String> {
+    let top = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim_end());
+    let base = match base {
+        Some(b) => b.to_string(),
+        None => git(&top, &["merge-base", "HEAD", &default_branch(&top)?])?.trim_end().to_string(),
+    };
+    if git(&top, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", base)]).is_err() {
+        return Err(format!("unknown git ref '{}'", base));
+    }
+    Ok((top, base))
+}
+
+/// Returns the directory git runs the hooks of the repository holding
+/// dir from, which `core.hooksPath` may move.
+pub fn hooks_dir(dir: &Path) -> Result<PathBuf, String> {
+    Ok(dir.join(git(dir, &["rev-parse", "--git-path", "hooks"])?.trim_end()))
+}
+
+/// Finds the default branch: origin's HEAD if known, else main or master.
+fn default_branch(top: &Path) -> Result<String, String> {
+    if let Ok(head) = git(top, &["symbolic-ref", "--short", "refs/remotes/origin/HEAD"]) {
+        return Ok(head.trim_end().to_string());
+    }
+    for name in ["main", "master", "origin/main", "origin/master"] {
+        if git(top, &["rev-parse", "--verify", "--quiet", name]).is_ok() {
+            return Ok(name.to_string());
+        }
+    }
+    Err("cannot find the default branch; pass a ref to compare against".to_string())
+}
+
+/// Runs git in dir and returns its output.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("cannot run git: {}", e))?;
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("git {}: {}", args.join(" "), stderr.trim()));
+    }
+    String::from_utf8(out.stdout).map_err(|e| format!("git {}: {}", args.join(" "), e))
+}
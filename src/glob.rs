@@ -0,0 +1,173 @@
+//! Gitignore-style path patterns.
+//!
+//! Paths are matched with `/` between components. `*` and `?` match
+//! within a component, `**` across components, and `[...]` one char from
+//! a set, such as `[a-z]` or `[!0-9]`. A backslash makes the next char
+//! literal. A pattern with no slash matches a name at any depth; one with
+//! a slash matches from the start, a leading slash included.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub(crate) struct Glob {
+    tokens: Vec<Token>,
+    /// Whether the pattern matches whole paths rather than names.
+    anchored: bool,
+}
+
+#[derive(Debug)]
+enum Token {
+    Char(char),
+    /// `?`.
+    Any,
+    /// `*`.
+    Star,
+    /// `**/`: no directories, or any number of them.
+    Dirs,
+    /// `**` anywhere else.
+    Deep,
+    /// `[...]`: inclusive ranges, and whether the set is negated.
+    Class(Vec<(char, char)>, bool),
+}
+
+impl Glob {
+    pub(crate) fn new(pattern: &str) -> Glob {
+        let anchored = pattern.contains('/');
+        let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            i += 1;
+            match c {
+                '\\' if i < chars.len() => {
+                    tokens.push(Token::Char(chars[i]));
+                    i += 1;
+                }
+                '?' => tokens.push(Token::Any),
+                '*' if chars.get(i) == Some(&'*') => {
+                    i += 1;
+                    if chars.get(i) == Some(&'/') {
+                        i += 1;
+                        tokens.push(Token::Dirs);
+                    } else {
+                        tokens.push(Token::Deep);
+                    }
+                }
+                '*' => tokens.push(Token::Star),
+                // A `[` that is never closed is literal, as in git.
+                '[' => match class(&chars[i..]) {
+                    Some((token, used)) => {
+                        tokens.push(token);
+                        i += used;
+                    }
+                    None => tokens.push(Token::Char('[')),
+                },
+                c => tokens.push(Token::Char(c)),
+            }
+        }
+        Glob { tokens, anchored }
+    }
+
+    /// Whether a relative path, with `/` between components, matches.
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        let path = if self.anchored { path } else { path.rsplit('/').next().unwrap_or(path) };
+        let chars: Vec<char> = path.chars().collect();
+        matches(&self.tokens, &chars)
+    }
+
+    /// Whether a relative path, or a directory it is in, matches.
+    pub(crate) fn matches_within(&self, path: &str) -> bool {
+        path.match_indices('/').map(|(i, _)| &path[..i]).chain([path]).any(|p| self.matches(p))
+    }
+}
+
+/// Says what is likely wrong with a pattern that still parses: a `[`
+/// that is never closed, a range that is backwards, or a trailing lone
+/// backslash.
+pub(crate) fn problem(pattern: &str) -> Option<String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 == chars.len() => return Some("ends in a lone backslash".to_string()),
+            '\\' => i += 2,
+            '[' => match class(&chars[i + 1..]) {
+                Some((Token::Class(ranges, _), used)) => {
+                    if let Some((lo, hi)) = ranges.into_iter().find(|(lo, hi)| lo > hi) {
+                        return Some(format!(
+                            "range '{}-{}' is backwards and matches nothing",
+                            lo, hi
+                        ));
+                    }
+                    i += used + 1;
+                }
+                _ => return Some("'[' is never closed, so it matches itself".to_string()),
+            },
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Parses a set after its `[`, returning it and the chars it used, `]`
+/// included.
+fn class(chars: &[char]) -> Option<(Token, usize)> {
+    let negated = matches!(chars.first(), Some('!' | '^'));
+    let mut i = usize::from(negated);
+    let mut ranges = Vec::new();
+    // A `]` right after the opening is part of the set.
+    while i < chars.len() && (chars[i] != ']' || i == usize::from(negated)) {
+        let lo = chars[i];
+        if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|&c| c != ']') {
+            ranges.push((lo, chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((lo, lo));
+            i += 1;
+        }
+    }
+    (i < chars.len()).then_some((Token::Class(ranges, negated), i + 1))
+}
+
+fn matches(tokens: &[Token], s: &[char]) -> bool {
+    let Some((first, rest)) = tokens.split_first() else {
+        return s.is_empty();
+    };
+    let one =
+        |ok: &dyn Fn(char) -> bool| s.first().is_some_and(|&c| ok(c)) && matches(rest, &s[1..]);
+    match first {
+        Token::Char(c) => one(&|x| x == *c),
+        Token::Any => one(&|x| x != '/'),
+        Token::Class(ranges, negated) => {
+            one(&|x| x != '/' && ranges.iter().any(|&(lo, hi)| lo <= x && x <= hi) != *negated)
+        }
+        Token::Star => (0..=s.len())
+            .take_while(|&i| i == 0 || s[i - 1] != '/')
+            .any(|i| matches(rest, &s[i..])),
+        Token::Deep => (0..=s.len()).any(|i| matches(rest, &s[i..])),
+        Token::Dirs => {
+            matches(rest, s)
+                || s.iter()
+                    .enumerate()
+                    .any(|(i, &c)| c == '/' && matches(rest, &s[i + 1..]))
+        }
+    }
+}
+
+/// A path relative to base, comparing canonical paths if the two are
+/// spelled differently.
+pub(crate) fn relative(base: &Path, path: &Path) -> Option<PathBuf> {
+    if let Ok(rel) = path.strip_prefix(base) {
+        return Some(rel.to_path_buf());
+    }
+    let (base, path) = (fs::canonicalize(base).ok()?, fs::canonicalize(path).ok()?);
+    path.strip_prefix(base).ok().map(Path::to_path_buf)
+}
+
+/// A relative path with `/` between components, for matching.
+pub(crate) fn slashed(rel: &Path) -> String {
+    rel.iter().map(|c| c.to_string_lossy()).collect::<Vec<_>>().join("/")
+}
@@ -0,0 +1,242 @@
+//! The graph behind `claude-lint graph`: which context files load or
+//! point to which.
+//!
+//! Nodes are the Markdown files in a .claude directory and the project's
+//! CLAUDE.md files, plus the files those import or link to. Edges are
+//! `@path` imports, followed from CLAUDE.md through the files it
+//! imports, and relative links. Claude Code loads CLAUDE.md, agents,
+//! skills, and commands on its own; any other file that none of them
+//! reaches, directly or through others, is an orphan: context that
+//! nothing ever loads.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::links::{self, normalize};
+use crate::rules::FileKind;
+use crate::{glob, imports, jobs, json, markdown};
+
+/// Fill and outline colors for orphans in DOT output.
+const ORPHAN: &str = "style=filled, fillcolor=\"#f4cccc\", color=\"#cc0000\"";
+
+pub struct Node {
+    pub path: PathBuf,
+    /// None for a file outside the linted ones, like an imported doc.
+    pub kind: Option<FileKind>,
+    /// Whether nothing Claude Code loads on its own reaches it.
+    pub orphan: bool,
+}
+
+/// How one file reaches another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Via {
+    Import,
+    Link,
+}
+
+impl Via {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Via::Import => "import",
+            Via::Link => "link",
+        }
+    }
+}
+
+/// An edge between two nodes, by index.
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub via: Via,
+}
+
+pub struct Graph {
+    /// The project around the .claude directory, which labels are
+    /// relative to.
+    pub root: PathBuf,
+    /// Linted files in work-list order, then the files they reach.
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    /// Reads the files in a .claude directory and around it that the
+    /// config does not exclude, and what they import and link to.
+    pub fn collect(root: &Path, config: &Config) -> Self {
+        let mut graph = Graph {
+            root: root.parent().unwrap_or(Path::new("")).to_path_buf(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        for (path, kind) in jobs::project_documents(root, &config.exclude) {
+            graph.node(normalize(&path), Some(kind));
+        }
+
+        let mut queue: Vec<usize> = (0..graph.nodes.len())
+            .filter(|&i| graph.nodes[i].kind == Some(FileKind::ClaudeMd))
+            .collect();
+        let mut followed = queue.clone();
+        while let Some(from) = queue.pop() {
+            for target in imports::direct(&graph.nodes[from].path) {
+                let to = graph.node(target, None);
+                graph.edge(from, to, Via::Import);
+                if !followed.contains(&to) {
+                    followed.push(to);
+                    queue.push(to);
+                }
+            }
+        }
+        // Links are followed out of the files Claude Code reads, not out
+        // of every file they point to, which could be the whole repository.
+        for from in 0..graph.nodes.len() {
+            let path = graph.nodes[from].path.clone();
+            if !markdown::is_markdown(&path) {
+                continue;
+            }
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            for target in links::linked(&path, &content) {
+                let to = graph.node(target, None);
+                graph.edge(from, to, Via::Link);
+            }
+        }
+
+        let mut reached: Vec<bool> = graph
+            .nodes
+            .iter()
+            .map(|n| n.kind.is_some_and(|k| k != FileKind::Reference))
+            .collect();
+        let mut queue: Vec<usize> = (0..reached.len()).filter(|&i| reached[i]).collect();
+        while let Some(from) = queue.pop() {
+            for edge in graph.edges.iter().filter(|e| e.from == from) {
+                if !reached[edge.to] {
+                    reached[edge.to] = true;
+                    queue.push(edge.to);
+                }
+            }
+        }
+        for (node, reached) in graph.nodes.iter_mut().zip(reached) {
+            node.orphan = !reached;
+        }
+        graph
+    }
+
+    /// Returns a node's index, adding it if it is new.
+    fn node(&mut self, path: PathBuf, kind: Option<FileKind>) -> usize {
+        if let Some(i) = self.nodes.iter().position(|n| n.path == path) {
+            return i;
+        }
+        self.nodes.push(Node { path, kind, orphan: false });
+        self.nodes.len() - 1
+    }
+
+    fn edge(&mut self, from: usize, to: usize, via: Via) {
+        if from != to && !self.edges.iter().any(|e| e.from == from && e.to == to && e.via == via) {
+            self.edges.push(Edge { from, to, via });
+        }
+    }
+
+    /// Renders the graph in Graphviz DOT, with each skill's files in a
+    /// box of their own, imports labelled, links dashed, and orphans
+    /// filled in red.
+    pub fn dot(&self) -> String {
+        let mut out = String::from(
+            "digraph claude {\n    rankdir=LR;\n    node [fontname=\"Helvetica\", fontsize=10];\n",
+        );
+        let skills: Vec<&Path> = self
+            .nodes
+            .iter()
+            .filter(|n| n.kind == Some(FileKind::Skill))
+            .filter_map(|n| n.path.parent())
+            .collect();
+        for (n, dir) in skills.iter().enumerate() {
+            out.push_str(&format!(
+                "    subgraph cluster_{} {{\n        label={};\n",
+                n,
+                quote(&self.label(dir))
+            ));
+            for i in (0..self.nodes.len()).filter(|&i| self.nodes[i].path.starts_with(dir)) {
+                out.push_str(&format!("        {}\n", self.node_dot(i)));
+            }
+            out.push_str("    }\n");
+        }
+        for i in (0..self.nodes.len())
+            .filter(|&i| !skills.iter().any(|dir| self.nodes[i].path.starts_with(dir)))
+        {
+            out.push_str(&format!("    {}\n", self.node_dot(i)));
+        }
+        for e in &self.edges {
+            let style = match e.via {
+                Via::Import => "label=\"import\"",
+                Via::Link => "style=dashed",
+            };
+            out.push_str(&format!(
+                "    {} -> {} [{}];\n",
+                quote(&self.label(&self.nodes[e.from].path)),
+                quote(&self.label(&self.nodes[e.to].path)),
+                style,
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn node_dot(&self, i: usize) -> String {
+        let node = &self.nodes[i];
+        let shape = match node.kind {
+            Some(FileKind::ClaudeMd) => "box",
+            Some(FileKind::Agent) => "ellipse",
+            Some(FileKind::Skill) => "component",
+            Some(FileKind::Reference) => "note",
+            Some(FileKind::Command) => "cds",
+            None => "plaintext",
+        };
+        let orphan = if node.orphan { format!(", {}", ORPHAN) } else { String::new() };
+        format!("{} [shape={}{}];", quote(&self.label(&node.path)), shape, orphan)
+    }
+
+    /// A path relative to the project, or in full when it is outside.
+    fn label(&self, path: &Path) -> String {
+        glob::relative(&self.root, path)
+            .map_or_else(|| path.display().to_string(), |rel| glob::slashed(&rel))
+    }
+
+    /// Renders the graph as a single JSON document: every node with its
+    /// kind and whether it is an orphan, and every edge by path.
+    ///
+    /// The schema is versioned like diagnostics output.
+    pub fn json(&self) -> String {
+        let path = |i: usize| json::string(&self.nodes[i].path.to_string_lossy());
+        let nodes: Vec<String> = (0..self.nodes.len())
+            .map(|i| {
+                let kind = self.nodes[i]
+                    .kind
+                    .map_or_else(|| "null".to_string(), |k| json::string(k.as_str()));
+                format!(
+                    "{{\"path\":{},\"kind\":{},\"orphan\":{}}}",
+                    path(i),
+                    kind,
+                    self.nodes[i].orphan
+                )
+            })
+            .collect();
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|e| {
+                format!(
+                    "{{\"from\":{},\"to\":{},\"via\":{}}}",
+                    path(e.from),
+                    path(e.to),
+                    json::string(e.via.as_str())
+                )
+            })
+            .collect();
+        format!("{{\"version\":1,\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))
+    }
+}
+
+/// Quotes a DOT ID.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
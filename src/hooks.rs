@@ -0,0 +1,267 @@
+//! Checks for hook definitions in settings files.
+//!
+//! A hook with a misspelled event never fires and a hook whose script is
+//! missing or not executable fails on every tool call, both silently
+//! from the user's point of view.
+
+use std::path::{Path, PathBuf};
+
+use crate::json::Value;
+use crate::settings::{span, type_error};
+use crate::Diagnostic;
+
+/// Events Claude Code fires hooks for.
+const EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
+/// Checks the hooks object: event names map to arrays of matcher groups,
+/// each with a hooks array of command objects.
+pub fn check_hooks(root: &Path, path: &Path, value: &Value, diags: &mut Vec<Diagnostic>) {
+    for event in value.as_object().unwrap_or_default() {
+        if !EVENTS.contains(&event.key.as_str()) {
+            diags.push(
+                Diagnostic::of(
+                    path,
+                    "CL070",
+                    format!(
+                        "unknown hook event '{}'; expected one of {}",
+                        event.key,
+                        EVENTS.join(", ")
+                    ),
+                )
+                .at(span(event.key_pos, event.key.chars().count() + 2)),
+            );
+            continue;
+        }
+        let name = format!("hooks.{}", event.key);
+        let Some(groups) = event.value.as_array() else {
+            diags.push(type_error(path, &name, "an array", &event.value));
+            continue;
+        };
+        for (i, group) in groups.iter().enumerate() {
+            let name = format!("{}[{}]", name, i);
+            if group.as_object().is_none() {
+                diags.push(type_error(path, &name, "an object", group));
+                continue;
+            }
+            match group.get("matcher") {
+                Some(m) if m.as_str().is_none() => {
+                    diags.push(type_error(path, &format!("{}.matcher", name), "a string", m))
+                }
+                Some(m) => {
+                    let text = m.as_str().unwrap_or_default();
+                    if let Err(why) = matcher_syntax(text) {
+                        diags.push(
+                            Diagnostic::of(
+                                path,
+                                "CL071",
+                                format!("invalid matcher '{}' in {}: {}", text, name, why),
+                            )
+                            .at(span(m.pos, text.chars().count() + 2)),
+                        );
+                    }
+                }
+                None => {}
+            }
+            let Some(hooks) = group.get("hooks") else {
+                diags.push(
+                    Diagnostic::of(path, "CL072", format!("{} is missing 'hooks'", name))
+                        .at(span(group.pos, 1)),
+                );
+                continue;
+            };
+            let Some(hooks) = hooks.as_array() else {
+                diags.push(type_error(path, &format!("{}.hooks", name), "an array", hooks));
+                continue;
+            };
+            for (j, hook) in hooks.iter().enumerate() {
+                check_hook(root, path, &format!("{}.hooks[{}]", name, j), hook, diags);
+            }
+        }
+    }
+}
+
+fn check_hook(root: &Path, path: &Path, name: &str, hook: &Value, diags: &mut Vec<Diagnostic>) {
+    if hook.as_object().is_none() {
+        diags.push(type_error(path, name, "an object", hook));
+        return;
+    }
+    if let Some(timeout) = hook.get("timeout") {
+        if timeout.type_name() != "number" {
+            diags.push(type_error(path, &format!("{}.timeout", name), "a number", timeout));
+        }
+    }
+    let field = match hook.get("type").map(|t| (t, t.as_str())) {
+        None => {
+            diags.push(
+                Diagnostic::of(path, "CL072", format!("{} is missing 'type'", name))
+                    .at(span(hook.pos, 1)),
+            );
+            return;
+        }
+        Some((_, Some("command"))) => "command",
+        Some((_, Some("prompt"))) => "prompt",
+        Some((t, Some(other))) => {
+            diags.push(
+                Diagnostic::of(
+                    path,
+                    "CL072",
+                    format!("{}.type '{}' is not one of command, prompt", name, other),
+                )
+                .at(span(t.pos, other.chars().count() + 2)),
+            );
+            return;
+        }
+        Some((t, None)) => {
+            diags.push(type_error(path, &format!("{}.type", name), "a string", t));
+            return;
+        }
+    };
+    let value = match hook.get(field) {
+        Some(v) if v.as_str().is_some_and(|s| !s.trim().is_empty()) => v,
+        Some(v) if v.as_str().is_none() => {
+            diags.push(type_error(path, &format!("{}.{}", name, field), "a string", v));
+            return;
+        }
+        _ => {
+            diags.push(
+                Diagnostic::of(path, "CL072", format!("{} has no '{}' to run", name, field))
+                    .at(span(hook.pos, 1)),
+            );
+            return;
+        }
+    };
+    if field == "command" {
+        let command = value.as_str().unwrap_or_default();
+        for (script, executes) in scripts(root, command) {
+            let problem = if !script.is_file() {
+                "does not exist"
+            } else if executes && !is_executable(&script) {
+                "is not executable"
+            } else {
+                continue;
+            };
+            diags.push(
+                Diagnostic::of(
+                    path,
+                    "CL073",
+                    format!("hook script {} {}", script.display(), problem),
+                )
+                .at(span(value.pos, command.chars().count() + 2)),
+            );
+        }
+    }
+}
+
+/// Checks that a matcher is a plausible tool-name pattern: "*", empty, or
+/// a regex with balanced groups and no dangling escape.
+pub fn matcher_syntax(matcher: &str) -> Result<(), &'static str> {
+    if matcher.is_empty() || matcher == "*" {
+        return Ok(());
+    }
+    if matcher.trim() != matcher {
+        return Err("leading or trailing whitespace");
+    }
+    if matcher.starts_with(['*', '+', '?']) {
+        return Err("pattern starts with a repetition; use \"*\" alone to match every tool");
+    }
+    let mut stack = Vec::new();
+    let mut chars = matcher.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.next().is_none() => return Err("dangling '\\'"),
+            '\\' => {}
+            '(' | '[' => stack.push(c),
+            ')' if stack.pop() != Some('(') => return Err("unbalanced parentheses"),
+            ']' if stack.pop() != Some('[') => return Err("unbalanced brackets"),
+            _ => {}
+        }
+    }
+    if stack.is_empty() {
+        Ok(())
+    } else {
+        Err("unclosed group")
+    }
+}
+
+/// Returns the scripts inside root a command refers to, and whether each
+/// is run directly (and so must be executable) rather than passed to an
+/// interpreter.
+fn scripts(root: &Path, command: &str) -> Vec<(PathBuf, bool)> {
+    let project = root.parent().unwrap_or(Path::new("."));
+    let mut out = Vec::new();
+    let mut command_position = true;
+    for word in shell_words(command) {
+        if matches!(word.as_str(), "&&" | "||" | ";" | "|") {
+            command_position = true;
+            continue;
+        }
+        let relative = ["$CLAUDE_PROJECT_DIR/", "${CLAUDE_PROJECT_DIR}/"]
+            .iter()
+            .find_map(|var| word.strip_prefix(var))
+            .unwrap_or(&word);
+        let candidate = Path::new(relative.strip_prefix("./").unwrap_or(relative));
+        let resolved = if candidate.is_absolute() {
+            candidate.to_path_buf()
+        } else {
+            project.join(candidate)
+        };
+        if relative.contains('/') && resolved.starts_with(root) {
+            out.push((resolved, command_position));
+        }
+        command_position = false;
+    }
+    out
+}
+
+/// Splits a command into words, honoring single and double quotes.
+fn shell_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(_: &Path) -> bool {
+    true
+}
@@ -0,0 +1,122 @@
+//! Files left out of linting: those `.claudelintignore` lists, and those
+//! the config's `exclude` globs match.
+//!
+//! Both use gitignore syntax, relative to the .claude directory. A later
+//! pattern wins over an earlier one, `!` brings a path back, and a
+//! trailing `/` matches only directories. Nothing in a left-out
+//! directory can be brought back. With `--no-follow-symlinks`, symlinks
+//! and everything reached through them are left out too, and with
+//! `--no-discover`, everything outside the .claude directory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::glob::{self, Glob};
+
+pub const FILE_NAME: &str = ".claudelintignore";
+
+#[derive(Debug, Default)]
+pub struct Ignore {
+    /// The .claude directory patterns are relative to; until it is set,
+    /// nothing is left out.
+    base: Option<PathBuf>,
+    patterns: Vec<Pattern>,
+    /// Whether symlinks are left out rather than followed.
+    skip_symlinks: bool,
+    /// Whether paths outside base, like a project's own CLAUDE.md, are
+    /// left out.
+    skip_outside: bool,
+}
+
+#[derive(Debug)]
+struct Pattern {
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl Ignore {
+    /// Adds a pattern, in gitignore syntax. Blank lines and `#` comments
+    /// are skipped.
+    pub fn add(&mut self, line: &str) {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        self.patterns.push(Pattern { glob: Glob::new(line), negated, dir_only });
+    }
+
+    /// Leaves out symlinks, and whatever is reached through them.
+    pub fn skip_symlinks(&mut self, skip: bool) {
+        self.skip_symlinks = skip;
+    }
+
+    /// Leaves out whatever is outside the .claude directory.
+    pub fn skip_outside(&mut self, skip: bool) {
+        self.skip_outside = skip;
+    }
+
+    /// Makes patterns relative to a .claude directory, and adds the ones
+    /// in its ignore file, after any added so far.
+    pub(crate) fn load(&mut self, root: &Path) -> Result<(), String> {
+        let path = root.join(FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(src) => src.lines().for_each(|line| self.add(line)),
+            // A root that is a file, as for a lone CLAUDE.md, has none.
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+                ) => {}
+            Err(e) => return Err(format!("{}: cannot read: {}", path.display(), e)),
+        }
+        self.base = Some(root.to_path_buf());
+        Ok(())
+    }
+
+    /// Whether a file or directory is left out, itself or through a
+    /// directory it is in.
+    pub fn excludes(&self, path: &Path) -> bool {
+        let Some(base) = &self.base else {
+            return false;
+        };
+        if self.patterns.is_empty() && !self.skip_symlinks && !self.skip_outside {
+            return false;
+        }
+        let Some(rel) = glob::relative(base, path) else {
+            return self.skip_outside;
+        };
+        if self.skip_symlinks && through_symlink(base, &rel) {
+            return true;
+        }
+        let parts: Vec<String> = rel
+            .iter()
+            .map(|c| c.to_string_lossy().into_owned())
+            .collect();
+        (1..=parts.len())
+            .any(|n| self.ignored(&parts[..n].join("/"), n < parts.len() || path.is_dir()))
+    }
+
+    fn ignored(&self, rel: &str, dir: bool) -> bool {
+        let last = self.patterns.iter().rev().find(|p| (dir || !p.dir_only) && p.glob.matches(rel));
+        last.is_some_and(|p| !p.negated)
+    }
+}
+
+/// Whether a path under base is a symlink, or inside a directory that is.
+fn through_symlink(base: &Path, rel: &Path) -> bool {
+    let mut path = base.to_path_buf();
+    rel.iter().any(|part| {
+        path.push(part);
+        fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink())
+    })
+}
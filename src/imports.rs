@@ -0,0 +1,192 @@
+//! `@path` import resolution for CLAUDE.md.
+//!
+//! Claude Code inlines `@path/to/file` imports when it loads CLAUDE.md,
+//! following imported files' own imports up to five hops deep. A missing
+//! target, a chain past the limit, or a cycle silently drops context.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::links::normalize;
+use crate::markdown::{self, Kind};
+use crate::{prose, Diagnostic, Span};
+
+/// Claude Code stops following imports past this many hops.
+pub const MAX_DEPTH: usize = 5;
+
+/// An `@path` import in a file.
+struct Import {
+    target: String,
+    span: Span,
+}
+
+pub fn check_imports(claude_md: &Path, diags: &mut Vec<Diagnostic>) {
+    if !claude_md.is_file() {
+        return;
+    }
+    let mut stack = vec![normalize(claude_md)];
+    let mut done = HashMap::new();
+    walk(claude_md, &mut stack, &mut done, diags);
+}
+
+/// Follows a file's imports depth first. The stack holds the chain from
+/// CLAUDE.md to path, inclusive; done maps each file followed so far to
+/// the fewest hops it was reached in.
+fn walk(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    done: &mut HashMap<PathBuf, usize>,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for import in imports(&content) {
+        let Some(target) = resolve(dir, &import.target) else {
+            diags.push(Diagnostic::of(
+                path,
+                "CL091",
+                format!("cannot resolve import '@{}': HOME is not set", import.target),
+            ).at(Some(import.span)));
+            continue;
+        };
+        if !target.is_file() {
+            diags.push(Diagnostic::of(
+                path,
+                "CL091",
+                format!("imported file '{}' does not exist", import.target),
+            ).at(Some(import.span)));
+            continue;
+        }
+        if let Some(i) = stack.iter().position(|p| *p == target) {
+            let cycle: Vec<String> = stack[i..]
+                .iter()
+                .chain([&target])
+                .map(|p| p.display().to_string())
+                .collect();
+            diags.push(
+                Diagnostic::of(
+                    path,
+                    "CL093",
+                    format!("import cycle: {}", cycle.join(" -> ")),
+                )
+                .at(Some(import.span)),
+            );
+            continue;
+        }
+        // The stack holds CLAUDE.md plus one entry per hop taken so far.
+        if stack.len() > MAX_DEPTH {
+            diags.push(
+                Diagnostic::of(
+                    path,
+                    "CL092",
+                    format!(
+                        "import '@{}' is more than {} hops from CLAUDE.md and will not load",
+                        import.target, MAX_DEPTH
+                    ),
+                )
+                .at(Some(import.span)),
+            );
+            continue;
+        }
+        // Revisit a file only if this chain reaches it in fewer hops.
+        if done.get(&target).is_some_and(|&depth| depth <= stack.len()) {
+            continue;
+        }
+        done.insert(target.clone(), stack.len());
+        stack.push(target.clone());
+        walk(&target, stack, done, diags);
+        stack.pop();
+    }
+}
+
+/// Returns the files that claude_md's imports load, each once, in the
+/// order they are first reached, without the ones past the hop limit.
+pub(crate) fn imported(claude_md: &Path) -> Vec<PathBuf> {
+    let mut queue = vec![(normalize(claude_md), 0)];
+    let mut i = 0;
+    while let Some((path, hops)) = queue.get(i).cloned() {
+        i += 1;
+        if hops == MAX_DEPTH {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for import in imports(&content) {
+            let Some(target) = resolve(dir, &import.target) else {
+                continue;
+            };
+            if target.is_file() && !queue.iter().any(|(p, _)| *p == target) {
+                queue.push((target, hops + 1));
+            }
+        }
+    }
+    queue.into_iter().skip(1).map(|(path, _)| path).collect()
+}
+
+/// Returns the existing files a file's own imports name, each once, in
+/// the order they appear, without following theirs.
+pub(crate) fn direct(path: &Path) -> Vec<PathBuf> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut out: Vec<PathBuf> = Vec::new();
+    for target in imports(&content).iter().filter_map(|i| resolve(dir, &i.target)) {
+        if target.is_file() && !out.contains(&target) {
+            out.push(target);
+        }
+    }
+    out
+}
+
+/// Resolves an import against the importing file's directory, or HOME
+/// for `~/` paths.
+fn resolve(dir: &Path, target: &str) -> Option<PathBuf> {
+    let path = match target.strip_prefix("~/") {
+        Some(rest) => PathBuf::from(env::var_os("HOME")?).join(rest),
+        None => dir.join(target),
+    };
+    Some(normalize(&path))
+}
+
+/// Finds imports in prose: an `@` at the start of a word followed by a
+/// path. Code and quotes are skipped, and so are bare mentions like
+/// `@alice`, which have no slash or extension.
+fn imports(content: &str) -> Vec<Import> {
+    let md = markdown::parse(content);
+    let mut out = Vec::new();
+    for line in md.lines.iter().filter(|l| {
+        matches!(
+            l.kind,
+            Kind::Paragraph | Kind::ListItem { .. } | Kind::Heading(_)
+        )
+    }) {
+        let text = prose::mask(line.text);
+        for (i, _) in text.match_indices('@') {
+            if text[..i].chars().next_back().is_some_and(|c| !c.is_whitespace() && c != '(') {
+                continue;
+            }
+            let rest = &text[i + 1..];
+            let end = rest.find(|c: char| c.is_whitespace() || c == ')').unwrap_or(rest.len());
+            let target = rest[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            if target.is_empty() || !(target.contains('/') || target.contains('.')) {
+                continue;
+            }
+            out.push(Import {
+                target: target.to_string(),
+                span: Span {
+                    line: line.number,
+                    column: text[..i].chars().count() + 1,
+                    length: target.chars().count() + 1,
+                },
+            });
+        }
+    }
+    out
+}
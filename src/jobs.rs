@@ -0,0 +1,569 @@
+//! The lint work list and its parallel runner.
+//!
+//! Discovery walks a directory once and turns every file or skill into
+//! an independent job. Jobs run on a pool of scoped threads, and results
+//! are put back in work-list order, so output does not depend on
+//! scheduling.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use crate::cache::{self, Cache};
+use crate::log;
+use crate::markdown;
+use crate::rules::{self, Check, FileKind};
+use crate::config::Config;
+use crate::ignore::Ignore;
+use crate::timings;
+use crate::tokens::{self, FileBudget};
+use crate::workspace;
+use crate::{
+    assets, checks, duplicates, imports, mcp, names, placeholders, readability, secrets, settings,
+    spelling, Diagnostic, Severity,
+};
+
+/// One unit of lint work.
+pub(crate) enum Job {
+    /// A CLAUDE.md or CLAUDE.local.md, checked and followed through
+    /// imports.
+    ClaudeMd(PathBuf),
+    Agent(PathBuf),
+    Command(PathBuf),
+    /// A skill directory, SKILL.md included.
+    Skill(PathBuf),
+    Reference(PathBuf),
+    /// Agent, skill, and command names and counts across the directory,
+    /// and files in agents/ that are not agents.
+    Names(PathBuf),
+    /// Symlinks anywhere in the directory that do not resolve.
+    Symlinks(PathBuf),
+    Settings(PathBuf),
+    Mcp(PathBuf),
+    /// The token budgets over all of a directory's Markdown files, and
+    /// over the part that loads in every session.
+    Tokens(PathBuf),
+    /// Prose repeated across a directory's Markdown files.
+    Duplicates(PathBuf),
+    /// A file linted on its own as the given kind, with only the checks
+    /// that look at single files.
+    File(PathBuf, FileKind),
+}
+
+impl Job {
+    /// Returns the cache key for jobs whose findings depend only on their
+    /// own file and the tree layout, hashed into tree.
+    fn key(&self, tree: u64) -> Option<u64> {
+        let (kind, path, file) = match self {
+            Job::Agent(p) => ("agent", p, p.clone()),
+            Job::Command(p) => ("command", p, p.clone()),
+            Job::Skill(dir) => ("skill", dir, dir.join("SKILL.md")),
+            Job::Reference(p) => ("reference", p, p.clone()),
+            Job::ClaudeMd(_)
+            | Job::Names(_)
+            | Job::Symlinks(_)
+            | Job::Settings(_)
+            | Job::Mcp(_)
+            | Job::Tokens(_)
+            | Job::Duplicates(_)
+            | Job::File(..) => return None,
+        };
+        let mut content = fs::read(file).unwrap_or_default();
+        // A skill's findings also depend on its scripts.
+        if let Job::Skill(dir) = self {
+            content.extend(assets::state(dir));
+        }
+        let tree = tree.to_le_bytes();
+        Some(cache::hash(&[&tree, kind.as_bytes(), path.to_string_lossy().as_bytes(), &content]))
+    }
+
+    /// Returns the existing files a job reads, for reports that list every
+    /// linted file.
+    pub(crate) fn files(&self) -> Vec<PathBuf> {
+        let files = match self {
+            Job::ClaudeMd(p)
+            | Job::Agent(p)
+            | Job::Command(p)
+            | Job::Reference(p)
+            | Job::File(p, _) => vec![p.clone()],
+            Job::Skill(dir) => vec![dir.join("SKILL.md")],
+            Job::Settings(root) => settings::FILES.iter().map(|name| root.join(name)).collect(),
+            Job::Mcp(root) => vec![root.parent().unwrap_or(Path::new("")).join(mcp::FILE_NAME)],
+            Job::Names(_) | Job::Symlinks(_) | Job::Tokens(_) | Job::Duplicates(_) => Vec::new(),
+        };
+        files.into_iter().filter(|p| p.is_file()).collect()
+    }
+
+    /// Whether a job needs to run when only the given absolute paths
+    /// changed. A CLAUDE.md does when it or a file it imports changed;
+    /// jobs over the whole tree always do.
+    pub(crate) fn affected(&self, changed: &[PathBuf]) -> bool {
+        let is_changed = |p: &Path| fs::canonicalize(p).is_ok_and(|abs| changed.contains(&abs));
+        let path = match self {
+            Job::Agent(p) | Job::Command(p) | Job::Reference(p) | Job::File(p, _) => p,
+            // A skill covers its references too.
+            Job::Skill(dir) => dir,
+            Job::ClaudeMd(p) => {
+                return is_changed(p) || imports::imported(p).iter().any(|i| is_changed(i))
+            }
+            Job::Names(_)
+            | Job::Symlinks(_)
+            | Job::Settings(_)
+            | Job::Mcp(_)
+            | Job::Tokens(_)
+            | Job::Duplicates(_) => return true,
+        };
+        fs::canonicalize(path).is_ok_and(|abs| changed.iter().any(|c| c.starts_with(&abs)))
+    }
+
+    /// Returns the Markdown file a job checks, with its kind.
+    fn document(&self) -> Option<(PathBuf, FileKind)> {
+        match self {
+            Job::ClaudeMd(p) => Some((p.clone(), FileKind::ClaudeMd)),
+            Job::Agent(p) => Some((p.clone(), FileKind::Agent)),
+            Job::Command(p) => Some((p.clone(), FileKind::Command)),
+            Job::Skill(dir) => Some((dir.join("SKILL.md"), FileKind::Skill)),
+            Job::Reference(p) => Some((p.clone(), FileKind::Reference)),
+            Job::File(p, kind) => Some((p.clone(), *kind)),
+            Job::Names(_)
+            | Job::Symlinks(_)
+            | Job::Settings(_)
+            | Job::Mcp(_)
+            | Job::Tokens(_)
+            | Job::Duplicates(_) => None,
+        }
+    }
+
+    /// Runs the built-in checks, through the cache if there is one, then
+    /// the extra ones, which are never cached.
+    fn run_cached(
+        &self,
+        cache: Option<(&Cache, u64)>,
+        extra: &[&dyn Check],
+        config: &Config,
+    ) -> Vec<Diagnostic> {
+        let start = Instant::now();
+        let mut diags = match cache.and_then(|(c, tree)| Some((c, self.key(tree)?))) {
+            Some((cache, key)) => match cache.get(key) {
+                Some(diags) => {
+                    log::debug!("cache hit for {}", self);
+                    diags
+                }
+                None => {
+                    log::debug!("cache miss for {}", self);
+                    let diags = self.run(config);
+                    cache.put(key, diags.clone());
+                    diags
+                }
+            },
+            None => self.run(config),
+        };
+        if let Some((path, kind)) = self.document() {
+            // Read problems were reported with the built-in findings.
+            if let Some(content) = rules::read(&path, config.lossy_utf8, &mut Vec::new()) {
+                rules::check_content(&path, kind, &content, extra, &mut diags);
+            }
+        }
+        if let Some((path, _)) = self.document() {
+            timings::file(&path, start.elapsed());
+        }
+        log::debug!(
+            "ran {} in {:.1}ms: {} finding(s)",
+            self,
+            start.elapsed().as_secs_f64() * 1000.0,
+            diags.len()
+        );
+        diags
+    }
+
+    fn run(&self, config: &Config) -> Vec<Diagnostic> {
+        let mut diags = Vec::new();
+        match self {
+            Job::ClaudeMd(path) => {
+                checks::check_claude_md(path, &mut diags);
+                duplicates::check_overlap(path, &mut diags);
+            }
+            Job::Skill(dir) => checks::check_skill_dir(dir, &mut diags),
+            Job::Names(root) => {
+                checks::check_agents_dir(&agent_dirs(root), &config.exclude, &mut diags);
+                names::check_names(root, &config.exclude, &mut diags);
+                names::check_descriptions(root, config, &mut diags);
+                names::check_counts(root, config, &mut diags);
+            }
+            Job::Symlinks(root) => checks::check_symlinks(root, &config.exclude, &mut diags),
+            Job::Settings(root) => {
+                settings::check_settings(root, &mut diags);
+                for name in settings::FILES {
+                    secrets::check_file(&root.join(name), config, &mut diags);
+                }
+            }
+            Job::Mcp(root) => {
+                mcp::check_mcp(root, &mut diags);
+                secrets::check_file(
+                    &root.parent().unwrap_or(Path::new("")).join(mcp::FILE_NAME),
+                    config,
+                    &mut diags,
+                );
+            }
+            Job::Tokens(root) => {
+                tokens::check_total(root, &config.tokens, &config.exclude, &mut diags);
+                tokens::check_eager(root, &config.tokens, &config.exclude, &mut diags);
+            }
+            Job::Duplicates(root) => duplicates::check_duplicates(root, config, &mut diags),
+            Job::Agent(_) | Job::Command(_) | Job::Reference(_) | Job::File(..) => {}
+        }
+        if let Some((path, kind)) = self.document() {
+            if let Some(content) = rules::read(&path, config.lossy_utf8, &mut diags) {
+                rules::check_content(&path, kind, &content, checks::BUILTIN, &mut diags);
+            }
+        }
+        if let Job::ClaudeMd(path) = self {
+            imports::check_imports(path, &mut diags);
+        }
+        diags
+    }
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (what, path) = match self {
+            Job::ClaudeMd(p) => ("claude_md", p),
+            Job::Agent(p) => ("agent", p),
+            Job::Command(p) => ("command", p),
+            Job::Skill(dir) => ("skill", dir),
+            Job::Reference(p) => ("reference", p),
+            Job::Names(root) => ("names", root),
+            Job::Symlinks(root) => ("symlinks", root),
+            Job::Settings(root) => ("settings", root),
+            Job::Mcp(root) => ("mcp", root),
+            Job::Tokens(root) => ("tokens", root),
+            Job::Duplicates(root) => ("duplicates", root),
+            Job::File(p, kind) => (kind.as_str(), p),
+        };
+        write!(f, "{} {}", what, path.display())
+    }
+}
+
+/// Builds the work list for a .claude directory, leaving out excluded
+/// files. A project directory always needs a CLAUDE.md, in it or beside
+/// it; the one beside it, any CLAUDE.local.md, and those further down
+/// the project are linted too. A user-level directory only checks its
+/// own CLAUDE.md if present.
+pub(crate) fn plan(root: &Path, require_claude_md: bool, exclude: &Ignore) -> Vec<Job> {
+    let kept = |paths: Vec<PathBuf>| paths.into_iter().filter(|p| !exclude.excludes(p));
+    let mut jobs = Vec::new();
+    let claude_md = root.join("CLAUDE.md");
+    // Only a directory named .claude has a project around it to look in.
+    let named = root.file_name().is_some_and(|n| n == ".claude");
+    let project = root.parent().filter(|p| named && require_claude_md && !exclude.excludes(p));
+    let beside = project.map(|p| p.join("CLAUDE.md")).filter(|p| p.is_file());
+    if ((require_claude_md && beside.is_none()) || claude_md.exists())
+        && !exclude.excludes(&claude_md)
+    {
+        jobs.push(Job::ClaudeMd(claude_md));
+    }
+    let local = project
+        .map(|p| p.join("CLAUDE.local.md"))
+        .into_iter()
+        .chain([root.join("CLAUDE.local.md")]);
+    jobs.extend(
+        kept(
+            beside
+                .into_iter()
+                .chain(local)
+                .filter(|p| p.is_file())
+                .collect(),
+        )
+        .map(Job::ClaudeMd),
+    );
+    jobs.extend(
+        project
+            .map(workspace::nested_claude_md)
+            .unwrap_or_default()
+            .into_iter()
+            .map(Job::ClaudeMd),
+    );
+    jobs.extend(kept(agent_dirs(root).iter().flat_map(|d| md_files(d)).collect()).map(Job::Agent));
+    for dir in kept(dirs(&root.join("skills"))) {
+        let refs: Vec<PathBuf> = kept(md_files_within(&dir.join("references"))).collect();
+        if !exclude.excludes(&dir.join("SKILL.md")) {
+            jobs.push(Job::Skill(dir));
+        }
+        jobs.extend(refs.into_iter().map(Job::Reference));
+    }
+    jobs.extend(kept(md_files(&root.join("commands"))).map(Job::Command));
+    jobs.push(Job::Names(root.to_path_buf()));
+    jobs.push(Job::Symlinks(root.to_path_buf()));
+    jobs.push(Job::Settings(root.to_path_buf()));
+    if require_claude_md {
+        jobs.push(Job::Mcp(root.to_path_buf()));
+    }
+    jobs.push(Job::Tokens(root.to_path_buf()));
+    jobs.push(Job::Duplicates(root.to_path_buf()));
+    jobs
+}
+
+/// Returns the Markdown files in a .claude directory, with their kinds,
+/// in work-list order.
+pub(crate) fn documents(root: &Path, exclude: &Ignore) -> Vec<(PathBuf, FileKind)> {
+    plan(root, false, exclude).iter().filter_map(Job::document).collect()
+}
+
+/// Like [`documents`], with the project's CLAUDE.md files around the
+/// directory too, for those that exist.
+pub(crate) fn project_documents(root: &Path, exclude: &Ignore) -> Vec<(PathBuf, FileKind)> {
+    plan(root, true, exclude)
+        .iter()
+        .filter_map(Job::document)
+        .filter(|(path, _)| path.is_file())
+        .collect()
+}
+
+/// Returns the Markdown files in a .claude directory that are excluded,
+/// in work-list order.
+pub(crate) fn skipped(root: &Path, exclude: &Ignore) -> Vec<PathBuf> {
+    let all = documents(root, &Ignore::default());
+    let skipped: Vec<PathBuf> = all
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|p| exclude.excludes(p))
+        .collect();
+    for path in &skipped {
+        log::debug!("excluded {}", path.display());
+    }
+    skipped
+}
+
+/// Runs jobs on up to one thread per core and concatenates their
+/// diagnostics in work-list order. With a cache, unchanged files reuse
+/// their earlier findings; root is the directory being linted. Checks
+/// that depend on the config, its own and the built-in ones it sets up,
+/// run uncached after the rest on every file.
+pub(crate) fn run(
+    jobs: &[Job],
+    cache: Option<(&Cache, &Path)>,
+    config: &Config,
+) -> Vec<Diagnostic> {
+    log::debug!("running {} job(s)", jobs.len());
+    for job in jobs {
+        log::trace!("queued {}", job);
+    }
+    with_extra(config, |extra| run_all(jobs, cache, extra, config))
+}
+
+/// Checks text that is not on disk, such as an editor buffer, as the
+/// file of the given kind at path. Only the per-file checks run.
+pub(crate) fn check_text(
+    path: &Path,
+    kind: FileKind,
+    bytes: Vec<u8>,
+    config: &Config,
+) -> Vec<Diagnostic> {
+    let start = Instant::now();
+    let mut diags = Vec::new();
+    if let Some(content) = rules::decode(path, bytes, config.lossy_utf8, &mut diags) {
+        rules::check_content(path, kind, &content, checks::BUILTIN, &mut diags);
+        with_extra(config, |extra| rules::check_content(path, kind, &content, extra, &mut diags));
+    }
+    timings::file(path, start.elapsed());
+    diags
+}
+
+/// Calls f with the checks that depend on the config: its own, and the
+/// built-in ones it sets up.
+fn with_extra<T>(config: &Config, f: impl FnOnce(&[&dyn Check]) -> T) -> T {
+    let budget = FileBudget(config.tokens);
+    let tools = checks::Tools(&config.tools);
+    let model = checks::Model(&config.models);
+    let line_endings = checks::LineEndings(config.line_endings);
+    let file_names = checks::FileNames(config.file_names);
+    let frontmatter = checks::FrontmatterFormat(&config.frontmatter);
+    let [agent_lines, skill_lines, command_lines] = checks::line_limits(config);
+    let capability = checks::Capability(config);
+    let capability_content = checks::CapabilityContent(config);
+    let imperatives = checks::Imperatives(config);
+    let required = checks::RequiredSections(config);
+    let forbidden = checks::ForbiddenSections(config);
+    let order = checks::SectionOrder(config);
+    let trivial = checks::TrivialFiles(config);
+    let casing = checks::HeadingCasing(config);
+    let sequences = checks::Sequences(config);
+    let optional = checks::OptionalNote(&config.optional);
+    let reference_size = checks::ReferenceSize(config);
+    let reference_count = checks::ReferenceCount(config);
+    let reference_types = checks::ReferenceTypes(&config.reference_files);
+    let secrets = secrets::Secrets(config);
+    let placeholders = placeholders::Placeholders(&config.placeholders);
+    let spelling = spelling::Spelling(&config.spelling);
+    let readability = readability::Readability(config);
+    let configured: [&dyn Check; 26] = [
+        &tools,
+        &model,
+        &line_endings,
+        &file_names,
+        &frontmatter,
+        &budget,
+        &agent_lines,
+        &skill_lines,
+        &command_lines,
+        &capability,
+        &capability_content,
+        &imperatives,
+        &required,
+        &forbidden,
+        &order,
+        &trivial,
+        &casing,
+        &sequences,
+        &optional,
+        &reference_size,
+        &reference_count,
+        &reference_types,
+        &secrets,
+        &placeholders,
+        &spelling,
+        &readability,
+    ];
+    let extra: Vec<&dyn Check> = config
+        .checks
+        .iter()
+        .map(|c| c.as_ref())
+        .chain(configured)
+        .collect();
+    f(&extra)
+}
+
+fn run_all(
+    jobs: &[Job],
+    cache: Option<(&Cache, &Path)>,
+    extra: &[&dyn Check],
+    config: &Config,
+) -> Vec<Diagnostic> {
+    // Lossy decoding changes what the built-in checks see.
+    let cache = cache.map(|(c, root)| {
+        (
+            c,
+            cache::hash(&[
+                &cache::fingerprint(root).to_le_bytes(),
+                &[u8::from(config.lossy_utf8)],
+            ]),
+        )
+    });
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(jobs.len());
+    // With a limit on errors, no job starts once it is reached; jobs
+    // already running finish.
+    let errors = AtomicUsize::new(0);
+    let stopped = || config.max_errors.is_some_and(|max| errors.load(Ordering::Relaxed) >= max);
+    let run = |job: &Job| {
+        let diags = job.run_cached(cache, extra, config);
+        if config.max_errors.is_some() {
+            let found = diags
+                .iter()
+                .filter(|d| config.severity(d) == Some(Severity::Error))
+                .count();
+            errors.fetch_add(found, Ordering::Relaxed);
+        }
+        diags
+    };
+    if workers <= 1 {
+        let mut diags = Vec::new();
+        for job in jobs {
+            if stopped() {
+                log::debug!("stopped at {} error(s)", errors.load(Ordering::Relaxed));
+                break;
+            }
+            diags.extend(run(job));
+        }
+        return diags;
+    }
+
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(jobs.len()));
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                if stopped() {
+                    break;
+                }
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(i) else {
+                    break;
+                };
+                let diags = run(job);
+                results.lock().expect("no worker panics while holding the lock").push((i, diags));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().expect("workers have finished");
+    results.sort_by_key(|&(i, _)| i);
+    results.into_iter().flat_map(|(_, d)| d).collect()
+}
+
+fn entries(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn md_files(dir: &Path) -> Vec<PathBuf> {
+    entries(dir)
+        .into_iter()
+        .filter(|p| p.is_file() && markdown::is_markdown(p))
+        .collect()
+}
+
+/// Like [`md_files`], in subdirectories too, each directory's files
+/// before its subdirectories.
+fn md_files_within(dir: &Path) -> Vec<PathBuf> {
+    dirs_within(dir, usize::MAX).iter().flat_map(|d| md_files(d)).collect()
+}
+
+/// How many levels of subdirectories of agents/ hold agents.
+const MAX_AGENT_DEPTH: usize = 5;
+
+/// Returns agents/ and its subdirectories, down to MAX_AGENT_DEPTH levels.
+fn agent_dirs(root: &Path) -> Vec<PathBuf> {
+    dirs_within(&root.join("agents"), MAX_AGENT_DEPTH)
+}
+
+/// Returns a directory and its subdirectories, down to max_depth levels,
+/// each directory before its subdirectories. Symlinked directories are
+/// followed, but each real directory is visited once, and one that holds
+/// dir, as a symlink back up the tree does, not at all.
+pub(crate) fn dirs_within(dir: &Path, max_depth: usize) -> Vec<PathBuf> {
+    fn walk(dir: &Path, levels: usize, seen: &mut HashSet<PathBuf>, out: &mut Vec<PathBuf>) {
+        let Ok(real) = fs::canonicalize(dir) else {
+            return;
+        };
+        if seen.iter().any(|s| s.starts_with(&real)) {
+            return;
+        }
+        seen.insert(real);
+        out.push(dir.to_path_buf());
+        if levels > 0 {
+            for sub in dirs(dir) {
+                walk(&sub, levels - 1, seen, out);
+            }
+        }
+    }
+    let mut out = Vec::new();
+    walk(dir, max_depth, &mut HashSet::new(), &mut out);
+    out
+}
+
+fn dirs(dir: &Path) -> Vec<PathBuf> {
+    entries(dir).into_iter().filter(|p| p.is_dir()).collect()
+}
@@ -0,0 +1,397 @@
+//! JSON reader that keeps source positions, and a string writer.
+//!
+//! Settings files are hand-edited, so errors and findings need to point
+//! at a line and column; that is all this adds over a plain parser.
+
+/// A 1-based source position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pos {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Value {
+    pub kind: Kind,
+    pub pos: Pos,
+}
+
+#[derive(Clone, Debug)]
+pub enum Kind {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<Member>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Member {
+    pub key: String,
+    pub key_pos: Pos,
+    pub value: Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct Error {
+    pub pos: Pos,
+    pub message: String,
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self.kind {
+            Kind::Null => "null",
+            Kind::Bool(_) => "boolean",
+            Kind::Number(_) => "number",
+            Kind::String(_) => "string",
+            Kind::Array(_) => "array",
+            Kind::Object(_) => "object",
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.kind {
+            Kind::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match &self.kind {
+            Kind::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[Member]> {
+        match &self.kind {
+            Kind::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Returns the first member with the given key, for objects.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?.iter().find(|m| m.key == key).map(|m| &m.value)
+    }
+}
+
+pub fn parse(src: &str) -> Result<Value, Error> {
+    let mut p = Parser { src: src.as_bytes(), pos: 0, line: 1, line_start: 0 };
+    // Editors sometimes save a BOM; JSON forbids it but tools accept it.
+    if p.src.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        p.pos = 3;
+        p.line_start = 3;
+    }
+    p.skip_ws();
+    let value = p.value(0)?;
+    p.skip_ws();
+    if p.pos < p.src.len() {
+        return Err(p.error("unexpected trailing characters"));
+    }
+    Ok(value)
+}
+
+/// Nesting limit, so hostile input cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl Parser<'_> {
+    fn here(&self) -> Pos {
+        let column = String::from_utf8_lossy(&self.src[self.line_start..self.pos])
+            .chars()
+            .count()
+            + 1;
+        Pos {
+            line: self.line,
+            column,
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error { pos: self.here(), message: message.to_string() }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(b) = self.peek() {
+            match b {
+                b'\n' => {
+                    self.pos += 1;
+                    self.line += 1;
+                    self.line_start = self.pos;
+                }
+                b' ' | b'\t' | b'\r' => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nesting too deep"));
+        }
+        let pos = self.here();
+        let kind = match self.peek() {
+            None => return Err(self.error("unexpected end of input")),
+            Some(b'{') => self.object(depth)?,
+            Some(b'[') => self.array(depth)?,
+            Some(b'"') => Kind::String(self.string()?),
+            Some(b't') => self.literal("true", Kind::Bool(true))?,
+            Some(b'f') => self.literal("false", Kind::Bool(false))?,
+            Some(b'n') => self.literal("null", Kind::Null)?,
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            Some(_) => return Err(self.error("expected a JSON value")),
+        };
+        Ok(Value { kind, pos })
+    }
+
+    fn literal(&mut self, word: &str, kind: Kind) -> Result<Kind, Error> {
+        if self.src[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(kind)
+        } else {
+            Err(self.error("expected a JSON value"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Kind, Error> {
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let from = p.pos;
+            while let Some(b'0'..=b'9') = p.peek() {
+                p.pos += 1;
+            }
+            p.pos - from
+        };
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        let valid = match self.peek() {
+            Some(b'0') => {
+                self.pos += 1;
+                true
+            }
+            Some(b'1'..=b'9') => digits(self) > 0,
+            _ => false,
+        } && (self.peek() != Some(b'.') || {
+            self.pos += 1;
+            digits(self) > 0
+        }) && (!matches!(self.peek(), Some(b'e' | b'E')) || {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            digits(self) > 0
+        });
+
+        let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or("");
+        match text.parse::<f64>() {
+            Ok(n) if valid => Ok(Kind::Number(n)),
+            _ => {
+                self.pos = start;
+                Err(self.error("invalid number"))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.pos += 1; // opening quote
+        let mut out = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return String::from_utf8(out)
+                        .map_err(|_| self.error("invalid UTF-8 in string"));
+                }
+                Some(b'\n') => return Err(self.error("unterminated string")),
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.pos += 1;
+                            let c = self.unicode_escape()?;
+                            let mut buf = [0; 4];
+                            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                Some(b) if b < 0x20 => return Err(self.error("control character in string")),
+                Some(b) => {
+                    out.push(b);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Reads the four hex digits after "\u", joining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, Error> {
+        let hi = self.hex4()?;
+        if (0xD800..0xDC00).contains(&hi) {
+            if !self.src[self.pos..].starts_with(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let lo = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&lo) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            let code = 0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00);
+            return char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"));
+        }
+        char::from_u32(hi).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let digits = self
+            .src
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        let text = std::str::from_utf8(digits).map_err(|_| self.error("invalid unicode escape"))?;
+        let n = u32::from_str_radix(text, 16).map_err(|_| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(n)
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Kind, Error> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Kind::Array(items));
+        }
+        loop {
+            self.skip_ws();
+            items.push(self.value(depth + 1)?);
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Kind::Array(items));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Kind, Error> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Kind::Object(members));
+        }
+        loop {
+            self.skip_ws();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key_pos = self.here();
+            let key = self.string()?;
+            self.skip_ws();
+            if self.peek() != Some(b':') {
+                return Err(self.error("expected ':'"));
+            }
+            self.pos += 1;
+            self.skip_ws();
+            let value = self.value(depth + 1)?;
+            members.push(Member { key, key_pos, value });
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Kind::Object(members));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
+
+/// Quotes and escapes a string as a JSON string literal.
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(src: &str) -> (usize, usize) {
+        let e = parse(src).expect_err(src);
+        (e.pos.line, e.pos.column)
+    }
+
+    #[test]
+    fn keeps_positions() {
+        let v = parse("\u{feff}{\n  \"a\": [1, true, null],\n  \"é\": \"x\\u00e9\\n\"\n}").unwrap();
+        let a = v.get("a").unwrap();
+        assert_eq!(a.pos, Pos { line: 2, column: 8 });
+        let types: Vec<_> = a.as_array().unwrap().iter().map(|v| v.type_name()).collect();
+        assert_eq!(types, ["number", "boolean", "null"]);
+        let members = v.as_object().unwrap();
+        assert_eq!(members[1].key_pos, Pos { line: 3, column: 3 });
+        assert_eq!(v.get("é").and_then(Value::as_str), Some("xé\n"));
+    }
+
+    #[test]
+    fn reports_where_input_goes_wrong() {
+        assert_eq!(error("{\n  \"a\": 1,\n}"), (3, 1));
+        assert_eq!(error("[1 2]"), (1, 4));
+        assert_eq!(error("\"open"), (1, 6));
+        assert_eq!(error("{} x"), (1, 4));
+        assert!(parse(&"[".repeat(MAX_DEPTH + 1)).is_err());
+    }
+
+    #[test]
+    fn writes_strings_that_read_back() {
+        let s = "quote \" slash \\ tab \t bell \u{7} é";
+        assert_eq!(parse(&string(s)).unwrap().as_str(), Some(s));
+    }
+}
@@ -0,0 +1,949 @@
+//! claude-lint: validate .claude/ directory structure
+//!
+//! Enforces a layering model where global context is non-procedural,
+//! agents express perspective without workflows, skills describe
+//! capabilities without success criteria, and references are elective.
+//!
+//! The binary only calls [`run`]; embedders call [`lint_dir`] and get
+//! back plain [`Diagnostic`] values.
+
+mod app;
+mod assets;
+pub(crate) mod baseline;
+pub mod cache;
+pub(crate) mod cli;
+mod checks;
+pub mod config;
+mod custom;
+pub(crate) mod daemon;
+mod duplicates;
+pub(crate) mod explain;
+pub mod fix;
+pub mod frontmatter;
+pub(crate) mod git;
+mod glob;
+pub(crate) mod graph;
+mod hooks;
+pub(crate) mod ignore;
+mod imports;
+mod jobs;
+pub(crate) mod json;
+mod links;
+pub(crate) mod log;
+pub(crate) mod lsp;
+pub mod markdown;
+mod mcp;
+mod names;
+pub(crate) mod output;
+pub(crate) mod owners;
+mod placeholders;
+mod plugin;
+pub(crate) mod precommit;
+pub(crate) mod prose;
+pub(crate) mod readability;
+mod regex;
+pub mod rules;
+pub(crate) mod scaffold;
+pub(crate) mod schema;
+mod secrets;
+mod settings;
+mod spelling;
+pub(crate) mod stats;
+pub(crate) mod terminal;
+pub(crate) mod timings;
+pub(crate) mod tokens;
+mod toml;
+pub(crate) mod urls;
+mod wasm;
+pub(crate) mod watch;
+pub mod workspace;
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+pub use config::Config;
+pub use names::Counts;
+use rules::FileKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    pub fn sarif_level(self) -> &'static str {
+        match self {
+            Severity::Info => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Whether a finding comes from a project or the user-level ~/.claude.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scope {
+    #[default]
+    Project,
+    User,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Project => "project",
+            Scope::User => "user",
+        }
+    }
+}
+
+/// A position in a file: 1-based line and column, and the length of the
+/// flagged text in characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+/// A single finding, tied to a file and optionally a position in it.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// The stable rule ID, such as "CL010".
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub span: Option<Span>,
+    /// A mechanical fix, applied by `--fix`.
+    pub fix: Option<fix::Edit>,
+    pub scope: Scope,
+}
+
+impl Diagnostic {
+    /// Makes a finding of a registered rule, at its default severity.
+    /// Fails on an ID that no rule has.
+    pub fn new(path: &Path, rule: &str, message: String) -> Result<Self, String> {
+        let rule = find_rule(rule).ok_or_else(|| format!("unknown rule ID '{}'", rule))?;
+        Ok(Diagnostic::of(path, rule.id, message))
+    }
+
+    /// Like [`Diagnostic::new`], for IDs the crate registered itself.
+    pub(crate) fn of(path: &Path, rule: &'static str, message: String) -> Self {
+        Diagnostic {
+            path: path.to_path_buf(),
+            rule,
+            severity: crate::rule(rule).severity,
+            message,
+            span: None,
+            fix: None,
+            scope: Scope::Project,
+        }
+    }
+
+    pub fn at(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn with_fix(mut self, edit: fix::Edit) -> Self {
+        self.fix = Some(edit);
+        self
+    }
+
+    pub(crate) fn rule(&self) -> &'static Rule {
+        rule(self.rule)
+    }
+}
+
+/// Metadata for a check. IDs are stable and never reused; names are
+/// human-readable aliases.
+pub struct Rule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: "CL001",
+        name: "missing-claude-md",
+        severity: Severity::Error,
+        description: "The project has no CLAUDE.md, in .claude or beside it.",
+    },
+    Rule {
+        id: "CL002",
+        name: "unreadable-file",
+        severity: Severity::Error,
+        description: "A file could not be read.",
+    },
+    Rule {
+        id: "CL003",
+        name: "missing-skill-md",
+        severity: Severity::Error,
+        description: "Every skill directory needs a SKILL.md.",
+    },
+    Rule {
+        id: "CL004",
+        name: "missing-frontmatter",
+        severity: Severity::Error,
+        description: "Agents and skills must start with YAML frontmatter.",
+    },
+    Rule {
+        id: "CL005",
+        name: "code-block",
+        severity: Severity::Error,
+        description: "Context files must not embed fenced code blocks.",
+    },
+    Rule {
+        id: "CL006",
+        name: "invalid-frontmatter",
+        severity: Severity::Error,
+        description: "Frontmatter must be well-formed YAML, closed with '---', with each key once.",
+    },
+    Rule {
+        id: "CL007",
+        name: "missing-frontmatter-field",
+        severity: Severity::Error,
+        description: "Required frontmatter fields must be present and non-empty.",
+    },
+    Rule {
+        id: "CL008",
+        name: "unknown-frontmatter-field",
+        severity: Severity::Error,
+        description: "Frontmatter fields must be ones the file kind understands.",
+    },
+    Rule {
+        id: "CL009",
+        name: "frontmatter-field-type",
+        severity: Severity::Error,
+        description: "Frontmatter field values must have the expected type.",
+    },
+    Rule {
+        id: "CL010",
+        name: "workflow-verb",
+        severity: Severity::Error,
+        description: "CLAUDE.md uses sequencing language that scripts behavior.",
+    },
+    Rule {
+        id: "CL011",
+        name: "procedural-sequence",
+        severity: Severity::Warning,
+        description: "CLAUDE.md and agents must not script steps in long numbered lists or \
+                      connective-led sentences.",
+    },
+    Rule {
+        id: "CL012",
+        name: "overlapping-claude-md",
+        severity: Severity::Warning,
+        description: "A CLAUDE.md beside .claude must not repeat lines from .claude/CLAUDE.md.",
+    },
+    Rule {
+        id: "CL020",
+        name: "agent-too-long",
+        severity: Severity::Error,
+        description: "Agent files are limited to 120 lines.",
+    },
+    Rule {
+        id: "CL021",
+        name: "procedural-section",
+        severity: Severity::Error,
+        description: "Agents express perspective, not procedure or workflow sections.",
+    },
+    Rule {
+        id: "CL022",
+        name: "duplicate-agent-name",
+        severity: Severity::Error,
+        description: "Agent names must be unique, and not shadow user-level agents.",
+    },
+    Rule {
+        id: "CL023",
+        name: "agent-name-mismatch",
+        severity: Severity::Error,
+        description: "An agent's frontmatter name must match its file name.",
+    },
+    Rule {
+        id: "CL024",
+        name: "unknown-tool",
+        severity: Severity::Warning,
+        description: "Agent tools must be ones Claude Code provides, MCP tools, or allowed in the \
+                      config.",
+    },
+    Rule {
+        id: "CL025",
+        name: "unknown-model",
+        severity: Severity::Warning,
+        description: "An agent's model must be an alias, a Claude model ID, or allowed in the \
+                      config.",
+    },
+    Rule {
+        id: "CL026",
+        name: "description-overlap",
+        severity: Severity::Warning,
+        description: "Agent descriptions must not overlap so much that delegation is a toss-up.",
+    },
+    Rule {
+        id: "CL027",
+        name: "stray-agent-file",
+        severity: Severity::Warning,
+        description: "Files in agents/ must be Markdown, or Claude Code never loads them.",
+    },
+    Rule {
+        id: "CL030",
+        name: "missing-capability",
+        severity: Severity::Error,
+        description: "SKILL.md must have a '## Capability' section.",
+    },
+    Rule {
+        id: "CL031",
+        name: "skill-too-long",
+        severity: Severity::Error,
+        description: "SKILL.md files are limited to 500 lines.",
+    },
+    Rule {
+        id: "CL032",
+        name: "success-criteria",
+        severity: Severity::Error,
+        description: "Skills describe capabilities, not success criteria.",
+    },
+    Rule {
+        id: "CL033",
+        name: "missing-references-section",
+        severity: Severity::Error,
+        description: "A skill with references/ must list them under '## References'.",
+    },
+    Rule {
+        id: "CL034",
+        name: "skill-name-mismatch",
+        severity: Severity::Error,
+        description: "A skill's frontmatter name must match its directory name.",
+    },
+    Rule {
+        id: "CL035",
+        name: "skill-description-too-long",
+        severity: Severity::Error,
+        description: "Skill descriptions are limited to 1024 characters.",
+    },
+    Rule {
+        id: "CL036",
+        name: "duplicate-skill-name",
+        severity: Severity::Error,
+        description: "Skill names must be unique, and not shadow user-level skills.",
+    },
+    Rule {
+        id: "CL037",
+        name: "capability-content",
+        severity: Severity::Warning,
+        description: "Skill Capability sections must say enough, and describe rather than \
+                      instruct.",
+    },
+    Rule {
+        id: "CL038",
+        name: "skill-script",
+        severity: Severity::Error,
+        description: "Skill scripts must start with a shebang line and be executable.",
+    },
+    Rule {
+        id: "CL039",
+        name: "skill-asset-reference",
+        severity: Severity::Warning,
+        description: "SKILL.md must mention each of its scripts, and the templates it names must \
+                      exist.",
+    },
+    Rule {
+        id: "CL040",
+        name: "reference-not-optional",
+        severity: Severity::Error,
+        description: "Reference files must state near the top that they are optional.",
+    },
+    Rule {
+        id: "CL041",
+        name: "dangling-reference",
+        severity: Severity::Error,
+        description: "Links in a skill's References section must point at existing files.",
+    },
+    Rule {
+        id: "CL042",
+        name: "orphaned-reference",
+        severity: Severity::Warning,
+        description: "Every reference file must be linked from the skill's References section.",
+    },
+    Rule {
+        id: "CL043",
+        name: "reference-too-long",
+        severity: Severity::Warning,
+        description: "Reference files are limited to 300 lines and about 3000 tokens.",
+    },
+    Rule {
+        id: "CL044",
+        name: "too-many-references",
+        severity: Severity::Warning,
+        description: "A skill is limited to 10 reference files.",
+    },
+    Rule {
+        id: "CL045",
+        name: "unexpected-reference-file",
+        severity: Severity::Warning,
+        description: "Files in references/ must be Markdown, or allowed in the config.",
+    },
+    Rule {
+        id: "CL050",
+        name: "command-too-long",
+        severity: Severity::Error,
+        description: "Command files are limited to 120 lines.",
+    },
+    Rule {
+        id: "CL051",
+        name: "filename-convention",
+        severity: Severity::Error,
+        description: "Agent and command file names and skill directory names must follow the \
+                      naming convention.",
+    },
+    Rule {
+        id: "CL052",
+        name: "arguments-usage",
+        severity: Severity::Warning,
+        description: "Commands must spell argument placeholders correctly and use a declared \
+                      argument-hint.",
+    },
+    Rule {
+        id: "CL053",
+        name: "duplicate-command-name",
+        severity: Severity::Error,
+        description: "Project commands must not shadow user-level commands.",
+    },
+    Rule {
+        id: "CL060",
+        name: "settings-parse-error",
+        severity: Severity::Error,
+        description: "Settings files must be valid JSON.",
+    },
+    Rule {
+        id: "CL061",
+        name: "settings-unknown-key",
+        severity: Severity::Warning,
+        description: "Settings files must only use keys Claude Code understands.",
+    },
+    Rule {
+        id: "CL062",
+        name: "settings-type",
+        severity: Severity::Error,
+        description: "Settings values must have the expected type and shape.",
+    },
+    Rule {
+        id: "CL063",
+        name: "permission-rule-syntax",
+        severity: Severity::Error,
+        description: "Permission rules must be a tool name with an optional parenthesized \
+                      specifier.",
+    },
+    Rule {
+        id: "CL070",
+        name: "unknown-hook-event",
+        severity: Severity::Error,
+        description: "Hooks must be registered for an event Claude Code fires.",
+    },
+    Rule {
+        id: "CL071",
+        name: "hook-matcher",
+        severity: Severity::Error,
+        description: "Hook matchers must be \"*\", empty, or a well-formed tool-name pattern.",
+    },
+    Rule {
+        id: "CL072",
+        name: "hook-command",
+        severity: Severity::Error,
+        description: "Hooks must have a known type and a non-empty command or prompt.",
+    },
+    Rule {
+        id: "CL073",
+        name: "hook-script",
+        severity: Severity::Error,
+        description: "Hook scripts inside the directory must exist and be executable.",
+    },
+    Rule {
+        id: "CL080",
+        name: "mcp-parse-error",
+        severity: Severity::Error,
+        description: "The project's .mcp.json must be valid JSON.",
+    },
+    Rule {
+        id: "CL081",
+        name: "mcp-server-config",
+        severity: Severity::Error,
+        description: "MCP servers must have a known type and the command or URL it needs.",
+    },
+    Rule {
+        id: "CL082",
+        name: "duplicate-mcp-server",
+        severity: Severity::Error,
+        description: "MCP server names must be unique.",
+    },
+    Rule {
+        id: "CL083",
+        name: "machine-specific-path",
+        severity: Severity::Warning,
+        description: "MCP server configuration must not point into a home directory.",
+    },
+    Rule {
+        id: "CL090",
+        name: "broken-link",
+        severity: Severity::Error,
+        description: "Relative Markdown links must resolve to files on disk.",
+    },
+    Rule {
+        id: "CL091",
+        name: "missing-import",
+        severity: Severity::Error,
+        description: "CLAUDE.md @imports must resolve to existing files.",
+    },
+    Rule {
+        id: "CL092",
+        name: "import-depth",
+        severity: Severity::Warning,
+        description: "CLAUDE.md @import chains must stay within Claude Code's five-hop limit.",
+    },
+    Rule {
+        id: "CL093",
+        name: "import-cycle",
+        severity: Severity::Warning,
+        description: "CLAUDE.md @imports must not form a cycle.",
+    },
+    Rule {
+        id: "CL094",
+        name: "home-directory-path",
+        severity: Severity::Warning,
+        description: "CLAUDE.md, agents, and skills must not name paths in one person's home \
+                      directory.",
+    },
+    Rule {
+        id: "CL095",
+        name: "dead-url",
+        severity: Severity::Warning,
+        description: "External http(s) links must answer, when checked with --check-urls.",
+    },
+    Rule {
+        id: "CL100",
+        name: "file-token-budget",
+        severity: Severity::Warning,
+        description: "Context files must stay within the per-file token budget.",
+    },
+    Rule {
+        id: "CL101",
+        name: "total-token-budget",
+        severity: Severity::Warning,
+        description: "A directory's context files together must stay within the total token \
+                      budget.",
+    },
+    Rule {
+        id: "CL102",
+        name: "duplicate-content",
+        severity: Severity::Warning,
+        description: "Blocks of prose must not be repeated across context files.",
+    },
+    Rule {
+        id: "CL103",
+        name: "eager-context-budget",
+        severity: Severity::Warning,
+        description: "What loads in every session, CLAUDE.md with agent frontmatter and skill \
+                      descriptions, must stay within its budget.",
+    },
+    Rule {
+        id: "CL104",
+        name: "too-many-components",
+        severity: Severity::Warning,
+        description: "A directory is limited to 30 agents, 30 skills, and 50 commands.",
+    },
+    Rule {
+        id: "CL110",
+        name: "invalid-utf8",
+        severity: Severity::Error,
+        description: "Context and settings files must be valid UTF-8.",
+    },
+    Rule {
+        id: "CL111",
+        name: "line-endings",
+        severity: Severity::Warning,
+        description: "Line endings must match the configured style.",
+    },
+    Rule {
+        id: "CL112",
+        name: "frontmatter-format",
+        severity: Severity::Warning,
+        description: "Frontmatter must follow the configured key order and quoting, with a blank \
+                      line after it.",
+    },
+    Rule {
+        id: "CL113",
+        name: "dangling-symlink",
+        severity: Severity::Error,
+        description: "Symlinks in the directory must point at something that exists.",
+    },
+    Rule {
+        id: "CL120",
+        name: "single-title",
+        severity: Severity::Warning,
+        description: "Agents and skills must have exactly one H1 heading.",
+    },
+    Rule {
+        id: "CL121",
+        name: "heading-level-skip",
+        severity: Severity::Warning,
+        description: "Headings must not skip levels.",
+    },
+    Rule {
+        id: "CL122",
+        name: "duplicate-heading",
+        severity: Severity::Warning,
+        description: "Headings within a file must be unique.",
+    },
+    Rule {
+        id: "CL123",
+        name: "empty-section",
+        severity: Severity::Warning,
+        description: "Sections must have content before the next heading.",
+    },
+    Rule {
+        id: "CL124",
+        name: "missing-section",
+        severity: Severity::Error,
+        description: "Files must have the sections the config requires for their kind.",
+    },
+    Rule {
+        id: "CL125",
+        name: "forbidden-section",
+        severity: Severity::Error,
+        description: "Files must not have the sections the config forbids for their kind.",
+    },
+    Rule {
+        id: "CL126",
+        name: "section-order",
+        severity: Severity::Warning,
+        description: "Sections must follow the order the config sets for their kind.",
+    },
+    Rule {
+        id: "CL127",
+        name: "trivial-file",
+        severity: Severity::Warning,
+        description: "Agents, skills, commands, and references must not be empty or nearly so.",
+    },
+    Rule {
+        id: "CL128",
+        name: "heading-casing",
+        severity: Severity::Warning,
+        description: "Headings of known sections must be written as expected, in case and spacing.",
+    },
+    Rule {
+        id: "CL130",
+        name: "secret",
+        severity: Severity::Error,
+        description: "Context, settings, and MCP files must not contain credentials.",
+    },
+    Rule {
+        id: "CL140",
+        name: "placeholder",
+        severity: Severity::Warning,
+        description: "Files must not ship TODO markers, filler text, or template placeholders.",
+    },
+    Rule {
+        id: "CL141",
+        name: "spelling",
+        severity: Severity::Warning,
+        description: "Prose and descriptions must use known words, when the config enables \
+                      spellcheck.",
+    },
+    Rule {
+        id: "CL142",
+        name: "readability",
+        severity: Severity::Warning,
+        description: "Prose must keep sentences short and plain, when the config sets a threshold.",
+    },
+    Rule {
+        id: "CL143",
+        name: "imperative-density",
+        severity: Severity::Warning,
+        description: "SKILL.md must describe more than it addresses or instructs the model.",
+    },
+];
+
+/// Rules added at run time with [`register`].
+static REGISTERED: RwLock<Vec<&'static Rule>> = RwLock::new(Vec::new());
+
+/// Registers a rule beyond the built-in ones, so that extra checks can
+/// report it. IDs and names must be unique.
+pub fn register(rule: Rule) -> Result<&'static Rule, String> {
+    let mut registered = REGISTERED.write().expect("no panic while registering");
+    for r in RULES.iter().chain(registered.iter().copied()) {
+        if r.id == rule.id {
+            return Err(format!("rule ID '{}' is already registered", rule.id));
+        }
+        if r.name == rule.name {
+            return Err(format!("rule name '{}' is already registered", rule.name));
+        }
+    }
+    let rule: &'static Rule = Box::leak(Box::new(rule));
+    registered.push(rule);
+    Ok(rule)
+}
+
+/// Returns the built-in rules followed by the registered ones.
+pub fn all_rules() -> Vec<&'static Rule> {
+    let registered = REGISTERED.read().expect("no panic while registering");
+    RULES.iter().chain(registered.iter().copied()).collect()
+}
+
+/// Looks up a rule by ID.
+pub fn find_rule(id: &str) -> Option<&'static Rule> {
+    all_rules().into_iter().find(|r| r.id == id)
+}
+
+/// Looks up a rule by ID. Checks only ever emit registered IDs.
+pub(crate) fn rule(id: &str) -> &'static Rule {
+    find_rule(id).expect("unregistered rule ID")
+}
+
+/// Runs the command line, from the process's arguments, as the binary
+/// does.
+pub fn run() -> std::process::ExitCode {
+    app::main()
+}
+
+/// Lints a .claude directory with default rule settings.
+pub fn lint_dir(root: &Path) -> Vec<Diagnostic> {
+    lint_dir_with(root, &Config::default())
+}
+
+/// Lints a .claude directory, applying the config's rule settings.
+/// Files are checked in parallel; the result is sorted as by [`sort`].
+pub fn lint_dir_with(root: &Path, config: &Config) -> Vec<Diagnostic> {
+    config.apply(jobs::run(&jobs::plan(root, true, &config.exclude), None, config))
+}
+
+/// Lints a CLAUDE.md or CLAUDE.local.md that lives outside a .claude
+/// directory, as packages in a monorepo often have.
+pub fn lint_claude_md_with(path: &Path, config: &Config) -> Vec<Diagnostic> {
+    config.apply(jobs::run(&[jobs::Job::ClaudeMd(path.to_path_buf())], None, config))
+}
+
+/// Lints a single Markdown file as the given kind, with only the checks
+/// that look at one file at a time.
+pub fn lint_file_with(path: &Path, kind: FileKind, config: &Config) -> Vec<Diagnostic> {
+    config.apply(jobs::run(&[jobs::Job::File(path.to_path_buf(), kind)], None, config))
+}
+
+/// Lints text that is not on disk, such as an unsaved editor buffer, as
+/// the file of the given kind at path. The path is only reported, never
+/// read, and checks that need the rest of the directory do not run.
+pub fn lint_text_with(
+    path: &Path,
+    kind: FileKind,
+    text: Vec<u8>,
+    config: &Config,
+) -> Vec<Diagnostic> {
+    config.apply(jobs::check_text(path, kind, text, config))
+}
+
+/// Lints a user-level ~/.claude directory. It holds the same agents,
+/// skills, and commands as a project, but CLAUDE.md is optional there.
+pub fn lint_user_dir_with(root: &Path, config: &Config) -> Vec<Diagnostic> {
+    lint_user(root, config, &jobs::plan(root, false, &config.exclude), None)
+}
+
+fn lint_user(
+    root: &Path,
+    config: &Config,
+    jobs: &[jobs::Job],
+    cache: Option<&cache::Cache>,
+) -> Vec<Diagnostic> {
+    let mut diags = jobs::run(jobs, cache.map(|c| (c, root)), config);
+    for d in &mut diags {
+        d.scope = Scope::User;
+    }
+    config.apply(diags)
+}
+
+/// Checks a project .claude directory against a user-level one for
+/// agents, skills, and commands that share a name.
+pub fn lint_shadowed(project: &Path, user: &Path, config: &Config) -> Vec<Diagnostic> {
+    let mut diags = Vec::new();
+    names::check_shadowed(project, user, &config.exclude, &mut diags);
+    config.apply(diags)
+}
+
+/// Returns the user-level ~/.claude directory, if HOME is set.
+pub fn user_dir() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".claude"))
+}
+
+/// Lints a target found by [`workspace::discover`] or named on the
+/// command line.
+pub fn lint_target(target: &workspace::Target, config: &Config) -> Vec<Diagnostic> {
+    match target {
+        workspace::Target::Dir(dir) => lint_dir_with(dir, config),
+        workspace::Target::ClaudeMd(path) => lint_claude_md_with(path, config),
+        workspace::Target::User(dir) => lint_user_dir_with(dir, config),
+        workspace::Target::File(path, kind) => lint_file_with(path, *kind, config),
+    }
+}
+
+/// Returns the files linting a target reads, in work-list order.
+pub fn linted_files(target: &workspace::Target, config: &Config) -> Vec<PathBuf> {
+    match target {
+        workspace::Target::Dir(dir) => jobs::plan(dir, true, &config.exclude)
+            .iter()
+            .flat_map(jobs::Job::files)
+            .collect(),
+        workspace::Target::ClaudeMd(path) | workspace::Target::File(path, _) => vec![path.clone()],
+        workspace::Target::User(dir) => jobs::plan(dir, false, &config.exclude)
+            .iter()
+            .flat_map(jobs::Job::files)
+            .collect(),
+    }
+}
+
+/// Returns how many agents, skills, and commands a directory target has,
+/// leaving out the ones the config excludes.
+pub fn component_counts(target: &workspace::Target, config: &Config) -> Option<Counts> {
+    match target {
+        workspace::Target::Dir(dir) | workspace::Target::User(dir) => {
+            Some(names::count(dir, &config.exclude))
+        }
+        workspace::Target::ClaudeMd(_) | workspace::Target::File(..) => None,
+    }
+}
+
+/// Returns the files a target leaves out because the config excludes
+/// them, in work-list order.
+pub fn skipped_files(target: &workspace::Target, config: &Config) -> Vec<PathBuf> {
+    match target {
+        workspace::Target::Dir(dir) | workspace::Target::User(dir) => {
+            jobs::skipped(dir, &config.exclude)
+        }
+        workspace::Target::File(path, _) if config.exclude.excludes(path) => vec![path.clone()],
+        workspace::Target::ClaudeMd(_) | workspace::Target::File(..) => Vec::new(),
+    }
+}
+
+/// Like [`lint_target`], reusing and updating cached per-file results.
+pub fn lint_target_cached(
+    target: &workspace::Target,
+    config: &Config,
+    cache: &cache::Cache,
+) -> Vec<Diagnostic> {
+    match target {
+        workspace::Target::Dir(dir) => config.apply(jobs::run(
+            &jobs::plan(dir, true, &config.exclude),
+            Some((cache, dir)),
+            config,
+        )),
+        workspace::Target::ClaudeMd(path) => lint_claude_md_with(path, config),
+        workspace::Target::User(dir) => lint_user(
+            dir,
+            config,
+            &jobs::plan(dir, false, &config.exclude),
+            Some(cache),
+        ),
+        workspace::Target::File(path, kind) => lint_file_with(path, *kind, config),
+    }
+}
+
+/// Like [`lint_target_cached`], but only for the files among changed, a
+/// list of absolute paths as from [`git::changed_files`]. Only findings
+/// on those files are kept, and the import findings of a CLAUDE.md that
+/// imports one. Returns None when nothing in the target changed.
+pub(crate) fn lint_target_changed(
+    target: &workspace::Target,
+    config: &Config,
+    cache: &cache::Cache,
+    changed: &[PathBuf],
+) -> Option<Vec<Diagnostic>> {
+    let abs = |p: &Path| std::fs::canonicalize(p).ok();
+    let (dir, user) = match target {
+        workspace::Target::ClaudeMd(path) => {
+            let path_abs = abs(path)?;
+            return changed.contains(&path_abs).then(|| lint_claude_md_with(path, config));
+        }
+        workspace::Target::File(path, kind) => {
+            let path_abs = abs(path)?;
+            return changed.contains(&path_abs).then(|| lint_file_with(path, *kind, config));
+        }
+        workspace::Target::Dir(dir) => (dir, false),
+        workspace::Target::User(dir) => (dir, true),
+    };
+    // A project may have no .claude yet, only the CLAUDE.md beside it.
+    let root = abs(dir).or_else(|| Some(abs(&dir.parent()?.join("."))?.join(dir.file_name()?)))?;
+    let project = root.parent().unwrap_or(Path::new(""));
+    let config_changed = changed.contains(&project.join(config::FILE_NAME));
+    let beside = |c: &PathBuf| {
+        *c == project.join(mcp::FILE_NAME) || (c.starts_with(project) && rules::is_claude_md(c))
+    };
+    if !config_changed && !changed.iter().any(|c| c.starts_with(&root) || beside(c)) {
+        return None;
+    }
+    // Entries for skipped files stay valid.
+    cache.keep_all();
+    // A new config can change any finding, so it reruns everything.
+    let jobs: Vec<jobs::Job> = jobs::plan(dir, !user, &config.exclude)
+        .into_iter()
+        .filter(|j| config_changed || j.affected(changed))
+        .collect();
+    let mut diags = if user {
+        lint_user(dir, config, &jobs, Some(cache))
+    } else {
+        config.apply(jobs::run(&jobs, Some((cache, dir)), config))
+    };
+    // Whole-tree checks and a CLAUDE.md whose imports changed also report
+    // on files that did not change; only its import findings stand.
+    if !config_changed {
+        diags.retain(|d| {
+            IMPORT_RULES.contains(&d.rule) || abs(&d.path).is_some_and(|p| changed.contains(&p))
+        });
+    }
+    Some(diags)
+}
+
+/// The rules about a CLAUDE.md's imports, which a change to an imported
+/// file can set off on the CLAUDE.md.
+const IMPORT_RULES: &[&str] = &["CL091", "CL092", "CL093"];
+
+/// Checks the http(s) links in files, as from [`linted_files`], and
+/// reports the ones that are dead. Answers are cached at cache, as from
+/// [`urls::default_cache_path`], if given. Fails when curl cannot be run
+/// or the cache cannot be written.
+pub(crate) fn check_urls(
+    files: &[PathBuf],
+    config: &Config,
+    cache: Option<&Path>,
+) -> Result<Vec<Diagnostic>, String> {
+    Ok(config.apply(urls::check(files, &config.urls, cache)?))
+}
+
+/// Sorts diagnostics by path, line, column, and rule ID, so output does
+/// not depend on the order a platform lists directories in. A finding on
+/// a whole file comes before those on its lines.
+pub fn sort(diags: &mut [Diagnostic]) {
+    let key = |d: &Diagnostic| d.span.map(|s| (s.line, s.column));
+    diags.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then_with(|| key(a).cmp(&key(b)))
+            .then_with(|| a.rule.cmp(b.rule))
+    });
+}
+
+/// Counts diagnostics of one severity.
+pub fn count(diags: &[Diagnostic], severity: Severity) -> usize {
+    diags.iter().filter(|d| d.severity == severity).count()
+}
@@ -0,0 +1,145 @@
+//! Relative link checking across the linted tree.
+//!
+//! Context files link each other by relative path, and a renamed or
+//! deleted file leaves the model chasing a link that goes nowhere. This
+//! resolves every relative link in every linted file against the disk.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::markdown::{self, Kind};
+use crate::rules::{Check, Document, FileKind};
+use crate::{Diagnostic, Span};
+
+/// Reports relative links that do not resolve.
+pub(crate) struct BrokenLinks;
+
+impl Check for BrokenLinks {
+    fn id(&self) -> &'static str {
+        "CL090"
+    }
+
+    fn applies_to(&self, _: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let md = &doc.markdown;
+        // CL041 already covers a skill's References section.
+        let skip = (doc.kind == FileKind::Skill)
+            .then(|| md.heading(2, "References"))
+            .flatten()
+            .map(|h| md.section(h));
+        let dir = doc.path.parent().unwrap_or(Path::new(""));
+
+        let mut diags = Vec::new();
+        for line in md.lines.iter().filter(|l| {
+            matches!(
+                l.kind,
+                Kind::Paragraph | Kind::ListItem { .. } | Kind::Heading(_)
+            )
+        }) {
+            if skip.is_some_and(|s| s.iter().any(|l| l.number == line.number)) {
+                continue;
+            }
+            let mut found = markdown::links(line.text);
+            found.extend(definition(line.text));
+            for link in found {
+                let Some(target) = local_target(link.target) else {
+                    continue;
+                };
+                if !normalize(&dir.join(&target)).exists() {
+                    diags.push(
+                        Diagnostic::of(doc.path, "CL090", format!("broken link to '{}'", target))
+                            .at(Some(Span {
+                                line: line.number,
+                                column: link.column,
+                                length: link.target.chars().count(),
+                            })),
+                    );
+                }
+            }
+        }
+        diags
+    }
+}
+
+/// Returns the existing files a document's relative links point to, each
+/// once, in the order they appear.
+pub(crate) fn linked(path: &Path, content: &str) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let md = markdown::parse(content);
+    let mut out: Vec<PathBuf> = Vec::new();
+    for line in md.lines.iter().filter(|l| {
+        matches!(
+            l.kind,
+            Kind::Paragraph | Kind::ListItem { .. } | Kind::Heading(_)
+        )
+    }) {
+        let mut found = markdown::links(line.text);
+        found.extend(definition(line.text));
+        for target in found.iter().filter_map(|link| local_target(link.target)) {
+            let target = normalize(&dir.join(target));
+            if target.is_file() && !out.contains(&target) {
+                out.push(target);
+            }
+        }
+    }
+    out
+}
+
+/// Recognizes a link reference definition, `[label]: target`.
+fn definition(text: &str) -> Option<markdown::Link<'_>> {
+    let lead = text.len() - text.trim_start_matches(' ').len();
+    if lead > 3 {
+        return None;
+    }
+    let rest = text[lead..].strip_prefix('[')?;
+    let close = rest.find("]:")?;
+    if close == 0 {
+        return None;
+    }
+    let after = &rest[close + 2..];
+    let target_start = after.len() - after.trim_start().len();
+    let target = after.split_whitespace().next()?;
+    let target = target
+        .strip_prefix('<')
+        .and_then(|t| t.strip_suffix('>'))
+        .unwrap_or(target);
+    let offset =
+        lead + 1 + close + 2 + target_start + usize::from(after.trim_start().starts_with('<'));
+    Some(markdown::Link {
+        target,
+        column: text[..offset].chars().count() + 1,
+    })
+}
+
+/// Returns a link target as a relative path, or None for URLs, anchors,
+/// and absolute paths.
+pub(crate) fn local_target(target: &str) -> Option<String> {
+    let path = target.split('#').next().unwrap_or_default();
+    if path.is_empty()
+        || path.contains("://")
+        || path.starts_with("mailto:")
+        || path.starts_with('/')
+    {
+        return None;
+    }
+    Some(path.replace("%20", " "))
+}
+
+/// Drops "." components and folds ".." so equal paths compare equal.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
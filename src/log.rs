@@ -0,0 +1,115 @@
+//! Internal logging, for finding out why a file was or was not linted.
+//!
+//! Events cover discovery, exclusions, cache hits, each check, and how
+//! long jobs and targets take. Logging is off unless `--log-level` or
+//! `RUST_LOG` sets a level; lines go to stderr with the time since the
+//! run started, the level, and the module. `RUST_LOG` takes the usual
+//! comma-separated `target=level` list, of which only a bare level and
+//! `claude_lint` targets count. The crate has no dependencies, so this
+//! stands in for the log and tracing crates.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// How much to log; each level includes the ones before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// The highest level logged, 0 when logging is off.
+static LEVEL: AtomicU8 = AtomicU8::new(0);
+static START: OnceLock<Instant> = OnceLock::new();
+
+impl Level {
+    /// Parses a level name, or `off`, which is None.
+    pub fn parse(s: &str) -> Result<Option<Level>, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(None),
+            "error" => Ok(Some(Level::Error)),
+            "warn" | "warning" => Ok(Some(Level::Warn)),
+            "info" => Ok(Some(Level::Info)),
+            "debug" => Ok(Some(Level::Debug)),
+            "trace" => Ok(Some(Level::Trace)),
+            _ => Err(format!(
+                "unknown log level '{}' (expected off, error, warn, info, debug, or trace)",
+                s
+            )),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+/// Reads a `RUST_LOG` filter. A `claude_lint` target wins over a bare
+/// level; entries that do not parse are ignored.
+pub fn from_filter(spec: &str) -> Option<Level> {
+    let mut default = None;
+    let mut own = None;
+    for part in spec.split(',').map(str::trim) {
+        match part.split_once('=') {
+            Some((target, level))
+                if target == "claude_lint" || target.starts_with("claude_lint::") =>
+            {
+                own = Level::parse(level).ok().or(own);
+            }
+            Some(_) => {}
+            None => default = Level::parse(part).ok().or(default),
+        }
+    }
+    own.unwrap_or(default.flatten())
+}
+
+/// Turns logging on at a level, or off, and starts the clock.
+pub fn init(level: Option<Level>) {
+    LEVEL.store(level.map_or(0, |l| l as u8), Ordering::Relaxed);
+    START.get_or_init(Instant::now);
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Writes an event from a module, if its level is on.
+pub fn write(level: Level, module: &str, args: fmt::Arguments) {
+    if enabled(level) {
+        let elapsed = START.get_or_init(Instant::now).elapsed().as_secs_f64();
+        let _ = writeln!(
+            io::stderr(),
+            "{:>9.3}s {:<5} {}: {}",
+            elapsed,
+            level.as_str(),
+            module,
+            args
+        );
+    }
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Debug, module_path!(), format_args!($($arg)*))
+    };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        $crate::log::write($crate::log::Level::Trace, module_path!(), format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {debug, trace};
@@ -0,0 +1,439 @@
+//! A minimal Language Server Protocol server over stdio.
+//!
+//! Editors send a file's URI when it is opened or saved; the server
+//! lints the `.claude` directory the file belongs to and publishes
+//! diagnostics for every file in it, with rule IDs as codes. Fixable
+//! findings are offered as quick fixes. Editors also send the whole text
+//! of an open file on each change, and the server lints that buffer on
+//! its own, with the checks that look at one file at a time, so results
+//! follow keystrokes; the checks across files catch up on save.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cache::{self, Cache};
+use crate::config::{self, Config};
+use crate::json::{self, Kind, Value};
+use crate::rules::{self, FileKind};
+use crate::workspace::Target;
+use crate::{lint_target_cached, lint_text_with, user_dir, Diagnostic, Severity};
+
+/// JSON-RPC error for requests the server does not implement.
+const METHOD_NOT_FOUND: i32 = -32601;
+
+#[derive(Default)]
+struct Server {
+    /// Files with published diagnostics, per lint target, so that fixed
+    /// files can be cleared.
+    published: HashMap<PathBuf, Vec<PathBuf>>,
+    /// The latest diagnostics per file, for code actions.
+    diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+    /// The text of each open file, as the editor has it.
+    buffers: HashMap<PathBuf, String>,
+}
+
+/// Serves requests until the client sends `exit` or closes the stream.
+pub fn serve(input: &mut impl BufRead, output: &mut impl Write) -> io::Result<()> {
+    let mut server = Server::default();
+    while let Some(body) = read_message(input)? {
+        let Ok(msg) = json::parse(&body) else {
+            continue;
+        };
+        let method = msg.get("method").and_then(Value::as_str).unwrap_or_default();
+        let id = msg.get("id").map(render_id);
+        let params = msg.get("params");
+        let document = params.and_then(|p| p.get("textDocument"));
+        let uri = document.and_then(|d| d.get("uri")).and_then(Value::as_str);
+
+        match (method, id) {
+            ("initialize", Some(id)) => respond(output, &id, &capabilities())?,
+            ("shutdown", Some(id)) => respond(output, &id, "null")?,
+            ("exit", _) => return Ok(()),
+            ("textDocument/didOpen", None) => {
+                let Some(path) = uri.and_then(uri_path) else {
+                    continue;
+                };
+                if let Some(text) = document.and_then(|d| d.get("text")).and_then(Value::as_str) {
+                    server.buffers.insert(path.clone(), text.to_string());
+                }
+                if let Some(target) = target_for(&path) {
+                    server.lint(output, &target)?;
+                }
+            }
+            ("textDocument/didChange", None) => {
+                // With full sync, the last change holds the whole text.
+                let changes = params
+                    .and_then(|p| p.get("contentChanges"))
+                    .and_then(Value::as_array)
+                    .unwrap_or_default();
+                let text = changes
+                    .last()
+                    .and_then(|c| c.get("text"))
+                    .and_then(Value::as_str);
+                if let (Some(path), Some(text)) = (uri.and_then(uri_path), text) {
+                    server.buffers.insert(path.clone(), text.to_string());
+                    if let Some(target) = target_for(&path) {
+                        server.lint_buffer(output, &target, &path)?;
+                    }
+                }
+            }
+            ("textDocument/didSave", None) => {
+                if let Some(target) = uri.and_then(uri_path).and_then(|p| target_for(&p)) {
+                    server.lint(output, &target)?;
+                }
+            }
+            ("textDocument/didClose", None) => {
+                let Some(path) = uri.and_then(uri_path) else {
+                    continue;
+                };
+                // Unsaved edits are dropped, so the file on disk counts again.
+                let dirty = server
+                    .buffers
+                    .remove(&path)
+                    .is_some_and(|text| fs::read_to_string(&path).ok() != Some(text));
+                if let Some(target) = target_for(&path).filter(|_| dirty) {
+                    server.lint(output, &target)?;
+                }
+            }
+            ("textDocument/codeAction", Some(id)) => {
+                let range = params.and_then(|p| p.get("range"));
+                let actions = match uri.and_then(uri_path) {
+                    Some(path) => server.code_actions(&path, range),
+                    None => "[]".to_string(),
+                };
+                respond(output, &id, &actions)?;
+            }
+            (_, Some(id)) => {
+                let body = format!(
+                    "{{\"jsonrpc\":\"2.0\",\"id\":{},\"error\":{{\"code\":{},\"message\":{}}}}}",
+                    id,
+                    METHOD_NOT_FOUND,
+                    json::string(&format!("method '{}' is not supported", method)),
+                );
+                send(output, &body)?;
+            }
+            // Other notifications.
+            (_, None) => {}
+        }
+    }
+    Ok(())
+}
+
+impl Server {
+    /// Lints a target and publishes its diagnostics, clearing files that
+    /// had findings last time and have none now. Open files whose text
+    /// differs from the disk are linted from their buffers instead.
+    fn lint(&mut self, output: &mut impl Write, target: &Target) -> io::Result<()> {
+        let config = load_config(output, target)?;
+        let cache_path = match target {
+            Target::Dir(dir) | Target::User(dir) => Some(cache::default_path(dir)),
+            Target::ClaudeMd(_) | Target::File(..) => None,
+        };
+        let cache = cache_path.as_deref().map_or_else(Cache::empty, Cache::load);
+        let diags = lint_target_cached(target, &config, &cache);
+        if let Some(path) = cache_path {
+            // A read-only tree only loses the speedup.
+            let _ = cache.save(&path);
+        }
+
+        let mut by_path: Vec<(PathBuf, Vec<Diagnostic>)> = Vec::new();
+        for d in diags {
+            match by_path.iter_mut().find(|(p, _)| *p == d.path) {
+                Some((_, list)) => list.push(d),
+                None => by_path.push((d.path.clone(), vec![d])),
+            }
+        }
+        let files: Vec<PathBuf> = by_path.iter().map(|(p, _)| p.clone()).collect();
+        for old in self.published.insert(target.path().to_path_buf(), files).unwrap_or_default() {
+            if !by_path.iter().any(|(p, _)| *p == old) {
+                publish(output, &old, &[], None)?;
+                self.diagnostics.remove(&old);
+            }
+        }
+        let dirty: Vec<PathBuf> = self
+            .buffers
+            .iter()
+            .filter(|(path, text)| {
+                target_for(path).is_some_and(|t| t.path() == target.path())
+                    && fs::read_to_string(path).ok().as_ref() != Some(*text)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        for (path, diags) in by_path.into_iter().filter(|(p, _)| !dirty.contains(p)) {
+            publish(output, &path, &diags, None)?;
+            self.diagnostics.insert(path, diags);
+        }
+        for path in dirty {
+            self.lint_buffer(output, target, &path)?;
+        }
+        Ok(())
+    }
+
+    /// Lints the open buffer of a file on its own and publishes its
+    /// diagnostics.
+    fn lint_buffer(
+        &mut self,
+        output: &mut impl Write,
+        target: &Target,
+        path: &Path,
+    ) -> io::Result<()> {
+        let (Some(text), Some(kind)) = (self.buffers.get(path), FileKind::infer(path)) else {
+            return Ok(());
+        };
+        let config = load_config(output, target)?;
+        let diags = lint_text_with(path, kind, text.as_bytes().to_vec(), &config);
+        publish(output, path, &diags, Some(text))?;
+        self.published.entry(target.path().to_path_buf()).or_default().retain(|p| p != path);
+        if !diags.is_empty() {
+            self.published.entry(target.path().to_path_buf()).or_default().push(path.to_path_buf());
+        }
+        self.diagnostics.insert(path.to_path_buf(), diags);
+        Ok(())
+    }
+
+    /// Returns quick fixes for the fixable findings on the lines of range.
+    fn code_actions(&self, path: &Path, range: Option<&Value>) -> String {
+        let line = |key: &str| {
+            range
+                .and_then(|r| r.get(key))
+                .and_then(|p| p.get("line"))
+                .and_then(|l| match l.kind {
+                    Kind::Number(n) => Some(n as usize),
+                    _ => None,
+                })
+        };
+        let (first, last) = (
+            line("start").unwrap_or(0),
+            line("end").unwrap_or(usize::MAX),
+        );
+        let Some(content) = self
+            .buffers
+            .get(path)
+            .cloned()
+            .or_else(|| fs::read_to_string(path).ok())
+        else {
+            return "[]".to_string();
+        };
+
+        let mut actions = Vec::new();
+        for d in self.diagnostics.get(path).into_iter().flatten() {
+            let Some(edit) = &d.fix else {
+                continue;
+            };
+            let line = d.span.map_or(0, |s| s.line.saturating_sub(1));
+            if line < first || line > last {
+                continue;
+            }
+            actions.push(format!(
+                "{{\"title\":{},\"kind\":\"quickfix\",\"diagnostics\":[{}],\
+                 \"edit\":{{\"changes\":{{{}:[{{\"range\":{},\"newText\":{}}}]}}}}}}",
+                json::string(&format!("Fix {} ({})", d.rule().name, d.rule)),
+                diagnostic(d, Some(&content)),
+                json::string(&path_uri(path)),
+                range_json(
+                    offset_position(&content, edit.start),
+                    offset_position(&content, edit.end)
+                ),
+                json::string(&edit.text),
+            ));
+        }
+        format!("[{}]", actions.join(","))
+    }
+}
+
+/// Picks what to lint for a file: the `.claude` directory holding it,
+/// the `.claude` next to a project-root file like CLAUDE.md, `.mcp.json`,
+/// or `.claude-lint.toml`, or a CLAUDE.md on its own.
+pub(crate) fn target_for(path: &Path) -> Option<Target> {
+    if let Some(dir) = path.ancestors().find(|a| a.file_name().is_some_and(|n| n == ".claude")) {
+        if user_dir().is_some_and(|u| u == dir) {
+            return Some(Target::User(dir.to_path_buf()));
+        }
+        return Some(Target::Dir(dir.to_path_buf()));
+    }
+    let sibling = path.parent()?.join(".claude");
+    if sibling.is_dir() {
+        return Some(Target::Dir(sibling));
+    }
+    rules::is_claude_md(path).then(|| Target::ClaudeMd(path.to_path_buf()))
+}
+
+fn capabilities() -> String {
+    format!(
+        "{{\"capabilities\":{{\"textDocumentSync\":{{\"openClose\":true,\"change\":1,\
+         \"save\":true}},\
+         \"codeActionProvider\":{{\"codeActionKinds\":[\"quickfix\"]}}}},\
+         \"serverInfo\":{{\"name\":\"claude-lint\",\"version\":{}}}}}",
+        json::string(env!("CARGO_PKG_VERSION")),
+    )
+}
+
+/// Loads the config for a target, or shows why it does not load and
+/// falls back to the defaults.
+fn load_config(output: &mut impl Write, target: &Target) -> io::Result<Config> {
+    match config::load(None, &target.config_root(), false) {
+        Ok(c) => Ok(c),
+        Err(e) => {
+            show_message(output, &e)?;
+            Ok(Config::default())
+        }
+    }
+}
+
+/// Publishes a file's diagnostics, measuring columns in its open buffer
+/// if it has one.
+fn publish(
+    output: &mut impl Write,
+    path: &Path,
+    diags: &[Diagnostic],
+    buffer: Option<&str>,
+) -> io::Result<()> {
+    let list: Vec<String> = diags.iter().map(|d| diagnostic(d, buffer)).collect();
+    let body = format!(
+        "{{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\
+         \"params\":{{\"uri\":{},\"diagnostics\":[{}]}}}}",
+        json::string(&path_uri(path)),
+        list.join(","),
+    );
+    send(output, &body)
+}
+
+fn show_message(output: &mut impl Write, message: &str) -> io::Result<()> {
+    let body = format!(
+        "{{\"jsonrpc\":\"2.0\",\"method\":\"window/showMessage\",\"params\":{{\"type\":1,\
+         \"message\":{}}}}}",
+        json::string(message),
+    );
+    send(output, &body)
+}
+
+fn diagnostic(d: &Diagnostic, buffer: Option<&str>) -> String {
+    let severity = match d.severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Info => 3,
+    };
+    // Findings without a span sit at the top of the file.
+    let range = match d.span {
+        Some(s) => {
+            let line = buffer
+                .map(str::to_string)
+                .or_else(|| fs::read_to_string(&d.path).ok())
+                .and_then(|c| c.lines().nth(s.line - 1).map(str::to_string))
+                .unwrap_or_default();
+            let start = utf16_column(&line, s.column - 1);
+            let end = utf16_column(&line, s.column - 1 + s.length);
+            range_json((s.line - 1, start), (s.line - 1, end))
+        }
+        None => range_json((0, 0), (0, 0)),
+    };
+    format!(
+        "{{\"range\":{},\"severity\":{},\"code\":{},\"source\":\"claude-lint\",\"message\":{}}}",
+        range,
+        severity,
+        json::string(d.rule),
+        json::string(&d.message),
+    )
+}
+
+fn range_json(start: (usize, usize), end: (usize, usize)) -> String {
+    format!(
+        "{{\"start\":{{\"line\":{},\"character\":{}}},\"end\":{{\"line\":{},\"character\":{}}}}}",
+        start.0, start.1, end.0, end.1,
+    )
+}
+
+/// Converts a count of chars into a line to UTF-16 code units, the LSP
+/// default. Columns past the end count as one unit each.
+fn utf16_column(line: &str, chars: usize) -> usize {
+    let n = line.chars().count();
+    line.chars().take(chars).map(char::len_utf16).sum::<usize>() + chars.saturating_sub(n)
+}
+
+/// Converts a byte offset into a 0-based line and UTF-16 column.
+fn offset_position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count();
+    (line, before[line_start..].chars().map(char::len_utf16).sum())
+}
+
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let bytes = rest.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%').then(|| rest.get(i + 1..i + 3)).flatten();
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(b) => {
+                out.push(b);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(out).ok()?))
+}
+
+fn path_uri(path: &Path) -> String {
+    let mut out = String::from("file://");
+    for b in path.to_string_lossy().bytes() {
+        if b.is_ascii_alphanumeric() || b"/-._~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// Renders a request ID back as JSON; IDs are numbers or strings.
+fn render_id(id: &Value) -> String {
+    match &id.kind {
+        Kind::Number(n) => format!("{}", n),
+        Kind::String(s) => json::string(s),
+        _ => "null".to_string(),
+    }
+}
+
+/// Reads one message framed by a Content-Length header, or None at the
+/// end of the stream.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(length) = length else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message without Content-Length"));
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    String::from_utf8(body).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn respond(output: &mut impl Write, id: &str, result: &str) -> io::Result<()> {
+    send(output, &format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":{}}}", id, result))
+}
+
+fn send(output: &mut impl Write, body: &str) -> io::Result<()> {
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
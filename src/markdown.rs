@@ -0,0 +1,326 @@
+//! Line-oriented Markdown block parser.
+//!
+//! Checks care about structure, not rendering: which lines are headings,
+//! which sit inside code fences, quotes, or HTML comments, and which are
+//! list items. This classifies every source line along those lines,
+//! following CommonMark for the block constructs context files use.
+
+use std::path::Path;
+
+/// A classified source line.
+#[derive(Clone, Debug)]
+pub struct Line<'a> {
+    /// 1-based line number.
+    pub number: usize,
+    /// Byte offset of the line start in the source.
+    pub offset: usize,
+    pub text: &'a str,
+    pub kind: Kind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Blank,
+    /// Leading YAML frontmatter, delimiters included.
+    Frontmatter,
+    /// An ATX or setext heading; setext underlines are Setext.
+    Heading(u8),
+    Setext,
+    /// A code fence delimiter line.
+    Fence,
+    /// A line inside a fenced code block.
+    Code,
+    /// A line inside an HTML comment block.
+    Comment,
+    Quote,
+    ListItem { ordered: bool },
+    Paragraph,
+}
+
+/// A heading with its text stripped of markers.
+#[derive(Clone, Debug)]
+pub struct Heading<'a> {
+    pub level: u8,
+    pub text: &'a str,
+    pub line: usize,
+    /// 1-based column where the heading text starts.
+    pub column: usize,
+}
+
+/// A fenced code block, from opening to closing fence.
+#[derive(Clone, Debug)]
+pub struct CodeBlock {
+    pub start_line: usize,
+    /// None if the fence is never closed.
+    pub end_line: Option<usize>,
+    pub fence: char,
+}
+
+pub struct Markdown<'a> {
+    pub lines: Vec<Line<'a>>,
+    pub headings: Vec<Heading<'a>>,
+    pub code_blocks: Vec<CodeBlock>,
+}
+
+impl<'a> Markdown<'a> {
+    /// Returns the first heading at a level with the given text, compared
+    /// as [`same_title`] does.
+    pub fn heading(&self, level: u8, text: &str) -> Option<&Heading<'a>> {
+        self.headings.iter().find(|h| h.level == level && same_title(h.text, text))
+    }
+
+    /// Returns the line at a 1-based line number.
+    pub fn line(&self, number: usize) -> Option<&Line<'a>> {
+        self.lines.get(number.checked_sub(1)?)
+    }
+
+    /// Returns the lines under a heading, up to the next heading at the
+    /// same or a higher level.
+    pub fn section(&self, heading: &Heading) -> &[Line<'a>] {
+        let start = heading.line.min(self.lines.len());
+        let len = self.lines[start..]
+            .iter()
+            .position(|l| matches!(l.kind, Kind::Heading(level) if level <= heading.level))
+            .unwrap_or(self.lines.len() - start);
+        &self.lines[start..start + len]
+    }
+}
+
+/// An inline link or image, `[text](target)`.
+#[derive(Clone, Debug)]
+pub struct Link<'a> {
+    pub target: &'a str,
+    /// 1-based column of the target.
+    pub column: usize,
+}
+
+/// Returns the inline links on a line, skipping code spans and comments.
+pub fn links(text: &str) -> Vec<Link<'_>> {
+    let masked = crate::prose::mask(text);
+    let mut out = Vec::new();
+    let mut from = 0;
+    while let Some(i) = masked[from..].find("](") {
+        let start = from + i + 2;
+        from = start;
+        let rest = &masked[start..];
+        let (offset, end) = if let Some(angle) = rest.strip_prefix('<') {
+            match angle.find('>') {
+                Some(e) => (1, 1 + e),
+                None => continue,
+            }
+        } else {
+            let Some(close) = rest.find(')') else {
+                continue;
+            };
+            (0, rest[..close].find([' ', '\t']).unwrap_or(close))
+        };
+        let target = &text[start + offset..start + end];
+        if !target.is_empty() {
+            out.push(Link { target, column: text[..start + offset].chars().count() + 1 });
+        }
+    }
+    out
+}
+
+/// Whether a path names a Markdown file: one ending in `.md` or
+/// `.markdown`, in any case.
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
+}
+
+/// Whether two heading texts name the same section, ignoring case and
+/// how the words are spaced.
+pub fn same_title(a: &str, b: &str) -> bool {
+    a.split_whitespace().map(str::to_lowercase).eq(b.split_whitespace().map(str::to_lowercase))
+}
+
+pub fn parse(src: &str) -> Markdown<'_> {
+    let mut lines = Vec::new();
+    let mut headings = Vec::new();
+    let mut code_blocks: Vec<CodeBlock> = Vec::new();
+
+    // A byte order mark is not part of the first line.
+    let mut offset = if src.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+    let raw: Vec<(usize, &str)> = src[offset..]
+        .split_inclusive('\n')
+        .map(|l| {
+            let start = offset;
+            offset += l.len();
+            (start, l.trim_end_matches('\n').trim_end_matches('\r'))
+        })
+        .collect();
+
+    let frontmatter_end = frontmatter_end(&raw);
+    // Open fence: char, length, and index into code_blocks.
+    let mut fence: Option<(char, usize, usize)> = None;
+    let mut in_comment = false;
+
+    for (i, &(offset, text)) in raw.iter().enumerate() {
+        let number = i + 1;
+        let kind = if frontmatter_end.is_some_and(|end| i <= end) {
+            Kind::Frontmatter
+        } else if let Some((c, len, index)) = fence {
+            if is_closing_fence(text, c, len) {
+                code_blocks[index].end_line = Some(number);
+                fence = None;
+                Kind::Fence
+            } else {
+                Kind::Code
+            }
+        } else if in_comment {
+            in_comment = !text.contains("-->");
+            Kind::Comment
+        } else if text.trim().is_empty() {
+            Kind::Blank
+        } else if let Some((c, len)) = opening_fence(text) {
+            fence = Some((c, len, code_blocks.len()));
+            code_blocks.push(CodeBlock { start_line: number, end_line: None, fence: c });
+            Kind::Fence
+        } else if let Some((level, start, end)) = atx_heading(text) {
+            headings.push(Heading {
+                level,
+                text: &text[start..end],
+                line: number,
+                column: text[..start].chars().count() + 1,
+            });
+            Kind::Heading(level)
+        } else if let Some(level) = setext_level(text).filter(|_| {
+            lines.last().is_some_and(|l: &Line| l.kind == Kind::Paragraph)
+        }) {
+            // The paragraph line above becomes the heading.
+            let prev = lines.last_mut().expect("checked above");
+            prev.kind = Kind::Heading(level);
+            let trimmed = prev.text.trim();
+            let start = prev.text.len() - prev.text.trim_start().len();
+            headings.push(Heading {
+                level,
+                text: trimmed,
+                line: prev.number,
+                column: prev.text[..start].chars().count() + 1,
+            });
+            Kind::Setext
+        } else if indent(text) < 4 && text.trim_start().starts_with("<!--") {
+            in_comment = !text.contains("-->");
+            Kind::Comment
+        } else if indent(text) < 4 && text.trim_start().starts_with('>') {
+            Kind::Quote
+        } else if let Some(ordered) = list_marker(text).map(|(ordered, _)| ordered) {
+            Kind::ListItem { ordered }
+        } else {
+            Kind::Paragraph
+        };
+        lines.push(Line { number, offset, text, kind });
+    }
+
+    Markdown { lines, headings, code_blocks }
+}
+
+/// Returns the index of the closing frontmatter line, if any.
+fn frontmatter_end(raw: &[(usize, &str)]) -> Option<usize> {
+    if raw.first().map(|&(_, t)| t) != Some("---") {
+        return None;
+    }
+    raw.iter()
+        .skip(1)
+        .position(|&(_, t)| t.trim_end() == "---")
+        .map(|p| p + 1)
+}
+
+fn indent(text: &str) -> usize {
+    text.len() - text.trim_start_matches(' ').len()
+}
+
+fn opening_fence(text: &str) -> Option<(char, usize)> {
+    if indent(text) > 3 {
+        return None;
+    }
+    let t = text.trim_start();
+    let c = t.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = t.chars().take_while(|&x| x == c).count();
+    if len < 3 {
+        return None;
+    }
+    // Backtick fence info strings cannot contain backticks.
+    if c == '`' && t[len..].contains('`') {
+        return None;
+    }
+    Some((c, len))
+}
+
+fn is_closing_fence(text: &str, c: char, len: usize) -> bool {
+    if indent(text) > 3 {
+        return false;
+    }
+    let t = text.trim();
+    t.chars().count() >= len && t.chars().all(|x| x == c)
+}
+
+/// Returns the level and the byte range of the heading text.
+fn atx_heading(text: &str) -> Option<(u8, usize, usize)> {
+    if indent(text) > 3 {
+        return None;
+    }
+    let lead = indent(text);
+    let t = &text[lead..];
+    let level = t.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &t[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    // Strip the optional closing sequence of #s.
+    let mut body = rest.trim();
+    let trimmed = body.trim_end_matches('#');
+    if trimmed.is_empty() || trimmed.ends_with([' ', '\t']) {
+        body = trimmed.trim_end();
+    }
+    let start = if body.is_empty() {
+        lead + level
+    } else {
+        lead + level + (rest.len() - rest.trim_start().len())
+    };
+    Some((level as u8, start, start + body.len()))
+}
+
+fn setext_level(text: &str) -> Option<u8> {
+    if indent(text) > 3 {
+        return None;
+    }
+    let t = text.trim();
+    if !t.is_empty() && t.chars().all(|c| c == '=') {
+        Some(1)
+    } else if !t.is_empty() && t.chars().all(|c| c == '-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Recognizes a list item marker, returning whether it is ordered and
+/// the byte offset where the item content starts.
+pub fn list_marker(text: &str) -> Option<(bool, usize)> {
+    let lead = indent(text);
+    let t = &text[lead..];
+    let mut chars = t.char_indices();
+    match chars.next()? {
+        (_, '-' | '*' | '+') => {
+            let rest = &t[1..];
+            (rest.is_empty() || rest.starts_with([' ', '\t'])).then_some((false, lead + 2))
+        }
+        (_, c) if c.is_ascii_digit() => {
+            let digits = t.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits > 9 {
+                return None;
+            }
+            let rest = &t[digits..];
+            let rest = rest.strip_prefix('.').or_else(|| rest.strip_prefix(')'))?;
+            (rest.is_empty() || rest.starts_with([' ', '\t'])).then_some((true, lead + digits + 2))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,181 @@
+//! Checks for the project's `.mcp.json`.
+//!
+//! The file sits next to the linted directory, in the project root. A
+//! server entry that is missing its command or URL fails only when
+//! Claude Code tries to start it, and paths into one person's home
+//! directory break the file for everyone else who checks it out.
+
+use std::path::Path;
+
+use crate::json::{self, Value};
+use crate::rules;
+use crate::settings::span;
+use crate::Diagnostic;
+
+pub const FILE_NAME: &str = ".mcp.json";
+
+/// Transport types and the field each one needs.
+const TRANSPORTS: &[(&str, &str)] = &[("stdio", "command"), ("sse", "url"), ("http", "url")];
+
+/// Prefixes of paths that only exist on the author's machine.
+const MACHINE_PATHS: &[&str] = &["/Users/", "/home/", "C:\\Users\\", "C:/Users/"];
+
+pub fn check_mcp(root: &Path, diags: &mut Vec<Diagnostic>) {
+    let path = root.parent().unwrap_or(Path::new("")).join(FILE_NAME);
+    if !path.is_file() {
+        return;
+    }
+    let Some(content) = rules::read(&path, false, diags) else {
+        return;
+    };
+    let doc = match json::parse(&content) {
+        Ok(doc) => doc,
+        Err(e) => {
+            diags.push(
+                Diagnostic::of(&path, "CL080", format!("invalid JSON: {}", e.message))
+                    .at(span(e.pos, 1)),
+            );
+            return;
+        }
+    };
+    let Some(servers) = doc.get("mcpServers") else {
+        diags.push(
+            Diagnostic::of(&path, "CL081", "missing 'mcpServers' object".to_string())
+                .at(span(doc.pos, 1)),
+        );
+        return;
+    };
+    let Some(servers) = servers.as_object() else {
+        diags.push(type_error(&path, "mcpServers", "an object", servers));
+        return;
+    };
+
+    for (i, server) in servers.iter().enumerate() {
+        if let Some(first) = servers[..i].iter().find(|s| s.key == server.key) {
+            diags.push(
+                Diagnostic::of(
+                    &path,
+                    "CL082",
+                    format!(
+                        "duplicate server '{}'; first defined on line {}",
+                        server.key, first.key_pos.line
+                    ),
+                )
+                .at(span(server.key_pos, server.key.chars().count() + 2)),
+            );
+            continue;
+        }
+        check_server(&path, &format!("mcpServers.{}", server.key), &server.value, diags);
+    }
+}
+
+fn check_server(path: &Path, name: &str, server: &Value, diags: &mut Vec<Diagnostic>) {
+    if server.as_object().is_none() {
+        diags.push(type_error(path, name, "an object", server));
+        return;
+    }
+    let transport = match server.get("type") {
+        None => "stdio",
+        Some(t) => match t.as_str() {
+            Some(s) if TRANSPORTS.iter().any(|&(k, _)| k == s) => s,
+            Some(s) => {
+                diags.push(
+                    Diagnostic::of(
+                        path,
+                        "CL081",
+                        format!("{}.type '{}' is not one of stdio, sse, http", name, s),
+                    )
+                    .at(span(t.pos, s.chars().count() + 2)),
+                );
+                return;
+            }
+            None => {
+                diags.push(type_error(path, &format!("{}.type", name), "a string", t));
+                return;
+            }
+        },
+    };
+    let required = TRANSPORTS.iter().find(|&&(k, _)| k == transport).map_or("command", |&(_, f)| f);
+    match server.get(required) {
+        Some(v) if v.as_str().is_some_and(|s| !s.trim().is_empty()) => {}
+        Some(v) if v.as_str().is_none() => diags.push(type_error(
+            path,
+            &format!("{}.{}", name, required),
+            "a string",
+            v,
+        )),
+        _ => diags.push(
+            Diagnostic::of(
+                path,
+                "CL081",
+                format!("{} ({}) needs a non-empty '{}'", name, transport, required),
+            )
+            .at(span(server.pos, 1)),
+        ),
+    }
+
+    if let Some(command) = server.get("command") {
+        check_machine_path(path, &format!("{}.command", name), command, diags);
+    }
+    if let Some(args) = server.get("args") {
+        let Some(items) = args.as_array() else {
+            diags.push(type_error(path, &format!("{}.args", name), "an array of strings", args));
+            return;
+        };
+        for item in items {
+            if item.as_str().is_none() {
+                diags.push(type_error(
+                    path,
+                    &format!("{}.args", name),
+                    "an array of strings",
+                    item,
+                ));
+            }
+            check_machine_path(path, &format!("{}.args", name), item, diags);
+        }
+    }
+    if let Some(env) = server.get("env") {
+        let Some(vars) = env.as_object() else {
+            diags.push(type_error(path, &format!("{}.env", name), "an object", env));
+            return;
+        };
+        for var in vars {
+            let var_name = format!("{}.env.{}", name, var.key);
+            if var.value.as_str().is_none() {
+                diags.push(type_error(path, &var_name, "a string", &var.value));
+            }
+            check_machine_path(path, &var_name, &var.value, diags);
+        }
+    }
+}
+
+/// Warns on a string that is, or contains as an option value, a path into
+/// a home directory.
+fn check_machine_path(path: &Path, name: &str, value: &Value, diags: &mut Vec<Diagnostic>) {
+    let Some(text) = value.as_str() else {
+        return;
+    };
+    let machine = std::iter::once(text)
+        .chain(text.split_once('=').map(|(_, v)| v))
+        .any(|s| MACHINE_PATHS.iter().any(|p| s.starts_with(p)));
+    if machine {
+        diags.push(
+            Diagnostic::of(
+                path,
+                "CL083",
+                format!("{} '{}' is a machine-specific path; use a relative path or an environment \
+                         variable", name, text),
+            )
+            .at(span(value.pos, text.chars().count() + 2)),
+        );
+    }
+}
+
+fn type_error(path: &Path, name: &str, expected: &str, found: &Value) -> Diagnostic {
+    Diagnostic::of(
+        path,
+        "CL081",
+        format!("{} should be {}, found {}", name, expected, found.type_name()),
+    )
+    .at(span(found.pos, 1))
+}
@@ -0,0 +1,239 @@
+//! Agent, skill, and command names across files.
+//!
+//! Claude Code looks agents, skills, and commands up by name, so two that
+//! share one shadow each other without a word. Agents and skills are
+//! named by their frontmatter, commands by their file names. Project
+//! definitions take precedence over user-level ones.
+//!
+//! Agents are also picked by their descriptions, so two that describe
+//! the same job make delegation a coin toss, and the more there are to
+//! pick from, the worse the pick.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::frontmatter;
+use crate::ignore::Ignore;
+use crate::jobs;
+use crate::rules::FileKind;
+use crate::scaffold;
+use crate::{Diagnostic, Span};
+
+/// Default limits on how many of each a directory may have.
+const MAX_AGENTS: usize = 30;
+const MAX_SKILLS: usize = 30;
+const MAX_COMMANDS: usize = 50;
+
+/// How many agents, skills, and commands a directory has.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub agents: usize,
+    pub skills: usize,
+    pub commands: usize,
+}
+
+struct Named {
+    kind: FileKind,
+    name: String,
+    path: PathBuf,
+    span: Option<Span>,
+}
+
+/// Reports agents, skills, and commands that reuse an earlier file's
+/// name in the same directory.
+pub fn check_names(root: &Path, exclude: &Ignore, diags: &mut Vec<Diagnostic>) {
+    let named = collect(root, exclude);
+    for (i, n) in named.iter().enumerate() {
+        if let Some(first) = named[..i]
+            .iter()
+            .find(|m| m.kind == n.kind && m.name == n.name)
+        {
+            diags.push(
+                Diagnostic::of(
+                    &n.path,
+                    rule(n.kind),
+                    format!(
+                        "{} name '{}' is already used by {}",
+                        noun(n.kind),
+                        n.name,
+                        first.path.display()
+                    ),
+                )
+                .at(n.span),
+            );
+        }
+    }
+}
+
+/// Reports project agents, skills, and commands that shadow user-level
+/// ones of the same name.
+pub fn check_shadowed(project: &Path, user: &Path, exclude: &Ignore, diags: &mut Vec<Diagnostic>) {
+    let theirs = collect(user, exclude);
+    for n in collect(project, exclude) {
+        if let Some(other) = theirs.iter().find(|m| m.kind == n.kind && m.name == n.name) {
+            diags.push(
+                Diagnostic::of(
+                    &n.path,
+                    rule(n.kind),
+                    format!(
+                        "{} name '{}' shadows the user-level one in {}",
+                        noun(n.kind),
+                        n.name,
+                        other.path.display()
+                    ),
+                )
+                .at(n.span),
+            );
+        }
+    }
+}
+
+/// Percent of shared words above which two agent descriptions overlap.
+const MAX_OVERLAP: usize = 60;
+
+/// Words that carry no meaning of their own in a description.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "its", "of",
+    "on", "or", "that", "the", "this", "to", "when", "with",
+];
+
+/// Reports agents whose descriptions share more than max_overlap
+/// percent of their words with an earlier agent's, by Jaccard
+/// similarity.
+pub fn check_descriptions(root: &Path, config: &Config, diags: &mut Vec<Diagnostic>) {
+    let mut agents: Vec<(PathBuf, String, BTreeSet<String>)> = Vec::new();
+    for (path, kind) in jobs::documents(root, &config.exclude) {
+        if kind != FileKind::Agent {
+            continue;
+        }
+        let Some(content) = fs::read_to_string(&path).ok() else {
+            continue;
+        };
+        let Some(Ok(fm)) = frontmatter::parse(&content) else {
+            continue;
+        };
+        let (Some(field), Some(name)) = (fm.get("description"), fm.get("name")) else {
+            continue;
+        };
+        let (frontmatter::Value::String(text), frontmatter::Value::String(name)) =
+            (&field.value, &name.value)
+        else {
+            continue;
+        };
+        // Fresh scaffolds all share the same stand-in for a description.
+        if *text == scaffold::agent_description(name.trim()) {
+            continue;
+        }
+        let words: BTreeSet<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|w| !w.is_empty() && !STOP_WORDS.contains(&w.as_str()))
+            .collect();
+        if words.is_empty() {
+            continue;
+        }
+        let span = Span {
+            line: field.line,
+            column: 1,
+            length: field.key.chars().count(),
+        };
+        let max = config
+            .option("CL026", "max_overlap", &path)
+            .unwrap_or(MAX_OVERLAP);
+        let similar = agents.iter().map(|(p, n, w)| {
+            (
+                p,
+                n,
+                100 * w.intersection(&words).count() / w.union(&words).count(),
+            )
+        });
+        if let Some((other, other_name, percent)) = similar
+            .filter(|&(_, _, percent)| percent > max)
+            .max_by_key(|&(_, _, percent)| percent)
+        {
+            let msg = format!(
+                "description overlaps agent '{}' ({}) by {}%, more than {}%",
+                other_name,
+                other.display(),
+                percent,
+                max
+            );
+            diags.push(Diagnostic::of(&path, "CL026", msg).at(Some(span)));
+        }
+        agents.push((path, name.trim().to_string(), words));
+    }
+}
+
+/// Counts a directory's agents, skills, and commands, leaving out the
+/// excluded ones.
+pub fn count(root: &Path, exclude: &Ignore) -> Counts {
+    let mut counts = Counts::default();
+    for (_, kind) in jobs::documents(root, exclude) {
+        match kind {
+            FileKind::Agent => counts.agents += 1,
+            FileKind::Skill => counts.skills += 1,
+            FileKind::Command => counts.commands += 1,
+            FileKind::ClaudeMd | FileKind::Reference => {}
+        }
+    }
+    counts
+}
+
+/// Reports a directory with more agents, skills, or commands than the
+/// max_agents, max_skills, and max_commands options allow.
+pub fn check_counts(root: &Path, config: &Config, diags: &mut Vec<Diagnostic>) {
+    let counts = count(root, &config.exclude);
+    for (n, noun, option, default) in [
+        (counts.agents, "agent", "max_agents", MAX_AGENTS),
+        (counts.skills, "skill", "max_skills", MAX_SKILLS),
+        (counts.commands, "command", "max_commands", MAX_COMMANDS),
+    ] {
+        let max = config.option("CL104", option, root).unwrap_or(default);
+        if n > max {
+            let s = if n == 1 { "" } else { "s" };
+            let msg = format!("directory has {} {}{}; expected at most {}", n, noun, s, max);
+            diags.push(Diagnostic::of(root, "CL104", msg));
+        }
+    }
+}
+
+fn collect(root: &Path, exclude: &Ignore) -> Vec<Named> {
+    let mut out = Vec::new();
+    for (path, kind) in jobs::documents(root, exclude) {
+        let named = match kind {
+            FileKind::Agent | FileKind::Skill => fs::read_to_string(&path).ok().and_then(|content| {
+                let fm = frontmatter::parse(&content)?.ok()?;
+                let field = fm.get("name")?;
+                let frontmatter::Value::String(name) = &field.value else {
+                    return None;
+                };
+                let span = Span { line: field.line, column: 1, length: field.key.chars().count() };
+                Some((name.trim().to_string(), Some(span)))
+            }),
+            FileKind::Command => path.file_stem().map(|s| (s.to_string_lossy().into_owned(), None)),
+            FileKind::ClaudeMd | FileKind::Reference => None,
+        };
+        if let Some((name, span)) = named.filter(|(name, _)| !name.is_empty()) {
+            out.push(Named { kind, name, path, span });
+        }
+    }
+    out
+}
+
+fn rule(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::Skill => "CL036",
+        FileKind::Command => "CL053",
+        _ => "CL022",
+    }
+}
+
+fn noun(kind: FileKind) -> &'static str {
+    match kind {
+        FileKind::Skill => "skill",
+        FileKind::Command => "command",
+        _ => "agent",
+    }
+}
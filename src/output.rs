@@ -0,0 +1,537 @@
+//! Renderings of diagnostics for other tools, and as an HTML report.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::owners::Owners;
+use crate::timings::Timings;
+use crate::{all_rules, cache, count, explain, json, Diagnostic, Severity};
+
+/// Renders diagnostics as a single JSON document, with the slowest
+/// rules and files when timings are given, and each finding's owners
+/// when CODEOWNERS is.
+///
+/// The schema is versioned; fields are only ever added, never renamed.
+pub fn json(diags: &[Diagnostic], timings: Option<&Timings>, owners: Option<&Owners>) -> String {
+    let mut out = String::from("{\"version\":1,\"diagnostics\":[");
+    for (i, e) in diags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&ndjson(e, owners));
+    }
+    out.push(']');
+    if let Some(t) = timings {
+        let ms = |d: &Duration| format!("{:.3}", d.as_secs_f64() * 1000.0);
+        let rules: Vec<String> = t
+            .rules
+            .iter()
+            .map(|(id, d)| format!("{{\"rule\":{},\"ms\":{}}}", json::string(id), ms(d)))
+            .collect();
+        let files: Vec<String> = t
+            .files
+            .iter()
+            .map(|(p, d)| {
+                format!(
+                    "{{\"path\":{},\"ms\":{}}}",
+                    json::string(&p.to_string_lossy()),
+                    ms(d)
+                )
+            })
+            .collect();
+        out.push_str(&format!(
+            ",\"timings\":{{\"rules\":[{}],\"files\":[{}]}}",
+            rules.join(","),
+            files.join(",")
+        ));
+    }
+    out.push('}');
+    out
+}
+
+/// Renders a diagnostic as one line of JSON, with the fields of a
+/// diagnostic in [`json`].
+pub fn ndjson(e: &Diagnostic, owners: Option<&Owners>) -> String {
+    let owners = owners.map_or_else(String::new, |o| {
+        let names: Vec<String> = o.of(&e.path).iter().map(|n| json::string(n)).collect();
+        format!(",\"owners\":[{}]", names.join(","))
+    });
+    format!(
+        "{{\"path\":{},\"rule\":{},\"name\":{},\"severity\":{},\"message\":{},\"line\":{},\
+         \"column\":{},\"fixable\":{},\"scope\":{}{}}}",
+        json::string(&e.path.to_string_lossy()),
+        json::string(e.rule),
+        json::string(e.rule().name),
+        json::string(e.severity.as_str()),
+        json::string(&e.message),
+        json_number(e.span.map(|s| s.line)),
+        json_number(e.span.map(|s| s.column)),
+        e.fix.is_some(),
+        json::string(e.scope.as_str()),
+        owners,
+    )
+}
+
+/// Renders diagnostics as a SARIF 2.1.0 log.
+pub fn sarif(diags: &[Diagnostic]) -> String {
+    let all = all_rules();
+    let mut rules = String::new();
+    for (i, r) in all.iter().enumerate() {
+        if i > 0 {
+            rules.push(',');
+        }
+        rules.push_str(&format!(
+            "{{\"id\":{},\"name\":{},\"shortDescription\":{{\"text\":{}}},\
+             \"defaultConfiguration\":{{\"level\":{}}}}}",
+            json::string(r.id),
+            json::string(r.name),
+            json::string(r.description),
+            json::string(r.severity.sarif_level()),
+        ));
+    }
+
+    let mut results = String::new();
+    for (i, e) in diags.iter().enumerate() {
+        if i > 0 {
+            results.push(',');
+        }
+        let index = all.iter().position(|r| r.id == e.rule).unwrap_or(0);
+        // Code scanning wants a region even for file-level findings.
+        let mut region = format!("\"startLine\":{}", e.span.map_or(1, |s| s.line));
+        if let Some(s) = e.span {
+            region.push_str(&format!(
+                ",\"startColumn\":{},\"endColumn\":{}",
+                s.column,
+                s.column + s.length
+            ));
+        }
+        results.push_str(&format!(
+            "{{\"ruleId\":{},\"ruleIndex\":{},\"level\":{},\"message\":{{\"text\":{}}},\
+             \"locations\":[{{\"physicalLocation\":{{\"artifactLocation\":{{\"uri\":{}}},\
+               \"region\":{{{}}}}}}}]}}",
+            json::string(e.rule),
+            index,
+            json::string(e.severity.sarif_level()),
+            json::string(&e.message),
+            json::string(&sarif_uri(&e.path)),
+            region,
+        ));
+    }
+
+    format!(
+        "{{\"$schema\":\"https://json.schemastore.org/sarif-2.1.0.json\",\"version\":\"2.1.0\",\
+         \"runs\":[{{\"tool\":{{\"driver\":{{\"name\":\"claude-lint\",\"version\":{},\
+           \"rules\":[{}]}}}},\
+         \"results\":[{}]}}]}}",
+        json::string(env!("CARGO_PKG_VERSION")),
+        rules,
+        results,
+    )
+}
+
+/// Renders diagnostics as a Code Climate issue array, the format of
+/// GitLab's code quality report.
+pub fn codeclimate(diags: &[Diagnostic]) -> String {
+    let mut seen: HashMap<(&str, &Path, &str), usize> = HashMap::new();
+    let mut out = String::from("[");
+    for (i, e) in diags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let severity = match e.severity {
+            Severity::Error => "major",
+            Severity::Warning => "minor",
+            Severity::Info => "info",
+        };
+        // Lines shift as files are edited, so they stay out of the
+        // fingerprint; repeats of the same finding are numbered instead.
+        let n = seen.entry((e.rule, &e.path, &e.message)).or_default();
+        *n += 1;
+        let path = e.path.to_string_lossy();
+        let path = path.trim_start_matches("./");
+        let count = n.to_string();
+        let fingerprint = cache::hash(&[
+            e.rule.as_bytes(),
+            path.as_bytes(),
+            e.message.as_bytes(),
+            count.as_bytes(),
+        ]);
+        out.push_str(&format!(
+            "{{\"type\":\"issue\",\"check_name\":{},\"description\":{},\"fingerprint\":\"{:016x}\",\
+             \"severity\":{},\
+             \"location\":{{\"path\":{},\"lines\":{{\"begin\":{}}}}}}}",
+            json::string(e.rule),
+            json::string(&e.message),
+            fingerprint,
+            json::string(severity),
+            json::string(path),
+            e.span.map_or(1, |s| s.line),
+        ));
+    }
+    out.push(']');
+    out
+}
+
+/// Renders a JUnit XML report: each linted file is a test case, and each
+/// error in it a failure. Warnings and info go to the case's output, so
+/// the report passes exactly when the run does.
+pub fn junit(files: &[PathBuf], diags: &[Diagnostic]) -> String {
+    // Findings can sit on paths outside the file list, like a skill
+    // directory without SKILL.md.
+    let mut cases: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+    for d in diags {
+        if !cases.contains(&d.path.as_path()) {
+            cases.push(&d.path);
+        }
+    }
+    let failures = diags.iter().filter(|d| d.severity == Severity::Error).count();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"claude-lint\" tests=\"{}\" failures=\"{}\">\n\
+         <testsuite name=\"claude-lint\" tests=\"{}\" failures=\"{}\">\n",
+        cases.len(),
+        failures,
+        cases.len(),
+        failures,
+    ));
+    for path in cases {
+        let name = xml(&path.to_string_lossy());
+        let found: Vec<&Diagnostic> = diags.iter().filter(|d| d.path == path).collect();
+        if found.is_empty() {
+            out.push_str(&format!("<testcase classname=\"claude-lint\" name=\"{}\"/>\n", name));
+            continue;
+        }
+        out.push_str(&format!("<testcase classname=\"claude-lint\" name=\"{}\">\n", name));
+        let mut notes = String::new();
+        for d in found {
+            let at = d.span.map_or(String::new(), |s| format!(":{}", s.line));
+            let line = format!(
+                "{}[{}]: {}{}: {}",
+                d.severity.as_str(),
+                d.rule,
+                path.display(),
+                at,
+                d.message
+            );
+            if d.severity == Severity::Error {
+                out.push_str(&format!(
+                    "<failure type=\"{}\" message=\"{}\">{}</failure>\n",
+                    d.rule,
+                    xml(&d.message),
+                    xml(&line),
+                ));
+            } else {
+                notes.push_str(&line);
+                notes.push('\n');
+            }
+        }
+        if !notes.is_empty() {
+            out.push_str(&format!("<system-out>{}</system-out>\n", xml(&notes)));
+        }
+        out.push_str("</testcase>\n");
+    }
+    out.push_str("</testsuite>\n</testsuites>");
+    out
+}
+
+/// Renders a Checkstyle XML report, which Jenkins and other CI servers
+/// read: an element per file with findings, and one per finding in it,
+/// with the rule ID as its source.
+pub fn checkstyle(diags: &[Diagnostic]) -> String {
+    let mut out =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n");
+    for (i, d) in diags.iter().enumerate() {
+        if i == 0 || d.path != diags[i - 1].path {
+            if i > 0 {
+                out.push_str("</file>\n");
+            }
+            out.push_str(&format!("<file name=\"{}\">\n", xml(&d.path.to_string_lossy())));
+        }
+        let at = d.span.map_or(String::new(), |s| {
+            format!(" line=\"{}\" column=\"{}\"", s.line, s.column)
+        });
+        out.push_str(&format!(
+            "<error{} severity=\"{}\" message=\"{}\" source=\"{}\"/>\n",
+            at,
+            d.severity.as_str(),
+            xml(&d.message),
+            d.rule,
+        ));
+    }
+    if !diags.is_empty() {
+        out.push_str("</file>\n");
+    }
+    out.push_str("</checkstyle>");
+    out
+}
+
+/// Lines of source shown before and after a finding's line in the HTML
+/// report.
+const CONTEXT_LINES: usize = 1;
+
+const HTML_STYLE: &str = "\
+body{font:15px/1.5 system-ui,sans-serif;margin:2em auto;max-width:60em;padding:0 1em;color:#222}\
+table{border-collapse:collapse}\
+td,th{padding:.2em .8em;border-bottom:1px solid #ddd;text-align:left}\
+td.n{text-align:right}.error{color:#b00}.warning{color:#a60}.info{color:#067}\
+pre{background:#f6f6f6;padding:.5em;overflow-x:auto}.gutter{color:#888;user-select:none}\
+mark.error{background:#fdd}mark.warning{background:#fec}mark.info{background:#def}";
+
+/// Renders a standalone HTML report: totals, a table of findings per
+/// file, each file's findings with the source around them, and the
+/// rules that fired. Sources are read from disk, or from buffer for text
+/// linted from stdin.
+pub fn html(files: &[PathBuf], diags: &[Diagnostic], buffer: Option<&str>) -> String {
+    let mut paths: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+    for d in diags {
+        if !paths.contains(&d.path.as_path()) {
+            paths.push(&d.path);
+        }
+    }
+    let counts = |found: &[&Diagnostic]| {
+        [Severity::Error, Severity::Warning, Severity::Info]
+            .map(|s| found.iter().filter(|d| d.severity == s).count())
+    };
+
+    let mut out =
+        String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!(
+        "<title>claude-lint report</title>\n<style>{}</style>\n</head>\n<body>\n",
+        HTML_STYLE
+    ));
+    out.push_str(&format!(
+        "<h1>claude-lint report</h1>\n<p>claude-lint {}: {} file(s), ",
+        env!("CARGO_PKG_VERSION"),
+        paths.len()
+    ));
+    out.push_str(&format!(
+        "<span class=\"error\">{} error(s)</span>, <span class=\"warning\">{} warning(s)</span>, \
+         <span class=\"info\">{} info</span>.</p>\n",
+        count(diags, Severity::Error),
+        count(diags, Severity::Warning),
+        count(diags, Severity::Info),
+    ));
+
+    out.push_str("<h2>Summary</h2>\n<table>\n<tr><th>File</th><th>Errors</th><th>Warnings</th>\
+                  <th>Info</th></tr>\n");
+    let mut sections = String::new();
+    for (i, path) in paths.iter().enumerate() {
+        let found: Vec<&Diagnostic> = diags.iter().filter(|d| d.path == *path).collect();
+        let name = xml(&path.to_string_lossy());
+        let [errors, warnings, info] = counts(&found);
+        let cell = if found.is_empty() {
+            name.clone()
+        } else {
+            format!("<a href=\"#file-{}\">{}</a>", i, name)
+        };
+        out.push_str(&format!(
+            "<tr><td>{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td>\
+             </tr>\n",
+            cell, errors, warnings, info
+        ));
+        if found.is_empty() {
+            continue;
+        }
+        sections.push_str(&format!("<section id=\"file-{}\">\n<h3>{}</h3>\n", i, name));
+        let source = buffer.map(str::to_string).or_else(|| fs::read_to_string(path).ok());
+        for d in found {
+            let at = d.span.map_or(String::new(), |s| format!(":{}:{}", s.line, s.column));
+            sections.push_str(&format!(
+                "<p><span class=\"{}\">{}</span> <a href=\"#{}\">{}</a> {}{}: {}</p>\n",
+                d.severity.as_str(),
+                d.severity.as_str(),
+                d.rule,
+                d.rule,
+                name,
+                at,
+                xml(&d.message),
+            ));
+            if let Some(content) = &source {
+                sections.push_str(&excerpt(d, content));
+            }
+        }
+        sections.push_str("</section>\n");
+    }
+    out.push_str("</table>\n");
+    if !sections.is_empty() {
+        out.push_str("<h2>Findings</h2>\n");
+        out.push_str(&sections);
+    }
+
+    let mut fired: Vec<&str> = diags.iter().map(|d| d.rule).collect();
+    fired.sort_unstable();
+    fired.dedup();
+    if !fired.is_empty() {
+        out.push_str("<h2>Rules</h2>\n");
+    }
+    for id in fired {
+        let rule = crate::rule(id);
+        out.push_str(&format!(
+            "<section id=\"{}\">\n<h3>{} {}</h3>\n<p>{} ({} by default)</p>\n",
+            rule.id,
+            rule.id,
+            xml(rule.name),
+            xml(rule.description),
+            rule.severity.as_str(),
+        ));
+        if let Some(e) = explain::explanation(id) {
+            out.push_str(&format!("<p>{}</p>\n", xml(&e.rationale.replace('\n', " "))));
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Renders the lines around a finding with its span highlighted, or
+/// nothing when it has no span or the line is not in the source.
+fn excerpt(d: &Diagnostic, source: &str) -> String {
+    let Some(span) = d.span else {
+        return String::new();
+    };
+    // Columns do not count a byte order mark.
+    let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+    let lines: Vec<&str> = source.lines().collect();
+    if span.line == 0 || span.line > lines.len() {
+        return String::new();
+    }
+    let first = span.line.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (span.line + CONTEXT_LINES).min(lines.len());
+    let width = last.to_string().len();
+    let mut out = String::from("<pre>");
+    for n in first..=last {
+        let line = lines[n - 1];
+        let text = if n == span.line {
+            let chars: Vec<char> = line.chars().collect();
+            let start = (span.column - 1).min(chars.len());
+            let end = (start + span.length).min(chars.len());
+            let part = |from: usize, to: usize| xml(&chars[from..to].iter().collect::<String>());
+            format!(
+                "{}<mark class=\"{}\">{}</mark>{}",
+                part(0, start),
+                d.severity.as_str(),
+                part(start, end),
+                part(end, chars.len())
+            )
+        } else {
+            xml(line)
+        };
+        out.push_str(&format!(
+            "<span class=\"gutter\">{:>width$} | </span>{}\n",
+            n,
+            text.trim_end(),
+            width = width
+        ));
+    }
+    out.push_str("</pre>\n");
+    out
+}
+
+/// Renders a TAP version 13 stream: each linted file is a test point,
+/// which fails when the file has errors. A file with findings gets a
+/// YAML block listing them, warnings included.
+pub fn tap(files: &[PathBuf], diags: &[Diagnostic]) -> String {
+    let mut points: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+    for d in diags {
+        if !points.contains(&d.path.as_path()) {
+            points.push(&d.path);
+        }
+    }
+    let mut out = format!("TAP version 13\n1..{}\n", points.len());
+    for (i, path) in points.iter().enumerate() {
+        let found: Vec<&Diagnostic> = diags.iter().filter(|d| d.path == *path).collect();
+        let failed = found.iter().any(|d| d.severity == Severity::Error);
+        out.push_str(&format!(
+            "{} {} - {}\n",
+            if failed { "not ok" } else { "ok" },
+            i + 1,
+            path.display()
+        ));
+        if found.is_empty() {
+            continue;
+        }
+        out.push_str("  ---\n  diagnostics:\n");
+        for d in found {
+            out.push_str(&format!(
+                "    - rule: {}\n      severity: {}\n",
+                d.rule,
+                d.severity.as_str()
+            ));
+            out.push_str(&format!("      message: {}\n", yaml_string(&d.message)));
+            if let Some(s) = d.span {
+                out.push_str(&format!("      line: {}\n      column: {}\n", s.line, s.column));
+            }
+        }
+        out.push_str("  ...\n");
+    }
+    out
+}
+
+/// Quotes a YAML scalar, in single quotes, which only escape themselves.
+fn yaml_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Renders diagnostics as GitHub Actions workflow commands, one per
+/// line, so findings annotate the pull request diff.
+pub fn github(diags: &[Diagnostic]) -> String {
+    let mut out = String::new();
+    for e in diags {
+        let command = match e.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "notice",
+        };
+        let mut props = format!("file={}", github_property(&e.path.to_string_lossy()));
+        if let Some(s) = e.span {
+            props.push_str(&format!(",line={},col={}", s.line, s.column));
+        }
+        props.push_str(&format!(
+            ",title={}",
+            github_property(&format!("{} {}", e.rule, e.rule().name))
+        ));
+        out.push_str(&format!(
+            "::{} {}::{}\n",
+            command,
+            props,
+            github_data(&e.message)
+        ));
+    }
+    out
+}
+
+/// Escapes a workflow command message.
+fn github_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a workflow command property, which also ends at ':' or ','.
+fn github_property(s: &str) -> String {
+    github_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Converts a path to a SARIF artifact URI: relative paths stay relative
+/// so code scanning can resolve them against the checkout.
+fn sarif_uri(path: &Path) -> String {
+    let s = path.to_string_lossy().replace('\\', "/");
+    let s = s.trim_start_matches("./");
+    if path.is_absolute() {
+        format!("file://{}", s)
+    } else {
+        s.to_string()
+    }
+}
+
+fn json_number(n: Option<usize>) -> String {
+    match n {
+        Some(n) => n.to_string(),
+        None => "null".to_string(),
+    }
+}
@@ -0,0 +1,73 @@
+//! Owners of files, from CODEOWNERS, for `--group-by owner` and JSON
+//! output.
+//!
+//! The file is looked for where GitHub looks, in `.github/`, at the top,
+//! and in `docs/`, in the nearest directory above the linted paths that
+//! has one. Each line is a gitignore-style pattern, relative to that
+//! directory, followed by its owners; the last line that matches a file
+//! wins, and one with no owners leaves the file unowned.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::glob::{self, Glob};
+
+/// Where CODEOWNERS may be, relative to the top of a repository, in the
+/// order GitHub prefers them.
+const LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug)]
+pub struct Owners {
+    /// The directory CODEOWNERS patterns are relative to.
+    root: PathBuf,
+    rules: Vec<(Glob, Vec<String>)>,
+}
+
+impl Owners {
+    /// Finds the CODEOWNERS for a path in it or the nearest directory
+    /// above it that has one.
+    pub fn discover(path: &Path) -> Result<Option<Owners>, String> {
+        let start = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        for dir in start.ancestors() {
+            if let Some(file) = LOCATIONS.iter().map(|l| dir.join(l)).find(|f| f.is_file()) {
+                let src = fs::read_to_string(&file)
+                    .map_err(|e| format!("{}: cannot read: {}", file.display(), e))?;
+                return Ok(Some(Owners::parse(dir, &src)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn parse(root: &Path, src: &str) -> Owners {
+        let mut rules = Vec::new();
+        for line in src.lines() {
+            let line = line.split(" #").next().unwrap_or_default().trim();
+            let mut words = line.split_whitespace();
+            let Some(pattern) = words.next().filter(|p| !p.starts_with('#')) else {
+                continue;
+            };
+            let pattern = pattern.strip_suffix('/').unwrap_or(pattern);
+            rules.push((Glob::new(pattern), words.map(str::to_string).collect()));
+        }
+        Owners { root: root.to_path_buf(), rules }
+    }
+
+    /// The owners of a file, none when no line claims it. The file need
+    /// not exist, as for a missing CLAUDE.md.
+    pub fn of(&self, path: &Path) -> &[String] {
+        let path = match (path.parent(), path.file_name()) {
+            (Some(dir), Some(name)) if !path.exists() => {
+                fs::canonicalize(dir).map_or(path.to_path_buf(), |d| d.join(name))
+            }
+            _ => path.to_path_buf(),
+        };
+        let Some(rel) = glob::relative(&self.root, &path).map(|rel| glob::slashed(&rel)) else {
+            return &[];
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|(glob, _)| glob.matches_within(&rel))
+            .map_or(&[], |(_, owners)| owners)
+    }
+}
@@ -0,0 +1,115 @@
+//! Unfinished content: TODO markers, filler text, and template
+//! placeholders left in committed files.
+//!
+//! The model reads a placeholder as an instruction like any other, so
+//! an agent that says "TODO: describe the review style" ships without
+//! one. Markers are matched in prose and frontmatter; code spans and
+//! code blocks are examples and are skipped. Each kind of marker can be
+//! given its own severity under `[placeholders]`.
+
+use std::collections::HashMap;
+
+use crate::markdown::Kind;
+use crate::prose;
+use crate::regex::Regex;
+use crate::rules::{Check, Document, FileKind};
+use crate::{Diagnostic, Severity, Span};
+
+/// Kinds of marker, by the name `[placeholders]` knows them by, with
+/// what they match.
+pub(crate) const MARKERS: &[(&str, &str)] = &[
+    ("todo", r"\bTODO\b"),
+    ("fixme", r"\bFIXME\b"),
+    ("tbd", r"\bTBD\b"),
+    ("xxx", r"\bXXX\b"),
+    ("lorem", r"(?i)\blorem ipsum\b"),
+    (
+        "template",
+        concat!(
+            r"(?i)<(?:your|insert|enter|add)[-_ ][^<>]{1,40}>",
+            r"|<[a-z][\w -]{0,40}[-_ ]here>",
+            r"|\{\{\s*[\w.-]+\s*\}\}",
+        ),
+    ),
+];
+
+pub(crate) struct Placeholders<'a>(pub &'a HashMap<String, Option<Severity>>);
+
+impl Check for Placeholders<'_> {
+    fn id(&self) -> &'static str {
+        "CL140"
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut diags = Vec::new();
+        for &(marker, pattern) in MARKERS {
+            let severity = self.0.get(marker).copied();
+            if severity == Some(None) {
+                continue;
+            }
+            let re = Regex::new(pattern).expect("built-in patterns are valid");
+            for line in doc
+                .markdown
+                .lines
+                .iter()
+                .filter(|l| l.kind == Kind::Frontmatter || prose::is_prose(l.kind))
+            {
+                let text = if line.kind == Kind::Frontmatter {
+                    line.text.to_string()
+                } else {
+                    prose::mask(line.text)
+                };
+                for (start, end) in re
+                    .find_all(&text)
+                    .expect("built-in patterns stay within budget")
+                {
+                    // A marker joined to a word by a hyphen, as in
+                    // "TBD-free", is part of that word.
+                    let mut rest = text[end..].chars();
+                    if rest.next() == Some('-') && rest.next().is_some_and(char::is_alphabetic) {
+                        continue;
+                    }
+                    let found = &line.text[start..end];
+                    let span = Span {
+                        line: line.number,
+                        column: line.text[..start].chars().count() + 1,
+                        length: found.chars().count(),
+                    };
+                    let mut diag = Diagnostic::of(
+                        doc.path,
+                        self.id(),
+                        format!("unfinished content '{}'", found),
+                    )
+                    .at(Some(span));
+                    if let Some(Some(severity)) = severity {
+                        diag.severity = severity;
+                    }
+                    diags.push(diag);
+                }
+            }
+        }
+        diags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn skips_markers_in_compound_words() {
+        let content = "Keep CLAUDE.md TBD-free.\n\nTBD-\nTBD: name the owners.\n";
+        let doc = Document::parse(Path::new("CLAUDE.md"), FileKind::ClaudeMd, content);
+        let lines: Vec<_> = Placeholders(&HashMap::new())
+            .check(&doc)
+            .iter()
+            .map(|d| d.span.unwrap().line)
+            .collect();
+        assert_eq!(lines, [3, 4]);
+    }
+}
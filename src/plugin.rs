@@ -0,0 +1,310 @@
+//! WebAssembly lint plugins.
+//!
+//! A plugin is a self-contained `.wasm` module, run by the interpreter in
+//! wasm.rs, that exports:
+//!
+//! - `memory`;
+//! - `claude_lint_alloc(len: i32) -> i32`, a buffer for the host to fill;
+//! - `claude_lint_rules() -> i64`, the rules the plugin reports;
+//! - `claude_lint_check(kind: i32, path: i32, path_len: i32, content: i32,
+//!   content_len: i32) -> i64`, its findings for one file.
+//!
+//! An i64 result points at UTF-8 text in memory: the high 32 bits hold the
+//! address and the low 32 bits the length. Rules come one per line as
+//! `ID\tname\tseverity\tdescription`, and findings as
+//! `ID\tline\tcolumn\tlength\tmessage`, with line 0 for the whole file.
+//! Kinds are numbered as in [`FileKind::ALL`]. Every file is checked in a
+//! fresh copy of the instance, so nothing carries over between files.
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::config::register_rule;
+use crate::rules::{Check, Document, FileKind};
+use crate::wasm::{Instance, Value};
+use crate::{cache, Diagnostic, Severity, Span};
+
+pub const DIR_NAME: &str = ".claude-lint-plugins";
+
+struct Plugin {
+    path: PathBuf,
+    /// The instance as it stood after instantiation.
+    fresh: Instance,
+    rules: Vec<&'static str>,
+}
+
+/// Reports one of a plugin's rules. The plugin runs once per file for all
+/// of them.
+struct PluginRule {
+    plugin: Arc<Plugin>,
+    id: &'static str,
+    /// Whether plugin failures are reported under this rule.
+    first: bool,
+}
+
+type Findings = Rc<Result<Vec<Diagnostic>, String>>;
+
+thread_local! {
+    /// The last file a plugin checked on this thread, keyed by plugin,
+    /// path, and content hash. A file's checks run back to back on one
+    /// thread, so one slot is enough.
+    static LAST: RefCell<Option<(usize, PathBuf, u64, Findings)>> = const { RefCell::new(None) };
+}
+
+/// Loads a plugin and registers its rules, returning one check per rule.
+pub(crate) fn load(path: &Path) -> Result<Vec<Box<dyn Check>>, String> {
+    let at = |e: String| format!("{}: {}", path.display(), e);
+    let bytes = fs::read(path).map_err(|e| at(format!("cannot read plugin: {}", e)))?;
+    let mut fresh = Instance::new(&bytes).map_err(at)?;
+    let listing = call_text(&mut fresh, "claude_lint_rules", &[]).map_err(at)?;
+    let mut rules = Vec::new();
+    for line in listing.lines().filter(|l| !l.trim().is_empty()) {
+        let [id, name, severity, description] = line.splitn(4, '\t').collect::<Vec<_>>()[..] else {
+            return Err(at(format!("malformed rule '{}'", line)));
+        };
+        let severity = match severity {
+            "error" => Severity::Error,
+            "warning" => Severity::Warning,
+            "info" => Severity::Info,
+            s => return Err(at(format!("rule {}: invalid severity '{}'", id, s))),
+        };
+        rules.push(
+            register_rule(id, name, severity, description)
+                .map_err(|e| at(format!("rule {}: {}", id, e)))?,
+        );
+    }
+    let plugin = Arc::new(Plugin { path: path.to_path_buf(), fresh, rules });
+    Ok(plugin
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(i, &id)| {
+            Box::new(PluginRule {
+                plugin: plugin.clone(),
+                id,
+                first: i == 0,
+            }) as Box<dyn Check>
+        })
+        .collect())
+}
+
+/// Returns the `.wasm` files in a directory, sorted.
+pub(crate) fn discover(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "wasm"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+impl Plugin {
+    fn check(&self, doc: &Document) -> Result<Vec<Diagnostic>, String> {
+        let mut instance = self.fresh.clone();
+        let kind = FileKind::ALL
+            .iter()
+            .position(|&k| k == doc.kind)
+            .expect("every kind is listed") as i32;
+        let path = doc.path.to_string_lossy();
+        let path_at = write(&mut instance, path.as_bytes())?;
+        let content_at = write(&mut instance, doc.content.as_bytes())?;
+        let args = [
+            Value::I32(kind),
+            Value::I32(path_at),
+            Value::I32(path.len() as i32),
+            Value::I32(content_at),
+            Value::I32(doc.content.len() as i32),
+        ];
+        let out = call_text(&mut instance, "claude_lint_check", &args)?;
+        let mut diags = Vec::new();
+        for line in out.lines().filter(|l| !l.trim().is_empty()) {
+            let fields: Vec<&str> = line.splitn(5, '\t').collect();
+            let [id, line_no, column, length, message] = fields[..] else {
+                return Err(format!("malformed finding '{}'", line));
+            };
+            let Some(&id) = self.rules.iter().find(|&&r| r == id) else {
+                return Err(format!("finding for undeclared rule '{}'", id));
+            };
+            let number = |s: &str| {
+                s.trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("malformed finding '{}'", line))
+            };
+            let span = match number(line_no)? {
+                0 => None,
+                n => Some(Span {
+                    line: n,
+                    column: number(column)?.max(1),
+                    length: number(length)?,
+                }),
+            };
+            diags.push(Diagnostic::of(doc.path, id, message.to_string()).at(span));
+        }
+        Ok(diags)
+    }
+
+    /// Returns the plugin's findings for a document, computing them once
+    /// for all of its rules.
+    fn findings(self: &Arc<Self>, doc: &Document) -> Findings {
+        let key = Arc::as_ptr(self) as usize;
+        let hash = cache::hash(&[doc.content.as_bytes()]);
+        LAST.with(|last| {
+            let mut last = last.borrow_mut();
+            if let Some((k, path, h, findings)) = last.as_ref() {
+                if *k == key && path == doc.path && *h == hash {
+                    return findings.clone();
+                }
+            }
+            let findings = Rc::new(self.check(doc));
+            *last = Some((key, doc.path.to_path_buf(), hash, findings.clone()));
+            findings
+        })
+    }
+}
+
+impl Check for PluginRule {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        match &*self.plugin.findings(doc) {
+            Ok(diags) => diags.iter().filter(|d| d.rule == self.id).cloned().collect(),
+            Err(e) if self.first => {
+                let name = self.plugin.path.file_name().unwrap_or_default().to_string_lossy();
+                vec![Diagnostic::of(doc.path, self.id, format!("plugin {} failed: {}", name, e))]
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// Copies bytes into a buffer the plugin allocates.
+fn write(instance: &mut Instance, bytes: &[u8]) -> Result<i32, String> {
+    let len = i32::try_from(bytes.len()).map_err(|_| "input too large".to_string())?;
+    let [Value::I32(at)] = instance.call("claude_lint_alloc", &[Value::I32(len)])?[..] else {
+        return Err("claude_lint_alloc must return an i32".to_string());
+    };
+    let start = at as u32 as usize;
+    instance
+        .memory_mut()
+        .get_mut(start..start + bytes.len())
+        .ok_or("claude_lint_alloc returned a buffer outside memory")?
+        .copy_from_slice(bytes);
+    Ok(at)
+}
+
+/// Calls a function returning packed text and reads the text.
+fn call_text(instance: &mut Instance, name: &str, args: &[Value]) -> Result<String, String> {
+    let [Value::I64(packed)] = instance.call(name, args)?[..] else {
+        return Err(format!("{} must return an i64", name));
+    };
+    let start = (packed as u64 >> 32) as usize;
+    let len = packed as u32 as usize;
+    let bytes = instance
+        .memory()
+        .get(start..start + len)
+        .ok_or_else(|| format!("{} returned text outside memory", name))?;
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| format!("{} returned text that is not UTF-8", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wasm::tests::{module, sleb, Func, I32, I64};
+
+    /// A body returning packed text at an address and length.
+    fn text(at: i64, len: i64) -> Vec<u8> {
+        let mut body = vec![0x42];
+        sleb((at << 32) | len, &mut body);
+        body
+    }
+
+    /// Writes a plugin with `data` at offset 16 of its memory and the given
+    /// bodies for its rules and check exports, then loads it.
+    fn plugin(
+        name: &str,
+        data: &[u8],
+        rules: &[u8],
+        check: &[u8],
+    ) -> Result<Vec<Box<dyn Check>>, String> {
+        let funcs = [
+            Func {
+                name: "claude_lint_alloc",
+                params: &[I32],
+                results: &[I32],
+                body: &[0x41, 0x80, 0x08],
+            },
+            Func {
+                name: "claude_lint_rules",
+                params: &[],
+                results: &[I64],
+                body: rules,
+            },
+            Func {
+                name: "claude_lint_check",
+                params: &[I32; 5],
+                results: &[I64],
+                body: check,
+            },
+        ];
+        let path =
+            std::env::temp_dir().join(format!("claude-lint-{}-{}.wasm", name, std::process::id()));
+        fs::write(&path, module((1, None), &[(16, data)], &funcs)).unwrap();
+        let loaded = load(&path);
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn reports_traps_under_the_first_rule() {
+        let rules =
+            b"PT001\tplugin-trap-one\twarning\tFirst.\nPT002\tplugin-trap-two\terror\tSecond.\n";
+        let checks = plugin("trap", rules, &text(16, rules.len() as i64), &[0x00]).unwrap();
+        let content = "# Notes\n";
+        let doc = Document::parse(Path::new("CLAUDE.md"), FileKind::ClaudeMd, content);
+        let found: Vec<_> = checks.iter().map(|c| c.check(&doc)).collect();
+        assert_eq!(found[0].len(), 1);
+        assert_eq!(found[0][0].rule, "PT001");
+        assert!(
+            found[0][0]
+                .message
+                .ends_with("failed: unreachable executed"),
+            "{}",
+            found[0][0].message
+        );
+        assert!(found[1].is_empty());
+    }
+
+    #[test]
+    fn rejects_text_outside_memory() {
+        // Text that starts inside the only page and runs past its end.
+        let outside = text(65530, 10);
+        let e = plugin("outside", b"", &outside, &[0x00]).err().unwrap();
+        assert!(e.ends_with("claude_lint_rules returned text outside memory"), "{}", e);
+
+        let rules = b"PB001\tplugin-bounds\twarning\tBounds.\n";
+        let checks = plugin("bounds", rules, &text(16, rules.len() as i64), &outside).unwrap();
+        let doc = Document::parse(Path::new("CLAUDE.md"), FileKind::ClaudeMd, "");
+        let found = checks[0].check(&doc);
+        assert!(
+            found[0]
+                .message
+                .ends_with("claude_lint_check returned text outside memory"),
+            "{}",
+            found[0].message
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Linting before commits: under the pre-commit framework, for
+//! `pre-commit` and `hook-config`, and from a plain git hook, for
+//! `install-hook`.
+//!
+//! pre-commit passes the staged files that match a hook's `files`
+//! pattern as arguments. Each is linted on its own, as the kind its place
+//! implies; anything else it passes, such as a README in an `agents`
+//! directory outside `.claude`, is skipped rather than failing the
+//! commit. The git pre-commit hook install-hook writes pipes in each
+//! staged file as it is in the index, so that unstaged edits neither
+//! hide findings nor cause them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::git;
+use crate::rules::{is_claude_md, FileKind};
+
+/// The hooks a repository's `.pre-commit-hooks.yaml` publishes.
+pub const HOOKS: &str = r#"- id: claude-lint
+  name: claude-lint
+  description: Lint CLAUDE.md, agents, skills, and commands.
+  entry: claude-lint pre-commit
+  language: rust
+  files: (^|/)(CLAUDE(\.local)?\.md|\.claude/.+\.md)$
+- id: claude-lint-fix
+  name: claude-lint (fix)
+  description: Fix what claude-lint can, then lint CLAUDE.md, agents, skills, and commands.
+  entry: claude-lint pre-commit --fix
+  language: rust
+  files: (^|/)(CLAUDE(\.local)?\.md|\.claude/.+\.md)$
+"#;
+
+/// The second line of every hook install-hook writes, by which it knows
+/// the hooks it may replace or remove.
+const MARK: &str = "# Installed by claude-lint install-hook;";
+
+/// What every hook runs first.
+const CHECK_PATH: &str = "command -v claude-lint >/dev/null 2>&1 || {
+    echo 'claude-lint is not on PATH; install it, or skip this hook with --no-verify' >&2
+    exit 1
+}
+";
+
+/// The git hooks install-hook writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hook {
+    /// Lints the staged context files.
+    PreCommit,
+    /// Lints the context files the branch changed, with whole-tree checks.
+    PrePush,
+}
+
+impl Hook {
+    pub fn name(self) -> &'static str {
+        match self {
+            Hook::PreCommit => "pre-commit",
+            Hook::PrePush => "pre-push",
+        }
+    }
+
+    /// The command that removes this hook.
+    fn uninstall_command(self) -> &'static str {
+        match self {
+            Hook::PreCommit => "claude-lint install-hook --uninstall",
+            Hook::PrePush => "claude-lint install-hook --pre-push --uninstall",
+        }
+    }
+
+    fn script(self) -> String {
+        let body = match self {
+            Hook::PreCommit => concat!(
+                "git diff --cached --name-only --diff-filter=ACMR -- '*CLAUDE.md' \
+                 '*CLAUDE.local.md' '*.claude/*.md' | {\n",
+                "    status=0\n",
+                "    while IFS= read -r file; do\n",
+                "        git show \":$file\" | claude-lint --stdin --stdin-path \"$file\" || \
+                 status=1\n",
+                "    done\n",
+                "    exit $status\n",
+                "}\n",
+            ),
+            Hook::PrePush => "exec claude-lint check --changed --recursive .\n",
+        };
+        format!(
+            "#!/bin/sh\n{} {} removes it.\n{}{}",
+            MARK,
+            self.uninstall_command(),
+            CHECK_PATH,
+            body
+        )
+    }
+}
+
+/// Writes a hook into the repository holding dir, replacing one that
+/// install-hook wrote before; with force, replacing any. Returns its path.
+pub fn install(dir: &Path, hook: Hook, force: bool) -> Result<PathBuf, String> {
+    let hooks = git::hooks_dir(dir)?;
+    let path = hooks.join(hook.name());
+    if !force && path.exists() && !installed(&path) {
+        return Err(format!(
+            "{} exists and was not installed by claude-lint; pass --force to replace it",
+            path.display()
+        ));
+    }
+    fs::create_dir_all(&hooks)
+        .map_err(|e| format!("{}: cannot create directory: {}", hooks.display(), e))?;
+    fs::write(&path, hook.script())
+        .map_err(|e| format!("{}: cannot write: {}", path.display(), e))?;
+    make_executable(&path)?;
+    Ok(path)
+}
+
+/// Removes a hook install-hook wrote from the repository holding dir.
+/// Returns its path.
+pub fn uninstall(dir: &Path, hook: Hook) -> Result<PathBuf, String> {
+    let path = git::hooks_dir(dir)?.join(hook.name());
+    if !path.exists() {
+        return Err(format!("{}: no {} hook to remove", path.display(), hook.name()));
+    }
+    if !installed(&path) {
+        return Err(format!("{} was not installed by claude-lint; leaving it", path.display()));
+    }
+    fs::remove_file(&path).map_err(|e| format!("{}: cannot remove: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn installed(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|s| s.lines().nth(1).is_some_and(|l| l.starts_with(MARK)))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("{}: cannot make executable: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// The kind of a file pre-commit passes, or None when it is not a
+/// context file: a CLAUDE.md, or Markdown in a `.claude` directory.
+pub fn kind(path: &Path) -> Option<FileKind> {
+    let inside = path.ancestors().skip(1).any(|a| a.file_name().is_some_and(|n| n == ".claude"));
+    if !path.is_file() || !(inside || is_claude_md(path)) {
+        return None;
+    }
+    FileKind::infer(path)
+}
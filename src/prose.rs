@@ -0,0 +1,181 @@
+//! Banned-phrase matching over prose.
+//!
+//! Only text a reader takes as instructions counts: paragraphs, list
+//! items, and headings. Code fences, inline code spans, block quotes, HTML
+//! comments, and frontmatter are examples or metadata, so they are masked
+//! out before matching. Matches respect word boundaries.
+
+use crate::markdown::{Kind, Markdown};
+use crate::Span;
+
+/// Where a phrase has to appear to count.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Position {
+    Anywhere,
+    /// At the start of a sentence, line, or list item, as connectives like
+    /// "First," are when they sequence steps rather than rank things.
+    SentenceStart,
+}
+
+/// A phrase to look for, matched case-insensitively.
+pub struct Phrase {
+    pub text: &'static str,
+    pub position: Position,
+}
+
+/// Returns the span of every occurrence of the phrase in prose lines.
+pub fn find(md: &Markdown, phrase: &Phrase) -> Vec<Span> {
+    find_text(md, phrase.text, phrase.position)
+}
+
+/// Like [`find`], for a phrase known only at run time.
+pub fn find_text(md: &Markdown, phrase: &str, position: Position) -> Vec<Span> {
+    let needle = phrase.to_lowercase();
+    let mut out = Vec::new();
+    for line in md.lines.iter().filter(|l| is_prose(l.kind)) {
+        let (text, origin) = fold(&mask(line.text));
+        let mut from = 0;
+        while let Some(i) = text[from..].find(&needle) {
+            let start = from + i;
+            let end = start + needle.len();
+            from = start + text[start..].chars().next().map_or(1, char::len_utf8);
+            if !at_boundary(&text, start, end) {
+                continue;
+            }
+            if position == Position::SentenceStart && !sentence_start(&text[..start]) {
+                continue;
+            }
+            let (start, end) = (origin[start], origin[end]);
+            out.push(Span {
+                line: line.number,
+                column: line.text[..start].chars().count() + 1,
+                length: line.text[start..end].chars().count(),
+            });
+        }
+    }
+    out
+}
+
+/// Lowercases text, returning it with the byte offset in the original of
+/// each folded byte, plus one past the end. Lowercasing can change a
+/// character's length, so offsets into the folded text need the map.
+fn fold(text: &str) -> (String, Vec<usize>) {
+    let mut folded = String::with_capacity(text.len());
+    let mut origin = Vec::with_capacity(text.len() + 1);
+    for (i, c) in text.char_indices() {
+        let before = folded.len();
+        folded.extend(c.to_lowercase());
+        origin.resize(origin.len() + folded.len() - before, i);
+    }
+    origin.push(text.len());
+    (folded, origin)
+}
+
+/// Checks whether lines of a kind are prose: paragraphs, list items, and
+/// headings.
+pub fn is_prose(kind: Kind) -> bool {
+    matches!(kind, Kind::Paragraph | Kind::ListItem { .. } | Kind::Heading(_))
+}
+
+/// Replaces inline code spans and HTML comments with spaces, keeping
+/// byte offsets intact.
+pub fn mask(line: &str) -> String {
+    let bytes = line.as_bytes();
+    let mut out = line.as_bytes().to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'`' {
+            let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+            if let Some(close) = closing_backticks(bytes, i + run, run) {
+                blank(&mut out, i, close + run);
+                i = close + run;
+            } else {
+                i += run;
+            }
+        } else if bytes[i..].starts_with(b"<!--") {
+            let end = bytes[i + 4..]
+                .windows(3)
+                .position(|w| w == b"-->")
+                .map_or(bytes.len(), |e| i + 4 + e + 3);
+            blank(&mut out, i, end);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    // Masked ranges start and end on ASCII delimiters, so they cover whole
+    // characters and the result stays UTF-8.
+    String::from_utf8(out).expect("masking preserves UTF-8")
+}
+
+/// Finds a backtick run of exactly len starting at or after from.
+fn closing_backticks(bytes: &[u8], from: usize, len: usize) -> Option<usize> {
+    let mut i = from;
+    while i < bytes.len() {
+        if bytes[i] == b'`' {
+            let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+            if run == len {
+                return Some(i);
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Blanks a byte range with spaces.
+fn blank(out: &mut [u8], start: usize, end: usize) {
+    for b in &mut out[start..end] {
+        *b = b' ';
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Checks that the match neither starts nor ends inside a word.
+fn at_boundary(text: &str, start: usize, end: usize) -> bool {
+    let first = text[start..].chars().next();
+    let last = text[..end].chars().next_back();
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    let starts_ok = !first.is_some_and(is_word) || !before.is_some_and(is_word);
+    let ends_ok = !last.is_some_and(is_word) || !after.is_some_and(is_word);
+    starts_ok && ends_ok
+}
+
+/// Checks whether text before a match ends a sentence or holds only
+/// line-leading markup (indentation, list markers, heading hashes).
+fn sentence_start(before: &str) -> bool {
+    let t = before.trim_end();
+    if t.is_empty() || t.ends_with(['.', '!', '?', ':', ';']) {
+        return true;
+    }
+    let markup = t.trim_start();
+    crate::markdown::list_marker(markup).is_some_and(|(_, content)| content >= markup.len())
+        || markup.chars().all(|c| c == '#' || c == '>' || c == '*' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_multibyte_phrases_across_case() {
+        let md = crate::markdown::parse("Ein Über-Satz, dann über alles.\n");
+        let spans = find_text(&md, "über", Position::Anywhere);
+        let columns: Vec<_> = spans.iter().map(|s| (s.column, s.length)).collect();
+        assert_eq!(columns, [(5, 4), (21, 4)]);
+    }
+
+    #[test]
+    fn maps_columns_past_length_changing_folds() {
+        // 'İ' lowercases to two characters, shifting later bytes.
+        let md = crate::markdown::parse("İİ then über\n");
+        let spans = find_text(&md, "über", Position::Anywhere);
+        assert_eq!(spans.iter().map(|s| s.column).collect::<Vec<_>>(), [9]);
+    }
+}
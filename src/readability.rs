@@ -0,0 +1,188 @@
+//! Sentence length and reading grade of prose.
+//!
+//! Long, convoluted sentences are followed less reliably than short
+//! ones. Paragraphs and list items are split into sentences at `.`, `!`,
+//! or `?` before a space and a capital, and at the end of each item or
+//! paragraph; syllables are counted by vowel groups. The grade is the
+//! Flesch-Kincaid grade level. Headings, code, and quotes do not count.
+
+use crate::config::Config;
+use crate::markdown::{self, Kind, Markdown};
+use crate::prose;
+use crate::rules::{Check, Document, FileKind};
+use crate::{Diagnostic, Span};
+
+const MAX_SENTENCE_WORDS: usize = 20;
+const MAX_GRADE: usize = 12;
+
+/// Counts over a file's prose.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Metrics {
+    pub sentences: usize,
+    pub words: usize,
+    pub syllables: usize,
+}
+
+/// A sentence of prose: its words, and where the first one is.
+pub(crate) struct Sentence {
+    pub span: Span,
+    pub words: Vec<String>,
+}
+
+impl Metrics {
+    pub fn measure(md: &Markdown) -> Self {
+        let mut metrics = Metrics::default();
+        for sentence in sentences(md) {
+            metrics.sentences += 1;
+            metrics.words += sentence.words.len();
+            metrics.syllables += sentence.words.iter().map(|w| syllables(w)).sum::<usize>();
+        }
+        metrics
+    }
+
+    /// Words per sentence, on average.
+    pub fn sentence_length(&self) -> f64 {
+        self.words as f64 / self.sentences.max(1) as f64
+    }
+
+    /// The Flesch-Kincaid grade level: the years of schooling the text
+    /// takes to follow. Very plain text scores 0 rather than below.
+    pub fn grade(&self) -> f64 {
+        if self.words == 0 {
+            return 0.0;
+        }
+        let grade = 0.39 * self.sentence_length()
+            + 11.8 * (self.syllables as f64 / self.words as f64)
+            - 15.59;
+        grade.max(0.0)
+    }
+}
+
+/// Splits the paragraphs and list items of a file into sentences, with
+/// code spans masked out.
+pub(crate) fn sentences(md: &Markdown) -> Vec<Sentence> {
+    let mut out = Vec::new();
+    // The words of the current paragraph or item, with their spans.
+    let mut block: Vec<(Span, String)> = Vec::new();
+    for line in &md.lines {
+        let start = match line.kind {
+            Kind::Paragraph => 0,
+            Kind::ListItem { .. } => {
+                split(std::mem::take(&mut block), &mut out);
+                markdown::list_marker(line.text).map_or(0, |(_, at)| at.min(line.text.len()))
+            }
+            _ => {
+                split(std::mem::take(&mut block), &mut out);
+                continue;
+            }
+        };
+        let masked = prose::mask(line.text);
+        let mut from = start;
+        for word in masked[start..].split_whitespace() {
+            let at = from + masked[from..].find(word).unwrap_or(0);
+            from = at + word.len();
+            if word.contains(char::is_alphanumeric) {
+                let span = Span {
+                    line: line.number,
+                    column: line.text[..at].chars().count() + 1,
+                    length: word.chars().count(),
+                };
+                block.push((span, word.to_string()));
+            }
+        }
+    }
+    split(block, &mut out);
+    out
+}
+
+/// Breaks a block's words into sentences at a `.`, `!`, or `?` that is
+/// followed by a capital.
+fn split(words: Vec<(Span, String)>, out: &mut Vec<Sentence>) {
+    let mut current: Option<Sentence> = None;
+    let mut ended = false;
+    for (span, word) in words {
+        let capital = word
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .starts_with(char::is_uppercase);
+        if ended && capital {
+            out.extend(current.take());
+        }
+        ended = word.trim_end_matches(['"', '\'', ')', '*', '_']).ends_with(['.', '!', '?']);
+        current.get_or_insert_with(|| Sentence { span, words: Vec::new() }).words.push(word);
+    }
+    out.extend(current);
+}
+
+/// Counts the vowel groups in a word, leaving out a silent final e.
+fn syllables(word: &str) -> usize {
+    let word: Vec<char> = word
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let vowel = |c: char| "aeiouy".contains(c);
+    let mut groups = 0;
+    let mut prev = false;
+    for &c in &word {
+        if vowel(c) && !prev {
+            groups += 1;
+        }
+        prev = vowel(c);
+    }
+    if word.len() > 2
+        && word.ends_with(&['e'])
+        && !word.ends_with(&['l', 'e'])
+        && !vowel(word[word.len() - 2])
+    {
+        groups -= 1;
+    }
+    groups.max(1)
+}
+
+/// Flags files whose sentences run long on average or whose grade is
+/// high. It runs only when the config sets the rule's severity or one of
+/// its thresholds, max_sentence_words and max_grade.
+pub(crate) struct Readability<'a>(pub &'a Config);
+
+impl Check for Readability<'_> {
+    fn id(&self) -> &'static str {
+        "CL142"
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        if !self.0.configures(self.id(), doc.path) {
+            return Vec::new();
+        }
+        let metrics = Metrics::measure(&doc.markdown);
+        if metrics.words == 0 {
+            return Vec::new();
+        }
+        let mut diags = Vec::new();
+        let max_words = self
+            .0
+            .option(self.id(), "max_sentence_words", doc.path)
+            .unwrap_or(MAX_SENTENCE_WORDS);
+        if metrics.sentence_length() > max_words as f64 {
+            let msg = format!(
+                "sentences average {:.1} words; expected at most {}",
+                metrics.sentence_length(),
+                max_words
+            );
+            diags.push(Diagnostic::of(doc.path, self.id(), msg));
+        }
+        let max_grade = self.0.option(self.id(), "max_grade", doc.path).unwrap_or(MAX_GRADE);
+        if metrics.grade() > max_grade as f64 {
+            let msg = format!(
+                "reading grade level is {:.1}; expected at most {}",
+                metrics.grade(),
+                max_grade
+            );
+            diags.push(Diagnostic::of(doc.path, self.id(), msg));
+        }
+        diags
+    }
+}
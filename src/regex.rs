@@ -0,0 +1,493 @@
+//! Minimal regular expressions for custom rules.
+//!
+//! A backtracking matcher over chars for the common subset: literals,
+//! `.`, classes (`[a-z]`, `[^...]`, `\d`, `\w`, `\s` and their negations),
+//! anchors (`^`, `$`, `\b`, `\B`), groups (`(...)`, `(?:...)`), alternation,
+//! and greedy or lazy repetition (`*`, `+`, `?`, `{n}`, `{n,}`, `{n,m}`). A
+//! leading `(?i)` makes the whole pattern case-insensitive. Backreferences
+//! and lookaround are not supported.
+//!
+//! Nested repetition such as `(a*)*b` can backtrack exponentially, so each
+//! match attempt has a step budget; running out is an error rather than a
+//! hang.
+
+use std::cell::Cell;
+
+pub struct Regex {
+    node: Node,
+    ignore_case: bool,
+}
+
+enum Node {
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    /// A word boundary, or with false a non-boundary.
+    Boundary(bool),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+struct Class {
+    items: Vec<Item>,
+    negated: bool,
+}
+
+enum Item {
+    Range(char, char),
+    /// `\d`, `\w`, or `\s`, negated as `\D`, `\W`, or `\S`.
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+/// Upper bound for `{n,m}` counts, so a typo cannot exhaust the stack.
+const MAX_REPEAT: usize = 1000;
+
+/// Nodes a match attempt at one start position may visit.
+const STEPS: usize = 1_000_000;
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let (ignore_case, rest) = match pattern.strip_prefix("(?i)") {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let mut parser = Parser { chars: rest.chars().collect(), pos: 0 };
+        let node = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            // Only an unmatched ')' stops the top-level alternation early.
+            return Err(format!("unmatched ')' at offset {}", parser.pos));
+        }
+        Ok(Regex { node, ignore_case })
+    }
+
+    /// Returns the byte range of every non-overlapping, non-empty match,
+    /// or an error if an attempt runs out of steps.
+    pub fn find_all(&self, text: &str) -> Result<Vec<(usize, usize)>, String> {
+        let chars: Vec<char> = text.chars().collect();
+        let offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).chain([text.len()]).collect();
+        let mut out = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let search = Search { re: self, s: &chars, fuel: Cell::new(STEPS) };
+            let mut end = None;
+            search.matches(&self.node, start, &mut |j| {
+                end = Some(j).filter(|&j| j > start);
+                end.is_some()
+            });
+            if search.fuel.get() == 0 {
+                return Err(format!(
+                    "pattern gave up after {} steps; avoid nesting repetition",
+                    STEPS
+                ));
+            }
+            match end {
+                Some(end) => {
+                    out.push((offsets[start], offsets[end]));
+                    start = end;
+                }
+                None => start += 1,
+            }
+        }
+        Ok(out)
+    }
+
+    /// Matches a node that consumes exactly one char.
+    fn single(&self, node: &Node, c: char) -> bool {
+        match node {
+            Node::Char(want) => self.same(c, *want),
+            Node::Any => c != '\n',
+            Node::Class(class) => self.in_class(class, c),
+            _ => false,
+        }
+    }
+
+    fn same(&self, a: char, b: char) -> bool {
+        a == b || self.ignore_case && lower(a) == lower(b)
+    }
+
+    fn in_class(&self, class: &Class, c: char) -> bool {
+        let hit = |c: char| {
+            class.items.iter().any(|item| match *item {
+                Item::Range(lo, hi) => lo <= c && c <= hi,
+                Item::Digit(want) => c.is_ascii_digit() == want,
+                Item::Word(want) => is_word(c) == want,
+                Item::Space(want) => c.is_whitespace() == want,
+            })
+        };
+        let found = hit(c) || self.ignore_case && (hit(lower(c)) || hit(upper(c)));
+        found != class.negated
+    }
+}
+
+/// One match attempt over a line, with the steps it has left.
+struct Search<'a> {
+    re: &'a Regex,
+    s: &'a [char],
+    fuel: Cell<usize>,
+}
+
+impl Search<'_> {
+    /// Matches node at s[i..] and calls k with each end position the match
+    /// can reach, in priority order, until k accepts one. Fails once the
+    /// steps run out.
+    fn matches(&self, node: &Node, i: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+        let fuel = self.fuel.get();
+        if fuel == 0 {
+            return false;
+        }
+        self.fuel.set(fuel - 1);
+        let s = self.s;
+        match node {
+            Node::Char(_) | Node::Any | Node::Class(_) => {
+                i < s.len() && self.re.single(node, s[i]) && k(i + 1)
+            }
+            Node::Start => i == 0 && k(i),
+            Node::End => i == s.len() && k(i),
+            Node::Boundary(want) => {
+                let before = i > 0 && is_word(s[i - 1]);
+                let after = i < s.len() && is_word(s[i]);
+                (before != after) == *want && k(i)
+            }
+            Node::Concat(nodes) => self.concat(nodes, i, k),
+            Node::Alt(nodes) => nodes.iter().any(|n| self.matches(n, i, k)),
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } if matches!(**node, Node::Char(_) | Node::Any | Node::Class(_)) => {
+                // Runs of single chars are counted rather than recursed
+                // through, so long lines cannot exhaust the stack.
+                let run = s[i..]
+                    .iter()
+                    .take(max.unwrap_or(usize::MAX))
+                    .take_while(|&&c| self.re.single(node, c))
+                    .count();
+                if run < *min {
+                    return false;
+                }
+                if *greedy {
+                    (*min..=run).rev().any(|n| k(i + n))
+                } else {
+                    (*min..=run).any(|n| k(i + n))
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => self.repeat(node, *min, *max, *greedy, 0, i, k),
+        }
+    }
+
+    fn concat(&self, nodes: &[Node], i: usize, k: &mut dyn FnMut(usize) -> bool) -> bool {
+        match nodes.split_first() {
+            None => k(i),
+            Some((first, rest)) => self.matches(first, i, &mut |j| self.concat(rest, j, k)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn repeat(
+        &self,
+        node: &Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        count: usize,
+        i: usize,
+        k: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        if count < min {
+            return self.matches(node, i, &mut |j| {
+                self.repeat(node, min, max, greedy, count + 1, j, k)
+            });
+        }
+        if !greedy && k(i) {
+            return true;
+        }
+        // An iteration that consumes nothing would loop forever.
+        if max.is_none_or(|m| count < m)
+            && self.matches(node, i, &mut |j| {
+                j > i && self.repeat(node, min, max, greedy, count + 1, j, k)
+            })
+        {
+            return true;
+        }
+        greedy && k(i)
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn upper(c: char) -> char {
+    c.to_uppercase().next().unwrap_or(c)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.sequence()?];
+        while self.eat('|') {
+            branches.push(self.sequence()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().expect("one branch")
+        } else {
+            Node::Alt(branches)
+        })
+    }
+
+    fn sequence(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        let at = self.pos;
+        let c = self.peek().expect("sequence checks for input");
+        let repetition = self.repetition_ahead();
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err(format!("unsupported group syntax at offset {}", at));
+                }
+                let inner = self.alternation()?;
+                if !self.eat(')') {
+                    return Err(format!("unclosed group at offset {}", at));
+                }
+                inner
+            }
+            '[' => Node::Class(self.class(at)?),
+            '\\' => match self.escape()? {
+                Escape::Char(c) => Node::Char(c),
+                Escape::Item(item) => Node::Class(Class { items: vec![item], negated: false }),
+                Escape::Boundary(want) => Node::Boundary(want),
+            },
+            '*' | '+' | '?' => return Err(format!("nothing to repeat at offset {}", at)),
+            '{' if repetition => return Err(format!("nothing to repeat at offset {}", at)),
+            c => Node::Char(c),
+        })
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let at = self.pos;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') if self.repetition_ahead() => {
+                self.pos += 1;
+                let min = self.number();
+                let max = if self.eat(',') {
+                    if self.peek() == Some('}') { None } else { Some(self.number()) }
+                } else {
+                    Some(min)
+                };
+                // The cursor is on the closing brace, which repetition_ahead
+                // vouched for; it is consumed below like any quantifier.
+                if max.is_some_and(|m| m < min) || min.max(max.unwrap_or(0)) > MAX_REPEAT {
+                    return Err(format!("invalid repetition at offset {}", at));
+                }
+                (min, max)
+            }
+            _ => return Ok(atom),
+        };
+        self.pos += 1;
+        if matches!(atom, Node::Start | Node::End | Node::Boundary(_)) {
+            return Err(format!("nothing to repeat at offset {}", at));
+        }
+        let greedy = !self.eat('?');
+        if matches!(self.peek(), Some('*' | '+' | '?')) {
+            return Err(format!("nothing to repeat at offset {}", self.pos));
+        }
+        Ok(Node::Repeat { node: Box::new(atom), min, max, greedy })
+    }
+
+    /// Checks for `{n}`, `{n,}`, or `{n,m}` at the cursor. Any other brace
+    /// is a literal.
+    fn repetition_ahead(&self) -> bool {
+        if self.peek() != Some('{') {
+            return false;
+        }
+        let rest = &self.chars[self.pos + 1..];
+        let Some(close) = rest.iter().position(|&c| c == '}') else {
+            return false;
+        };
+        let body: String = rest[..close].iter().collect();
+        let (min, max) = body.split_once(',').unwrap_or((&body, "0"));
+        !min.is_empty()
+            && min.bytes().all(|b| b.is_ascii_digit())
+            && max.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    fn number(&mut self) -> usize {
+        let mut n: usize = 0;
+        while let Some(d) = self.peek().and_then(|c| c.to_digit(10)) {
+            n = n.saturating_mul(10).saturating_add(d as usize);
+            self.pos += 1;
+        }
+        n
+    }
+
+    fn class(&mut self, at: usize) -> Result<Class, String> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(format!("unclosed character class at offset {}", at));
+            };
+            self.pos += 1;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let lo = match c {
+                '\\' => match self.escape()? {
+                    Escape::Char(c) => c,
+                    Escape::Item(item) => {
+                        items.push(item);
+                        continue;
+                    }
+                    Escape::Boundary(_) => {
+                        return Err(format!("'\\b' inside a class at offset {}", self.pos - 2))
+                    }
+                },
+                c => c,
+            };
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']') {
+                self.pos += 1;
+                let hi = match self.peek() {
+                    Some('\\') => {
+                        self.pos += 1;
+                        match self.escape()? {
+                            Escape::Char(c) => c,
+                            _ => {
+                                return Err(format!(
+                                    "invalid class range at offset {}",
+                                    self.pos - 2
+                                ))
+                            }
+                        }
+                    }
+                    Some(c) => {
+                        self.pos += 1;
+                        c
+                    }
+                    None => return Err(format!("unclosed character class at offset {}", at)),
+                };
+                if hi < lo {
+                    return Err(format!("invalid class range '{}-{}'", lo, hi));
+                }
+                items.push(Item::Range(lo, hi));
+            } else {
+                items.push(Item::Range(lo, lo));
+            }
+        }
+        Ok(Class { items, negated })
+    }
+
+    /// Reads the escape after a backslash.
+    fn escape(&mut self) -> Result<Escape, String> {
+        let Some(c) = self.peek() else {
+            return Err("trailing backslash".to_string());
+        };
+        self.pos += 1;
+        Ok(match c {
+            'd' | 'D' => Escape::Item(Item::Digit(c == 'd')),
+            'w' | 'W' => Escape::Item(Item::Word(c == 'w')),
+            's' | 'S' => Escape::Item(Item::Space(c == 's')),
+            'b' | 'B' => Escape::Boundary(c == 'b'),
+            'n' => Escape::Char('\n'),
+            't' => Escape::Char('\t'),
+            c if c.is_ascii_alphanumeric() => {
+                return Err(format!("unsupported escape '\\{}' at offset {}", c, self.pos - 2))
+            }
+            c => Escape::Char(c),
+        })
+    }
+}
+
+enum Escape {
+    Char(char),
+    Item(Item),
+    Boundary(bool),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Result<Vec<(usize, usize)>, String> {
+        Regex::new(pattern).expect("pattern compiles").find_all(text)
+    }
+
+    #[test]
+    fn matches_the_supported_subset() {
+        assert_eq!(find(r"\bfoo\d+", "a foo12 xfoo3"), Ok(vec![(2, 7)]));
+        assert_eq!(find(r"(?i)always|never", "Never, ALWAYS"), Ok(vec![(0, 5), (7, 13)]));
+        assert_eq!(find(r"a{2,3}?", "aaaa"), Ok(vec![(0, 2), (2, 4)]));
+        assert_eq!(find(r"[^a-c]+$", "abcxyz"), Ok(vec![(3, 6)]));
+        assert_eq!(find(r"ü+", "xüüy"), Ok(vec![(1, 5)]));
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        for pattern in ["(a", "a)", "[a", "*a", r"\q", "a{3,2}", "(?=a)"] {
+            assert!(Regex::new(pattern).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn gives_up_on_nested_repetition() {
+        let line = "a".repeat(46);
+        assert!(find("(a*)*b", &line).is_err());
+        assert!(find("(a|aa)*c", &line).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! The per-file rule interface.
+//!
+//! A check looks at one parsed document of a kind it applies to and
+//! reports findings under one rule ID. The built-in checks are in
+//! checks.rs; more can be added through [`Config::checks`], with their
+//! rule metadata registered by [`register`]. Checks that span files
+//! (settings, hooks, MCP servers, imports) run once per directory
+//! instead.
+//!
+//! [`Config::checks`]: crate::config::Config::checks
+//! [`register`]: crate::register
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::log;
+use crate::timings;
+use crate::markdown::{self, Markdown};
+use crate::{Diagnostic, Severity, Span};
+
+/// The names of the files Claude Code loads as memory, linted as
+/// CLAUDE.md wherever they are. CLAUDE.local.md is a person's own,
+/// usually kept out of version control.
+pub const CLAUDE_MD_NAMES: &[&str] = &["CLAUDE.md", "CLAUDE.local.md"];
+
+/// Whether a file is a CLAUDE.md or CLAUDE.local.md.
+pub fn is_claude_md(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| CLAUDE_MD_NAMES.iter().any(|m| n == *m))
+}
+
+/// The kinds of Markdown file in a `.claude` directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    ClaudeMd,
+    Agent,
+    Skill,
+    Reference,
+    Command,
+}
+
+impl FileKind {
+    pub const ALL: &'static [FileKind] = &[
+        FileKind::ClaudeMd,
+        FileKind::Agent,
+        FileKind::Skill,
+        FileKind::Reference,
+        FileKind::Command,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FileKind::ClaudeMd => "claude_md",
+            FileKind::Agent => "agent",
+            FileKind::Skill => "skill",
+            FileKind::Reference => "reference",
+            FileKind::Command => "command",
+        }
+    }
+
+    /// Looks a kind up by its [`as_str`](FileKind::as_str) name.
+    pub fn parse(name: &str) -> Option<FileKind> {
+        FileKind::ALL.iter().copied().find(|k| k.as_str() == name)
+    }
+
+    /// Tells a file's kind from where it sits in a `.claude` directory:
+    /// `agents/**/*.md`, `skills/*/SKILL.md`, `skills/*/references/**/*.md`,
+    /// or `commands/*.md`, where `.md` may be `.markdown`, in any case.
+    /// Any CLAUDE.md or CLAUDE.local.md is one, and a file in a
+    /// subdirectory of agents/ is an agent only when that agents/ sits
+    /// directly in a `.claude` directory.
+    pub fn infer(path: &Path) -> Option<FileKind> {
+        let name = path.file_name()?;
+        if is_claude_md(path) {
+            return Some(FileKind::ClaudeMd);
+        }
+        if !markdown::is_markdown(path) {
+            return None;
+        }
+        let dirs: Vec<&str> = path
+            .ancestors()
+            .skip(1)
+            .map_while(|a| a.file_name()?.to_str())
+            .collect();
+        if name != "SKILL.md"
+            && (0..dirs.len())
+                .any(|i| dirs[i] == "references" && dirs.get(i + 2) == Some(&"skills"))
+        {
+            return Some(FileKind::Reference);
+        }
+        if (1..dirs.len()).any(|i| dirs[i] == "agents" && dirs.get(i + 1) == Some(&".claude")) {
+            return Some(FileKind::Agent);
+        }
+        match (*dirs.first()?, dirs.get(1).copied()) {
+            (_, Some("skills")) if name == "SKILL.md" => Some(FileKind::Skill),
+            ("agents", _) => Some(FileKind::Agent),
+            ("commands", _) => Some(FileKind::Command),
+            _ => None,
+        }
+    }
+}
+
+/// A file parsed once for every check that looks at it.
+pub struct Document<'a> {
+    pub path: &'a Path,
+    pub kind: FileKind,
+    pub content: &'a str,
+    pub markdown: Markdown<'a>,
+    /// None when the file does not start with a `---` line.
+    pub frontmatter: Option<Result<Frontmatter, frontmatter::Error>>,
+}
+
+impl<'a> Document<'a> {
+    pub fn parse(path: &'a Path, kind: FileKind, content: &'a str) -> Self {
+        Document {
+            path,
+            kind,
+            content,
+            markdown: markdown::parse(content),
+            frontmatter: frontmatter::parse(content),
+        }
+    }
+
+    /// Returns the frontmatter if it is present and well-formed.
+    pub fn fields(&self) -> Option<&Frontmatter> {
+        self.frontmatter.as_ref()?.as_ref().ok()
+    }
+}
+
+/// A check over single documents.
+pub trait Check: Send + Sync {
+    /// The ID of the rule the check reports; it must be registered.
+    fn id(&self) -> &'static str;
+
+    fn applies_to(&self, kind: FileKind) -> bool;
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic>;
+}
+
+/// Reads a linted file as text. A missing file is left to the checks
+/// that require it; one that cannot be read is reported as CL002, and one
+/// that is not UTF-8 as CL110. With lossy, such a file is still returned,
+/// its bad bytes replaced, and CL110 is only a warning.
+pub(crate) fn read(path: &Path, lossy: bool, diags: &mut Vec<Diagnostic>) -> Option<String> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            diags.push(Diagnostic::of(path, "CL002", format!("cannot read file: {}", e)));
+            return None;
+        }
+    };
+    decode(path, bytes, lossy, diags)
+}
+
+/// Decodes a linted file's bytes as text, reporting CL110 as in `read`.
+pub(crate) fn decode(
+    path: &Path,
+    bytes: Vec<u8>,
+    lossy: bool,
+    diags: &mut Vec<Diagnostic>,
+) -> Option<String> {
+    let e = match String::from_utf8(bytes) {
+        Ok(content) => return Some(content),
+        Err(e) => e,
+    };
+    let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+    let line_start = valid.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let span = Span {
+        line: valid.iter().filter(|&&b| b == b'\n').count() + 1,
+        column: String::from_utf8_lossy(&valid[line_start..]).chars().count() + 1,
+        length: 1,
+    };
+    if !lossy {
+        diags.push(Diagnostic::of(path, "CL110", "not valid UTF-8".to_string()).at(Some(span)));
+        return None;
+    }
+    let mut diag = Diagnostic::of(
+        path,
+        "CL110",
+        "not valid UTF-8; checked with invalid bytes replaced".to_string(),
+    )
+    .at(Some(span));
+    diag.severity = Severity::Warning;
+    diags.push(diag);
+    Some(String::from_utf8_lossy(e.as_bytes()).into_owned())
+}
+
+/// Parses a file's content and runs the checks that apply to its kind.
+pub(crate) fn check_content(
+    path: &Path,
+    kind: FileKind,
+    content: &str,
+    checks: &[&dyn Check],
+    diags: &mut Vec<Diagnostic>,
+) {
+    let doc = Document::parse(path, kind, content);
+    for check in checks.iter().filter(|c| c.applies_to(kind)) {
+        let start = Instant::now();
+        let found = check.check(&doc);
+        timings::rule(check.id(), start.elapsed());
+        log::trace!("{} on {}: {} finding(s)", check.id(), path.display(), found.len());
+        diags.extend(found);
+    }
+}
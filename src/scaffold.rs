@@ -0,0 +1,182 @@
+//! Starter files for `claude-lint init` and `claude-lint new`.
+//!
+//! Every template passes all rules as written, so a new directory starts
+//! clean and each finding after that comes from an edit.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CLAUDE_MD: &str = "\
+# Project Context
+
+## Purpose
+
+What this project is, who it serves, and what it is not.
+
+## Norms
+
+- Conventions the code follows, stated as facts.
+- Tools and commands the project relies on.
+
+## Epistemics
+
+- What is known, what is assumed, and how sure anyone is.
+";
+
+const EXAMPLE_SKILL: &str = "\
+---
+name: example
+description: An example skill. Say what it makes possible, so Claude can tell when it applies.
+---
+
+# Example
+
+## Capability
+
+This section says what the skill makes possible, in terms of the task
+rather than the steps. The example is a placeholder: replace it with a
+real skill, or delete it.
+";
+
+/// Creates a skeleton .claude directory: CLAUDE.md, agents/, and skills/
+/// with an example skill. Existing files are kept as they are. Returns
+/// the paths created.
+pub fn init(root: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut created = Vec::new();
+    for dir in [root.to_path_buf(), root.join("agents"), root.join("skills").join("example")] {
+        if !dir.is_dir() {
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("{}: cannot create directory: {}", dir.display(), e))?;
+            created.push(dir);
+        }
+    }
+    for (path, content) in [
+        (root.join("CLAUDE.md"), CLAUDE_MD),
+        (root.join("skills").join("example").join("SKILL.md"), EXAMPLE_SKILL),
+    ] {
+        if write_new(&path, content)? {
+            created.push(path);
+        }
+    }
+    Ok(created)
+}
+
+/// Creates `agents/<name>.md`, with the name in kebab-case. Returns the
+/// path created.
+pub fn new_agent(root: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = kebab_case(name)?;
+    let dir = root.join("agents");
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("{}: cannot create directory: {}", dir.display(), e))?;
+    let path = dir.join(format!("{}.md", name));
+    let content = format!(
+        "---\n\
+         name: {name}\n\
+         description: {description}\n\
+         ---\n\
+         \n\
+         # {title}\n\
+         \n\
+         ## Perspective\n\
+         \n\
+         The point of view this agent brings, and what it values when it\n\
+         weighs a change.\n",
+        name = name,
+        description = agent_description(&name),
+        title = title(&name),
+    );
+    if !write_new(&path, &content)? {
+        return Err(format!("{}: already exists", path.display()));
+    }
+    Ok(path)
+}
+
+/// The description `new agent` writes for an agent named name, for the
+/// user to replace.
+pub(crate) fn agent_description(name: &str) -> String {
+    format!("Say what the {} agent is for, so Claude can tell when to hand it work.", name)
+}
+
+/// Creates `skills/<name>/` with a SKILL.md and an empty `references/`,
+/// with the name in kebab-case. Returns the skill directory.
+pub fn new_skill(root: &Path, name: &str) -> Result<PathBuf, String> {
+    let name = kebab_case(name)?;
+    let dir = root.join("skills").join(&name);
+    if dir.exists() {
+        return Err(format!("{}: already exists", dir.display()));
+    }
+    let refs = dir.join("references");
+    fs::create_dir_all(&refs)
+        .map_err(|e| format!("{}: cannot create directory: {}", refs.display(), e))?;
+    let content = format!(
+        "---\n\
+         name: {name}\n\
+         description: Say what the {name} skill makes possible, so Claude can tell \
+         when it applies.\n\
+         ---\n\
+         \n\
+         # {title}\n\
+         \n\
+         ## Capability\n\
+         \n\
+         This section says what the {name} skill makes possible, in terms of\n\
+         the task rather than the steps.\n\
+         \n\
+         ## References\n\
+         \n\
+         None yet. Optional playbooks go in references/ and are linked here.\n",
+        name = name,
+        title = title(&name),
+    );
+    write_new(&dir.join("SKILL.md"), &content)?;
+    Ok(dir)
+}
+
+/// Lowercases a name and joins its words with hyphens. Words break at
+/// anything but letters and digits, and before a capital that follows a
+/// lowercase letter or digit, as in `CodeReviewer`.
+pub fn kebab_case(name: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            prev = None;
+            continue;
+        }
+        let joins = prev.is_some_and(|p| {
+            !(c.is_ascii_uppercase() && (p.is_ascii_lowercase() || p.is_ascii_digit()))
+        });
+        if !out.is_empty() && !joins {
+            out.push('-');
+        }
+        out.push(c.to_ascii_lowercase());
+        prev = Some(c);
+    }
+    if out.is_empty() {
+        return Err(format!("invalid name '{}' (expected letters or digits)", name));
+    }
+    Ok(out)
+}
+
+/// Turns a kebab-case name into a title, as in `code-reviewer` to
+/// `Code Reviewer`.
+fn title(name: &str) -> String {
+    name.split('-')
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map_or_else(String::new, |c| {
+                c.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Writes a file unless it exists, returning whether it was written.
+fn write_new(path: &Path, content: &str) -> Result<bool, String> {
+    if path.exists() {
+        return Ok(false);
+    }
+    fs::write(path, content).map_err(|e| format!("{}: cannot write: {}", path.display(), e))?;
+    Ok(true)
+}
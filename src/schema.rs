@@ -0,0 +1,576 @@
+//! The shape of `.claude-lint.toml`, for `config schema` and `config
+//! check`.
+//!
+//! The loader stops at the first thing it cannot use. This describes
+//! every key once, so that a config can be checked for all its problems
+//! together, and so that editors can be given a JSON Schema to complete
+//! and validate against. It is kept in step with `config::parse` by hand.
+
+use crate::cli::or_list;
+use crate::config::{self, OPTIONS};
+use crate::glob;
+use crate::json;
+use crate::placeholders;
+use crate::regex::Regex;
+use crate::rules::FileKind;
+use crate::toml::{Table, Value};
+use crate::{all_rules, Rule};
+
+const SEVERITIES: &[&str] = &["error", "warning", "info", "off"];
+
+/// What a value may be.
+enum Shape {
+    Boolean,
+    /// An integer from the first bound to the second, if any.
+    Integer(i64, Option<i64>),
+    /// A number above zero.
+    Positive,
+    Text,
+    /// A string that is not blank.
+    Word,
+    /// A path pattern.
+    Glob,
+    /// A regular expression.
+    Pattern,
+    Choice(Vec<&'static str>),
+    Array(Box<Shape>),
+    /// A table with only these keys.
+    Table(Vec<Field>),
+    /// A string or a list of strings from a set.
+    OneOrMore(Vec<&'static str>),
+    /// Rule settings, keyed by rule ID or name.
+    Rules,
+}
+
+struct Field {
+    key: String,
+    help: String,
+    shape: Shape,
+    required: bool,
+}
+
+fn field(key: &str, help: &str, shape: Shape) -> Field {
+    Field { key: key.to_string(), help: help.to_string(), shape, required: false }
+}
+
+fn required(key: &str, help: &str, shape: Shape) -> Field {
+    Field { required: true, ..field(key, help, shape) }
+}
+
+fn list(shape: Shape) -> Shape {
+    Shape::Array(Box::new(shape))
+}
+
+fn kinds() -> Vec<&'static str> {
+    FileKind::ALL.iter().map(|k| k.as_str()).collect()
+}
+
+/// The whole config.
+fn config() -> Shape {
+    let severity = || Shape::Choice(SEVERITIES.to_vec());
+    let allow = |help: &str| Shape::Table(vec![field("allow", help, list(Shape::Word))]);
+    let sections = kinds()
+        .into_iter()
+        .map(|kind| {
+            let policy = vec![
+                field("required", "sections each file must have", list(Shape::Word)),
+                field("forbidden", "sections no file may have", list(Shape::Word)),
+                field("order", "the order sections go in, where present", list(Shape::Word)),
+            ];
+            field(kind, &format!("section policy for {} files", kind), Shape::Table(policy))
+        })
+        .collect();
+    let count = || Shape::Integer(0, None);
+    let positive = || Shape::Integer(1, None);
+    Shape::Table(vec![
+        field(
+            "extends",
+            "a profile (strict, default, or relaxed), or the path or URL of a config to build on",
+            Shape::Word,
+        ),
+        field(
+            "plugins",
+            "plugin files or directories, relative to the config",
+            list(Shape::Word),
+        ),
+        field(
+            "exclude",
+            "globs for files left out of linting",
+            list(Shape::Glob),
+        ),
+        field(
+            "lossy_utf8",
+            "check files that are not UTF-8 with their invalid bytes replaced",
+            Shape::Boolean,
+        ),
+        field(
+            "line_endings",
+            "the line endings files may use",
+            Shape::Choice(vec!["lf", "crlf", "consistent"]),
+        ),
+        field(
+            "file_names",
+            "how agent, skill, and command names are written",
+            Shape::Choice(vec!["kebab-case", "snake_case", "lowercase"]),
+        ),
+        field("rules", "severities and options by rule ID or name", Shape::Rules),
+        field(
+            "override",
+            "rule settings for some files; later ones win",
+            list(Shape::Table(vec![
+                required("files", "globs for the files this applies to", list(Shape::Glob)),
+                field("rules", "severities and options by rule ID or name", Shape::Rules),
+            ])),
+        ),
+        field(
+            "custom",
+            "rules defined by a pattern or phrases",
+            list(Shape::Table(vec![
+                required("id", "the rule's ID, not starting with CL", Shape::Word),
+                required("name", "the rule's name", Shape::Word),
+                required(
+                    "kind",
+                    "the kinds of file it checks",
+                    Shape::OneOrMore(kinds()),
+                ),
+                field(
+                    "pattern",
+                    "a regular expression to look for",
+                    Shape::Pattern,
+                ),
+                field(
+                    "phrases",
+                    "phrases to look for, instead of a pattern",
+                    list(Shape::Word),
+                ),
+                field(
+                    "require",
+                    "report files without a match, instead of matches",
+                    Shape::Boolean,
+                ),
+                required(
+                    "message",
+                    "the finding's message; {match} is what matched",
+                    Shape::Text,
+                ),
+                field("severity", "warning unless set", severity()),
+                field("description", "what the rule is for, as rules lists it", Shape::Text),
+            ])),
+        ),
+        field("sections", "section policies by file kind", Shape::Table(sections)),
+        field(
+            "tokens",
+            "token budgets",
+            Shape::Table(vec![
+                field("max_per_file", "tokens one file may take (CL100)", count()),
+                field(
+                    "max_total",
+                    "tokens all files may take together (CL101)",
+                    count(),
+                ),
+                field(
+                    "max_eager",
+                    "tokens loaded at the start of every session (CL103)",
+                    count(),
+                ),
+                field(
+                    "max_eager_lines",
+                    "lines loaded at the start of every session (CL103)",
+                    count(),
+                ),
+                field(
+                    "chars_per_token",
+                    "characters per estimated token",
+                    Shape::Positive,
+                ),
+            ]),
+        ),
+        field(
+            "tools",
+            "tools agents may list",
+            allow("tool names beyond the ones Claude Code ships"),
+        ),
+        field(
+            "models",
+            "models agents may name",
+            allow("models beyond the aliases and Claude model IDs"),
+        ),
+        field(
+            "references",
+            "reference files",
+            Shape::Table(vec![
+                field(
+                    "optional",
+                    "phrasings that mark a reference optional (CL040)",
+                    list(Shape::Word),
+                ),
+                field(
+                    "allow",
+                    "globs for files other than Markdown references may hold (CL045)",
+                    list(Shape::Glob),
+                ),
+            ]),
+        ),
+        field(
+            "secrets",
+            "secret detection",
+            Shape::Table(vec![field(
+                "allow",
+                "patterns for strings CL130 lets through",
+                list(Shape::Pattern),
+            )]),
+        ),
+        field(
+            "frontmatter",
+            "frontmatter layout (CL112)",
+            Shape::Table(vec![
+                field("order", "keys in the order they go", list(Shape::Word)),
+                field(
+                    "quotes",
+                    "when string values are quoted",
+                    Shape::Choice(vec!["minimal", "double"]),
+                ),
+            ]),
+        ),
+        field(
+            "placeholders",
+            "severities by placeholder marker (CL140)",
+            Shape::Table(
+                placeholders::MARKERS
+                    .iter()
+                    .map(|&(m, _)| field(m, &format!("the severity of {}", m), severity()))
+                    .collect(),
+            ),
+        ),
+        field(
+            "spelling",
+            "the spellcheck (CL141)",
+            Shape::Table(vec![
+                field("words", "the project's words", list(Shape::Word)),
+                field(
+                    "dictionary",
+                    "a file of words, one a line, relative to the config",
+                    Shape::Word,
+                ),
+            ]),
+        ),
+        field(
+            "urls",
+            "link checking with --check-urls",
+            Shape::Table(vec![
+                field("timeout", "seconds to wait for each request", positive()),
+                field("concurrency", "requests in flight at once", positive()),
+                field("cache_hours", "hours an answer stays cached", positive()),
+                field("ignore", "URL prefixes that are never checked", list(Shape::Word)),
+            ]),
+        ),
+    ])
+}
+
+/// The options of a rule, as fields.
+fn rule_fields(rule: &Rule) -> Vec<Field> {
+    let mut fields = vec![field(
+        "severity",
+        "the rule's severity, or off",
+        Shape::Choice(SEVERITIES.to_vec()),
+    )];
+    for &(_, name, min, max) in OPTIONS.iter().filter(|&&(id, ..)| id == rule.id) {
+        let max = (max != usize::MAX).then_some(max as i64);
+        fields.push(field(name, "", Shape::Integer(min as i64, max)));
+    }
+    fields
+}
+
+/// Checks a parsed config against the shape, and returns every problem
+/// found, each with where it is.
+pub fn check(doc: &Table) -> Vec<String> {
+    let mut problems = Vec::new();
+    let Shape::Table(fields) = config() else {
+        unreachable!("the config is a table");
+    };
+    table(&fields, doc, "", &mut problems);
+    problems
+}
+
+fn table(fields: &[Field], doc: &Table, at: &str, out: &mut Vec<String>) {
+    let path = |key: &str| if at.is_empty() { key.to_string() } else { format!("{}.{}", at, key) };
+    for (key, value) in doc {
+        match fields.iter().find(|f| f.key == *key) {
+            Some(f) => walk(&f.shape, value, &path(key), out),
+            None if at.is_empty() => out.push(format!("unknown key '{}'", key)),
+            None => out.push(format!("{}: unknown key '{}'", at, key)),
+        }
+    }
+    for f in fields.iter().filter(|f| f.required && !doc.contains_key(&f.key)) {
+        out.push(format!("{}: missing '{}'", if at.is_empty() { "config" } else { at }, f.key));
+    }
+}
+
+fn walk(shape: &Shape, value: &Value, at: &str, out: &mut Vec<String>) {
+    let mut expected = |what: &str| {
+        out.push(format!(
+            "{}: expected {}, found {}",
+            at,
+            what,
+            value.type_name()
+        ))
+    };
+    match (shape, value) {
+        (Shape::Boolean, Value::Boolean(_)) => {}
+        (Shape::Boolean, _) => expected("boolean"),
+        (Shape::Integer(min, max), Value::Integer(n)) => {
+            if n < min || max.is_some_and(|max| *n > max) {
+                let max = max.map_or(usize::MAX, |m| m as usize);
+                out.push(format!("{}: {}", at, config::out_of_range(*min as usize, max)));
+            }
+        }
+        (Shape::Integer(..), _) => expected("integer"),
+        (Shape::Positive, Value::Integer(n)) if *n > 0 => {}
+        (Shape::Positive, Value::Float(f)) if f.is_finite() && *f > 0.0 => {}
+        (Shape::Positive, Value::Integer(_) | Value::Float(_)) => {
+            out.push(format!("{}: must be a positive number", at))
+        }
+        (Shape::Positive, _) => expected("number"),
+        (Shape::Text, Value::String(_)) => {}
+        (Shape::Word | Shape::Glob | Shape::Pattern, Value::String(s)) if s.trim().is_empty() => {
+            out.push(format!("{}: must not be empty", at));
+        }
+        (Shape::Word, Value::String(_)) => {}
+        (Shape::Glob, Value::String(s)) => {
+            if let Some(why) = glob::problem(s.trim()) {
+                out.push(format!("{}: glob '{}': {}", at, s, why));
+            }
+        }
+        (Shape::Pattern, Value::String(s)) => {
+            if let Err(e) = Regex::new(s) {
+                out.push(format!("{}: invalid pattern '{}': {}", at, s, e));
+            }
+        }
+        (Shape::Text | Shape::Word | Shape::Glob | Shape::Pattern, _) => expected("string"),
+        (Shape::Choice(choices), Value::String(s)) => {
+            if !choices.contains(&s.as_str()) {
+                out.push(format!("{}: invalid value '{}' (expected {})", at, s, or_list(choices)));
+            }
+        }
+        (Shape::Choice(_), _) => expected("string"),
+        (Shape::Array(item), Value::Array(items)) => {
+            for (i, v) in items.iter().enumerate() {
+                walk(item, v, &format!("{}[{}]", at, i), out);
+            }
+        }
+        (Shape::Array(_), _) => expected("array"),
+        (Shape::Table(fields), Value::Table(doc)) => table(fields, doc, at, out),
+        (Shape::Table(_), _) => expected("table"),
+        (Shape::OneOrMore(choices), Value::Array(items)) if !items.is_empty() => {
+            let choice = Shape::Choice(choices.clone());
+            for (i, v) in items.iter().enumerate() {
+                walk(&choice, v, &format!("{}[{}]", at, i), out);
+            }
+        }
+        (Shape::OneOrMore(choices), Value::String(_)) => {
+            walk(&Shape::Choice(choices.clone()), value, at, out)
+        }
+        (Shape::OneOrMore(_), _) => expected("string or non-empty array"),
+        (Shape::Rules, Value::Table(rules)) => {
+            let known = all_rules();
+            for (key, value) in rules {
+                let at = format!("{}.{}", at, key);
+                let Some(rule) = known.iter().find(|r| r.id == key || r.name == key) else {
+                    out.push(format!("{}: unknown rule '{}'", at, key));
+                    continue;
+                };
+                let fields = rule_fields(rule);
+                match value {
+                    Value::String(_) => walk(&fields[0].shape, value, &at, out),
+                    Value::Table(options) => table(&fields, options, &at, out),
+                    v => out.push(format!(
+                        "{}: expected string or table, found {}",
+                        at,
+                        v.type_name()
+                    )),
+                }
+            }
+        }
+        (Shape::Rules, _) => expected("table"),
+    }
+}
+
+/// A JSON value being built, for writing with indentation.
+enum Json {
+    Object(Vec<(String, Json)>),
+    Array(Vec<Json>),
+    String(String),
+    Number(i64),
+    Bool(bool),
+}
+
+fn object(members: Vec<(&str, Json)>) -> Json {
+    Json::Object(members.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn text(s: &str) -> Json {
+    Json::String(s.to_string())
+}
+
+fn strings(words: &[&str]) -> Json {
+    Json::Array(words.iter().map(|w| text(w)).collect())
+}
+
+/// Returns a JSON Schema for the config, for editors to complete and
+/// validate `.claude-lint.toml` with.
+pub fn json() -> String {
+    let mut schema = match to_json(&config(), None) {
+        Json::Object(members) => members,
+        _ => unreachable!("the config is a table"),
+    };
+    schema.insert(0, ("$schema".to_string(), text("https://json-schema.org/draft/2020-12/schema")));
+    schema.insert(1, ("title".to_string(), text(config::FILE_NAME)));
+    schema.insert(2, ("description".to_string(), text("claude-lint configuration")));
+    schema.push(("$defs".to_string(), Json::Object(rule_defs())));
+    let mut out = String::new();
+    write(&Json::Object(schema), 0, &mut out);
+    out.push('\n');
+    out
+}
+
+/// Definitions for rule settings: the `[rules]` table, and each built-in
+/// rule's settings, which its ID and its name both refer to.
+fn rule_defs() -> Vec<(String, Json)> {
+    let rule = |r: &Rule| {
+        let severity = object(vec![("enum", strings(SEVERITIES))]);
+        let options = to_json(&Shape::Table(rule_fields(r)), None);
+        object(vec![
+            ("description", text(r.description)),
+            ("anyOf", Json::Array(vec![severity, options])),
+        ])
+    };
+    let builtin: Vec<&Rule> = all_rules().into_iter().filter(|r| r.id.starts_with("CL")).collect();
+    let mut properties = Vec::new();
+    for r in &builtin {
+        let reference = || object(vec![("$ref", text(&format!("#/$defs/{}", r.id)))]);
+        properties.push((r.id.to_string(), reference()));
+        properties.push((r.name.to_string(), reference()));
+    }
+    // Rules from [[custom]] and plugins have no options, and only built-in
+    // IDs start with CL.
+    let custom = Rule {
+        id: "",
+        name: "",
+        severity: crate::Severity::Warning,
+        description: "a custom or plugin rule",
+    };
+    let ids: Vec<&str> = builtin.iter().map(|r| r.id).collect();
+    let names = object(vec![(
+        "anyOf",
+        Json::Array(vec![
+            object(vec![("enum", strings(&ids))]),
+            object(vec![("not", object(vec![("pattern", text("^CL[0-9]"))]))]),
+        ]),
+    )]);
+    let rules = object(vec![
+        ("type", text("object")),
+        ("properties", Json::Object(properties)),
+        ("propertyNames", names),
+        ("additionalProperties", rule(&custom)),
+    ]);
+    let mut defs = vec![("rules".to_string(), rules)];
+    defs.extend(builtin.iter().map(|r| (r.id.to_string(), rule(r))));
+    defs
+}
+
+fn to_json(shape: &Shape, help: Option<&str>) -> Json {
+    let mut members: Vec<(&str, Json)> = match shape {
+        Shape::Boolean => vec![("type", text("boolean"))],
+        Shape::Integer(min, max) => {
+            let mut m = vec![("type", text("integer")), ("minimum", Json::Number(*min))];
+            m.extend(max.map(|max| ("maximum", Json::Number(max))));
+            m
+        }
+        Shape::Positive => vec![("type", text("number")), ("exclusiveMinimum", Json::Number(0))],
+        Shape::Text => vec![("type", text("string"))],
+        Shape::Word | Shape::Glob => vec![("type", text("string")), ("pattern", text("\\S"))],
+        Shape::Pattern => vec![("type", text("string")), ("format", text("regex"))],
+        Shape::Choice(choices) => vec![("enum", strings(choices))],
+        Shape::Array(item) => vec![("type", text("array")), ("items", to_json(item, None))],
+        Shape::Table(fields) => {
+            let properties = fields
+                .iter()
+                .map(|f| (f.key.clone(), to_json(&f.shape, Some(&f.help))))
+                .collect();
+            let required: Vec<&str> = fields
+                .iter()
+                .filter(|f| f.required)
+                .map(|f| f.key.as_str())
+                .collect();
+            let mut m = vec![
+                ("type", text("object")),
+                ("properties", Json::Object(properties)),
+            ];
+            if !required.is_empty() {
+                m.push(("required", strings(&required)));
+            }
+            m.push(("additionalProperties", Json::Bool(false)));
+            m
+        }
+        Shape::OneOrMore(choices) => vec![(
+            "anyOf",
+            Json::Array(vec![
+                object(vec![("enum", strings(choices))]),
+                object(vec![
+                    ("type", text("array")),
+                    ("items", object(vec![("enum", strings(choices))])),
+                    ("minItems", Json::Number(1)),
+                ]),
+            ]),
+        )],
+        // Both places rule settings go share one definition.
+        Shape::Rules => vec![("$ref", text("#/$defs/rules"))],
+    };
+    if let Some(help) = help.filter(|h| !h.is_empty()) {
+        members.insert(0, ("description", text(help)));
+    }
+    object(members)
+}
+
+fn write(value: &Json, depth: usize, out: &mut String) {
+    let indent = |depth: usize| "  ".repeat(depth);
+    match value {
+        Json::Object(members) if members.is_empty() => out.push_str("{}"),
+        Json::Object(members) => {
+            out.push_str("{\n");
+            for (i, (key, value)) in members.iter().enumerate() {
+                out.push_str(&format!("{}{}: ", indent(depth + 1), json::string(key)));
+                write(value, depth + 1, out);
+                out.push_str(if i + 1 < members.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&format!("{}}}", indent(depth)));
+        }
+        // Lists of plain values fit on one line.
+        Json::Array(items)
+            if items
+                .iter()
+                .all(|v| matches!(v, Json::String(_) | Json::Number(_))) =>
+        {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write(item, depth, out);
+            }
+            out.push(']');
+        }
+        Json::Array(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&indent(depth + 1));
+                write(item, depth + 1, out);
+                out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&format!("{}]", indent(depth)));
+        }
+        Json::String(s) => out.push_str(&json::string(s)),
+        Json::Number(n) => out.push_str(&n.to_string()),
+        Json::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+    }
+}
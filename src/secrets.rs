@@ -0,0 +1,158 @@
+//! Credentials pasted into context files.
+//!
+//! Context files are committed and sent to the model, so a key in one
+//! has leaked twice. Well-known token formats are matched by pattern;
+//! anything else long and random enough to be a key is caught by its
+//! entropy. Findings show only the start of what matched. Strings the
+//! config's `[secrets] allow` patterns match are left alone.
+
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::regex::Regex;
+use crate::rules::{Check, Document, FileKind};
+use crate::{Diagnostic, Span};
+
+/// Token formats, by what they are.
+const PATTERNS: &[(&str, &str)] = &[
+    ("AWS access key ID", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("AWS secret access key", r"(?i)aws_?secret_?access_?key\W{1,4}[A-Za-z0-9/+=]{40}"),
+    ("GitHub token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("GitHub token", r"\bgithub_pat_[A-Za-z0-9_]{22,}\b"),
+    ("Anthropic API key", r"\bsk-ant-[A-Za-z0-9_-]{20,}"),
+    ("OpenAI API key", r"\bsk-(?:proj-)?[A-Za-z0-9]{32,}\b"),
+    ("Slack token", r"\bxox[abposr]-[A-Za-z0-9-]{10,}"),
+    ("Stripe key", r"\b[rs]k_live_[A-Za-z0-9]{20,}\b"),
+    ("Google API key", r"\bAIza[0-9A-Za-z_-]{35}"),
+    ("private key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+];
+
+/// Runs of key-like chars shorter than this are not judged by entropy.
+const MIN_RANDOM_LEN: usize = 32;
+
+/// Bits per char above which a run reads as random. Hex, at four bits
+/// at most, stays under it, so hashes and UUIDs pass.
+const MIN_ENTROPY: f64 = 4.3;
+
+fn patterns() -> &'static [(&'static str, Regex)] {
+    static COMPILED: OnceLock<Vec<(&str, Regex)>> = OnceLock::new();
+    COMPILED.get_or_init(|| {
+        PATTERNS
+            .iter()
+            .map(|&(what, p)| (what, Regex::new(p).expect("built-in patterns are valid")))
+            .collect()
+    })
+}
+
+/// Flags credentials in Markdown files, code blocks included.
+pub(crate) struct Secrets<'a>(pub &'a Config);
+
+impl Check for Secrets<'_> {
+    fn id(&self) -> &'static str {
+        "CL130"
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let mut diags = Vec::new();
+        scan(doc.path, doc.content, self.0, &mut diags);
+        diags
+    }
+}
+
+/// Flags credentials in a file that is not Markdown, such as settings,
+/// if it exists.
+pub(crate) fn check_file(path: &Path, config: &Config, diags: &mut Vec<Diagnostic>) {
+    if let Ok(content) = fs::read_to_string(path) {
+        scan(path, &content, config, diags);
+    }
+}
+
+fn scan(path: &Path, content: &str, config: &Config, diags: &mut Vec<Diagnostic>) {
+    for (i, line) in content.lines().enumerate() {
+        let mut found: Vec<(usize, usize, &str)> = Vec::new();
+        for (what, re) in patterns() {
+            for (start, end) in re.find_all(line).expect("built-in patterns stay within budget") {
+                if !found.iter().any(|&(s, e, _)| start < e && s < end) {
+                    found.push((start, end, what));
+                }
+            }
+        }
+        for (start, end) in random_runs(line) {
+            if !found.iter().any(|&(s, e, _)| start < e && s < end) {
+                found.push((start, end, "high-entropy string"));
+            }
+        }
+        found.sort_by_key(|&(start, _, _)| start);
+        for (start, end, what) in found {
+            let text = &line[start..end];
+            if config.secret_allowed(text) {
+                continue;
+            }
+            let shown: String = text.chars().take(4).collect();
+            let msg = format!(
+                "possible {} '{}...'; keep credentials out of context files",
+                what, shown
+            );
+            let span = Span {
+                line: i + 1,
+                column: line[..start].chars().count() + 1,
+                length: text.chars().count(),
+            };
+            diags.push(Diagnostic::of(path, "CL130", msg).at(Some(span)));
+        }
+    }
+}
+
+/// Finds runs of base64 and token chars that are long, mix letters and
+/// digits, and are random enough to be a key.
+fn random_runs(line: &str) -> Vec<(usize, usize)> {
+    // A slash ends a run, so paths and URLs are judged by component.
+    let key_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '=' | '_' | '-');
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (key_char(c), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if random(&line[s..i]) {
+                    out.push((s, i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn random(run: &str) -> bool {
+    let run = run.trim_end_matches('=');
+    if run.len() < MIN_RANDOM_LEN {
+        return false;
+    }
+    let has = |f: fn(&char) -> bool| run.chars().any(|c| f(&c));
+    if !(has(char::is_ascii_uppercase)
+        && has(char::is_ascii_lowercase)
+        && has(char::is_ascii_digit))
+    {
+        return false;
+    }
+    let mut counts = [0usize; 128];
+    for b in run.bytes() {
+        counts[usize::from(b & 0x7f)] += 1;
+    }
+    let len = run.len() as f64;
+    let entropy: f64 = counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| n as f64 / len)
+        .map(|p| -p * p.log2())
+        .sum();
+    entropy >= MIN_ENTROPY
+}
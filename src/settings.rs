@@ -0,0 +1,227 @@
+//! Checks for `settings.json` and `settings.local.json`.
+//!
+//! Claude Code skips a settings file it cannot parse without saying much,
+//! so these checks report parse errors with positions, unknown keys, and
+//! the shape of permissions and hooks.
+
+use std::path::Path;
+
+use crate::json::{self, Member, Pos, Value};
+use crate::{hooks, rules, Diagnostic, Span};
+
+pub const FILES: &[&str] = &["settings.json", "settings.local.json"];
+
+/// Top-level keys Claude Code understands, with the JSON type of each.
+const KNOWN_KEYS: &[(&str, &str)] = &[
+    ("$schema", "string"),
+    ("apiKeyHelper", "string"),
+    ("awsAuthRefresh", "string"),
+    ("awsCredentialExport", "string"),
+    ("cleanupPeriodDays", "number"),
+    ("companyAnnouncements", "array"),
+    ("disableAllHooks", "boolean"),
+    ("disabledMcpjsonServers", "array"),
+    ("enableAllProjectMcpServers", "boolean"),
+    ("enabledMcpjsonServers", "array"),
+    ("env", "object"),
+    ("forceLoginMethod", "string"),
+    ("forceLoginOrgUUID", "string"),
+    ("hooks", "object"),
+    ("includeCoAuthoredBy", "boolean"),
+    ("model", "string"),
+    ("otelHeadersHelper", "string"),
+    ("outputStyle", "string"),
+    ("permissions", "object"),
+    ("statusLine", "object"),
+];
+
+const PERMISSION_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions"];
+
+pub fn check_settings(root: &Path, diags: &mut Vec<Diagnostic>) {
+    for name in FILES {
+        let path = root.join(name);
+        if !path.is_file() {
+            continue;
+        }
+        let Some(content) = rules::read(&path, false, diags) else {
+            continue;
+        };
+        check_file(root, &path, &content, diags);
+    }
+}
+
+fn check_file(root: &Path, path: &Path, content: &str, diags: &mut Vec<Diagnostic>) {
+    let doc = match json::parse(content) {
+        Ok(doc) => doc,
+        Err(e) => {
+            diags.push(
+                Diagnostic::of(path, "CL060", format!("invalid JSON: {}", e.message))
+                    .at(span(e.pos, 1)),
+            );
+            return;
+        }
+    };
+    let Some(members) = doc.as_object() else {
+        diags.push(type_error(path, "settings", "an object", &doc));
+        return;
+    };
+
+    for m in members {
+        let Some(&(_, kind)) = KNOWN_KEYS.iter().find(|(k, _)| *k == m.key) else {
+            diags.push(unknown_key(path, "", m));
+            continue;
+        };
+        if m.value.type_name() != kind {
+            diags.push(type_error(path, &m.key, article(kind), &m.value));
+            continue;
+        }
+        match m.key.as_str() {
+            "permissions" => check_permissions(path, &m.value, diags),
+            "hooks" => hooks::check_hooks(root, path, &m.value, diags),
+            "env" => {
+                for var in m.value.as_object().unwrap_or_default() {
+                    if var.value.as_str().is_none() {
+                        diags.push(type_error(
+                            path,
+                            &format!("env.{}", var.key),
+                            "a string",
+                            &var.value,
+                        ));
+                    }
+                }
+            }
+            "companyAnnouncements" | "enabledMcpjsonServers" | "disabledMcpjsonServers" => {
+                check_string_array(path, &m.key, &m.value, diags);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_permissions(path: &Path, value: &Value, diags: &mut Vec<Diagnostic>) {
+    for m in value.as_object().unwrap_or_default() {
+        let name = format!("permissions.{}", m.key);
+        match m.key.as_str() {
+            "allow" | "ask" | "deny" => {
+                check_string_array(path, &name, &m.value, diags);
+                for rule in m.value.as_array().unwrap_or_default() {
+                    let Some(text) = rule.as_str() else {
+                        continue;
+                    };
+                    if let Err(why) = permission_rule_syntax(text) {
+                        diags.push(Diagnostic::of(
+                            path,
+                            "CL063",
+                            format!("invalid permission rule '{}' in {}: {}", text, name, why),
+                        ).at(span(rule.pos, text.chars().count() + 2)));
+                    }
+                }
+            }
+            "additionalDirectories" => {
+                check_string_array(path, &name, &m.value, diags);
+            }
+            "defaultMode" => match m.value.as_str() {
+                Some(mode) if PERMISSION_MODES.contains(&mode) => {}
+                Some(mode) => diags.push(Diagnostic::of(
+                    path,
+                    "CL062",
+                    format!(
+                        "{} '{}' is not one of {}",
+                        name,
+                        mode,
+                        PERMISSION_MODES.join(", ")
+                    ),
+                ).at(span(m.value.pos, mode.chars().count() + 2))),
+                None => diags.push(type_error(path, &name, "a string", &m.value)),
+            },
+            "disableBypassPermissionsMode" if m.value.as_str().is_none() => {
+                diags.push(type_error(path, &name, "a string", &m.value));
+            }
+            "disableBypassPermissionsMode" => {}
+            _ => diags.push(unknown_key(path, "permissions.", m)),
+        }
+    }
+}
+
+/// Checks a permission rule: a tool name, optionally followed by a
+/// parenthesized, non-empty specifier, as in `Bash(npm run test:*)`.
+pub fn permission_rule_syntax(rule: &str) -> Result<(), &'static str> {
+    if rule.trim() != rule {
+        return Err("leading or trailing whitespace");
+    }
+    let (tool, spec) = match rule.find('(') {
+        Some(open) => {
+            let Some(inner) = rule[open + 1..].strip_suffix(')') else {
+                return Err("specifier must end with ')'");
+            };
+            (&rule[..open], Some(inner))
+        }
+        None => (rule, None),
+    };
+    if tool.is_empty() {
+        return Err("missing tool name");
+    }
+    let mut chars = tool.chars();
+    let first_ok = chars.next().is_some_and(|c| c.is_ascii_alphabetic());
+    if !first_ok || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err("tool name must be letters, digits, '_' or '-'");
+    }
+    if let Some(spec) = spec {
+        if spec.is_empty() {
+            return Err("empty specifier; drop the parentheses to match every use");
+        }
+        let mut depth = 0i32;
+        for c in spec.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            if depth < 0 {
+                return Err("unbalanced parentheses");
+            }
+        }
+        if depth != 0 {
+            return Err("unbalanced parentheses");
+        }
+    }
+    Ok(())
+}
+
+/// Reports a value that is not an array of strings.
+fn check_string_array(path: &Path, name: &str, value: &Value, diags: &mut Vec<Diagnostic>) {
+    let Some(items) = value.as_array() else {
+        diags.push(type_error(path, name, "an array of strings", value));
+        return;
+    };
+    for item in items.iter().filter(|item| item.as_str().is_none()) {
+        diags.push(type_error(path, name, "an array of strings", item));
+    }
+}
+
+pub(crate) fn span(pos: Pos, length: usize) -> Option<Span> {
+    Some(Span { line: pos.line, column: pos.column, length })
+}
+
+fn article(kind: &str) -> &'static str {
+    match kind {
+        "array" => "an array",
+        "object" => "an object",
+        "boolean" => "a boolean",
+        "number" => "a number",
+        _ => "a string",
+    }
+}
+
+pub(crate) fn type_error(path: &Path, name: &str, expected: &str, found: &Value) -> Diagnostic {
+    Diagnostic::of(
+        path,
+        "CL062",
+        format!("{} should be {}, found {}", name, expected, found.type_name()),
+    ).at(span(found.pos, 1))
+}
+
+fn unknown_key(path: &Path, prefix: &str, m: &Member) -> Diagnostic {
+    Diagnostic::of(path, "CL061", format!("unknown settings key '{}{}'", prefix, m.key))
+        .at(span(m.key_pos, m.key.chars().count() + 2))
+}
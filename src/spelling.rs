@@ -0,0 +1,194 @@
+//! Spelling in prose and descriptions, when the config turns it on.
+//!
+//! A misspelled word in a description is still read as a word, and the
+//! model guesses what it meant. Words are checked against a bundled
+//! English list, Claude Code's own vocabulary, and the project's words
+//! from `[spelling]`. Code spans, link targets, and URLs are skipped, as
+//! are words with digits, underscores, internal capitals, or all
+//! capitals, which are names or acronyms rather than prose. Frontmatter
+//! is skipped except for the description.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use crate::markdown;
+use crate::prose;
+use crate::rules::{Check, Document, FileKind};
+use crate::{Diagnostic, Span};
+
+/// Words Claude Code and its files use that the English list lacks.
+const TERMS: &[&str] = &[
+    "frontmatter",
+    "toml",
+    "linter",
+    "linters",
+    "kebab",
+    "subcommand",
+    "subcommands",
+    "changelog",
+    "epistemics",
+    "autofix",
+];
+
+/// The bundled English words, by how common each is, most common first.
+fn english() -> &'static HashMap<&'static str, usize> {
+    static WORDS: OnceLock<HashMap<&str, usize>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        include_str!("words.txt")
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .enumerate()
+            .map(|(i, w)| (w, i))
+            .collect()
+    })
+}
+
+/// Flags words no dictionary knows. It takes the project's words, in
+/// lowercase; None turns it off.
+pub(crate) struct Spelling<'a>(pub &'a Option<HashSet<String>>);
+
+impl Check for Spelling<'_> {
+    fn id(&self) -> &'static str {
+        "CL141"
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let Some(project) = self.0 else {
+            return Vec::new();
+        };
+        let mut lines: Vec<(usize, String)> = Vec::new();
+        if let Some(Ok(fm)) = &doc.frontmatter {
+            if let Some(field) = fm.get("description") {
+                let span = fm.lines_of(field);
+                for (i, text) in doc
+                    .content
+                    .lines()
+                    .enumerate()
+                    .take(span.end - 1)
+                    .skip(span.start - 1)
+                {
+                    let value = if i + 1 == field.line {
+                        text.find(':').map_or(0, |c| c + 1)
+                    } else {
+                        0
+                    };
+                    lines.push((i + 1, " ".repeat(value) + &text[value..]));
+                }
+            }
+        }
+        for line in doc.markdown.lines.iter().filter(|l| prose::is_prose(l.kind)) {
+            let mut masked = prose::mask(line.text);
+            for link in markdown::links(line.text) {
+                let start = link.target.as_ptr() as usize - line.text.as_ptr() as usize;
+                masked.replace_range(
+                    start..start + link.target.len(),
+                    &" ".repeat(link.target.len()),
+                );
+            }
+            lines.push((line.number, masked));
+        }
+
+        let mut diags = Vec::new();
+        for (number, text) in lines {
+            for (start, word) in words(&text) {
+                if known(word, project) {
+                    continue;
+                }
+                let msg = match suggestion(word, project) {
+                    Some(s) => format!("unknown word '{}'; did you mean '{}'?", word, s),
+                    None => format!("unknown word '{}'", word),
+                };
+                let span = Span {
+                    line: number,
+                    column: text[..start].chars().count() + 1,
+                    length: word.chars().count(),
+                };
+                diags.push(Diagnostic::of(doc.path, self.id(), msg).at(Some(span)));
+            }
+        }
+        diags
+    }
+}
+
+/// Splits text into the words worth checking, with their byte offsets.
+/// Hyphenated words are checked part by part.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    let mut from = 0;
+    for token in text.split_whitespace() {
+        let at = from + text[from..].find(token).unwrap_or(0);
+        from = at + token.len();
+        let trimmed = token.trim_start_matches(|c: char| !c.is_alphanumeric());
+        let lead = token.len() - trimmed.len();
+        let trimmed = trimmed.trim_end_matches(|c: char| !c.is_alphanumeric());
+        if trimmed.contains(|c: char| !(c.is_ascii_alphabetic() || matches!(c, '-' | '\'' | '’'))) {
+            continue;
+        }
+        let mut offset = at + lead;
+        for part in trimmed.split('-') {
+            let word = part
+                .strip_suffix("'s")
+                .or_else(|| part.strip_suffix("’s"))
+                .unwrap_or(part);
+            if word.chars().count() > 1
+                && word
+                    .chars()
+                    .skip(1)
+                    .all(|c| c.is_ascii_lowercase() || c == '\'' || c == '’')
+            {
+                out.push((offset, word));
+            }
+            offset += part.len() + 1;
+        }
+    }
+    out
+}
+
+fn known(word: &str, project: &HashSet<String>) -> bool {
+    let lower = word.to_lowercase().replace('’', "'");
+    english().contains_key(lower.as_str())
+        || TERMS.contains(&lower.as_str())
+        || project.contains(&lower)
+}
+
+/// Returns the most common known word one edit away: a letter added,
+/// dropped, changed, or two swapped. A capitalized word gets a
+/// capitalized suggestion.
+fn suggestion(word: &str, project: &HashSet<String>) -> Option<String> {
+    let lower: Vec<char> = word.to_lowercase().chars().collect();
+    let mut candidates: Vec<Vec<char>> = Vec::new();
+    for i in 0..=lower.len() {
+        if i < lower.len() {
+            candidates.push([&lower[..i], &lower[i + 1..]].concat());
+        }
+        if i + 1 < lower.len() {
+            let mut swapped = lower.clone();
+            swapped.swap(i, i + 1);
+            candidates.push(swapped);
+        }
+        for c in 'a'..='z' {
+            candidates.push([&lower[..i], &[c], &lower[i..]].concat());
+            if i < lower.len() {
+                candidates.push([&lower[..i], &[c], &lower[i + 1..]].concat());
+            }
+        }
+    }
+    let rank = |w: &str| if project.contains(w) { Some(0) } else { english().get(w).copied() };
+    let best = candidates
+        .iter()
+        .map(|c| c.iter().collect::<String>())
+        .filter_map(|c| rank(&c).map(|r| (r, c)))
+        .min()?
+        .1;
+    let mut chars = best.chars();
+    match (word.chars().next(), chars.next()) {
+        (Some(first), Some(c)) if first.is_uppercase() => {
+            Some(c.to_uppercase().chain(chars).collect())
+        }
+        _ => Some(best),
+    }
+}
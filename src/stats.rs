@@ -0,0 +1,144 @@
+//! The inventory behind `claude-lint stats`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::frontmatter;
+use crate::jobs;
+use crate::json;
+use crate::markdown;
+use crate::readability::Metrics;
+use crate::rules::FileKind;
+
+/// One Markdown file in a .claude directory.
+pub struct File {
+    pub path: PathBuf,
+    pub kind: FileKind,
+    /// The frontmatter `name`, if set.
+    pub name: Option<String>,
+    /// The frontmatter `description`, if set.
+    pub description: Option<String>,
+    pub lines: usize,
+    pub tokens: usize,
+    /// Sentence, word, and syllable counts of its prose.
+    pub prose: Metrics,
+}
+
+/// File, line, and token counts over a set of files.
+#[derive(Clone, Copy, Default)]
+pub struct Totals {
+    pub files: usize,
+    pub lines: usize,
+    pub tokens: usize,
+}
+
+pub struct Inventory {
+    pub root: PathBuf,
+    /// Files in work-list order. Unreadable ones are left out.
+    pub files: Vec<File>,
+}
+
+impl Inventory {
+    /// Reads every Markdown file in a .claude directory that the config
+    /// does not exclude, estimating tokens with its budget's ratio.
+    pub fn collect(root: &Path, config: &Config) -> Self {
+        let budget = &config.tokens;
+        let files = jobs::documents(root, &config.exclude)
+            .into_iter()
+            .filter_map(|(path, kind)| {
+                let content = fs::read_to_string(&path).ok()?;
+                let fields = frontmatter::parse(&content).and_then(Result::ok);
+                let field = |key| fields.as_ref()?.string(key).map(str::to_string);
+                Some(File {
+                    name: field("name"),
+                    description: field("description"),
+                    lines: content.lines().count(),
+                    tokens: budget.estimate(&content),
+                    prose: Metrics::measure(&markdown::parse(&content)),
+                    path,
+                    kind,
+                })
+            })
+            .collect();
+        Inventory { root: root.to_path_buf(), files }
+    }
+
+    /// Sums the files of one kind, or of all kinds.
+    pub fn totals(&self, kind: Option<FileKind>) -> Totals {
+        let mut totals = Totals::default();
+        for f in self.files.iter().filter(|f| kind.is_none_or(|k| f.kind == k)) {
+            totals.files += 1;
+            totals.lines += f.lines;
+            totals.tokens += f.tokens;
+        }
+        totals
+    }
+
+    /// Returns up to n files, most tokens first.
+    pub fn largest(&self, n: usize) -> Vec<&File> {
+        let mut files: Vec<&File> = self.files.iter().collect();
+        files.sort_by(|a, b| b.tokens.cmp(&a.tokens).then_with(|| a.path.cmp(&b.path)));
+        files.truncate(n);
+        files
+    }
+
+    /// Renders the inventory as a single JSON document, with totals per
+    /// kind, the n largest files, and every file with its readability.
+    ///
+    /// The schema is versioned like diagnostics output.
+    pub fn json(&self, n: usize) -> String {
+        let path = |p: &Path| json::string(&p.to_string_lossy());
+        let totals = |t: Totals| {
+            format!(
+                "\"files\":{},\"lines\":{},\"tokens\":{}",
+                t.files, t.lines, t.tokens
+            )
+        };
+        let optional = |s: &Option<String>| {
+            s.as_deref()
+                .map_or_else(|| "null".to_string(), json::string)
+        };
+        let kinds: Vec<String> = FileKind::ALL
+            .iter()
+            .map(|&k| {
+                format!(
+                    "{{\"kind\":{},{}}}",
+                    json::string(k.as_str()),
+                    totals(self.totals(Some(k)))
+                )
+            })
+            .collect();
+        let largest: Vec<String> = self.largest(n).iter().map(|f| path(&f.path)).collect();
+        let files: Vec<String> = self
+            .files
+            .iter()
+            .map(|f| {
+                format!(
+                    "{{\"path\":{},\"kind\":{},\"name\":{},\"description\":{},\"lines\":{},\
+                     \"tokens\":{},\"sentences\":{},\"words\":{},\"sentence_length\":{:.1},\
+                     \"grade\":{:.1}}}",
+                    path(&f.path),
+                    json::string(f.kind.as_str()),
+                    optional(&f.name),
+                    optional(&f.description),
+                    f.lines,
+                    f.tokens,
+                    f.prose.sentences,
+                    f.prose.words,
+                    f.prose.sentence_length(),
+                    f.prose.grade(),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"version\":1,\"root\":{},\"total\":{{{}}},\"kinds\":[{}],\"largest\":[{}],\
+             \"files\":[{}]}}",
+            path(&self.root),
+            totals(self.totals(None)),
+            kinds.join(","),
+            largest.join(","),
+            files.join(","),
+        )
+    }
+}
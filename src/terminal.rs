@@ -0,0 +1,174 @@
+//! Text output for terminals: colored severities, and the source line
+//! under each finding with its span underlined.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+use crate::{Diagnostic, Severity};
+
+/// The most chars of a source line a snippet shows, such as of a
+/// minified JSON file on one line.
+const SNIPPET_WIDTH: usize = 100;
+
+/// How many chars before a span a cut snippet keeps.
+const SNIPPET_LEAD: usize = 30;
+
+/// When to color output, from `--color`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Color {
+    /// Whether to color stderr. Auto colors a terminal unless NO_COLOR is
+    /// set to anything but the empty string.
+    pub fn enabled(self) -> bool {
+        match self {
+            Color::Always => true,
+            Color::Never => false,
+            Color::Auto => {
+                io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        }
+    }
+}
+
+/// ANSI styling, applied only when enabled.
+#[derive(Clone, Copy, Debug)]
+pub struct Paint(pub bool);
+
+impl Paint {
+    /// A severity's label in its color.
+    pub fn severity(self, severity: Severity) -> String {
+        self.wrap(color(severity), severity.as_str())
+    }
+
+    pub fn bold(self, text: &str) -> String {
+        self.wrap("1", text)
+    }
+
+    /// Gutter text, such as line numbers.
+    pub fn gutter(self, text: &str) -> String {
+        self.wrap("1;34", text)
+    }
+
+    fn wrap(self, code: &str, text: &str) -> String {
+        if self.0 {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "1;31",
+        Severity::Warning => "1;33",
+        Severity::Info => "1;36",
+    }
+}
+
+/// Renders a diagnostic's line of source with carets under its span, as
+/// lines to print after the diagnostic. Empty when there is no span or
+/// the line is not in the source. A long line is cut to a window around
+/// the span, with ellipses where it was cut.
+pub fn snippet(d: &Diagnostic, source: &str, paint: Paint) -> String {
+    let Some(span) = d.span else {
+        return String::new();
+    };
+    // Columns do not count a byte order mark.
+    let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+    let Some(line) = source.lines().nth(span.line - 1) else {
+        return String::new();
+    };
+    let chars: Vec<char> = line.chars().collect();
+    let start = (span.column - 1).min(chars.len());
+    let (lo, hi) = if chars.len() <= SNIPPET_WIDTH {
+        (0, chars.len())
+    } else {
+        let lo = start.saturating_sub(SNIPPET_LEAD).min(chars.len() - SNIPPET_WIDTH);
+        (lo, lo + SNIPPET_WIDTH)
+    };
+    let end = (start + span.length).min(hi);
+    let (open, close) = (
+        if lo > 0 { "..." } else { "" },
+        if hi < chars.len() { "..." } else { "" },
+    );
+    let before: String = open
+        .chars()
+        .chain(chars[lo..start].iter().copied())
+        .collect();
+    let marked: String = chars[start..end].iter().collect();
+    let shown: String = chars[lo..hi].iter().collect();
+    // The pad is the text before the span blanked out, keeping its tabs,
+    // so the carets land under the span however the line renders.
+    let pad: String = before
+        .chars()
+        .map(|c| if c == '\t' { "\t".to_string() } else { " ".repeat(width(c)) })
+        .collect();
+    // A span past the end of the line, or an empty one, still gets a caret.
+    let carets = "^".repeat(marked.chars().map(width).sum::<usize>().max(1));
+
+    let number = span.line.to_string();
+    let indent = " ".repeat(number.len());
+    format!(
+        "{} {}\n{} {} {}{}{}\n{} {} {}{}\n",
+        indent,
+        paint.gutter("|"),
+        paint.gutter(&number),
+        paint.gutter("|"),
+        open,
+        shown.trim_end(),
+        close,
+        indent,
+        paint.gutter("|"),
+        pad,
+        paint.wrap(color(d.severity), &carets),
+    )
+}
+
+/// The columns a char takes in a terminal: two for East Asian wide and
+/// fullwidth text and most emoji, one for the rest.
+fn width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Span;
+    use std::path::Path;
+
+    fn carets(source: &str, column: usize, length: usize) -> String {
+        let span = Span { line: 1, column, length };
+        let d = Diagnostic::of(Path::new("CLAUDE.md"), "CL005", String::new()).at(Some(span));
+        let out = snippet(&d, source, Paint(false));
+        out.lines().nth(2).unwrap().to_string()
+    }
+
+    #[test]
+    fn pads_carets_by_rendered_width() {
+        assert_eq!(carets("日本語 step 1", 5, 6), "  |        ^^^^^^");
+        assert_eq!(carets("\t- step 1", 4, 6), "  | \t  ^^^^^^");
+        assert_eq!(carets("see 日本", 5, 2), "  |     ^^^^");
+    }
+}
@@ -0,0 +1,75 @@
+//! Where a run spends its time, for `--timings`.
+//!
+//! Each check is timed on each file, and each job on the file it checks,
+//! so a slow custom rule or an outsized file stands out. Times add up
+//! across files and threads. Nothing is recorded until recording is
+//! turned on.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static ON: AtomicBool = AtomicBool::new(false);
+static RULES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+static FILES: Mutex<Vec<(PathBuf, Duration)>> = Mutex::new(Vec::new());
+
+/// Time spent per rule and per file, slowest first.
+#[derive(Debug, Default)]
+pub struct Timings {
+    pub rules: Vec<(&'static str, Duration)>,
+    pub files: Vec<(PathBuf, Duration)>,
+}
+
+impl Timings {
+    /// Keeps the n slowest of each.
+    pub fn truncate(&mut self, n: usize) {
+        self.rules.truncate(n);
+        self.files.truncate(n);
+    }
+}
+
+/// Starts recording.
+pub fn enable() {
+    ON.store(true, Ordering::Relaxed);
+}
+
+pub(crate) fn enabled() -> bool {
+    ON.load(Ordering::Relaxed)
+}
+
+pub(crate) fn rule(id: &'static str, took: Duration) {
+    if enabled() {
+        RULES.lock().expect("no check panicked while recording").push((id, took));
+    }
+}
+
+pub(crate) fn file(path: &Path, took: Duration) {
+    if enabled() {
+        FILES.lock().expect("no job panicked while recording").push((path.to_path_buf(), took));
+    }
+}
+
+/// Returns what was recorded so far, totaled, and starts over.
+pub fn take() -> Timings {
+    Timings {
+        rules: total(std::mem::take(
+            &mut *RULES.lock().expect("no check panicked while recording"),
+        )),
+        files: total(std::mem::take(
+            &mut *FILES.lock().expect("no job panicked while recording"),
+        )),
+    }
+}
+
+/// Adds up the times per key and sorts them slowest first, ties by key.
+fn total<K: std::hash::Hash + Eq + Ord>(times: Vec<(K, Duration)>) -> Vec<(K, Duration)> {
+    let mut totals: HashMap<K, Duration> = HashMap::new();
+    for (key, took) in times {
+        *totals.entry(key).or_default() += took;
+    }
+    let mut out: Vec<(K, Duration)> = totals.into_iter().collect();
+    out.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    out
+}
@@ -0,0 +1,186 @@
+//! Token estimates for context files.
+//!
+//! Claude's tokenizer is not public, so counts are estimated: a run of
+//! ASCII letters and digits costs a token per `chars_per_token` chars,
+//! rounded up, and any other visible char, such as punctuation, Markdown
+//! syntax, or a non-Latin letter, a token of its own. Whitespace is free.
+//! The estimate errs high rather than low.
+//!
+//! Most of a directory loads only when it is used, but some is in
+//! context from the start of every session: the CLAUDE.md files beside
+//! and in `.claude`, with what they import, the frontmatter of each agent,
+//! and each skill's description. That part has a budget of its own.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter;
+use crate::ignore::Ignore;
+use crate::imports;
+use crate::jobs;
+use crate::rules::{Check, Document, FileKind};
+use crate::Diagnostic;
+
+/// Token limits and the estimate's ratio, from the `[tokens]` table.
+#[derive(Clone, Copy, Debug)]
+pub struct Budget {
+    /// The most one context file may cost.
+    pub max_per_file: usize,
+    /// The most a directory's context files may cost together.
+    pub max_total: usize,
+    /// The most what loads at the start of every session may cost.
+    pub max_eager: usize,
+    /// The most lines what loads at the start of every session may have.
+    pub max_eager_lines: Option<usize>,
+    /// Letters and digits per token.
+    pub chars_per_token: f64,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget {
+            max_per_file: 5000,
+            max_total: 50000,
+            max_eager: 20000,
+            max_eager_lines: None,
+            chars_per_token: 4.0,
+        }
+    }
+}
+
+impl Budget {
+    /// Estimates the tokens in a text.
+    pub fn estimate(&self, text: &str) -> usize {
+        let words = |run: usize| (run as f64 / self.chars_per_token).ceil() as usize;
+        let mut tokens = 0;
+        let mut run = 0;
+        for c in text.chars() {
+            if c.is_ascii_alphanumeric() {
+                run += 1;
+                continue;
+            }
+            tokens += words(run);
+            run = 0;
+            if !c.is_whitespace() {
+                tokens += 1;
+            }
+        }
+        tokens + words(run)
+    }
+}
+
+/// Reports files over the per-file budget.
+pub(crate) struct FileBudget(pub Budget);
+
+impl Check for FileBudget {
+    fn id(&self) -> &'static str {
+        "CL100"
+    }
+
+    fn applies_to(&self, _kind: FileKind) -> bool {
+        true
+    }
+
+    fn check(&self, doc: &Document) -> Vec<Diagnostic> {
+        let tokens = self.0.estimate(doc.content);
+        if tokens <= self.0.max_per_file {
+            return Vec::new();
+        }
+        let msg = format!(
+            "about {} tokens, over the budget of {} per file",
+            tokens, self.0.max_per_file
+        );
+        vec![Diagnostic::of(doc.path, self.id(), msg)]
+    }
+}
+
+/// Reports a directory whose files together are over the total budget.
+pub(crate) fn check_total(
+    root: &Path,
+    budget: &Budget,
+    exclude: &Ignore,
+    diags: &mut Vec<Diagnostic>,
+) {
+    let total: usize = jobs::documents(root, exclude)
+        .iter()
+        .filter_map(|(path, _)| fs::read_to_string(path).ok())
+        .map(|content| budget.estimate(&content))
+        .sum();
+    if total > budget.max_total {
+        let msg = format!(
+            "context files total about {} tokens, over the budget of {}",
+            total, budget.max_total
+        );
+        diags.push(Diagnostic::of(root, "CL101", msg));
+    }
+}
+
+/// Reports a directory whose eagerly loaded context is over its token
+/// budget or line limit.
+pub(crate) fn check_eager(
+    root: &Path,
+    budget: &Budget,
+    exclude: &Ignore,
+    diags: &mut Vec<Diagnostic>,
+) {
+    // Tokens and lines of CLAUDE.md, agents, and skills, in that order.
+    let mut parts = [(0, 0); 3];
+    let mut add = |i: usize, text: &str| {
+        parts[i].0 += budget.estimate(text);
+        parts[i].1 += text.lines().count();
+    };
+    let mut read: Vec<PathBuf> = Vec::new();
+    for (path, kind) in jobs::documents(root, exclude) {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        match kind {
+            // Nested CLAUDE.md files load when Claude Code works there.
+            FileKind::ClaudeMd if path.parent() == Some(root) || path.parent() == root.parent() => {
+                for file in [path.clone()].into_iter().chain(imports::imported(&path)) {
+                    if !read.contains(&file) {
+                        add(0, &fs::read_to_string(&file).unwrap_or_default());
+                        read.push(file);
+                    }
+                }
+            }
+            FileKind::Agent => {
+                if let Some(Ok(fm)) = frontmatter::parse(&content) {
+                    add(1, &lines(&content, 1, fm.end_line + 1));
+                }
+            }
+            FileKind::Skill => {
+                if let Some(Ok(fm)) = frontmatter::parse(&content) {
+                    if let Some(field) = fm.get("description") {
+                        let span = fm.lines_of(field);
+                        add(2, &lines(&content, span.start, span.end));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let [(md, md_lines), (agents, agent_lines), (skills, skill_lines)] = parts;
+    let (tokens, total_lines) = (md + agents + skills, md_lines + agent_lines + skill_lines);
+    if tokens > budget.max_eager {
+        let msg = format!(
+            "context loaded in every session is about {} tokens (CLAUDE.md {}, agents {}, skills \
+             {}), over the budget of {}",
+            tokens, md, agents, skills, budget.max_eager
+        );
+        diags.push(Diagnostic::of(root, "CL103", msg));
+    }
+    if let Some(max) = budget.max_eager_lines.filter(|&max| total_lines > max) {
+        let msg = format!(
+            "context loaded in every session is {} lines (CLAUDE.md {}, agents {}, skills {}), \
+             over the limit of {}",
+            total_lines, md_lines, agent_lines, skill_lines, max
+        );
+        diags.push(Diagnostic::of(root, "CL103", msg));
+    }
+}
+
+/// Returns lines from..to of a text, 1-based and end-exclusive.
+fn lines(content: &str, from: usize, to: usize) -> String {
+    content.lines().skip(from - 1).take(to.saturating_sub(from)).collect::<Vec<_>>().join("\n")
+}
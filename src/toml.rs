@@ -0,0 +1,456 @@
+//! Minimal TOML reader for the config file.
+//!
+//! Covers the subset a config needs: tables, arrays of tables, dotted
+//! keys, strings, integers, floats, booleans, arrays, and inline tables.
+//! Dates and multi-line strings are not supported.
+
+use std::collections::BTreeMap;
+
+pub type Table = BTreeMap<String, Value>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+    Table(Table),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Boolean(_) => "boolean",
+            Value::Array(_) => "array",
+            Value::Table(_) => "table",
+        }
+    }
+}
+
+/// Parses a document, returning "line N: ..." on error.
+pub fn parse(src: &str) -> Result<Table, String> {
+    let mut p = Parser { chars: src.chars().collect(), pos: 0, line: 1 };
+    p.document().map_err(|e| format!("line {}: {}", p.line, e))
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", c))
+        }
+    }
+
+    /// Skips spaces and tabs, plus a trailing comment.
+    fn skip_inline(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.bump();
+        }
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines, and comments.
+    fn skip_all(&mut self) {
+        loop {
+            self.skip_inline();
+            if matches!(self.peek(), Some('\n') | Some('\r')) {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_inline();
+        self.eat('\r');
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(format!("unexpected '{}' after value", c)),
+        }
+    }
+
+    fn document(&mut self) -> Result<Table, String> {
+        let mut root = Table::new();
+        // Path of the table currently receiving key/value pairs.
+        let mut current: Vec<String> = Vec::new();
+        loop {
+            self.skip_all();
+            match self.peek() {
+                None => return Ok(root),
+                Some('[') => {
+                    self.bump();
+                    let array = self.eat('[');
+                    self.skip_inline();
+                    let path = self.key_path()?;
+                    self.expect(']')?;
+                    if array {
+                        self.expect(']')?;
+                    }
+                    // Placed before the line ends, so errors name its line.
+                    if array {
+                        push_array_table(&mut root, &path)?;
+                    } else {
+                        open_table(&mut root, &path)?;
+                    }
+                    self.end_of_line()?;
+                    current = path;
+                }
+                Some(_) => {
+                    let path = self.key_path()?;
+                    self.expect('=')?;
+                    self.skip_inline();
+                    let value = self.value()?;
+                    let table = resolve(&mut root, &current)?;
+                    insert(table, &path, value)?;
+                    self.end_of_line()?;
+                }
+            }
+        }
+    }
+
+    fn key_path(&mut self) -> Result<Vec<String>, String> {
+        let mut path = vec![self.key()?];
+        loop {
+            self.skip_inline();
+            if !self.eat('.') {
+                return Ok(path);
+            }
+            self.skip_inline();
+            path.push(self.key()?);
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let mut key = String::new();
+                while let Some(c) = self.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                        key.push(c);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                if key.is_empty() {
+                    Err("expected key".to_string())
+                } else {
+                    Ok(key)
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) => self.scalar(),
+            None => Err("expected value".to_string()),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.string_char()? {
+                '"' => return Ok(s),
+                '\\' => match self.bump() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('u') => s.push(self.unicode(4)?),
+                    Some('U') => s.push(self.unicode(8)?),
+                    _ => return Err("invalid escape".to_string()),
+                },
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Takes the next char of a string, which must not end the line, so
+    /// errors name the line the string is on.
+    fn string_char(&mut self) -> Result<char, String> {
+        match self.peek() {
+            None | Some('\n') => Err("unterminated string".to_string()),
+            Some(c) => {
+                self.pos += 1;
+                Ok(c)
+            }
+        }
+    }
+
+    fn unicode(&mut self, n: usize) -> Result<char, String> {
+        let mut code = 0;
+        for _ in 0..n {
+            let digit = self
+                .bump()
+                .and_then(|c| c.to_digit(16))
+                .ok_or("invalid unicode escape")?;
+            code = code * 16 + digit;
+        }
+        char::from_u32(code).ok_or_else(|| "invalid unicode escape".to_string())
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.expect('\'')?;
+        let mut s = String::new();
+        loop {
+            match self.string_char()? {
+                '\'' => return Ok(s),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_all();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_all();
+            if !self.eat(',') {
+                self.skip_all();
+                self.expect(']')?;
+                return Ok(Value::Array(items));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut table = Table::new();
+        self.skip_inline();
+        if self.eat('}') {
+            return Ok(Value::Table(table));
+        }
+        loop {
+            self.skip_inline();
+            let path = self.key_path()?;
+            self.expect('=')?;
+            self.skip_inline();
+            let value = self.value()?;
+            insert(&mut table, &path, value)?;
+            self.skip_inline();
+            if self.eat('}') {
+                return Ok(Value::Table(table));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Value, String> {
+        let mut word = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+' | '.') {
+                word.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        match word.as_str() {
+            "true" => return Ok(Value::Boolean(true)),
+            "false" => return Ok(Value::Boolean(false)),
+            "" => return Err("expected value".to_string()),
+            _ => {}
+        }
+        let digits = word.replace('_', "");
+        if let Ok(n) = digits.parse::<i64>() {
+            return Ok(Value::Integer(n));
+        }
+        if let Ok(f) = digits.parse::<f64>() {
+            if digits.chars().any(|c| c.is_ascii_digit()) {
+                return Ok(Value::Float(f));
+            }
+        }
+        Err(format!("invalid value '{}'", word))
+    }
+}
+
+/// Walks to the table at path, descending into the last element of
+/// arrays of tables.
+fn resolve<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, String> {
+    let mut table = root;
+    for key in path {
+        table = match table.get_mut(key) {
+            Some(Value::Table(t)) => t,
+            Some(Value::Array(items)) => match items.last_mut() {
+                Some(Value::Table(t)) => t,
+                _ => return Err(format!("'{}' is not a table", key)),
+            },
+            Some(_) => return Err(format!("'{}' is not a table", key)),
+            None => return Err(format!("table '{}' not defined", key)),
+        };
+    }
+    Ok(table)
+}
+
+fn open_table(root: &mut Table, path: &[String]) -> Result<(), String> {
+    let (last, parents) = path.split_last().expect("key path is never empty");
+    let parent = descend(root, parents)?;
+    match parent.get(last) {
+        None => {
+            parent.insert(last.clone(), Value::Table(Table::new()));
+            Ok(())
+        }
+        Some(Value::Table(_)) => Ok(()),
+        Some(_) => Err(format!("'{}' is already defined", last)),
+    }
+}
+
+fn push_array_table(root: &mut Table, path: &[String]) -> Result<(), String> {
+    let (last, parents) = path.split_last().expect("key path is never empty");
+    let parent = descend(root, parents)?;
+    match parent
+        .entry(last.clone())
+        .or_insert_with(|| Value::Array(Vec::new()))
+    {
+        Value::Array(items) => {
+            items.push(Value::Table(Table::new()));
+            Ok(())
+        }
+        _ => Err(format!("'{}' is not an array of tables", last)),
+    }
+}
+
+/// Like resolve, but creates missing intermediate tables.
+fn descend<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, String> {
+    let mut table = root;
+    for key in path {
+        let entry = table
+            .entry(key.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+        table = match entry {
+            Value::Table(t) => t,
+            Value::Array(items) => match items.last_mut() {
+                Some(Value::Table(t)) => t,
+                _ => return Err(format!("'{}' is not a table", key)),
+            },
+            _ => return Err(format!("'{}' is not a table", key)),
+        };
+    }
+    Ok(table)
+}
+
+fn insert(table: &mut Table, path: &[String], value: Value) -> Result<(), String> {
+    let (last, parents) = path.split_last().expect("key path is never empty");
+    let parent = descend(table, parents)?;
+    if parent.contains_key(last) {
+        return Err(format!("duplicate key '{}'", last));
+    }
+    parent.insert(last.clone(), value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_config_subset() {
+        let src = r#"
+# Comment.
+strict = true
+max = -3
+ratio = 1.5
+
+[rules]
+CL001 = "off" # Trailing comment.
+"quoted key" = 'literal \n'
+list = [1, 2,
+  3]
+
+[rules.nested]
+a.b = { c = "é\t" }
+
+[[custom]]
+id = "X1"
+
+[[custom]]
+id = "X2"
+"#;
+        let t = parse(src).unwrap();
+        assert_eq!(t["strict"], Value::Boolean(true));
+        assert_eq!(t["max"], Value::Integer(-3));
+        assert_eq!(t["ratio"], Value::Float(1.5));
+        let Value::Table(rules) = &t["rules"] else { panic!("rules is a table") };
+        assert_eq!(rules["CL001"], Value::String("off".to_string()));
+        assert_eq!(rules["quoted key"], Value::String("literal \\n".to_string()));
+        let list = [1, 2, 3].map(Value::Integer).to_vec();
+        assert_eq!(rules["list"], Value::Array(list));
+        let Value::Table(nested) = &rules["nested"] else { panic!("nested is a table") };
+        let inner = Table::from([("c".to_string(), Value::String("é\t".to_string()))]);
+        let a = Table::from([("b".to_string(), Value::Table(inner))]);
+        assert_eq!(nested["a"], Value::Table(a));
+        let Value::Array(custom) = &t["custom"] else { panic!("custom is an array") };
+        assert_eq!(custom.len(), 2);
+    }
+
+    fn error(src: &str) -> String {
+        parse(src).expect_err(src)
+    }
+
+    #[test]
+    fn reports_the_failing_line() {
+        assert_eq!(error("a = 1\na = 2\n"), "line 2: duplicate key 'a'");
+        assert_eq!(error("a = \"open\n"), "line 1: unterminated string");
+        assert_eq!(error("\nt = 1\n[t]\n"), "line 3: 't' is already defined");
+        assert_eq!(error("a = 1 2\n"), "line 1: unexpected '2' after value");
+        assert_eq!(error("a = 1\n[[a]]\n"), "line 2: 'a' is not an array of tables");
+        assert!(parse("a = 1979-05-27\n").is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! Liveness of the http(s) links in context files, for `--check-urls`.
+//!
+//! Skills link to external docs, and a page that moves leaves the model
+//! reading an error page. Checking needs the network, so it only runs
+//! when asked. Requests go through `curl`, which std alone cannot
+//! replace for https; each URL is checked once per run, a few at a time,
+//! with a timeout. Answers from servers are remembered for a while in
+//! `.claude-lint-urls` next to the linted directory; failures to connect
+//! are not, since they are often the network's fault rather than the
+//! link's.
+//!
+//! A config's `extends` fetches through `curl` here too, with
+//! `--remote-config`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::json::{self, Value};
+use crate::markdown::{self, Kind};
+use crate::prose;
+use crate::{Diagnostic, Span};
+
+pub const CACHE_FILE_NAME: &str = ".claude-lint-urls";
+
+/// Settings from `[urls]`.
+#[derive(Debug)]
+pub struct Settings {
+    /// Seconds to wait for each request.
+    pub timeout: u64,
+    /// Requests in flight at once.
+    pub concurrency: usize,
+    /// Hours an answer stays cached.
+    pub cache_hours: u64,
+    /// URL prefixes that are never checked.
+    pub ignore: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { timeout: 10, concurrency: 8, cache_hours: 24, ignore: Vec::new() }
+    }
+}
+
+/// What checking a URL found: the final HTTP status, or why there was
+/// none.
+#[derive(Clone, Debug)]
+enum Outcome {
+    Status(u16),
+    Failed(String),
+}
+
+/// Returns where the URL cache for a linted directory lives: next to it,
+/// like the lint cache.
+pub fn default_cache_path(root: &Path) -> PathBuf {
+    root.parent().unwrap_or(Path::new("")).join(CACHE_FILE_NAME)
+}
+
+/// Checks the http(s) links in the Markdown files among files and
+/// reports the dead ones. With a cache path, answers younger than the
+/// configured age are reused, and the cache is rewritten. Fails when
+/// curl cannot be run or the cache cannot be written.
+pub(crate) fn check(
+    files: &[PathBuf],
+    settings: &Settings,
+    cache: Option<&Path>,
+) -> Result<Vec<Diagnostic>, String> {
+    let mut found = Vec::new();
+    for path in files.iter().filter(|p| markdown::is_markdown(p)) {
+        if let Ok(content) = fs::read_to_string(path) {
+            for (url, span) in extract(&content) {
+                if !skipped(&url, settings) {
+                    found.push((path, url, span));
+                }
+            }
+        }
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let mut known = cache.map(load).unwrap_or_default();
+    known.retain(|_, (checked, _)| now.saturating_sub(*checked) < settings.cache_hours * 3600);
+
+    let mut pending: Vec<&str> = found
+        .iter()
+        .map(|(_, url, _)| url.as_str())
+        .filter(|u| !known.contains_key(*u))
+        .collect();
+    pending.sort_unstable();
+    pending.dedup();
+    let mut outcomes: HashMap<String, Outcome> = known
+        .iter()
+        .map(|(u, (_, s))| (u.clone(), Outcome::Status(*s)))
+        .collect();
+    for (url, outcome) in fetch_all(&pending, settings)? {
+        if let Outcome::Status(status) = outcome {
+            known.insert(url.clone(), (now, status));
+        }
+        outcomes.insert(url, outcome);
+    }
+    if let Some(path) = cache {
+        save(path, &known)
+            .map_err(|e| format!("{}: cannot write URL cache: {}", path.display(), e))?;
+    }
+
+    let mut diags = Vec::new();
+    for (path, url, span) in found {
+        let reason = match &outcomes[&url] {
+            Outcome::Status(status) if dead(*status) => format!("HTTP {}", status),
+            Outcome::Status(_) => continue,
+            Outcome::Failed(why) => why.clone(),
+        };
+        diags.push(
+            Diagnostic::of(
+                path,
+                "CL095",
+                format!("dead link to '{}' ({})", url, reason),
+            )
+            .at(Some(span)),
+        );
+    }
+    Ok(diags)
+}
+
+/// Finds the http(s) URLs in a file's prose, whether link targets,
+/// autolinks, or bare, with where each starts. Code is left alone.
+fn extract(content: &str) -> Vec<(String, Span)> {
+    let mut out = Vec::new();
+    for line in markdown::parse(content)
+        .lines
+        .iter()
+        .filter(|l| prose::is_prose(l.kind) || l.kind == Kind::Quote)
+    {
+        let masked = prose::mask(line.text);
+        let mut from = 0;
+        while let Some(i) = masked[from..].find("http") {
+            let start = from + i;
+            let rest = &masked[start..];
+            from = start + 4;
+            if !(rest.starts_with("http://") || rest.starts_with("https://")) {
+                continue;
+            }
+            if line.text[..start].ends_with(|c: char| c.is_alphanumeric()) {
+                continue;
+            }
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "<>()[]\"'`".contains(c))
+                .unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', '*', '_']);
+            if url.len() > "https://".len() {
+                let span = Span {
+                    line: line.number,
+                    column: line.text[..start].chars().count() + 1,
+                    length: url.chars().count(),
+                };
+                out.push((url.to_string(), span));
+            }
+            from = start + end;
+        }
+    }
+    out
+}
+
+/// Whether a URL is left unchecked: one the config ignores, or one on a
+/// local or reserved example host, which would never answer.
+fn skipped(url: &str, settings: &Settings) -> bool {
+    if settings.ignore.iter().any(|prefix| url.starts_with(prefix.as_str())) {
+        return true;
+    }
+    let rest = url.split_once("://").map_or("", |(_, r)| r);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host
+        .rsplit_once(':')
+        .filter(|(_, port)| port.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(host, |(h, _)| h);
+    let host = host.to_ascii_lowercase();
+    if host == "localhost" || host.starts_with("127.") || host == "[::1]" || host == "0.0.0.0" {
+        return true;
+    }
+    ["example.com", "example.org", "example.net"]
+        .iter()
+        .any(|d| host == *d || host.ends_with(&format!(".{}", d)))
+        || [".example", ".test", ".invalid", ".localhost", ".local"]
+            .iter()
+            .any(|tld| host.ends_with(tld))
+}
+
+/// Whether a status means the link is gone. Pages behind a login and
+/// rate limits answer with 401, 403, and 429, and are not dead.
+fn dead(status: u16) -> bool {
+    status >= 400 && !matches!(status, 401 | 403 | 429)
+}
+
+/// Checks each URL, at most the configured number at a time.
+fn fetch_all(urls: &[&str], settings: &Settings) -> Result<Vec<(String, Outcome)>, String> {
+    let workers = settings.concurrency.max(1).min(urls.len());
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(urls.len()));
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(url) = urls.get(i) else {
+                    break;
+                };
+                let outcome = fetch(url, settings.timeout);
+                results
+                    .lock()
+                    .expect("no worker panics while holding the lock")
+                    .push((url.to_string(), outcome));
+            });
+        }
+    });
+    results
+        .into_inner()
+        .expect("workers have finished")
+        .into_iter()
+        .map(|(url, o)| o.map(|o| (url, o)))
+        .collect()
+}
+
+/// Asks for a URL's headers, following redirects. Servers that refuse
+/// HEAD are asked again with a GET for the first byte.
+fn fetch(url: &str, timeout: u64) -> Result<Outcome, String> {
+    let outcome = curl(url, timeout, &["--head"])?;
+    match outcome {
+        Outcome::Status(status) if status >= 400 => curl(url, timeout, &["--range", "0-0"]),
+        outcome => Ok(outcome),
+    }
+}
+
+/// Fetches a URL's body, failing on an HTTP error.
+pub(crate) fn get(url: &str, timeout: u64) -> Result<String, String> {
+    let out = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--max-redirs", "10"])
+        .args(["--max-time", &timeout.to_string()])
+        .arg("--")
+        .arg(url)
+        .output()
+        .map_err(|e| format!("cannot run curl, which --remote-config needs: {}", e))?;
+    if !out.status.success() {
+        // curl says "curl: (22) The requested URL returned error: 404".
+        let why = String::from_utf8_lossy(&out.stderr);
+        let why = why.trim().trim_start_matches("curl: ");
+        return Err(format!(
+            "cannot fetch {}: {}",
+            url,
+            why.split_once(") ").map_or(why, |(_, w)| w)
+        ));
+    }
+    String::from_utf8(out.stdout).map_err(|_| format!("cannot fetch {}: not UTF-8", url))
+}
+
+fn curl(url: &str, timeout: u64, args: &[&str]) -> Result<Outcome, String> {
+    let null = if cfg!(windows) { "NUL" } else { "/dev/null" };
+    let out = Command::new("curl")
+        .args([
+            "--silent",
+            "--location",
+            "--max-redirs",
+            "10",
+            "--output",
+            null,
+            "--write-out",
+            "%{http_code}",
+        ])
+        .args(["--max-time", &timeout.to_string()])
+        .args(args)
+        .arg("--")
+        .arg(url)
+        .output()
+        .map_err(|e| format!("cannot run curl, which --check-urls needs: {}", e))?;
+    let why = match out.status.code() {
+        Some(0) => {
+            let code = String::from_utf8_lossy(&out.stdout);
+            return Ok(code.trim().parse().map_or_else(
+                |_| Outcome::Failed("no HTTP status".to_string()),
+                Outcome::Status,
+            ));
+        }
+        Some(6) => "host not found".to_string(),
+        Some(7) => "connection refused".to_string(),
+        Some(28) => format!("no answer within {}s", timeout),
+        Some(35 | 51 | 58 | 60) => "TLS handshake failed".to_string(),
+        Some(47) => "too many redirects".to_string(),
+        Some(code) => format!("curl failed with exit code {}", code),
+        None => "curl was interrupted".to_string(),
+    };
+    Ok(Outcome::Failed(why))
+}
+
+/// Loads cached answers, by URL, with when each was checked. A missing
+/// or corrupt cache is empty.
+fn load(path: &Path) -> HashMap<String, (u64, u16)> {
+    let mut out = HashMap::new();
+    let Some(doc) = fs::read_to_string(path).ok().and_then(|src| json::parse(&src).ok()) else {
+        return out;
+    };
+    let number = |v: Option<&Value>| match v.map(|v| &v.kind) {
+        Some(json::Kind::Number(n)) if *n >= 0.0 => Some(*n as u64),
+        _ => None,
+    };
+    for entry in doc.get("urls").and_then(Value::as_array).unwrap_or_default() {
+        let url = entry.get("url").and_then(Value::as_str);
+        let checked = number(entry.get("checked"));
+        let status = number(entry.get("status")).and_then(|s| u16::try_from(s).ok());
+        if let (Some(url), Some(checked), Some(status)) = (url, checked, status) {
+            out.insert(url.to_string(), (checked, status));
+        }
+    }
+    out
+}
+
+fn save(path: &Path, known: &HashMap<String, (u64, u16)>) -> std::io::Result<()> {
+    let mut urls: Vec<(&String, &(u64, u16))> = known.iter().collect();
+    urls.sort();
+    let mut out = String::from("{\"urls\":[");
+    for (i, (url, (checked, status))) in urls.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&format!(
+            "\n{{\"url\":{},\"status\":{},\"checked\":{}}}",
+            json::string(url),
+            status,
+            checked
+        ));
+    }
+    out.push_str("\n]}\n");
+    fs::write(path, out)
+}
@@ -0,0 +1,1495 @@
+//! Minimal WebAssembly interpreter for lint plugins.
+//!
+//! Runs self-contained modules: the MVP instruction set with floats, plus
+//! sign extension, saturating truncation, and bulk memory, which current
+//! compilers emit by default. Modules get no imports, so they can touch
+//! nothing but their own memory, and every call runs on a fuel budget
+//! with bounded memory, stack, and call depth. Malformed modules and
+//! traps surface as errors, never as panics.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Instructions a single call may execute.
+const FUEL: u64 = 100_000_000;
+/// Memory ceiling in 64 KiB pages (64 MiB).
+const MAX_PAGES: usize = 1024;
+const PAGE: usize = 65536;
+const MAX_FRAMES: usize = 10_000;
+/// Value stack and locals ceiling, in slots.
+const MAX_SLOTS: usize = 1 << 20;
+const MAX_TABLE: usize = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ValType {
+    I32,
+    I64,
+    F32,
+    F64,
+}
+
+#[derive(PartialEq, Eq)]
+struct FuncType {
+    params: Vec<ValType>,
+    results: Vec<ValType>,
+}
+
+#[derive(Clone, Copy)]
+enum BlockType {
+    Empty,
+    Value,
+    Type(u32),
+}
+
+enum Op {
+    Unreachable,
+    Nop,
+    Block { ty: BlockType, end: usize },
+    Loop { ty: BlockType },
+    If { ty: BlockType, else_: usize, end: usize },
+    Else { end: usize },
+    End,
+    Br(u32),
+    BrIf(u32),
+    BrTable(Box<[u32]>, u32),
+    Return,
+    Call(u32),
+    CallIndirect(u32),
+    Drop,
+    Select,
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    Load { op: u8, offset: u32 },
+    Store { op: u8, offset: u32 },
+    MemorySize,
+    MemoryGrow,
+    Const(u64),
+    /// Stack-only numeric instructions, 0x45 through 0xc4.
+    Numeric(u8),
+    /// Saturating float-to-int truncation, 0xfc 0 through 7.
+    TruncSat(u8),
+    MemoryInit(u32),
+    DataDrop(u32),
+    MemoryCopy,
+    MemoryFill,
+}
+
+struct Func {
+    ty: u32,
+    locals: usize,
+    code: Vec<Op>,
+}
+
+struct Global {
+    mutable: bool,
+    init: u64,
+}
+
+struct Data {
+    /// None for passive segments.
+    offset: Option<u32>,
+    bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct Module {
+    types: Vec<FuncType>,
+    funcs: Vec<Func>,
+    table: Option<usize>,
+    memory: Option<(usize, Option<usize>)>,
+    globals: Vec<Global>,
+    exports: HashMap<String, (u8, u32)>,
+    start: Option<u32>,
+    elements: Vec<(u32, Vec<u32>)>,
+    data: Vec<Data>,
+}
+
+/// An instantiated module. Cloning copies its memory and globals, so a
+/// clone of a fresh instance is fresh too.
+#[derive(Clone)]
+pub struct Instance {
+    module: Arc<Module>,
+    memory: Vec<u8>,
+    max_pages: usize,
+    globals: Vec<u64>,
+    table: Vec<Option<u32>>,
+    dropped: Vec<bool>,
+    fuel: u64,
+}
+
+struct Label {
+    height: usize,
+    arity: usize,
+    /// The op to continue at; usize::MAX for the function body.
+    target: usize,
+    is_loop: bool,
+}
+
+struct Frame {
+    func: usize,
+    pc: usize,
+    locals: usize,
+    labels: usize,
+    base: usize,
+    arity: usize,
+}
+
+impl Instance {
+    /// Decodes and instantiates a module, running its start function.
+    pub fn new(bytes: &[u8]) -> Result<Self, String> {
+        let module = Arc::new(decode(bytes)?);
+        let (min, max) = module.memory.unwrap_or((0, Some(0)));
+        if min > MAX_PAGES {
+            return Err(format!("module wants {} memory pages; the limit is {}", min, MAX_PAGES));
+        }
+        let mut table = vec![None; module.table.unwrap_or(0)];
+        for (offset, funcs) in &module.elements {
+            let start = *offset as usize;
+            let slots = table
+                .get_mut(start..start + funcs.len())
+                .ok_or("element segment out of table bounds")?;
+            for (slot, &f) in slots.iter_mut().zip(funcs) {
+                *slot = Some(f);
+            }
+        }
+        let mut memory = vec![0; min * PAGE];
+        for data in &module.data {
+            if let Some(offset) = data.offset {
+                let start = offset as usize;
+                memory
+                    .get_mut(start..start + data.bytes.len())
+                    .ok_or("data segment out of memory bounds")?
+                    .copy_from_slice(&data.bytes);
+            }
+        }
+        let mut instance = Instance {
+            globals: module.globals.iter().map(|g| g.init).collect(),
+            dropped: module.data.iter().map(|d| d.offset.is_some()).collect(),
+            max_pages: max.unwrap_or(MAX_PAGES).min(MAX_PAGES),
+            memory,
+            table,
+            fuel: FUEL,
+            module,
+        };
+        if let Some(start) = instance.module.start {
+            instance.run(start as usize, Vec::new())?;
+        }
+        Ok(instance)
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    /// Calls an exported function with a fresh fuel budget.
+    pub fn call(&mut self, name: &str, args: &[Value]) -> Result<Vec<Value>, String> {
+        self.call_with(name, args, FUEL)
+    }
+
+    fn call_with(&mut self, name: &str, args: &[Value], fuel: u64) -> Result<Vec<Value>, String> {
+        let Some(&(0, index)) = self.module.exports.get(name) else {
+            return Err(format!("no exported function '{}'", name));
+        };
+        let module = self.module.clone();
+        let ty = module
+            .funcs
+            .get(index as usize)
+            .and_then(|f| module.types.get(f.ty as usize))
+            .ok_or("invalid export")?;
+        let types: Vec<ValType> = args.iter().map(|a| a.ty()).collect();
+        if types != ty.params {
+            return Err(format!("'{}' takes {:?}, not {:?}", name, ty.params, types));
+        }
+        self.fuel = fuel;
+        let raw = self.run(index as usize, args.iter().map(|a| a.bits()).collect())?;
+        Ok(raw.into_iter().zip(&ty.results).map(|(bits, &t)| Value::from_bits(t, bits)).collect())
+    }
+
+    fn run(&mut self, func: usize, args: Vec<u64>) -> Result<Vec<u64>, String> {
+        let module = self.module.clone();
+        let mut stack = args;
+        let mut locals: Vec<u64> = Vec::new();
+        let mut labels: Vec<Label> = Vec::new();
+        let mut frames: Vec<Frame> = Vec::new();
+        enter(&module, func, &mut stack, &mut locals, &mut labels, &mut frames)?;
+
+        loop {
+            let frame = frames.last_mut().expect("a frame while running");
+            let pc = frame.pc;
+            frame.pc += 1;
+            let (locals_base, labels_base) = (frame.locals, frame.labels);
+            let op = module.funcs[frame.func].code.get(pc).ok_or("ran off the end of a function")?;
+            if self.fuel == 0 {
+                return Err("out of fuel".to_string());
+            }
+            self.fuel -= 1;
+            if stack.len() > MAX_SLOTS {
+                return Err("value stack exhausted".to_string());
+            }
+
+            let mut branch = None;
+            match op {
+                Op::Unreachable => return Err("unreachable executed".to_string()),
+                Op::Nop => {}
+                Op::Block { ty, end } => {
+                    let (params, results) = module.arity(*ty)?;
+                    let height = stack.len().checked_sub(params).ok_or(UNDERFLOW)?;
+                    labels.push(Label { height, arity: results, target: end + 1, is_loop: false });
+                }
+                Op::Loop { ty } => {
+                    let (params, _) = module.arity(*ty)?;
+                    let height = stack.len().checked_sub(params).ok_or(UNDERFLOW)?;
+                    labels.push(Label { height, arity: params, target: pc + 1, is_loop: true });
+                }
+                Op::If { ty, else_, end } => {
+                    let cond = pop(&mut stack)? as u32;
+                    let (params, results) = module.arity(*ty)?;
+                    let height = stack.len().checked_sub(params).ok_or(UNDERFLOW)?;
+                    let label = Label { height, arity: results, target: end + 1, is_loop: false };
+                    if cond != 0 {
+                        labels.push(label);
+                    } else if else_ != end {
+                        labels.push(label);
+                        jump(&mut frames, else_ + 1);
+                    } else {
+                        jump(&mut frames, end + 1);
+                    }
+                }
+                Op::Else { end } => {
+                    labels.pop();
+                    jump(&mut frames, end + 1);
+                }
+                Op::End => {
+                    let label = labels.pop().ok_or("unbalanced end")?;
+                    if label.target == usize::MAX {
+                        if let Some(results) =
+                            leave(&mut stack, &mut locals, &mut labels, &mut frames)?
+                        {
+                            return Ok(results);
+                        }
+                    }
+                }
+                Op::Br(depth) => branch = Some(*depth),
+                Op::BrIf(depth) => {
+                    if pop(&mut stack)? as u32 != 0 {
+                        branch = Some(*depth);
+                    }
+                }
+                Op::BrTable(targets, default) => {
+                    let i = pop(&mut stack)? as u32 as usize;
+                    branch = Some(*targets.get(i).unwrap_or(default));
+                }
+                Op::Return => {
+                    if let Some(results) = leave(&mut stack, &mut locals, &mut labels, &mut frames)?
+                    {
+                        return Ok(results);
+                    }
+                }
+                Op::Call(f) => enter(
+                    &module,
+                    *f as usize,
+                    &mut stack,
+                    &mut locals,
+                    &mut labels,
+                    &mut frames,
+                )?,
+                Op::CallIndirect(ty) => {
+                    let i = pop(&mut stack)? as u32 as usize;
+                    let f = self.table.get(i).copied().flatten().ok_or("undefined table element")?;
+                    let want = module.types.get(*ty as usize).ok_or("invalid type index")?;
+                    let have = module
+                        .funcs
+                        .get(f as usize)
+                        .and_then(|func| module.types.get(func.ty as usize));
+                    if have != Some(want) {
+                        return Err("indirect call type mismatch".to_string());
+                    }
+                    enter(&module, f as usize, &mut stack, &mut locals, &mut labels, &mut frames)?;
+                }
+                Op::Drop => {
+                    pop(&mut stack)?;
+                }
+                Op::Select => {
+                    let cond = pop(&mut stack)? as u32;
+                    let b = pop(&mut stack)?;
+                    let a = pop(&mut stack)?;
+                    stack.push(if cond != 0 { a } else { b });
+                }
+                Op::LocalGet(i) => stack.push(*local(&mut locals, locals_base, *i)?),
+                Op::LocalSet(i) => {
+                    let v = pop(&mut stack)?;
+                    *local(&mut locals, locals_base, *i)? = v;
+                }
+                Op::LocalTee(i) => {
+                    let v = *stack.last().ok_or(UNDERFLOW)?;
+                    *local(&mut locals, locals_base, *i)? = v;
+                }
+                Op::GlobalGet(i) => stack.push(
+                    *self
+                        .globals
+                        .get(*i as usize)
+                        .ok_or("invalid global index")?,
+                ),
+                Op::GlobalSet(i) => {
+                    let v = pop(&mut stack)?;
+                    match (self.globals.get_mut(*i as usize), module.globals.get(*i as usize)) {
+                        (Some(g), Some(Global { mutable: true, .. })) => *g = v,
+                        _ => return Err("invalid global write".to_string()),
+                    }
+                }
+                Op::Load { op, offset } => {
+                    let base = pop(&mut stack)? as u32;
+                    stack.push(self.load(*op, base, *offset)?);
+                }
+                Op::Store { op, offset } => {
+                    let v = pop(&mut stack)?;
+                    let base = pop(&mut stack)? as u32;
+                    self.store(*op, base, *offset, v)?;
+                }
+                Op::MemorySize => stack.push((self.memory.len() / PAGE) as u64),
+                Op::MemoryGrow => {
+                    let delta = pop(&mut stack)? as u32 as usize;
+                    let pages = self.memory.len() / PAGE;
+                    if pages + delta > self.max_pages {
+                        stack.push(u32::MAX as u64);
+                    } else {
+                        self.memory.resize((pages + delta) * PAGE, 0);
+                        stack.push(pages as u64);
+                    }
+                }
+                Op::Const(bits) => stack.push(*bits),
+                Op::Numeric(op) => numeric(*op, &mut stack)?,
+                Op::TruncSat(op) => {
+                    let v = pop(&mut stack)?;
+                    let (f32_, f64_) = (f32::from_bits(v as u32), f64::from_bits(v));
+                    stack.push(match op {
+                        0 => f32_ as i32 as u32 as u64,
+                        1 => f32_ as u32 as u64,
+                        2 => f64_ as i32 as u32 as u64,
+                        3 => f64_ as u32 as u64,
+                        4 => f32_ as i64 as u64,
+                        5 => f32_ as u64,
+                        6 => f64_ as i64 as u64,
+                        _ => f64_ as u64,
+                    });
+                }
+                Op::MemoryInit(seg) => {
+                    let n = pop(&mut stack)? as u32 as usize;
+                    let src = pop(&mut stack)? as u32 as usize;
+                    let dst = pop(&mut stack)? as u32 as usize;
+                    let seg = *seg as usize;
+                    let bytes: &[u8] = match self.dropped.get(seg) {
+                        Some(false) => &module.data[seg].bytes,
+                        Some(true) => &[],
+                        None => return Err("invalid data segment".to_string()),
+                    };
+                    let from = bytes.get(src..src.saturating_add(n)).ok_or(OUT_OF_BOUNDS)?;
+                    self.memory
+                        .get_mut(dst..dst.saturating_add(n))
+                        .ok_or(OUT_OF_BOUNDS)?
+                        .copy_from_slice(from);
+                }
+                Op::DataDrop(seg) => {
+                    *self
+                        .dropped
+                        .get_mut(*seg as usize)
+                        .ok_or("invalid data segment")? = true
+                }
+                Op::MemoryCopy => {
+                    let n = pop(&mut stack)? as u32 as usize;
+                    let src = pop(&mut stack)? as u32 as usize;
+                    let dst = pop(&mut stack)? as u32 as usize;
+                    if src.max(dst) + n > self.memory.len() {
+                        return Err(OUT_OF_BOUNDS.to_string());
+                    }
+                    self.memory.copy_within(src..src + n, dst);
+                }
+                Op::MemoryFill => {
+                    let n = pop(&mut stack)? as u32 as usize;
+                    let byte = pop(&mut stack)? as u8;
+                    let dst = pop(&mut stack)? as u32 as usize;
+                    self.memory.get_mut(dst..dst + n).ok_or(OUT_OF_BOUNDS)?.fill(byte);
+                }
+            }
+
+            if let Some(depth) = branch {
+                let idx = labels
+                    .len()
+                    .checked_sub(1 + depth as usize)
+                    .filter(|&i| i >= labels_base);
+                let label = labels
+                    .get(idx.ok_or("invalid branch depth")?)
+                    .expect("checked index");
+                if label.target == usize::MAX {
+                    if let Some(results) = leave(&mut stack, &mut locals, &mut labels, &mut frames)?
+                    {
+                        return Ok(results);
+                    }
+                    continue;
+                }
+                let from = stack.len().checked_sub(label.arity).ok_or(UNDERFLOW)?;
+                if from < label.height {
+                    return Err(UNDERFLOW.to_string());
+                }
+                stack.copy_within(from.., label.height);
+                stack.truncate(label.height + label.arity);
+                let (target, keep) = (label.target, label.is_loop);
+                labels.truncate(idx.expect("checked index") + keep as usize);
+                jump(&mut frames, target);
+            }
+        }
+    }
+
+    fn address(&self, base: u32, offset: u32, size: usize) -> Result<usize, String> {
+        let start = base as usize + offset as usize;
+        if start + size > self.memory.len() {
+            return Err(OUT_OF_BOUNDS.to_string());
+        }
+        Ok(start)
+    }
+
+    fn load(&self, op: u8, base: u32, offset: u32) -> Result<u64, String> {
+        let size = match op {
+            0x29 | 0x2b => 8,
+            0x28 | 0x2a | 0x34 | 0x35 => 4,
+            0x2e | 0x2f | 0x32 | 0x33 => 2,
+            _ => 1,
+        };
+        let at = self.address(base, offset, size)?;
+        let mut buf = [0u8; 8];
+        buf[..size].copy_from_slice(&self.memory[at..at + size]);
+        let raw = u64::from_le_bytes(buf);
+        Ok(match op {
+            0x2c => raw as i8 as i32 as u32 as u64,
+            0x2e => raw as i16 as i32 as u32 as u64,
+            0x30 => raw as i8 as i64 as u64,
+            0x32 => raw as i16 as i64 as u64,
+            0x34 => raw as i32 as i64 as u64,
+            _ => raw,
+        })
+    }
+
+    fn store(&mut self, op: u8, base: u32, offset: u32, v: u64) -> Result<(), String> {
+        let size = match op {
+            0x37 | 0x39 => 8,
+            0x36 | 0x38 | 0x3e => 4,
+            0x3b | 0x3d => 2,
+            _ => 1,
+        };
+        let at = self.address(base, offset, size)?;
+        self.memory[at..at + size].copy_from_slice(&v.to_le_bytes()[..size]);
+        Ok(())
+    }
+}
+
+const UNDERFLOW: &str = "value stack underflow";
+const OUT_OF_BOUNDS: &str = "out of bounds memory access";
+
+fn jump(frames: &mut [Frame], pc: usize) {
+    frames.last_mut().expect("a frame while running").pc = pc;
+}
+
+fn pop(stack: &mut Vec<u64>) -> Result<u64, String> {
+    stack.pop().ok_or_else(|| UNDERFLOW.to_string())
+}
+
+fn local(locals: &mut [u64], base: usize, i: u32) -> Result<&mut u64, String> {
+    locals.get_mut(base + i as usize).ok_or_else(|| "invalid local index".to_string())
+}
+
+/// Pushes a frame for func, moving its arguments off the stack.
+fn enter(
+    module: &Module,
+    func: usize,
+    stack: &mut Vec<u64>,
+    locals: &mut Vec<u64>,
+    labels: &mut Vec<Label>,
+    frames: &mut Vec<Frame>,
+) -> Result<(), String> {
+    if frames.len() >= MAX_FRAMES || locals.len() > MAX_SLOTS {
+        return Err("call stack exhausted".to_string());
+    }
+    let f = module.funcs.get(func).ok_or("invalid function index")?;
+    let ty = module.types.get(f.ty as usize).ok_or("invalid type index")?;
+    let at = stack.len().checked_sub(ty.params.len()).ok_or(UNDERFLOW)?;
+    let start = locals.len();
+    locals.extend(stack.drain(at..));
+    locals.resize(locals.len() + f.locals, 0);
+    labels.push(Label {
+        height: stack.len(),
+        arity: ty.results.len(),
+        target: usize::MAX,
+        is_loop: false,
+    });
+    frames.push(Frame {
+        func,
+        pc: 0,
+        locals: start,
+        labels: labels.len() - 1,
+        base: stack.len(),
+        arity: ty.results.len(),
+    });
+    Ok(())
+}
+
+/// Pops the current frame, keeping its results. Returns them once the
+/// outermost frame is gone.
+fn leave(
+    stack: &mut Vec<u64>,
+    locals: &mut Vec<u64>,
+    labels: &mut Vec<Label>,
+    frames: &mut Vec<Frame>,
+) -> Result<Option<Vec<u64>>, String> {
+    let frame = frames.pop().expect("a frame while running");
+    let from = stack.len().checked_sub(frame.arity).filter(|&f| f >= frame.base).ok_or(UNDERFLOW)?;
+    stack.copy_within(from.., frame.base);
+    stack.truncate(frame.base + frame.arity);
+    labels.truncate(frame.labels);
+    locals.truncate(frame.locals);
+    Ok(frames.is_empty().then(|| std::mem::take(stack)))
+}
+
+fn numeric(op: u8, s: &mut Vec<u64>) -> Result<(), String> {
+    fn i32_(v: u64) -> i32 {
+        v as u32 as i32
+    }
+    fn u32_(v: u64) -> u32 {
+        v as u32
+    }
+    fn i64_(v: u64) -> i64 {
+        v as i64
+    }
+    fn f32_(v: u64) -> f32 {
+        f32::from_bits(v as u32)
+    }
+    fn f64_(v: u64) -> f64 {
+        f64::from_bits(v)
+    }
+    fn u64_(v: u64) -> u64 {
+        v
+    }
+    fn from_u64(v: u64) -> u64 {
+        v
+    }
+    fn from_i32(v: i32) -> u64 {
+        v as u32 as u64
+    }
+    fn from_u32(v: u32) -> u64 {
+        v as u64
+    }
+    fn from_i64(v: i64) -> u64 {
+        v as u64
+    }
+    fn from_f32(v: f32) -> u64 {
+        v.to_bits() as u64
+    }
+    fn from_f64(v: f64) -> u64 {
+        v.to_bits()
+    }
+    fn from_bool(v: bool) -> u64 {
+        v as u64
+    }
+    macro_rules! un {
+        ($get:ident, $put:ident, |$a:ident| $e:expr) => {{
+            let $a = $get(pop(s)?);
+            s.push($put($e));
+        }};
+    }
+    macro_rules! bin {
+        ($get:ident, $put:ident, |$a:ident, $b:ident| $e:expr) => {{
+            let $b = $get(pop(s)?);
+            let $a = $get(pop(s)?);
+            s.push($put($e));
+        }};
+    }
+    match op {
+        0x45 => un!(u32_, from_bool, |a| a == 0),
+        0x46 => bin!(u32_, from_bool, |a, b| a == b),
+        0x47 => bin!(u32_, from_bool, |a, b| a != b),
+        0x48 => bin!(i32_, from_bool, |a, b| a < b),
+        0x49 => bin!(u32_, from_bool, |a, b| a < b),
+        0x4a => bin!(i32_, from_bool, |a, b| a > b),
+        0x4b => bin!(u32_, from_bool, |a, b| a > b),
+        0x4c => bin!(i32_, from_bool, |a, b| a <= b),
+        0x4d => bin!(u32_, from_bool, |a, b| a <= b),
+        0x4e => bin!(i32_, from_bool, |a, b| a >= b),
+        0x4f => bin!(u32_, from_bool, |a, b| a >= b),
+        0x50 => un!(i64_, from_bool, |a| a == 0),
+        0x51 => bin!(i64_, from_bool, |a, b| a == b),
+        0x52 => bin!(i64_, from_bool, |a, b| a != b),
+        0x53 => bin!(i64_, from_bool, |a, b| a < b),
+        0x54 => bin!(u64_, from_bool, |a, b| a < b),
+        0x55 => bin!(i64_, from_bool, |a, b| a > b),
+        0x56 => bin!(u64_, from_bool, |a, b| a > b),
+        0x57 => bin!(i64_, from_bool, |a, b| a <= b),
+        0x58 => bin!(u64_, from_bool, |a, b| a <= b),
+        0x59 => bin!(i64_, from_bool, |a, b| a >= b),
+        0x5a => bin!(u64_, from_bool, |a, b| a >= b),
+        0x5b => bin!(f32_, from_bool, |a, b| a == b),
+        0x5c => bin!(f32_, from_bool, |a, b| a != b),
+        0x5d => bin!(f32_, from_bool, |a, b| a < b),
+        0x5e => bin!(f32_, from_bool, |a, b| a > b),
+        0x5f => bin!(f32_, from_bool, |a, b| a <= b),
+        0x60 => bin!(f32_, from_bool, |a, b| a >= b),
+        0x61 => bin!(f64_, from_bool, |a, b| a == b),
+        0x62 => bin!(f64_, from_bool, |a, b| a != b),
+        0x63 => bin!(f64_, from_bool, |a, b| a < b),
+        0x64 => bin!(f64_, from_bool, |a, b| a > b),
+        0x65 => bin!(f64_, from_bool, |a, b| a <= b),
+        0x66 => bin!(f64_, from_bool, |a, b| a >= b),
+        0x67 => un!(u32_, from_u32, |a| a.leading_zeros()),
+        0x68 => un!(u32_, from_u32, |a| a.trailing_zeros()),
+        0x69 => un!(u32_, from_u32, |a| a.count_ones()),
+        0x6a => bin!(u32_, from_u32, |a, b| a.wrapping_add(b)),
+        0x6b => bin!(u32_, from_u32, |a, b| a.wrapping_sub(b)),
+        0x6c => bin!(u32_, from_u32, |a, b| a.wrapping_mul(b)),
+        0x6d => {
+            let b = i32_(pop(s)?);
+            let a = i32_(pop(s)?);
+            s.push(from_i32(div_s(a.checked_div(b), b == 0)?));
+        }
+        0x6e => bin!(u32_, from_u32, |a, b| a
+            .checked_div(b)
+            .ok_or(DIVIDE_BY_ZERO)?),
+        0x6f => bin!(i32_, from_i32, |a, b| if b == 0 {
+            return Err(DIVIDE_BY_ZERO.to_string());
+        } else {
+            a.wrapping_rem(b)
+        }),
+        0x70 => bin!(u32_, from_u32, |a, b| a
+            .checked_rem(b)
+            .ok_or(DIVIDE_BY_ZERO)?),
+        0x71 => bin!(u32_, from_u32, |a, b| a & b),
+        0x72 => bin!(u32_, from_u32, |a, b| a | b),
+        0x73 => bin!(u32_, from_u32, |a, b| a ^ b),
+        0x74 => bin!(u32_, from_u32, |a, b| a.wrapping_shl(b)),
+        0x75 => bin!(i32_, from_i32, |a, b| a.wrapping_shr(b as u32)),
+        0x76 => bin!(u32_, from_u32, |a, b| a.wrapping_shr(b)),
+        0x77 => bin!(u32_, from_u32, |a, b| a.rotate_left(b % 32)),
+        0x78 => bin!(u32_, from_u32, |a, b| a.rotate_right(b % 32)),
+        0x79 => un!(u64_, from_u64, |a| a.leading_zeros() as u64),
+        0x7a => un!(u64_, from_u64, |a| a.trailing_zeros() as u64),
+        0x7b => un!(u64_, from_u64, |a| a.count_ones() as u64),
+        0x7c => bin!(u64_, from_u64, |a, b| a.wrapping_add(b)),
+        0x7d => bin!(u64_, from_u64, |a, b| a.wrapping_sub(b)),
+        0x7e => bin!(u64_, from_u64, |a, b| a.wrapping_mul(b)),
+        0x7f => {
+            let b = i64_(pop(s)?);
+            let a = i64_(pop(s)?);
+            s.push(from_i64(div_s(a.checked_div(b), b == 0)?));
+        }
+        0x80 => bin!(u64_, from_u64, |a, b| a
+            .checked_div(b)
+            .ok_or(DIVIDE_BY_ZERO)?),
+        0x81 => bin!(i64_, from_i64, |a, b| if b == 0 {
+            return Err(DIVIDE_BY_ZERO.to_string());
+        } else {
+            a.wrapping_rem(b)
+        }),
+        0x82 => bin!(u64_, from_u64, |a, b| a
+            .checked_rem(b)
+            .ok_or(DIVIDE_BY_ZERO)?),
+        0x83 => bin!(u64_, from_u64, |a, b| a & b),
+        0x84 => bin!(u64_, from_u64, |a, b| a | b),
+        0x85 => bin!(u64_, from_u64, |a, b| a ^ b),
+        0x86 => bin!(u64_, from_u64, |a, b| a.wrapping_shl(b as u32)),
+        0x87 => bin!(i64_, from_i64, |a, b| a.wrapping_shr(b as u32)),
+        0x88 => bin!(u64_, from_u64, |a, b| a.wrapping_shr(b as u32)),
+        0x89 => bin!(u64_, from_u64, |a, b| a.rotate_left((b % 64) as u32)),
+        0x8a => bin!(u64_, from_u64, |a, b| a.rotate_right((b % 64) as u32)),
+        0x8b => un!(f32_, from_f32, |a| a.abs()),
+        0x8c => un!(f32_, from_f32, |a| -a),
+        0x8d => un!(f32_, from_f32, |a| a.ceil()),
+        0x8e => un!(f32_, from_f32, |a| a.floor()),
+        0x8f => un!(f32_, from_f32, |a| a.trunc()),
+        0x90 => un!(f32_, from_f32, |a| a.round_ties_even()),
+        0x91 => un!(f32_, from_f32, |a| a.sqrt()),
+        0x92 => bin!(f32_, from_f32, |a, b| a + b),
+        0x93 => bin!(f32_, from_f32, |a, b| a - b),
+        0x94 => bin!(f32_, from_f32, |a, b| a * b),
+        0x95 => bin!(f32_, from_f32, |a, b| a / b),
+        0x96 => bin!(f32_, from_f32, |a, b| fmin(a, b)),
+        0x97 => bin!(f32_, from_f32, |a, b| fmax(a, b)),
+        0x98 => bin!(f32_, from_f32, |a, b| a.copysign(b)),
+        0x99 => un!(f64_, from_f64, |a| a.abs()),
+        0x9a => un!(f64_, from_f64, |a| -a),
+        0x9b => un!(f64_, from_f64, |a| a.ceil()),
+        0x9c => un!(f64_, from_f64, |a| a.floor()),
+        0x9d => un!(f64_, from_f64, |a| a.trunc()),
+        0x9e => un!(f64_, from_f64, |a| a.round_ties_even()),
+        0x9f => un!(f64_, from_f64, |a| a.sqrt()),
+        0xa0 => bin!(f64_, from_f64, |a, b| a + b),
+        0xa1 => bin!(f64_, from_f64, |a, b| a - b),
+        0xa2 => bin!(f64_, from_f64, |a, b| a * b),
+        0xa3 => bin!(f64_, from_f64, |a, b| a / b),
+        0xa4 => bin!(f64_, from_f64, |a, b| fmin(a, b)),
+        0xa5 => bin!(f64_, from_f64, |a, b| fmax(a, b)),
+        0xa6 => bin!(f64_, from_f64, |a, b| a.copysign(b)),
+        0xa7 => un!(u64_, from_u32, |a| a as u32),
+        0xa8 => un!(f32_, from_i32, |a| trunc(a as f64, -2147483648.0, 2147483648.0)? as i32),
+        0xa9 => un!(f32_, from_u32, |a| trunc(a as f64, 0.0, 4294967296.0)? as u32),
+        0xaa => un!(f64_, from_i32, |a| trunc(a, -2147483648.0, 2147483648.0)? as i32),
+        0xab => un!(f64_, from_u32, |a| trunc(a, 0.0, 4294967296.0)? as u32),
+        0xac => un!(i32_, from_i64, |a| a as i64),
+        0xad => un!(u32_, from_u64, |a| a as u64),
+        0xae => un!(f32_, from_i64, |a| trunc(
+            a as f64,
+            -9223372036854775808.0,
+            9223372036854775808.0
+        )? as i64),
+        0xaf => un!(
+            f32_,
+            from_u64,
+            |a| trunc(a as f64, 0.0, 18446744073709551616.0)? as u64
+        ),
+        0xb0 => un!(
+            f64_,
+            from_i64,
+            |a| trunc(a, -9223372036854775808.0, 9223372036854775808.0)? as i64
+        ),
+        0xb1 => un!(f64_, from_u64, |a| trunc(a, 0.0, 18446744073709551616.0)?
+            as u64),
+        0xb2 => un!(i32_, from_f32, |a| a as f32),
+        0xb3 => un!(u32_, from_f32, |a| a as f32),
+        0xb4 => un!(i64_, from_f32, |a| a as f32),
+        0xb5 => un!(u64_, from_f32, |a| a as f32),
+        0xb6 => un!(f64_, from_f32, |a| a as f32),
+        0xb7 => un!(i32_, from_f64, |a| a as f64),
+        0xb8 => un!(u32_, from_f64, |a| a as f64),
+        0xb9 => un!(i64_, from_f64, |a| a as f64),
+        0xba => un!(u64_, from_f64, |a| a as f64),
+        0xbb => un!(f32_, from_f64, |a| a as f64),
+        // Reinterpretations keep the bits, which is all a slot holds.
+        0xbc..=0xbf => {}
+        0xc0 => un!(u32_, from_i32, |a| a as i8 as i32),
+        0xc1 => un!(u32_, from_i32, |a| a as i16 as i32),
+        0xc2 => un!(u64_, from_i64, |a| a as i8 as i64),
+        0xc3 => un!(u64_, from_i64, |a| a as i16 as i64),
+        0xc4 => un!(u64_, from_i64, |a| a as i32 as i64),
+        _ => return Err(format!("unsupported instruction 0x{:02x}", op)),
+    }
+    Ok(())
+}
+
+const DIVIDE_BY_ZERO: &str = "integer divide by zero";
+
+/// Resolves a signed division, which traps on zero and on overflow.
+fn div_s<T>(q: Option<T>, by_zero: bool) -> Result<T, String> {
+    match q {
+        Some(q) => Ok(q),
+        None if by_zero => Err(DIVIDE_BY_ZERO.to_string()),
+        None => Err("integer overflow".to_string()),
+    }
+}
+
+/// Truncates toward zero, trapping unless the result lies in [lo, hi).
+fn trunc(x: f64, lo: f64, hi: f64) -> Result<f64, String> {
+    if x.is_nan() {
+        return Err("invalid conversion to integer".to_string());
+    }
+    let t = x.trunc();
+    if t < lo || t >= hi {
+        return Err("integer overflow".to_string());
+    }
+    Ok(t)
+}
+
+/// Takes the minimum as wasm does: NaN wins, and -0 is below +0.
+fn fmin<T: Float>(a: T, b: T) -> T {
+    if a.nan() || b.nan() {
+        T::NAN
+    } else if a == b {
+        T::or_bits(a, b)
+    } else if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+fn fmax<T: Float>(a: T, b: T) -> T {
+    if a.nan() || b.nan() {
+        T::NAN
+    } else if a == b {
+        T::and_bits(a, b)
+    } else if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+trait Float: Copy + PartialOrd {
+    const NAN: Self;
+    fn nan(self) -> bool;
+    fn or_bits(a: Self, b: Self) -> Self;
+    fn and_bits(a: Self, b: Self) -> Self;
+}
+
+impl Float for f32 {
+    const NAN: Self = f32::NAN;
+    fn nan(self) -> bool {
+        self.is_nan()
+    }
+    fn or_bits(a: Self, b: Self) -> Self {
+        f32::from_bits(a.to_bits() | b.to_bits())
+    }
+    fn and_bits(a: Self, b: Self) -> Self {
+        f32::from_bits(a.to_bits() & b.to_bits())
+    }
+}
+
+impl Float for f64 {
+    const NAN: Self = f64::NAN;
+    fn nan(self) -> bool {
+        self.is_nan()
+    }
+    fn or_bits(a: Self, b: Self) -> Self {
+        f64::from_bits(a.to_bits() | b.to_bits())
+    }
+    fn and_bits(a: Self, b: Self) -> Self {
+        f64::from_bits(a.to_bits() & b.to_bits())
+    }
+}
+
+impl Value {
+    fn ty(self) -> ValType {
+        match self {
+            Value::I32(_) => ValType::I32,
+            Value::I64(_) => ValType::I64,
+            Value::F32(_) => ValType::F32,
+            Value::F64(_) => ValType::F64,
+        }
+    }
+
+    fn bits(self) -> u64 {
+        match self {
+            Value::I32(v) => v as u32 as u64,
+            Value::I64(v) => v as u64,
+            Value::F32(v) => v.to_bits() as u64,
+            Value::F64(v) => v.to_bits(),
+        }
+    }
+
+    fn from_bits(ty: ValType, bits: u64) -> Self {
+        match ty {
+            ValType::I32 => Value::I32(bits as u32 as i32),
+            ValType::I64 => Value::I64(bits as i64),
+            ValType::F32 => Value::F32(f32::from_bits(bits as u32)),
+            ValType::F64 => Value::F64(f64::from_bits(bits)),
+        }
+    }
+}
+
+impl Module {
+    /// Returns the param and result counts of a block.
+    fn arity(&self, ty: BlockType) -> Result<(usize, usize), String> {
+        Ok(match ty {
+            BlockType::Empty => (0, 0),
+            BlockType::Value => (0, 1),
+            BlockType::Type(i) => {
+                let t = self.types.get(i as usize).ok_or("invalid block type")?;
+                (t.params.len(), t.results.len())
+            }
+        })
+    }
+}
+
+// Decoding.
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn done(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        let b = *self.bytes.get(self.pos).ok_or("unexpected end of module")?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&e| e <= self.bytes.len())
+            .ok_or("unexpected end of module")?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn uleb(&mut self, bits: u32) -> Result<u64, String> {
+        let mut out = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = self.byte()?;
+            if shift >= bits {
+                return Err("integer too long".to_string());
+            }
+            out |= ((b & 0x7f) as u64) << shift;
+            shift += 7;
+            if b & 0x80 == 0 {
+                return Ok(out);
+            }
+        }
+    }
+
+    fn sleb(&mut self, bits: u32) -> Result<i64, String> {
+        let mut out = 0i64;
+        let mut shift = 0;
+        loop {
+            let b = self.byte()?;
+            if shift >= bits {
+                return Err("integer too long".to_string());
+            }
+            out |= ((b & 0x7f) as i64) << shift;
+            shift += 7;
+            if b & 0x80 == 0 {
+                if shift < 64 && b & 0x40 != 0 {
+                    out |= -1i64 << shift;
+                }
+                return Ok(out);
+            }
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(self.uleb(32)? as u32)
+    }
+
+    /// Reads a vector length, bounded by the bytes left so a corrupt
+    /// count cannot trigger a huge allocation.
+    fn len(&mut self) -> Result<usize, String> {
+        let n = self.u32()? as usize;
+        if n > self.bytes.len() - self.pos {
+            return Err("vector length exceeds module size".to_string());
+        }
+        Ok(n)
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        let n = self.len()?;
+        String::from_utf8(self.take(n)?.to_vec()).map_err(|_| "name is not UTF-8".to_string())
+    }
+
+    fn val_type(&mut self) -> Result<ValType, String> {
+        match self.byte()? {
+            0x7f => Ok(ValType::I32),
+            0x7e => Ok(ValType::I64),
+            0x7d => Ok(ValType::F32),
+            0x7c => Ok(ValType::F64),
+            b => Err(format!("unsupported value type 0x{:02x}", b)),
+        }
+    }
+
+    fn limits(&mut self) -> Result<(usize, Option<usize>), String> {
+        match self.byte()? {
+            0 => Ok((self.u32()? as usize, None)),
+            1 => Ok((self.u32()? as usize, Some(self.u32()? as usize))),
+            _ => Err("unsupported limits (shared or 64-bit)".to_string()),
+        }
+    }
+
+    /// Evaluates a constant expression against the globals so far.
+    fn const_expr(&mut self, globals: &[Global]) -> Result<u64, String> {
+        let v = match self.byte()? {
+            0x41 => self.sleb(32)? as i32 as u32 as u64,
+            0x42 => self.sleb(64)? as u64,
+            0x43 => u32::from_le_bytes(self.take(4)?.try_into().expect("4 bytes")) as u64,
+            0x44 => u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")),
+            0x23 => globals.get(self.u32()? as usize).ok_or("invalid global index")?.init,
+            0xd2 => self.u32()? as u64,
+            b => return Err(format!("unsupported constant expression 0x{:02x}", b)),
+        };
+        if self.byte()? != 0x0b {
+            return Err("constant expression too long".to_string());
+        }
+        Ok(v)
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<Module, String> {
+    let mut r = Reader { bytes, pos: 0 };
+    if r.take(8).ok() != Some(b"\0asm\x01\0\0\0".as_slice()) {
+        return Err("not a WebAssembly 1.0 module".to_string());
+    }
+    let mut module = Module::default();
+    let mut func_types = Vec::new();
+    while !r.done() {
+        let id = r.byte()?;
+        let size = r.len()?;
+        let mut s = Reader { bytes: r.take(size)?, pos: 0 };
+        match id {
+            0 | 12 => {}
+            1 => {
+                for _ in 0..s.len()? {
+                    if s.byte()? != 0x60 {
+                        return Err("malformed function type".to_string());
+                    }
+                    let params = (0..s.len()?).map(|_| s.val_type()).collect::<Result<_, _>>()?;
+                    let results = (0..s.len()?).map(|_| s.val_type()).collect::<Result<_, _>>()?;
+                    module.types.push(FuncType { params, results });
+                }
+            }
+            2 => {
+                if s.len()? > 0 {
+                    return Err("plugins cannot import from the host".to_string());
+                }
+            }
+            3 => func_types = (0..s.len()?).map(|_| s.u32()).collect::<Result<_, _>>()?,
+            4 => {
+                for _ in 0..s.len()? {
+                    if s.byte()? != 0x70 {
+                        return Err("unsupported table type".to_string());
+                    }
+                    let (min, _) = s.limits()?;
+                    if module.table.is_some() || min > MAX_TABLE {
+                        return Err("unsupported table".to_string());
+                    }
+                    module.table = Some(min);
+                }
+            }
+            5 => {
+                for _ in 0..s.len()? {
+                    if module.memory.is_some() {
+                        return Err("more than one memory".to_string());
+                    }
+                    module.memory = Some(s.limits()?);
+                }
+            }
+            6 => {
+                for _ in 0..s.len()? {
+                    s.val_type()?;
+                    let mutable = s.byte()? == 1;
+                    let init = s.const_expr(&module.globals)?;
+                    module.globals.push(Global { mutable, init });
+                }
+            }
+            7 => {
+                for _ in 0..s.len()? {
+                    let name = s.name()?;
+                    let kind = s.byte()?;
+                    let index = s.u32()?;
+                    module.exports.insert(name, (kind, index));
+                }
+            }
+            8 => module.start = Some(s.u32()?),
+            9 => {
+                for _ in 0..s.len()? {
+                    let flags = s.u32()?;
+                    let offset = match flags {
+                        0 | 4 => Some(s.const_expr(&module.globals)? as u32),
+                        2 | 6 => {
+                            s.u32()?;
+                            Some(s.const_expr(&module.globals)? as u32)
+                        }
+                        1 | 3 | 5 | 7 => None,
+                        _ => return Err("malformed element segment".to_string()),
+                    };
+                    if flags & 3 != 0 {
+                        // The element kind or reference type.
+                        s.byte()?;
+                    }
+                    let funcs = (0..s.len()?)
+                        .map(|_| {
+                            if flags & 4 == 0 {
+                                s.u32()
+                            } else {
+                                s.const_expr(&[]).map(|v| v as u32)
+                            }
+                        })
+                        .collect::<Result<_, _>>()?;
+                    if let Some(offset) = offset {
+                        module.elements.push((offset, funcs));
+                    }
+                }
+            }
+            10 => {
+                let n = s.len()?;
+                if n != func_types.len() {
+                    return Err("function and code counts differ".to_string());
+                }
+                for &ty in &func_types {
+                    let size = s.len()?;
+                    let mut body = Reader { bytes: s.take(size)?, pos: 0 };
+                    let mut locals = 0usize;
+                    for _ in 0..body.len()? {
+                        locals += body.u32()? as usize;
+                        body.val_type()?;
+                        if locals > MAX_SLOTS {
+                            return Err("too many locals".to_string());
+                        }
+                    }
+                    let code = decode_code(&mut body)?;
+                    module.funcs.push(Func { ty, locals, code });
+                }
+            }
+            11 => {
+                for _ in 0..s.len()? {
+                    let offset = match s.u32()? {
+                        0 => Some(s.const_expr(&module.globals)? as u32),
+                        1 => None,
+                        2 => {
+                            s.u32()?;
+                            Some(s.const_expr(&module.globals)? as u32)
+                        }
+                        _ => return Err("malformed data segment".to_string()),
+                    };
+                    let n = s.len()?;
+                    module.data.push(Data { offset, bytes: s.take(n)?.to_vec() });
+                }
+            }
+            _ => return Err(format!("unknown section {}", id)),
+        }
+    }
+    if func_types.len() != module.funcs.len() {
+        return Err("function and code counts differ".to_string());
+    }
+    Ok(module)
+}
+
+/// Decodes a function body, resolving each block to its end.
+fn decode_code(r: &mut Reader) -> Result<Vec<Op>, String> {
+    let mut code = Vec::new();
+    // Indices of the open block, loop, and if ops.
+    let mut open: Vec<usize> = Vec::new();
+    loop {
+        let at = code.len();
+        let op = match r.byte()? {
+            0x00 => Op::Unreachable,
+            0x01 => Op::Nop,
+            b @ 0x02..=0x04 => {
+                let ty = block_type(r)?;
+                open.push(at);
+                match b {
+                    0x02 => Op::Block { ty, end: 0 },
+                    0x03 => Op::Loop { ty },
+                    _ => Op::If { ty, else_: 0, end: 0 },
+                }
+            }
+            0x05 => {
+                match open.last().map(|&i| &mut code[i]) {
+                    Some(Op::If { else_, .. }) => *else_ = at,
+                    _ => return Err("else outside if".to_string()),
+                }
+                Op::Else { end: 0 }
+            }
+            0x0b => {
+                match open.pop() {
+                    Some(start) => {
+                        let mut else_at = None;
+                        match &mut code[start] {
+                            Op::Block { end, .. } => *end = at,
+                            Op::If { else_, end, .. } => {
+                                *end = at;
+                                if *else_ == 0 {
+                                    *else_ = at;
+                                } else {
+                                    else_at = Some(*else_);
+                                }
+                            }
+                            _ => {}
+                        }
+                        if let Some(Op::Else { end }) = else_at.map(|i| &mut code[i]) {
+                            *end = at;
+                        }
+                    }
+                    None => {
+                        code.push(Op::End);
+                        if !r.done() {
+                            return Err("code after function end".to_string());
+                        }
+                        return Ok(code);
+                    }
+                }
+                Op::End
+            }
+            0x0c => Op::Br(r.u32()?),
+            0x0d => Op::BrIf(r.u32()?),
+            0x0e => {
+                let targets = (0..r.len()?).map(|_| r.u32()).collect::<Result<_, _>>()?;
+                Op::BrTable(targets, r.u32()?)
+            }
+            0x0f => Op::Return,
+            0x10 => Op::Call(r.u32()?),
+            0x11 => {
+                let ty = r.u32()?;
+                if r.u32()? != 0 {
+                    return Err("call_indirect on a table other than 0".to_string());
+                }
+                Op::CallIndirect(ty)
+            }
+            0x1a => Op::Drop,
+            0x1b => Op::Select,
+            0x1c => {
+                for _ in 0..r.len()? {
+                    r.val_type()?;
+                }
+                Op::Select
+            }
+            0x20 => Op::LocalGet(r.u32()?),
+            0x21 => Op::LocalSet(r.u32()?),
+            0x22 => Op::LocalTee(r.u32()?),
+            0x23 => Op::GlobalGet(r.u32()?),
+            0x24 => Op::GlobalSet(r.u32()?),
+            op @ 0x28..=0x3e => {
+                r.u32()?;
+                let offset = r.u32()?;
+                if op <= 0x35 {
+                    Op::Load { op, offset }
+                } else {
+                    Op::Store { op, offset }
+                }
+            }
+            b @ (0x3f | 0x40) => {
+                r.byte()?;
+                if b == 0x3f {
+                    Op::MemorySize
+                } else {
+                    Op::MemoryGrow
+                }
+            }
+            0x41 => Op::Const(r.sleb(32)? as i32 as u32 as u64),
+            0x42 => Op::Const(r.sleb(64)? as u64),
+            0x43 => Op::Const(u32::from_le_bytes(r.take(4)?.try_into().expect("4 bytes")) as u64),
+            0x44 => Op::Const(u64::from_le_bytes(r.take(8)?.try_into().expect("8 bytes"))),
+            op @ 0x45..=0xc4 => Op::Numeric(op),
+            0xfc => match r.u32()? {
+                op @ 0..=7 => Op::TruncSat(op as u8),
+                8 => {
+                    let seg = r.u32()?;
+                    r.byte()?;
+                    Op::MemoryInit(seg)
+                }
+                9 => Op::DataDrop(r.u32()?),
+                10 => {
+                    r.byte()?;
+                    r.byte()?;
+                    Op::MemoryCopy
+                }
+                11 => {
+                    r.byte()?;
+                    Op::MemoryFill
+                }
+                op => return Err(format!("unsupported instruction 0xfc {}", op)),
+            },
+            b => return Err(format!("unsupported instruction 0x{:02x}", b)),
+        };
+        code.push(op);
+    }
+}
+
+fn block_type(r: &mut Reader) -> Result<BlockType, String> {
+    match r.bytes.get(r.pos).copied() {
+        Some(0x40) => {
+            r.pos += 1;
+            Ok(BlockType::Empty)
+        }
+        Some(0x7c..=0x7f) => {
+            r.pos += 1;
+            Ok(BlockType::Value)
+        }
+        _ => {
+            let i = r.sleb(33)?;
+            u32::try_from(i).map(BlockType::Type).map_err(|_| "invalid block type".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A function to build: its export name, parameter and result types as
+    /// value type bytes, and its body without the final `end`.
+    pub(crate) struct Func<'a> {
+        pub name: &'a str,
+        pub params: &'a [u8],
+        pub results: &'a [u8],
+        pub body: &'a [u8],
+    }
+
+    pub(crate) const I32: u8 = 0x7f;
+    pub(crate) const I64: u8 = 0x7e;
+
+    pub(crate) fn uleb(mut n: u64, out: &mut Vec<u8>) {
+        loop {
+            let byte = (n & 0x7f) as u8;
+            n >>= 7;
+            if n == 0 {
+                out.push(byte);
+                return;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    pub(crate) fn sleb(mut n: i64, out: &mut Vec<u8>) {
+        loop {
+            let byte = (n & 0x7f) as u8;
+            n >>= 7;
+            if (n == 0 && byte & 0x40 == 0) || (n == -1 && byte & 0x40 != 0) {
+                out.push(byte);
+                return;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    fn section(id: u8, items: &[Vec<u8>], out: &mut Vec<u8>) {
+        let mut body = Vec::new();
+        uleb(items.len() as u64, &mut body);
+        items.iter().for_each(|i| body.extend(i));
+        out.push(id);
+        uleb(body.len() as u64, out);
+        out.extend(body);
+    }
+
+    fn bytes(b: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        uleb(b.len() as u64, &mut out);
+        out.extend(b);
+        out
+    }
+
+    /// Encodes a module with one exported memory of the given page limits,
+    /// active data segments at the given offsets, and exported functions.
+    pub(crate) fn module(
+        pages: (u64, Option<u64>),
+        data: &[(i64, &[u8])],
+        funcs: &[Func],
+    ) -> Vec<u8> {
+        let mut out = b"\0asm\x01\0\0\0".to_vec();
+        let types: Vec<_> = funcs
+            .iter()
+            .map(|f| [vec![0x60], bytes(f.params), bytes(f.results)].concat())
+            .collect();
+        section(1, &types, &mut out);
+        let indices: Vec<_> = (0..funcs.len() as u8).map(|i| vec![i]).collect();
+        section(3, &indices, &mut out);
+        let mut memory = vec![u8::from(pages.1.is_some())];
+        uleb(pages.0, &mut memory);
+        pages.1.inspect(|&max| uleb(max, &mut memory));
+        section(5, &[memory], &mut out);
+        let mut exports = vec![[bytes(b"memory"), vec![2, 0]].concat()];
+        exports.extend(
+            funcs
+                .iter()
+                .enumerate()
+                .map(|(i, f)| [bytes(f.name.as_bytes()), vec![0, i as u8]].concat()),
+        );
+        section(7, &exports, &mut out);
+        let code: Vec<_> = funcs.iter().map(|f| bytes(&[&[0], f.body, &[0x0b]].concat())).collect();
+        section(10, &code, &mut out);
+        let segments: Vec<_> = data
+            .iter()
+            .map(|&(at, b)| {
+                let mut s = vec![0, 0x41];
+                sleb(at, &mut s);
+                s.push(0x0b);
+                [s, bytes(b)].concat()
+            })
+            .collect();
+        section(11, &segments, &mut out);
+        out
+    }
+
+    #[test]
+    fn runs_out_of_fuel() {
+        // Counts local 0 down to zero: loop local.get 0 i32.const 1 i32.sub
+        // local.tee 0 br_if 0 end, six instructions a round.
+        let body = [0x03, 0x40, 0x20, 0x00, 0x41, 0x01, 0x6b, 0x22, 0x00, 0x0d, 0x00, 0x0b];
+        let count = Func { name: "count", params: &[I32], results: &[], body: &body };
+        let mut instance = Instance::new(&module((0, None), &[], &[count])).unwrap();
+        assert_eq!(
+            instance.call_with("count", &[Value::I32(100)], 1000),
+            Ok(vec![])
+        );
+        assert_eq!(
+            instance.call_with("count", &[Value::I32(200)], 1000),
+            Err("out of fuel".to_string())
+        );
+        // Each call gets a fresh budget, so the instance stays usable.
+        assert_eq!(instance.call_with("count", &[Value::I32(100)], 1000), Ok(vec![]));
+    }
+
+    #[test]
+    fn caps_memory_at_64_mib() {
+        assert!(Instance::new(&module((MAX_PAGES as u64 + 1, None), &[], &[])).is_err());
+        // local.get 0 memory.grow 0
+        let grow = Func {
+            name: "grow",
+            params: &[I32],
+            results: &[I32],
+            body: &[0x20, 0x00, 0x40, 0x00],
+        };
+        let mut instance = Instance::new(&module((1, None), &[], &[grow])).unwrap();
+        assert_eq!(
+            instance.call("grow", &[Value::I32(MAX_PAGES as i32)]),
+            Ok(vec![Value::I32(-1)])
+        );
+        assert_eq!(
+            instance.call("grow", &[Value::I32(MAX_PAGES as i32 - 1)]),
+            Ok(vec![Value::I32(1)])
+        );
+        assert_eq!(instance.memory().len(), MAX_PAGES * PAGE);
+        assert_eq!(instance.call("grow", &[Value::I32(1)]), Ok(vec![Value::I32(-1)]));
+    }
+
+    #[test]
+    fn reports_traps() {
+        let trap = Func { name: "trap", params: &[], results: &[], body: &[0x00] };
+        let mut instance = Instance::new(&module((0, None), &[], &[trap])).unwrap();
+        assert_eq!(instance.call("trap", &[]), Err("unreachable executed".to_string()));
+        assert!(instance.call("missing", &[]).is_err());
+        assert!(instance.call("trap", &[Value::I32(1)]).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_modules() {
+        let one = Func { name: "one", params: &[], results: &[I32], body: &[0x41, 0x01] };
+        let good = module((1, Some(1)), &[(0, b"hi")], &[one]);
+        assert!(Instance::new(&good).is_ok());
+        // Truncations fail rather than panic, unless they end between
+        // sections.
+        for len in 0..good.len() {
+            let _ = Instance::new(&good[..len]);
+        }
+        assert!(Instance::new(&good[..good.len() - 1]).is_err());
+        assert!(Instance::new(&good[..4]).is_err());
+        assert!(Instance::new(b"\0asm\x02\0\0\0").is_err());
+        assert!(Instance::new(b"not wasm").is_err());
+        assert!(Instance::new(&module((1, Some(1)), &[(PAGE as i64 - 1, b"hi")], &[])).is_err());
+        // A body that leaves nothing for its i32 result.
+        let empty = Func {
+            name: "empty",
+            params: &[],
+            results: &[I32],
+            body: &[],
+        };
+        let bad =
+            Instance::new(&module((0, None), &[], &[empty])).and_then(|mut i| i.call("empty", &[]));
+        assert!(bad.is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Change detection for `--watch`.
+//!
+//! Std has no file notification API, so watching polls: it stamps the
+//! size and modification time of every file a lint run reads, and a
+//! different stamp means something changed. The cache then limits the
+//! rerun to the files that did.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::workspace::{self, Target};
+use crate::{cache, config, mcp, rules};
+
+/// How often to poll.
+pub const INTERVAL: Duration = Duration::from_millis(300);
+
+/// Returns a hash of the files the targets' runs read, plus an explicit
+/// config file if one is given.
+pub fn stamp(targets: &[Target], config: Option<&Path>) -> u64 {
+    let mut entries = Vec::new();
+    for target in targets {
+        let path = target.path();
+        let root = target.config_root();
+        let parent = root.parent().unwrap_or(Path::new(""));
+        match target {
+            Target::Dir(_) | Target::User(_) => list(path, &mut entries),
+            Target::ClaudeMd(_) | Target::File(..) => entry(path, &mut entries),
+        }
+        entry(&parent.join(config::FILE_NAME), &mut entries);
+        if let Target::Dir(_) = target {
+            entry(&parent.join(mcp::FILE_NAME), &mut entries);
+            for name in rules::CLAUDE_MD_NAMES {
+                entry(&parent.join(name), &mut entries);
+            }
+            for path in workspace::nested_claude_md(parent) {
+                entry(&path, &mut entries);
+            }
+        }
+    }
+    if let Some(path) = config {
+        entry(path, &mut entries);
+    }
+    // Directory order is not guaranteed to be stable.
+    entries.sort();
+    let parts: Vec<&[u8]> = entries.iter().map(|e| e.as_bytes()).collect();
+    cache::hash(&parts)
+}
+
+fn list(dir: &Path, out: &mut Vec<String>) {
+    entry(dir, out);
+    for e in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = e.path();
+        if e.file_type().is_ok_and(|t| t.is_dir()) {
+            list(&path, out);
+        } else {
+            entry(&path, out);
+        }
+    }
+}
+
+/// Records a path with its size and modification time, or as missing.
+fn entry(path: &Path, out: &mut Vec<String>) {
+    let meta = fs::metadata(path).ok();
+    let mtime = meta.as_ref()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    let len = meta.map_or(u64::MAX, |m| m.len());
+    out.push(format!("{}\0{}\0{}", path.display(), len, mtime));
+}
@@ -0,0 +1,162 @@
+//! Discovery of lint targets in a monorepo.
+//!
+//! A workspace can hold a `.claude/` directory per package, plus
+//! CLAUDE.md files that sit directly in package directories. Both are
+//! found by walking the tree, skipping build output and VCS metadata.
+//! The CLAUDE.md files in a project's subdirectories, which Claude Code
+//! loads when it works there, are found the same way.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::rules::{self, FileKind};
+
+/// Directory names never descended into.
+const SKIP: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+
+/// A lint target: a `.claude` directory, a CLAUDE.md outside one, the
+/// user-level ~/.claude, or a single file named on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Dir(PathBuf),
+    ClaudeMd(PathBuf),
+    User(PathBuf),
+    File(PathBuf, FileKind),
+}
+
+impl Target {
+    pub fn path(&self) -> &Path {
+        match self {
+            Target::Dir(p) | Target::ClaudeMd(p) | Target::User(p) | Target::File(p, _) => p,
+        }
+    }
+
+    /// Returns the path the config is looked up next to: the target
+    /// itself, or for a file inside a `.claude` directory, the directory.
+    pub fn config_root(&self) -> PathBuf {
+        match self {
+            Target::Dir(p) | Target::User(p) => p.clone(),
+            Target::ClaudeMd(p) | Target::File(p, _) => {
+                // A relative path may sit in the .claude directory itself.
+                let abs = fs::canonicalize(p).unwrap_or_else(|_| p.clone());
+                let dir = abs
+                    .ancestors()
+                    .skip(1)
+                    .find(|a| a.file_name().is_some_and(|n| n == ".claude"));
+                dir.map_or_else(|| p.clone(), Path::to_path_buf)
+            }
+        }
+    }
+}
+
+/// Walks a workspace and returns its lint targets in path order.
+/// Symlinked directories are not followed, so links cannot loop.
+pub fn discover(workspace: &Path) -> Vec<Target> {
+    let mut out = Vec::new();
+    walk(workspace, &mut out);
+    out
+}
+
+fn walk(dir: &Path, out: &mut Vec<Target>) {
+    let mut entries: Vec<_> = fs::read_dir(dir).into_iter().flatten().flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        let Ok(kind) = entry.file_type() else {
+            continue;
+        };
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        // Keep paths under "." free of a "./" prefix.
+        let path = if dir == Path::new(".") {
+            PathBuf::from(entry.file_name())
+        } else {
+            entry.path()
+        };
+        if kind.is_file() && rules::is_claude_md(&path) {
+            // The .claude directory beside it lints it.
+            if !dir.join(".claude").is_dir() {
+                out.push(Target::ClaudeMd(path));
+            }
+        } else if kind.is_dir() && name == ".claude" {
+            out.push(Target::Dir(path));
+        } else if kind.is_dir() && !name.starts_with('.') && !SKIP.contains(&name.as_ref()) {
+            walk(&path, out);
+        }
+    }
+}
+
+/// Drops the CLAUDE.md targets that a `.claude` directory target among
+/// them lints too, as files nested in its project.
+pub fn drop_nested(targets: &mut Vec<Target>) {
+    let dirs: Vec<PathBuf> = targets
+        .iter()
+        .filter_map(|t| match t {
+            Target::Dir(dir) => Some(dir.clone()),
+            _ => None,
+        })
+        .collect();
+    targets.retain(|t| match t {
+        Target::ClaudeMd(path) => {
+            let project = path
+                .ancestors()
+                .skip(1)
+                .find(|a| project_dir(a).join(".claude").is_dir());
+            !project.is_some_and(|p| dirs.contains(&p.join(".claude")))
+        }
+        _ => true,
+    });
+}
+
+/// Returns the CLAUDE.md and CLAUDE.local.md files in a project's
+/// subdirectories, in path order. A subdirectory with a `.claude` of its
+/// own is another project, and is left out with everything in it.
+pub fn nested_claude_md(project: &Path) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    for sub in subdirs(project_dir(project)) {
+        nested(&sub, &mut out);
+    }
+    out
+}
+
+fn nested(dir: &Path, out: &mut Vec<PathBuf>) {
+    if dir.join(".claude").is_dir() {
+        return;
+    }
+    let mut entries: Vec<_> = fs::read_dir(dir).into_iter().flatten().flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    for entry in &entries {
+        if entry.file_type().is_ok_and(|k| k.is_file()) && rules::is_claude_md(&entry.path()) {
+            out.push(entry.path());
+        }
+    }
+    for sub in subdirs(dir) {
+        nested(&sub, out);
+    }
+}
+
+/// The directories in dir that are walked, in name order, without a
+/// "./" prefix under ".".
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(dir).into_iter().flatten().flatten().collect();
+    entries.sort_by_key(|e| e.file_name());
+    entries
+        .into_iter()
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            e.file_type().is_ok_and(|k| k.is_dir())
+                && !name.starts_with('.')
+                && !SKIP.contains(&name.as_ref())
+        })
+        .map(|e| if dir == Path::new(".") { PathBuf::from(e.file_name()) } else { e.path() })
+        .collect()
+}
+
+/// The directory a path names, with "" read as ".".
+fn project_dir(path: &Path) -> &Path {
+    if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    }
+}